@@ -0,0 +1,63 @@
+/* C interface of guess-zeidel-method. Static library libguess_zeidel_method.a
+ * and shared libguess_zeidel_method.so are built with
+ *   cargo rustc --release --lib --features capi --crate-type staticlib
+ *   cargo rustc --release --lib --features capi --crate-type cdylib
+ *
+ * Functions are only ever added, never changed. This header is kept in sync
+ * with src/capi.rs by hand.
+ */
+
+#ifndef GUESS_ZEIDEL_SOLVER_H
+#define GUESS_ZEIDEL_SOLVER_H
+
+#include <stddef.h>
+
+#define SOLVER_OK 0
+/* Iterations did not converge within max_iterations: delta grew, or was
+ * still shrinking when they ran out */
+#define SOLVER_DIVERGED 1
+/* Null pointer, zero size, size whose matrix does not fit into memory,
+ * non-finite number or invalid system (like zero on diagonal) */
+#define SOLVER_INVALID_ARGUMENT 2
+/* Any other failure */
+#define SOLVER_FAILED 3
+/* Matrix is singular or numerically singular, no unique solution exists */
+#define SOLVER_SINGULAR 4
+/* Delta stopped decreasing above epsilon long before max_iterations */
+#define SOLVER_STAGNATED 5
+/* Numbers outgrew Decimal during iterations, coefficients are too large */
+#define SOLVER_OVERFLOW 6
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Solves input document written in the schema `solver` binary reads and
+ * returns result document, the same as `solver --format json` writes.
+ * Returns NULL only when input is NULL or not UTF-8.
+ * Returned string must be released with solver_string_free. */
+char *solver_solve_json(const char *input);
+
+/* Releases string returned by this library. NULL is ignored. */
+void solver_string_free(char *string);
+
+/* Solves n x n system given as row-major matrix and rhs by Gauss-Seidel
+ * and writes n values to solution. Returns one of SOLVER_* codes,
+ * solution is written only on SOLVER_OK.
+ * Doubles are converted to decimals by their shortest representation,
+ * so 0.1 is solved as exactly 0.1. */
+int solver_solve_dense(size_t n,
+                       const double *matrix,
+                       const double *rhs,
+                       double epsilon,
+                       size_t max_iterations,
+                       double *solution);
+
+/* Version of the library, static string which must not be released */
+const char *solver_version(void);
+
+#ifdef __cplusplus
+} /* extern "C" */
+#endif
+
+#endif /* GUESS_ZEIDEL_SOLVER_H */
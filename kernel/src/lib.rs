@@ -0,0 +1,172 @@
+//! Gauss-Seidel kernel for systems of size known at compile time. Needs
+//! neither std nor heap: matrices are arrays, so the same solve runs on
+//! microcontrollers. Semantics follow `solver`: iterations stop once largest
+//! change of component is below epsilon
+#![no_std]
+
+use core::fmt;
+use core::ops::{Add, Div, Mul, Sub};
+
+/// Numbers kernel computes with
+pub trait Scalar:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+
+    /// `None` when result does not fit. Floats go to infinity instead, so
+    /// only bounded types override these
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        Some(self - other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn checked_div(self, other: Self) -> Option<Self> {
+        Some(self / other)
+    }
+}
+
+macro_rules! float_scalar {
+    ($($float:ty),*) => {
+        $(
+            impl Scalar for $float {
+                const ZERO: Self = 0.0;
+                const ONE: Self = 1.0;
+
+                // float abs of core is not stable on every toolchain embedded projects use
+                fn abs(self) -> Self {
+                    if self < 0.0 {
+                        -self
+                    } else {
+                        self
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_scalar!(f32, f64);
+
+#[cfg(feature = "decimal")]
+impl Scalar for rust_decimal::Decimal {
+    const ZERO: Self = rust_decimal::Decimal::ZERO;
+    const ONE: Self = rust_decimal::Decimal::ONE;
+
+    fn abs(self) -> Self {
+        rust_decimal::Decimal::abs(&self)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_mul(self, other)
+    }
+
+    fn checked_div(self, other: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_div(self, other)
+    }
+}
+
+/// `matrix x = rhs` of `N` equations, row-major
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct System<T, const N: usize> {
+    pub matrix: [[T; N]; N],
+    pub rhs: [T; N],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelError {
+    /// Diagonal element is zero, so component can't be expressed from its equation
+    ZeroDiagonal { row: usize },
+    /// Epsilon was not reached within max iterations
+    Diverge,
+    /// Sum of row, by index from 0, does not fit into number type on
+    /// iteration, from 1
+    Overflow { row: usize, iteration: usize },
+}
+
+impl fmt::Display for KernelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KernelError::ZeroDiagonal { row } => write!(f, "Zero on diagonal in row {}", row + 1),
+            KernelError::Diverge => write!(f, "Method diverged"),
+            KernelError::Overflow { row, iteration } => {
+                write!(f, "Overflow in row {} on iteration {iteration}", row + 1)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solution<T, const N: usize> {
+    pub values: [T; N],
+    pub iterations: usize,
+    /// Largest change of component on the last iteration
+    pub delta: T,
+}
+
+impl<T: Scalar, const N: usize> System<T, N> {
+    pub fn new(matrix: [[T; N]; N], rhs: [T; N]) -> Self {
+        System { matrix, rhs }
+    }
+
+    /// One Gauss-Seidel sweep over all rows, returns largest change of
+    /// component, or row whose arithmetic overflowed
+    pub fn sweep(&self, values: &mut [T; N]) -> Result<T, usize> {
+        let mut delta = T::ZERO;
+        for i in 0..N {
+            let s = (0..N)
+                .filter(|j| *j != i)
+                .try_fold(T::ZERO, |acc, j| acc.checked_add(self.matrix[i][j].checked_mul(values[j])?))
+                .ok_or(i)?;
+            let x = (self.rhs[i].checked_sub(s))
+                .and_then(|rest| rest.checked_div(self.matrix[i][i]))
+                .ok_or(i)?;
+            let d = x.checked_sub(values[i]).ok_or(i)?.abs();
+            if d > delta {
+                delta = d;
+            }
+            values[i] = x;
+        }
+        Ok(delta)
+    }
+
+    /// Iterates from approximation of all ones until change is below `epsilon`.
+    /// At least one sweep is done, also for `max_iterations` of 0
+    pub fn solve(&self, epsilon: T, max_iterations: usize) -> Result<Solution<T, N>, KernelError> {
+        if let Some(row) = (0..N).find(|i| self.matrix[*i][*i] == T::ZERO) {
+            return Err(KernelError::ZeroDiagonal { row });
+        }
+
+        let mut values = [T::ONE; N];
+        for iteration in 1..=max_iterations.max(1) {
+            let delta = self
+                .sweep(&mut values)
+                .map_err(|row| KernelError::Overflow { row, iteration })?;
+            if delta < epsilon {
+                return Ok(Solution {
+                    values,
+                    iterations: iteration,
+                    delta,
+                });
+            }
+        }
+        Err(KernelError::Diverge)
+    }
+}
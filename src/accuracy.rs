@@ -0,0 +1,120 @@
+//! Verdict on how many digits of solution can be trusted, for readers who
+//! can't tell from deltas and residuals. Relative error of solution is
+//! estimated by the larger of two sources: residual amplified by condition
+//! number of matrix, and epsilon iterations stopped at
+
+use core::fmt;
+use nalgebra::DVector;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+use crate::condition::{condition_number, matrix_norm, Condition};
+use crate::norm::Norm;
+use crate::solver::Equation;
+
+/// Significant digits Decimal holds
+const DECIMAL_DIGITS: u32 = 28;
+
+/// What limits accuracy most
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// Iterations stopped once change was below epsilon
+    Epsilon,
+    /// Residual of solution, condition number is not estimated
+    Residual,
+    /// Residual amplified by condition number
+    Conditioning,
+    /// Nothing but digits Decimal holds
+    Precision,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Epsilon => write!(f, "epsilon"),
+            Limit::Residual => write!(f, "residual"),
+            Limit::Conditioning => write!(f, "conditioning"),
+            Limit::Precision => write!(f, "precision"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accuracy {
+    /// Significant digits of largest component which are likely correct
+    pub digits: u32,
+    /// `|b - A x| / (|A| |x| + |b|)` in max norm: how much system has to
+    /// change for solution to be exact
+    pub backward_error: Decimal,
+    /// See [`condition_number`], `None` for systems too large to estimate it
+    pub condition: Option<Condition>,
+    pub limited_by: Limit,
+}
+
+impl Accuracy {
+    pub fn assess(equation: &Equation, solution: &DVector<Decimal>) -> Self {
+        let matrix = &equation.input_matrix;
+        let residual = &equation.expression_rhs - matrix * solution;
+        let norm = matrix_norm(matrix);
+        let solution_norm = Norm::LInf.of(solution.iter().copied());
+        let scale = norm * solution_norm + Norm::LInf.of(equation.expression_rhs.iter().copied());
+        let backward_error = match scale.is_zero() {
+            true => dec!(0),
+            false => Norm::LInf.of(residual.iter().copied()) / scale,
+        };
+
+        let condition = condition_number(equation);
+
+        let mut estimates = vec![(Decimal::new(1, DECIMAL_DIGITS), Limit::Precision)];
+        match condition {
+            Some(condition) => estimates.push((
+                condition.value.checked_mul(backward_error).unwrap_or(Decimal::MAX),
+                Limit::Conditioning,
+            )),
+            None => estimates.push((backward_error, Limit::Residual)),
+        }
+        let direct = equation.method.is_direct() || equation.uses_thomas() || equation.uses_block_thomas();
+        if !direct && !solution_norm.is_zero() {
+            estimates.push((equation.epsilon / solution_norm, Limit::Epsilon));
+        }
+        let (error, limited_by) = estimates
+            .into_iter()
+            .max_by_key(|(error, _)| *error)
+            .expect("precision is always estimated");
+
+        let digits = match error.checked_log10() {
+            Some(log) if log < dec!(0) => (-log).floor().to_u32().unwrap_or(0).min(DECIMAL_DIGITS),
+            _ => 0,
+        };
+        Accuracy {
+            digits,
+            backward_error,
+            condition,
+            limited_by,
+        }
+    }
+}
+
+impl fmt::Display for Accuracy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.limited_by {
+            Limit::Epsilon => "limited by epsilon".to_owned(),
+            Limit::Residual => "limited by residual, condition number is not estimated for large systems".to_owned(),
+            Limit::Conditioning => match self.condition {
+                Some(condition) if condition.is_singular() => "matrix is singular or nearly so".to_owned(),
+                Some(condition) => format!(
+                    "limited by residual amplified by condition number {}",
+                    condition.value.round_sf(3).unwrap_or(condition.value).normalize()
+                ),
+                None => "limited by conditioning of matrix".to_owned(),
+            },
+            Limit::Precision => "limited only by precision of Decimal".to_owned(),
+        };
+        match self.digits {
+            0 => write!(f, "not trustworthy, error may be as large as solution itself: {reason}"),
+            1 => write!(f, "trustworthy to about 1 significant digit, {reason}"),
+            digits => write!(f, "trustworthy to about {digits} significant digits, {reason}"),
+        }
+    }
+}
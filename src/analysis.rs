@@ -0,0 +1,177 @@
+use core::fmt;
+use nalgebra::DVector;
+use rust_decimal::{Decimal, MathematicalOps};
+
+use crate::condition::matrix_norm;
+use crate::norm::Norm;
+use crate::solver::Equation;
+
+/// Norms of matrix and right hand side, the ones conditioning is discussed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemNorms {
+    /// `|A|_1`, largest sum of absolute values of column
+    pub one: Decimal,
+    /// `|A|_inf`, largest sum of absolute values of row
+    pub infinity: Decimal,
+    /// Square root of sum of squares of all entries, `None` when squares
+    /// do not fit into Decimal
+    pub frobenius: Option<Decimal>,
+    /// `|b|` in `rhs_norm`
+    pub rhs: Decimal,
+    /// Norm of residual, see [`Equation::norm`]
+    pub rhs_norm: Norm,
+}
+
+impl fmt::Display for SystemNorms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = |value: Decimal| value.round_sf(6).unwrap_or(value).normalize();
+        write!(f, "|A|1 {}, |A|inf {}, ", number(self.one), number(self.infinity))?;
+        match self.frobenius {
+            Some(frobenius) => write!(f, "Frobenius {}", number(frobenius))?,
+            None => write!(f, "Frobenius too large for Decimal")?,
+        }
+        write!(f, ", |b|{} {}", self.rhs_norm.name().trim_start_matches('L'), number(self.rhs))
+    }
+}
+
+/// Norms of matrix and right hand side of `equation`, right hand side in
+/// norm of residual
+pub fn system_norms(equation: &Equation) -> SystemNorms {
+    let matrix = &equation.input_matrix;
+    let one = (matrix.column_iter())
+        .map(|column| Norm::L1.of(column.iter().copied()))
+        .fold(Decimal::ZERO, Decimal::max);
+    let frobenius = (matrix.iter())
+        .try_fold(Decimal::ZERO, |sum, value| sum.checked_add(value.checked_mul(*value)?))
+        .map(|sum| sum.sqrt().expect("sum of squares is not negative"));
+    let rhs_norm = equation.norm.unwrap_or(Norm::L2);
+    SystemNorms {
+        one,
+        infinity: matrix_norm(matrix),
+        frobenius,
+        rhs: rhs_norm.of(equation.expression_rhs.iter().copied()),
+        rhs_norm,
+    }
+}
+
+/// Two equations which have proportional coefficients
+#[derive(Debug)]
+pub struct ProportionalRows {
+    pub first: usize,
+    pub second: usize,
+    /// `second` row is `first` row multiplied by this
+    pub factor: Decimal,
+    /// Right hand sides are in the same proportion, so equations repeat each other.
+    /// Otherwise they contradict each other
+    pub consistent: bool,
+}
+
+impl fmt::Display for ProportionalRows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ProportionalRows { first, second, .. } = self;
+        let factor = self.factor.normalize();
+        let relation = if factor == Decimal::ONE {
+            format!("Equation {second} has the same coefficients as equation {first}")
+        } else {
+            format!("Equation {second} has coefficients of equation {first} multiplied by {factor}")
+        };
+
+        if self.consistent {
+            write!(
+                f,
+                "{relation} and so does its right hand side: it adds no information, so there is no unique solution"
+            )
+        } else {
+            write!(
+                f,
+                "{relation}, but its right hand side differs: equations contradict each other, so there is no solution"
+            )
+        }
+    }
+}
+
+/// `A x - b`, signed residual of every equation in input order
+pub fn residual_vector(equation: &Equation, solution: &DVector<Decimal>) -> DVector<Decimal> {
+    let residual = &equation.input_matrix * solution - &equation.expression_rhs;
+    match equation.row_order.is_empty() {
+        true => residual,
+        false => {
+            let mut ordered = residual.clone();
+            for (row, value) in residual.iter().enumerate() {
+                ordered[equation.input_row(row)] = *value;
+            }
+            ordered
+        }
+    }
+}
+
+/// `|b_i - A_i x| / |A_i|` of every equation, with Euclidean norm of row.
+/// Scaling makes residuals of equations written in different units comparable:
+/// multiplying equation by constant does not change its weighted residual.
+/// Residual of all-zero row is left unscaled. Equations are in input order,
+/// even when they were swapped, see [`Equation::row_order`]
+pub fn weighted_residuals(equation: &Equation, solution: &DVector<Decimal>) -> Vec<Decimal> {
+    let matrix = &equation.input_matrix;
+    let mut residuals: Vec<Decimal> = (0..matrix.nrows())
+        .map(|row| {
+            let residual = (equation.expression_rhs[row]
+                - (0..matrix.ncols()).fold(Decimal::ZERO, |acc, column| {
+                    acc + matrix[(row, column)] * solution[column]
+                }))
+            .abs();
+            let row_norm = Norm::L2.of(matrix.row(row).iter().copied());
+            if row_norm.is_zero() {
+                residual
+            } else {
+                residual / row_norm
+            }
+        })
+        .collect();
+    if !equation.row_order.is_empty() {
+        let solved = residuals.clone();
+        for (row, residual) in solved.into_iter().enumerate() {
+            residuals[equation.input_row(row)] = residual;
+        }
+    }
+    residuals
+}
+
+/// Finds pairs of rows where one is scalar multiple of another.
+/// Proportionality is checked by cross products, so no division
+/// (and rounding) is made while comparing
+pub fn find_proportional_rows(equation: &Equation) -> Vec<ProportionalRows> {
+    let matrix = &equation.input_matrix;
+    let rhs = &equation.expression_rhs;
+    let matrix_size = matrix.nrows();
+    let mut found = Vec::new();
+
+    for first in 0..matrix_size {
+        let Some(pivot) = (0..matrix_size).find(|column| !matrix[(first, *column)].is_zero())
+        else {
+            continue;
+        };
+
+        for second in first + 1..matrix_size {
+            let a = matrix[(first, pivot)];
+            let b = matrix[(second, pivot)];
+            if b.is_zero() {
+                continue;
+            }
+
+            let proportional = (0..matrix_size)
+                .all(|column| matrix[(first, column)] * b == matrix[(second, column)] * a);
+            if !proportional {
+                continue;
+            }
+
+            found.push(ProportionalRows {
+                first: first + 1,
+                second: second + 1,
+                factor: b / a,
+                consistent: rhs[first] * b == rhs[second] * a,
+            });
+        }
+    }
+
+    found
+}
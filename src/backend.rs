@@ -0,0 +1,278 @@
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::solver::{exhausted, ESolveError, Equation};
+
+/// From this size matrix no longer fits into cache, and sweep goes by tiles
+pub const BLOCKED_MIN_SIZE: usize = 2000;
+/// Rows of tile: contributions to them are gathered from contiguous pieces
+/// of columns, 64 rows of `f64` are eight cache lines
+const TILE: usize = 64;
+
+/// Coefficient `f64` can't hold: it has more significant digits than `f64` carries
+#[derive(Debug, Clone)]
+pub struct ConversionLoss {
+    pub location: String,
+    pub value: Decimal,
+    pub converted: f64,
+    /// `|converted - value| / |value|`
+    pub relative_error: Decimal,
+}
+
+impl fmt::Display for ConversionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} becomes {} in f64, relative error {:e}",
+            self.location,
+            self.value,
+            self.converted,
+            self.relative_error.normalize()
+        )
+    }
+}
+
+/// Checked conversion failed, every lossy coefficient is listed
+#[derive(Debug)]
+pub struct ConversionError {
+    pub losses: Vec<ConversionLoss>,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} coefficients can't be represented in f64:", self.losses.len())?;
+        for loss in &self.losses {
+            write!(f, "\n  {loss}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Running sum which keeps rounding error of every addition aside
+/// (Neumaier's variant of Kahan summation), so error of sum stays about one
+/// rounding instead of growing with amount of terms
+#[derive(Debug, Clone, Copy, Default)]
+struct CompensatedSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    fn new(start: f64) -> Self {
+        CompensatedSum {
+            sum: start,
+            compensation: 0.0,
+        }
+    }
+
+    fn add(&mut self, term: f64) {
+        let total = self.sum + term;
+        // low digits of smaller addend are the ones rounded away
+        self.compensation += match self.sum.abs() >= term.abs() {
+            true => (self.sum - total) + term,
+            false => (term - total) + self.sum,
+        };
+        self.sum = total;
+    }
+
+    fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// System converted to `f64`, faster to iterate but with about 16 significant digits
+#[derive(Debug, Clone)]
+pub struct F64System {
+    pub matrix: DMatrix<f64>,
+    pub rhs: DVector<f64>,
+    /// Row sums and residual are summed with compensation, see
+    /// [`F64System::sum`]. Costs about four times the additions
+    pub compensated: bool,
+}
+
+impl F64System {
+    /// Fails when any coefficient changes in conversion. Decimal fractions
+    /// like `0.1` are not exact in binary, but they are read back unchanged
+    /// and are not counted as lost
+    pub fn from_equation(equation: &Equation) -> Result<Self, ConversionError> {
+        match Self::from_equation_lossy(equation) {
+            (system, losses) if losses.is_empty() => Ok(system),
+            (_, losses) => Err(ConversionError { losses }),
+        }
+    }
+
+    /// Converts anyway, reporting what was lost
+    pub fn from_equation_lossy(equation: &Equation) -> (Self, Vec<ConversionLoss>) {
+        let mut losses = Vec::new();
+        let mut convert = |value: Decimal, location: &dyn Fn() -> String| {
+            let (converted, loss) = checked_f64(value);
+            if let Some(relative_error) = loss {
+                losses.push(ConversionLoss {
+                    location: location(),
+                    value,
+                    converted,
+                    relative_error,
+                });
+            }
+            converted
+        };
+
+        let matrix = &equation.input_matrix;
+        let matrix = DMatrix::from_fn(matrix.nrows(), matrix.ncols(), |row, column| {
+            convert(matrix[(row, column)], &|| {
+                format!("Value in row {} column {}", row + 1, column + 1)
+            })
+        });
+        let rhs = DVector::from_fn(equation.expression_rhs.len(), |row, _| {
+            convert(equation.expression_rhs[row], &|| format!("Right hand side of row {}", row + 1))
+        });
+
+        let system = F64System {
+            matrix,
+            rhs,
+            compensated: true,
+        };
+        (system, losses)
+    }
+
+    /// Sum of `terms`, compensated unless turned off. Plain sum of `n`
+    /// terms may be off by `n` roundings, which for large systems eats
+    /// several of 16 digits `f64` has
+    fn sum(&self, terms: impl Iterator<Item = f64>) -> f64 {
+        match self.compensated {
+            true => {
+                let mut sum = CompensatedSum::default();
+                terms.for_each(|term| sum.add(term));
+                sum.value()
+            }
+            false => terms.sum(),
+        }
+    }
+
+    /// `A x - b`, every row summed by [`F64System::sum`]
+    pub fn residual(&self, values: &DVector<f64>) -> DVector<f64> {
+        let size = self.rhs.len();
+        DVector::from_fn(size, |row, _| {
+            let products = (0..size).map(|column| self.matrix[(row, column)] * values[column]);
+            self.sum(products.chain([-self.rhs[row]]))
+        })
+    }
+
+    /// One Gauss-Seidel sweep over all rows, returns largest change of
+    /// component. Systems of [`BLOCKED_MIN_SIZE`] and more are swept by tiles
+    pub fn sweep(&self, values: &mut DVector<f64>) -> f64 {
+        match self.rhs.len() >= BLOCKED_MIN_SIZE {
+            true => self.blocked_sweep(values),
+            false => self.naive_sweep(values),
+        }
+    }
+
+    fn naive_sweep(&self, values: &mut DVector<f64>) -> f64 {
+        let size = self.rhs.len();
+        let mut delta = 0.0_f64;
+        for i in 0..size {
+            let s = self.sum((0..size).filter(|j| *j != i).map(|j| self.matrix[(i, j)] * values[j]));
+            let x = (self.rhs[i] - s) / self.matrix[(i, i)];
+            delta = delta.max((x - values[i]).abs());
+            values[i] = x;
+        }
+        delta
+    }
+
+    /// Same sweep with matrix read column by column. Matrix is stored by
+    /// columns, so going along row jumps over whole column on every step.
+    /// For tile of rows, columns left of it already hold new values and
+    /// columns right of it old ones, so their contributions are gathered
+    /// first from contiguous pieces of columns. Then rows of tile are
+    /// solved one by one as usual, with the small diagonal block in cache
+    fn blocked_sweep(&self, values: &mut DVector<f64>) -> f64 {
+        let size = self.rhs.len();
+        let data = self.matrix.as_slice();
+        let mut sums = [CompensatedSum::default(); TILE];
+        let mut delta = 0.0_f64;
+        for start in (0..size).step_by(TILE) {
+            let end = (start + TILE).min(size);
+            let sums = &mut sums[..end - start];
+            for (sum, rhs) in sums.iter_mut().zip(&self.rhs.as_slice()[start..end]) {
+                *sum = CompensatedSum::new(*rhs);
+            }
+            for j in (0..start).chain(end..size) {
+                let value = values[j];
+                let column = &data[j * size + start..j * size + end];
+                match self.compensated {
+                    true => {
+                        for (sum, coefficient) in sums.iter_mut().zip(column) {
+                            sum.add(-coefficient * value);
+                        }
+                    }
+                    false => {
+                        for (sum, coefficient) in sums.iter_mut().zip(column) {
+                            sum.sum -= coefficient * value;
+                        }
+                    }
+                }
+            }
+
+            for i in start..end {
+                let s = self.sum((start..end).filter(|j| *j != i).map(|j| data[j * size + i] * values[j]));
+                let x = (sums[i - start].value() - s) / data[i * size + i];
+                delta = delta.max((x - values[i]).abs());
+                values[i] = x;
+            }
+        }
+        delta
+    }
+
+    /// Iterates from approximation of all ones until change is below `epsilon`
+    pub fn solve(&self, epsilon: f64, max_iterations: usize) -> Result<F64Solution, ESolveError> {
+        let size = self.rhs.len();
+        if let Some(row) = (0..size).find(|i| self.matrix[(*i, *i)] == 0.0) {
+            return Err(ESolveError::ZeroDiagonal { row });
+        }
+
+        let mut values = DVector::from_element(size, 1.0);
+        let (mut best, mut last) = (f64::INFINITY, f64::INFINITY);
+        for iteration in 1..=max_iterations {
+            let delta = self.sweep(&mut values);
+            if !delta.is_finite() {
+                return Err(ESolveError::Diverged { growth_factor: None });
+            }
+            (best, last) = (best.min(delta), delta);
+            if delta < epsilon {
+                let residual = self.residual(&values);
+                return Ok(F64Solution {
+                    residual_norm: self.sum(residual.iter().map(|value| value * value)).sqrt(),
+                    values,
+                    iterations: iteration,
+                    delta,
+                });
+            }
+        }
+        let decimal = |value: f64| Decimal::from_f64(value).unwrap_or(Decimal::MAX);
+        Err(exhausted(decimal(best), decimal(last)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct F64Solution {
+    pub values: DVector<f64>,
+    pub iterations: usize,
+    /// Largest change of component on the last iteration
+    pub delta: f64,
+    /// Euclidean norm of `A x - b`
+    pub residual_norm: f64,
+}
+
+/// Converted value and relative error if value does not survive round trip
+fn checked_f64(value: Decimal) -> (f64, Option<Decimal>) {
+    // every Decimal is in f64 range, only digits may be lost
+    let converted = value.to_f64().expect("Decimal is within f64 range");
+    if value.is_zero() || Decimal::from_f64(converted) == Some(value) {
+        return (converted, None);
+    }
+
+    let exact = Decimal::from_f64_retain(converted).unwrap_or(value);
+    (converted, Some(((exact - value) / value).abs()))
+}
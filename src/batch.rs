@@ -0,0 +1,403 @@
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::compression;
+use crate::input::EquesionInput;
+use crate::result::{SchemaVersion, SolveResult};
+use crate::service::{self, ServiceSettings};
+use crate::solver::Method;
+
+mod problems;
+mod workers;
+
+pub use workers::{run as run_distributed, WorkerSpec};
+
+/// Progress of batch run, written one JSON object per line with `--events`
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum BatchEvent<'a> {
+    Started {
+        input: &'a str,
+        index: usize,
+        total: usize,
+    },
+    Finished {
+        input: &'a str,
+        index: usize,
+        total: usize,
+        status: &'static str,
+        elapsed_ms: u128,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        iterations: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// Input was finished by earlier run, according to resume state
+    Skipped {
+        input: &'a str,
+        index: usize,
+        total: usize,
+        status: &'a str,
+    },
+}
+
+/// Line of resume state file. Written after input is finished, so killed
+/// run loses at most the input it was solving
+#[derive(Serialize, Deserialize)]
+struct CompletedInput {
+    input: String,
+    status: String,
+}
+
+/// Inputs finished by previous runs and the file new ones are recorded to
+pub struct ResumeState {
+    path: PathBuf,
+    completed: HashMap<String, String>,
+    file: LineWriter<File>,
+}
+
+impl ResumeState {
+    /// Missing file means nothing is done yet. Unreadable lines (like
+    /// the last one, cut short by interruption) are ignored
+    pub fn open(path: &Path) -> Result<Self, BatchError> {
+        let wrap = |err| BatchError::State(path.to_owned(), err);
+        let completed = match fs::read_to_string(path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| serde_json::from_str::<CompletedInput>(line).ok())
+                .map(|completed| (completed.input, completed.status))
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(wrap(err)),
+        };
+
+        let mut file = File::options().create(true).append(true).open(path).map_err(wrap)?;
+        // previous run may have been killed in the middle of line
+        if fs::read(path).map_err(wrap)?.last().is_some_and(|byte| *byte != b'\n') {
+            writeln!(file).map_err(wrap)?;
+        }
+
+        Ok(ResumeState {
+            path: path.to_owned(),
+            completed,
+            file: LineWriter::new(file),
+        })
+    }
+
+    fn record(&mut self, input: &str, status: &str) -> Result<(), BatchError> {
+        let line = serde_json::to_string(&CompletedInput {
+            input: input.to_owned(),
+            status: status.to_owned(),
+        })
+        .expect("state consists of plain strings");
+        writeln!(self.file, "{line}").map_err(|err| BatchError::State(self.path.clone(), err))
+    }
+}
+
+#[derive(Debug)]
+pub enum BatchError {
+    Inputs(PathBuf, io::Error),
+    Events(io::Error),
+    Output(PathBuf, io::Error),
+    State(PathBuf, io::Error),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::Inputs(path, err) => write!(f, "Can't list {}: {err}", path.display()),
+            BatchError::Events(err) => write!(f, "Can't write events: {err}"),
+            BatchError::Output(path, err) => write!(f, "Can't write {}: {err}", path.display()),
+            BatchError::State(path, err) => {
+                write!(f, "Can't use resume state {}: {err}", path.display())
+            }
+        }
+    }
+}
+
+/// How many of batch inputs were solved
+pub struct BatchSummary {
+    /// Skipped inputs solved by earlier runs included
+    pub solved: usize,
+    pub total: usize,
+    pub skipped: usize,
+}
+
+impl fmt::Display for BatchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Solved {} of {} inputs", self.solved, self.total)?;
+        if self.skipped > 0 {
+            write!(f, " ({} finished by earlier runs)", self.skipped)?;
+        }
+        Ok(())
+    }
+}
+
+/// One thing to solve: input file or problem of batch file
+pub struct BatchInput {
+    /// Identifies input in progress, events and resume state
+    label: String,
+    /// Result file name without extension
+    result_name: String,
+    source: Source,
+}
+
+enum Source {
+    File(PathBuf),
+    Problem(Box<Result<(EquesionInput, Option<Method>), String>>),
+}
+
+impl BatchInput {
+    fn file(path: PathBuf) -> Self {
+        BatchInput {
+            label: path.to_string_lossy().into_owned(),
+            result_name: file_name(&path),
+            source: Source::File(path),
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Batch files (see [`problems`]) stand for problems they hold
+fn expand(path: PathBuf) -> Vec<BatchInput> {
+    let content = match fs::read(&path).and_then(compression::decompress_if_needed) {
+        Ok(content) if problems::is_batch_file(&content) => content,
+        // anything else, unreadable files included, is left to solve to report
+        _ => return vec![BatchInput::file(path)],
+    };
+
+    let label = path.to_string_lossy().into_owned();
+    let base = path.parent().unwrap_or(Path::new(""));
+    match problems::parse_batch_file(&content, base) {
+        Ok(problems) => problems
+            .into_iter()
+            .enumerate()
+            .map(|(index, problem)| BatchInput {
+                label: format!("{label}#{}", problem.name),
+                result_name: format!("{}.{}", file_name(&path), index + 1),
+                source: Source::Problem(Box::new(problem.input)),
+            })
+            .collect(),
+        Err(err) => vec![BatchInput {
+            label: label.clone(),
+            result_name: file_name(&path),
+            source: Source::Problem(Box::new(Err(format!("Invalid batch file {label}: {err}")))),
+        }],
+    }
+}
+
+/// Directories stand for regular files they hold (not recursively), in name order
+pub fn collect_inputs(paths: &[String]) -> Result<Vec<BatchInput>, BatchError> {
+    let mut inputs = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        if !path.is_dir() {
+            inputs.extend(expand(path));
+            continue;
+        }
+
+        let mut files = fs::read_dir(&path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|err| BatchError::Inputs(path.clone(), err))?;
+        files.retain(|file| file.is_file());
+        files.sort();
+        inputs.extend(files.into_iter().flat_map(expand));
+    }
+    Ok(inputs)
+}
+
+/// Records progress of batch: prints it to stderr, appends events, writes
+/// result files and resume state, counts outcomes
+struct Recorder<'a> {
+    events: Option<LineWriter<File>>,
+    output_dir: Option<&'a Path>,
+    resume: Option<ResumeState>,
+    total: usize,
+    solved: usize,
+    skipped: usize,
+}
+
+impl<'a> Recorder<'a> {
+    fn open(
+        events: Option<&Path>,
+        output_dir: Option<&'a Path>,
+        resume: Option<ResumeState>,
+        total: usize,
+    ) -> Result<Self, BatchError> {
+        let events = events
+            .map(|path| {
+                File::options()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map(LineWriter::new)
+            })
+            .transpose()
+            .map_err(BatchError::Events)?;
+        Ok(Recorder {
+            events,
+            output_dir,
+            resume,
+            total,
+            solved: 0,
+            skipped: 0,
+        })
+    }
+
+    fn emit(&mut self, event: &BatchEvent) -> Result<(), BatchError> {
+        match &mut self.events {
+            Some(events) => {
+                let line = serde_json::to_string(event).expect("event consists of plain data");
+                writeln!(events, "{line}").map_err(BatchError::Events)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Whether input was finished by earlier run, such input is recorded as skipped
+    fn skip_completed(&mut self, input: &str, position: usize) -> Result<bool, BatchError> {
+        let total = self.total;
+        let completed = self
+            .resume
+            .as_ref()
+            .and_then(|resume| resume.completed.get(input))
+            .cloned();
+        let Some(status) = completed else {
+            return Ok(false);
+        };
+        eprintln!("[{position}/{total}] {input}: {status} by earlier run, skipped");
+        self.emit(&BatchEvent::Skipped {
+            input,
+            index: position,
+            total,
+            status: &status,
+        })?;
+        self.skipped += 1;
+        if status == "solved" {
+            self.solved += 1;
+        }
+        Ok(true)
+    }
+
+    /// `on` names worker solving input, if it is not solved here
+    fn started(&mut self, input: &str, position: usize, on: Option<&str>) -> Result<(), BatchError> {
+        let total = self.total;
+        match on {
+            Some(worker) => eprintln!("[{position}/{total}] {input}: started on {worker}"),
+            None => eprintln!("[{position}/{total}] {input}: started"),
+        }
+        self.emit(&BatchEvent::Started {
+            input,
+            index: position,
+            total,
+        })
+    }
+
+    fn finished(
+        &mut self,
+        (input, result_name): (&str, &str),
+        position: usize,
+        result: &SolveResult,
+        elapsed: Duration,
+    ) -> Result<(), BatchError> {
+        let total = self.total;
+        let (iterations, message) = match result {
+            SolveResult::Solved { metadata, .. } => (Some(metadata.iterations), None),
+            SolveResult::Parametric { .. } => (None, None),
+            SolveResult::Error { message, .. } => (None, Some(message.clone())),
+        };
+        let details = match (&iterations, &message) {
+            (Some(iterations), _) => format!(" in {iterations} iterations, {elapsed:?}"),
+            (None, Some(message)) => format!(", {elapsed:?}: {}", message.replace('\n', " ")),
+            (None, None) => format!(", {elapsed:?}"),
+        };
+        eprintln!("[{position}/{total}] {input}: {}{details}", result.status());
+        if matches!(result, SolveResult::Solved { .. }) {
+            self.solved += 1;
+        }
+
+        if let Some(directory) = self.output_dir {
+            let target = directory.join(format!("{result_name}.result.json"));
+            let content = serde_json::to_string_pretty(&result.document(SchemaVersion::default()))
+                .expect("result consists of plain data")
+                + "\n";
+            fs::write(&target, content).map_err(|err| BatchError::Output(target.clone(), err))?;
+        }
+
+        self.emit(&BatchEvent::Finished {
+            input,
+            index: position,
+            total,
+            status: result.status(),
+            elapsed_ms: elapsed.as_millis(),
+            iterations,
+            message,
+        })?;
+
+        if let Some(resume) = &mut self.resume {
+            resume.record(input, result.status())?;
+        }
+        Ok(())
+    }
+
+    fn summary(&self) -> BatchSummary {
+        BatchSummary {
+            solved: self.solved,
+            total: self.total,
+            skipped: self.skipped,
+        }
+    }
+}
+
+/// Solves inputs one by one. Progress is printed to stderr and, when `events`
+/// is given, appended to it as NDJSON. Results are written to `output_dir`
+/// as `<input file name>.result.json` (`<batch file name>.<problem>.result.json`
+/// for problems of batch files). Inputs completed according to `resume`
+/// are skipped, newly finished ones are recorded there
+pub fn run(
+    inputs: Vec<BatchInput>,
+    settings: &ServiceSettings,
+    events: Option<&Path>,
+    output_dir: Option<&Path>,
+    resume: Option<ResumeState>,
+) -> Result<BatchSummary, BatchError> {
+    let mut recorder = Recorder::open(events, output_dir, resume, inputs.len())?;
+    for (index, batch_input) in inputs.into_iter().enumerate() {
+        let position = index + 1;
+        if recorder.skip_completed(&batch_input.label, position)? {
+            continue;
+        }
+        recorder.started(&batch_input.label, position, None)?;
+
+        let started = Instant::now();
+        // one broken input must not take the rest of the run down with it
+        let solve = || match batch_input.source {
+            Source::File(path) => service::solve_file(&path, settings),
+            Source::Problem(problem) => match *problem {
+                Ok((parsed, method)) => {
+                    service::solve_input(parsed, method, settings, &settings.limits, |_| {}, |_, _| {})
+                }
+                Err(message) => SolveResult::error(message),
+            },
+        };
+        let result = service::catch_crash(solve);
+        let names = (batch_input.label.as_str(), batch_input.result_name.as_str());
+        recorder.finished(names, position, &result, started.elapsed())?;
+    }
+
+    Ok(recorder.summary())
+}
@@ -0,0 +1,203 @@
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::compression;
+use crate::input::{parse_document, EquesionInput};
+use crate::solver::Method;
+
+/// Several problems in one file, each one inheriting settings it does not set:
+///
+/// ```json
+/// {
+///   "defaults": { "epsilon": "0.001", "max_iterations": 64, "method": "gauss-seidel" },
+///   "problems": [
+///     { "name": "task 1", "input_matrix": [["2", "1"], ["1", "3"]], "expression_rhs": ["3", "4"] },
+///     { "name": "task 2", "input_file": "task2.json", "epsilon": "0.000001" }
+///   ]
+/// }
+/// ```
+///
+/// Problem settings take precedence over batch defaults, which take precedence
+/// over settings of referenced input file. Relative `input_file` paths
+/// are resolved against batch file location
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchFile {
+    #[serde(default)]
+    defaults: ProblemSettings,
+    /// Parsed one by one, so mistake is reported for problem it is made in
+    problems: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+struct ProblemSettings {
+    epsilon: Option<String>,
+    max_iterations: Option<usize>,
+    method: Option<String>,
+}
+
+impl ProblemSettings {
+    fn or(self, defaults: &ProblemSettings) -> ProblemSettings {
+        ProblemSettings {
+            epsilon: self.epsilon.or_else(|| defaults.epsilon.clone()),
+            max_iterations: self.max_iterations.or(defaults.max_iterations),
+            method: self.method.or_else(|| defaults.method.clone()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchProblem {
+    /// Taken before parsing, so errors can name the problem
+    #[serde(rename = "name")]
+    _name: Option<IgnoredAny>,
+    epsilon: Option<String>,
+    max_iterations: Option<usize>,
+    method: Option<String>,
+    input_matrix: Option<Vec<Vec<String>>>,
+    expression_rhs: Option<Vec<String>>,
+    input_file: Option<String>,
+    /// Replaces fixed variables of referenced input file
+    #[serde(default)]
+    fixed: BTreeMap<String, String>,
+    /// Replace bounds of referenced input file
+    #[serde(default)]
+    lower: BTreeMap<String, String>,
+    #[serde(default)]
+    upper: BTreeMap<String, String>,
+}
+
+/// Cheap look at document to find out whether it is a batch file
+#[derive(Deserialize)]
+struct BatchProbe {
+    problems: Option<IgnoredAny>,
+}
+
+pub fn is_batch_file(content: &[u8]) -> bool {
+    serde_json::from_slice::<BatchProbe>(content)
+        .map(|probe| probe.problems.is_some())
+        .unwrap_or(false)
+}
+
+/// Problem ready to be solved, or explanation why it can't be
+pub struct Problem {
+    /// Name given in batch file, or position in it
+    pub name: String,
+    pub input: Result<(EquesionInput, Option<Method>), String>,
+}
+
+/// Whole file is rejected only if it is not a batch file at all.
+/// Mistakes in single problem are reported for that problem
+pub fn parse_batch_file(content: &[u8], base: &Path) -> Result<Vec<Problem>, String> {
+    let batch: BatchFile = serde_json::from_slice(content).map_err(|err| err.to_string())?;
+
+    Ok(batch
+        .problems
+        .into_iter()
+        .enumerate()
+        .map(|(index, problem)| {
+            let name = problem
+                .get("name")
+                .and_then(|name| name.as_str())
+                .map(str::to_owned)
+                .unwrap_or_else(|| format!("problem {}", index + 1));
+            let input = serde_json::from_value(problem)
+                .map_err(|err| err.to_string())
+                .and_then(|problem| resolve(problem, &batch.defaults, base))
+                .map_err(|err| format!("Problem {} (\"{name}\"): {err}", index + 1));
+            Problem { name, input }
+        })
+        .collect())
+}
+
+fn resolve(
+    problem: BatchProblem,
+    defaults: &ProblemSettings,
+    base: &Path,
+) -> Result<(EquesionInput, Option<Method>), String> {
+    let settings = ProblemSettings {
+        epsilon: problem.epsilon,
+        max_iterations: problem.max_iterations,
+        method: problem.method,
+    }
+    .or(defaults);
+
+    let mut input = match (problem.input_file, problem.input_matrix, problem.expression_rhs) {
+        (Some(file), None, None) => {
+            let path = base.join(&file);
+            let content = std::fs::read(&path)
+                .and_then(compression::decompress_if_needed)
+                .map_err(|err| format!("can't read {}: {err}", path.display()))?;
+            let base = path.parent().unwrap_or(Path::new(""));
+            parse_document(content, base).map_err(|err| err.to_string().trim_end().to_owned())?
+        }
+        (None, Some(input_matrix), Some(expression_rhs)) => EquesionInput {
+            input_matrix,
+            expression_rhs,
+            max_iterations: settings
+                .max_iterations
+                .ok_or("max_iterations is set neither by problem nor by batch defaults")?,
+            epsilon: settings
+                .epsilon
+                .clone()
+                .ok_or("epsilon is set neither by problem nor by batch defaults")?,
+            schur_block: None,
+            fixed: BTreeMap::new(),
+            lower: BTreeMap::new(),
+            upper: BTreeMap::new(),
+            blocks: Vec::new(),
+            grid: None,
+            method: None,
+            refinement_steps: None,
+            tolerance_mode: None,
+            norm: None,
+            initial_guess: None,
+        },
+        (Some(_), _, _) => {
+            return Err(
+                "either input_file or input_matrix with expression_rhs is expected, not both"
+                    .to_owned(),
+            )
+        }
+        _ => {
+            return Err("input_matrix and expression_rhs (or input_file) are required".to_owned())
+        }
+    };
+
+    if !problem.fixed.is_empty() {
+        input.fixed = problem.fixed;
+    }
+    if !problem.lower.is_empty() || !problem.upper.is_empty() {
+        input.lower = problem.lower;
+        input.upper = problem.upper;
+    }
+    if let Some(max_iterations) = settings.max_iterations {
+        input.max_iterations = max_iterations;
+    }
+    if let Some(epsilon) = settings.epsilon {
+        input.epsilon = epsilon;
+    }
+
+    match Decimal::from_str(&input.epsilon) {
+        Ok(epsilon) if epsilon > Decimal::ZERO => (),
+        _ => return Err(format!("epsilon \"{}\" is not a positive number", input.epsilon)),
+    }
+
+    // method of problem or defaults overrides the one of its input file
+    let method = match settings.method {
+        Some(method) => Some(
+            method
+                .parse()
+                .map_err(|err| format!("method \"{method}\" is unknown! {err}"))?,
+        ),
+        None => None,
+    };
+
+    Ok((input, method))
+}
@@ -0,0 +1,292 @@
+//! Batch solved by worker processes, possibly on other machines. Worker
+//! speaks line protocol of daemon on its stdin and stdout (`solver worker`,
+//! started over SSH or any other command) or over TCP (`solver worker
+//! --listen`). Inputs are resolved by coordinator, so workers need neither
+//! input files nor files manifests refer to
+
+use core::fmt;
+use core::str::FromStr;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{BatchError, BatchInput, BatchSummary, Recorder, ResumeState, Source};
+use crate::compression;
+use crate::input::parse_document;
+use crate::result::SolveResult;
+
+/// Where worker runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkerSpec {
+    /// Shell command speaking protocol on its stdin and stdout, like
+    /// `ssh node1 solver worker`
+    Command(String),
+    /// Address of `solver worker --listen`, written as `tcp://node1:7878`
+    Tcp(String),
+}
+
+impl FromStr for WorkerSpec {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.strip_prefix("tcp://") {
+            Some("") => Err("Expected address after tcp://, like tcp://node1:7878".to_owned()),
+            Some(address) => Ok(WorkerSpec::Tcp(address.to_owned())),
+            None if value.trim().is_empty() => Err("Expected command starting worker".to_owned()),
+            None => Ok(WorkerSpec::Command(value.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for WorkerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerSpec::Command(command) => write!(f, "`{command}`"),
+            WorkerSpec::Tcp(address) => write!(f, "tcp://{address}"),
+        }
+    }
+}
+
+/// Open connection to worker
+struct Connection {
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Option<Child>,
+}
+
+impl Connection {
+    fn open(spec: &WorkerSpec) -> io::Result<Self> {
+        match spec {
+            WorkerSpec::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                Ok(Connection {
+                    reader: Box::new(BufReader::new(stream.try_clone()?)),
+                    writer: Box::new(stream),
+                    child: None,
+                })
+            }
+            WorkerSpec::Command(command) => {
+                let mut child = Command::new("sh")
+                    .arg("-c")
+                    .arg(command)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let stdin = child.stdin.take().expect("stdin is piped");
+                let stdout = child.stdout.take().expect("stdout is piped");
+                Ok(Connection {
+                    reader: Box::new(BufReader::new(stdout)),
+                    writer: Box::new(stdin),
+                    child: Some(child),
+                })
+            }
+        }
+    }
+
+    /// Sends one document, waits for its result
+    fn solve(&mut self, document: &str) -> io::Result<SolveResult> {
+        self.writer.write_all(document.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "worker closed connection"));
+        }
+        serde_json::from_str(&line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // closed stdin tells worker to exit, lost one may hang instead
+        self.writer = Box::new(io::sink());
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Input as single line document, or why it failed before solving
+fn document(source: Source) -> Result<String, String> {
+    let parsed = match source {
+        Source::File(path) => {
+            let content = fs::read(&path)
+                .and_then(compression::decompress_if_needed)
+                .map_err(|err| format!("Can't read {}: {err}", path.display()))?;
+            let base = path.parent().unwrap_or(Path::new(""));
+            parse_document(content, base).map_err(|err| err.to_string())?
+        }
+        Source::Problem(problem) => {
+            let (mut parsed, method) = (*problem)?;
+            if let Some(method) = method {
+                parsed.method = Some(method.to_string());
+            }
+            parsed
+        }
+    };
+    Ok(serde_json::to_string(&parsed).expect("input consists of plain data"))
+}
+
+struct Job {
+    position: usize,
+    document: String,
+    /// Worker was lost while solving it. Job fails if the next one is lost too
+    retried: bool,
+}
+
+enum Message {
+    Started { position: usize, worker: usize },
+    Finished { position: usize, result: SolveResult, elapsed: Duration },
+    /// Worker is gone, its jobs are taken by the rest
+    Lost { worker: usize, reason: String },
+}
+
+/// Same as [`super::run`], but every input is solved by the next free worker.
+/// Worker which fails is dropped, input it was solving goes to another one,
+/// once: input which takes down two workers fails. Inputs left when no
+/// worker remains fail too
+pub fn run(
+    inputs: Vec<BatchInput>,
+    workers: &[WorkerSpec],
+    events: Option<&Path>,
+    output_dir: Option<&Path>,
+    resume: Option<ResumeState>,
+) -> Result<BatchSummary, BatchError> {
+    let mut recorder = Recorder::open(events, output_dir, resume, inputs.len())?;
+    let mut names: Vec<(String, String)> = Vec::with_capacity(inputs.len());
+    let mut jobs = VecDeque::new();
+    for (index, batch_input) in inputs.into_iter().enumerate() {
+        let position = index + 1;
+        names.push((batch_input.label.clone(), batch_input.result_name.clone()));
+        if recorder.skip_completed(&batch_input.label, position)? {
+            continue;
+        }
+        match document(batch_input.source) {
+            Ok(document) => jobs.push_back(Job {
+                position,
+                document,
+                retried: false,
+            }),
+            Err(message) => {
+                let result = SolveResult::error(message);
+                let (label, result_name) = &names[index];
+                recorder.started(label, position, None)?;
+                recorder.finished((label, result_name), position, &result, Duration::ZERO)?;
+            }
+        }
+    }
+
+    let queue = Arc::new((Mutex::new(Queue { jobs, in_flight: 0 }), Condvar::new()));
+    let (sender, receiver) = mpsc::channel();
+    let handles: Vec<_> = (workers.iter().cloned().enumerate())
+        .map(|(worker, spec)| {
+            let queue = Arc::clone(&queue);
+            let sender = sender.clone();
+            thread::spawn(move || serve_queue(worker, &spec, &queue, &sender))
+        })
+        .collect();
+    drop(sender);
+
+    let mut alive = workers.len();
+    for message in receiver {
+        match message {
+            Message::Started { position, worker } => {
+                let on = format!("worker {} {}", worker + 1, workers[worker]);
+                recorder.started(&names[position - 1].0, position, Some(&on))?;
+            }
+            Message::Finished { position, result, elapsed } => {
+                let (label, result_name) = &names[position - 1];
+                recorder.finished((label, result_name), position, &result, elapsed)?;
+            }
+            Message::Lost { worker, reason } => {
+                alive -= 1;
+                eprintln!("Worker {} {} is dropped: {reason}, {alive} left", worker + 1, workers[worker]);
+            }
+        }
+    }
+    for handle in handles {
+        handle.join().expect("worker thread does not panic");
+    }
+
+    // every worker is gone
+    let left: Vec<Job> = queue.0.lock().expect("queue is not poisoned").jobs.drain(..).collect();
+    for job in left {
+        let (label, result_name) = &names[job.position - 1];
+        let result = SolveResult::error("No worker is left to solve input");
+        recorder.finished((label, result_name), job.position, &result, Duration::ZERO)?;
+    }
+
+    Ok(recorder.summary())
+}
+
+/// Jobs not taken yet and how many are being solved: job of lost worker
+/// comes back, so idle workers wait for busy ones before leaving
+struct Queue {
+    jobs: VecDeque<Job>,
+    in_flight: usize,
+}
+
+/// Takes jobs until all of them are done or connection to worker fails
+fn serve_queue(worker: usize, spec: &WorkerSpec, queue: &(Mutex<Queue>, Condvar), sender: &mpsc::Sender<Message>) {
+    let (state, changed) = queue;
+    let lost = |reason: String| {
+        let _ = sender.send(Message::Lost { worker, reason });
+    };
+    let mut connection = match Connection::open(spec) {
+        Ok(connection) => connection,
+        Err(err) => return lost(format!("can't start it: {err}")),
+    };
+
+    loop {
+        let job = {
+            let mut state = state.lock().expect("queue is not poisoned");
+            while state.jobs.is_empty() && state.in_flight > 0 {
+                state = changed.wait(state).expect("queue is not poisoned");
+            }
+            let Some(job) = state.jobs.pop_front() else {
+                return;
+            };
+            state.in_flight += 1;
+            job
+        };
+
+        let _ = sender.send(Message::Started {
+            position: job.position,
+            worker,
+        });
+        let started = Instant::now();
+        let outcome = connection.solve(&job.document);
+        let mut state = state.lock().expect("queue is not poisoned");
+        state.in_flight -= 1;
+        changed.notify_all();
+        match outcome {
+            Ok(result) => {
+                let _ = sender.send(Message::Finished {
+                    position: job.position,
+                    result,
+                    elapsed: started.elapsed(),
+                });
+            }
+            Err(err) if job.retried => {
+                let _ = sender.send(Message::Finished {
+                    position: job.position,
+                    result: SolveResult::error(format!("Two workers were lost solving input, the last one: {err}")),
+                    elapsed: started.elapsed(),
+                });
+                return lost(err.to_string());
+            }
+            Err(err) => {
+                state.jobs.push_front(Job { retried: true, ..job });
+                return lost(err.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! C interface, see `include/solver.h`. Kept small on purpose: every function
+//! here is part of stable ABI and changes only by adding new functions.
+//! Panics never cross the boundary, they are reported as failures
+
+use std::collections::BTreeMap;
+use std::ffi::{c_char, c_double, CStr, CString};
+use std::ops::ControlFlow;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+
+use crate::input::{build_equation, EquesionInput};
+use crate::result::SchemaVersion;
+use crate::service::{self, ServiceSettings};
+
+pub const SOLVER_OK: i32 = 0;
+/// Iterations did not converge within `max_iterations`: delta grew, or was
+/// still shrinking when they ran out. Both kept this code when failures of
+/// solver got told apart, callers relying on it keep working
+pub const SOLVER_DIVERGED: i32 = 1;
+/// Null pointer, zero size, size whose matrix does not fit into memory,
+/// non-finite number or invalid system (like zero on diagonal)
+pub const SOLVER_INVALID_ARGUMENT: i32 = 2;
+/// Any other failure
+pub const SOLVER_FAILED: i32 = 3;
+/// Matrix is singular or numerically singular, no unique solution exists
+pub const SOLVER_SINGULAR: i32 = 4;
+/// Delta stopped decreasing above epsilon long before `max_iterations`
+pub const SOLVER_STAGNATED: i32 = 5;
+/// Numbers outgrew Decimal during iterations, coefficients are too large
+pub const SOLVER_OVERFLOW: i32 = 6;
+
+/// Solves input document written in the schema `solver` binary reads and
+/// returns result document, the same as `solver --format json` writes.
+/// Returns null only when `input` is null or not UTF-8.
+///
+/// # Safety
+///
+/// `input` must be null or point to null-terminated string. Returned string
+/// must be released with [`solver_string_free`]
+#[no_mangle]
+pub unsafe extern "C" fn solver_solve_json(input: *const c_char) -> *mut c_char {
+    if input.is_null() {
+        return ptr::null_mut();
+    }
+    let Ok(input) = CStr::from_ptr(input).to_str() else {
+        return ptr::null_mut();
+    };
+
+    let content = input.as_bytes().to_vec();
+    let result = catch_unwind(|| {
+        let result = service::solve_local_document(content, &ServiceSettings::default());
+        serde_json::to_string(&result.document(SchemaVersion::default())).expect("result consists of plain data")
+    })
+    .unwrap_or_else(|_| r#"{"status":"error","message":"Solver panicked"}"#.to_owned());
+
+    CString::new(result).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Releases string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or returned by this library and not released yet
+#[no_mangle]
+pub unsafe extern "C" fn solver_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Solves `n` x `n` system given as row-major `matrix` and `rhs` by Gauss-Seidel
+/// and writes `n` values to `solution`. Returns one of `SOLVER_*` codes,
+/// `solution` is written only on `SOLVER_OK`.
+///
+/// Doubles are converted to decimals by their shortest representation,
+/// so `0.1` is solved as exactly `0.1`.
+///
+/// # Safety
+///
+/// `matrix` must point to `n * n` doubles, `rhs` and `solution` to `n` doubles each
+#[no_mangle]
+pub unsafe extern "C" fn solver_solve_dense(
+    n: usize,
+    matrix: *const c_double,
+    rhs: *const c_double,
+    epsilon: c_double,
+    max_iterations: usize,
+    solution: *mut c_double,
+) -> i32 {
+    if n == 0 || matrix.is_null() || rhs.is_null() || solution.is_null() {
+        return SOLVER_INVALID_ARGUMENT;
+    }
+    // no allocation may be larger than isize::MAX bytes, so no slice either
+    let Some(entries) = (n.checked_mul(n))
+        .filter(|entries| entries.checked_mul(std::mem::size_of::<c_double>()).is_some_and(|bytes| bytes <= isize::MAX as usize))
+    else {
+        return SOLVER_INVALID_ARGUMENT;
+    };
+    let matrix = std::slice::from_raw_parts(matrix, entries);
+    let rhs = std::slice::from_raw_parts(rhs, n);
+    let solution = std::slice::from_raw_parts_mut(solution, n);
+
+    catch_unwind(AssertUnwindSafe(|| solve_dense(n, matrix, rhs, epsilon, max_iterations, solution)))
+        .unwrap_or(SOLVER_FAILED)
+}
+
+fn solve_dense(
+    n: usize,
+    matrix: &[f64],
+    rhs: &[f64],
+    epsilon: f64,
+    max_iterations: usize,
+    solution: &mut [f64],
+) -> i32 {
+    let decimal = |value: f64| Decimal::from_f64(value).map(|value| value.to_string());
+    let (Some(input_matrix), Some(expression_rhs), Some(epsilon)) = (
+        matrix.chunks(n).map(|row| row.iter().map(|value| decimal(*value)).collect()).collect(),
+        rhs.iter().map(|value| decimal(*value)).collect(),
+        decimal(epsilon),
+    ) else {
+        return SOLVER_INVALID_ARGUMENT;
+    };
+
+    let input = EquesionInput {
+        input_matrix,
+        expression_rhs,
+        max_iterations,
+        epsilon,
+        schur_block: None,
+        fixed: BTreeMap::new(),
+        lower: BTreeMap::new(),
+        upper: BTreeMap::new(),
+        blocks: Vec::new(),
+        grid: None,
+        method: None,
+        refinement_steps: None,
+        tolerance_mode: None,
+        norm: None,
+        initial_guess: None,
+    };
+    let Ok(equation) = build_equation(input, None) else {
+        return SOLVER_INVALID_ARGUMENT;
+    };
+
+    match equation.solve_with_progress(|_| ControlFlow::Continue(())) {
+        Ok(result) => {
+            for (target, value) in solution.iter_mut().zip(result.iter()) {
+                *target = value.to_f64().unwrap_or(f64::NAN);
+            }
+            SOLVER_OK
+        }
+        Err(
+            crate::solver::ESolveError::Diverged { .. } | crate::solver::ESolveError::MaxIterationsExceeded { .. },
+        ) => SOLVER_DIVERGED,
+        Err(crate::solver::ESolveError::Singular { .. }) => SOLVER_SINGULAR,
+        Err(crate::solver::ESolveError::Stagnated { .. }) => SOLVER_STAGNATED,
+        Err(crate::solver::ESolveError::NumericOverflow { .. }) => SOLVER_OVERFLOW,
+        Err(_) => SOLVER_FAILED,
+    }
+}
+
+/// Version of the library, static string which must not be released
+#[no_mangle]
+pub extern "C" fn solver_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
@@ -0,0 +1,833 @@
+use core::fmt;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use guess_zeidel_method::batch::WorkerSpec;
+use guess_zeidel_method::compression::Compression;
+use guess_zeidel_method::input::InputOptions;
+use guess_zeidel_method::memory::parse_byte_size;
+use guess_zeidel_method::norm::Norm;
+use guess_zeidel_method::parallel::{Parallelism, SolverPool};
+use guess_zeidel_method::preprocess::Reordering;
+use guess_zeidel_method::report::{NumberFormat, ReportFormat};
+use guess_zeidel_method::result::SchemaVersion;
+use guess_zeidel_method::server::AccessPolicy;
+use guess_zeidel_method::service::ServiceSettings;
+use guess_zeidel_method::decomposition::DumpFormat;
+use guess_zeidel_method::fingerprint::CanonicalOptions;
+use guess_zeidel_method::study::ParamRange;
+use guess_zeidel_method::template::parse_param;
+use guess_zeidel_method::solver::{
+    Acceleration, InitialGuess, Method, Preconditioning, Rounding, ToleranceMode, DEFAULT_AITKEN_PERIOD,
+};
+
+#[derive(Debug)]
+pub enum Command {
+    /// Boxed, solve takes far more options than other commands
+    Solve(Box<SolveArguments>),
+    Redact(RedactArguments),
+    Daemon(DaemonArguments),
+    Serve(ServeArguments),
+    OpenApi,
+    Diff(DiffArguments),
+    Lint(LintArguments),
+    Hash(HashArguments),
+    Replay(ReplayArguments),
+    Batch(BatchArguments),
+    Worker(WorkerArguments),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "plain" => Ok(OutputFormat::Plain),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err("Expected one of: plain, json".to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SolveArguments {
+    pub input: InputOptions,
+    /// Validate input and report what solve would take without solving
+    pub dry_run: bool,
+    pub parallelism: Parallelism,
+    pub format: ReportFormat,
+    /// Schema json result is written in
+    pub schema: SchemaVersion,
+    /// Separators and notation of numbers in every format but json
+    pub number_format: NumberFormat,
+    /// Write result here instead of stdout
+    pub output: Option<String>,
+    /// Columns plain and table formats fit into, 0 for no limit. Width of
+    /// terminal by default
+    pub width: Option<usize>,
+    /// Render result through this template instead of `format`
+    pub output_template: Option<PathBuf>,
+    /// Golden result file solution must match
+    pub assert_matches: Option<String>,
+    /// Allowed deviation from golden result. Epsilon of input is used if absent
+    pub tolerance: Option<Decimal>,
+    /// Describe all solutions of rank-deficient system instead of failing
+    pub parametric: bool,
+    /// Renumber unknowns before solving, solution is reported in original order
+    pub reordering: Option<Reordering>,
+    /// Solve independent subsystems of reducible system separately
+    pub split_components: bool,
+    /// Scale rows and columns before solving, solution is reported unscaled
+    pub equilibrate: bool,
+    /// Overrides method of input
+    pub method: Option<Method>,
+    /// Relaxation factor of Gauss-Seidel and SSOR sweeps
+    pub relaxation: Option<Decimal>,
+    /// Report inverse of matrix besides solution
+    pub inverse: bool,
+    /// Report determinant of matrix besides solution
+    pub determinant: bool,
+    /// Overrides refinement steps of input
+    pub refinement_steps: Option<usize>,
+    /// Largest relative change of coefficients of perturbed system solved
+    /// besides the given one, see [`guess_zeidel_method::jitter`]
+    pub jitter: Option<Decimal>,
+    /// Seed of `jitter`, random when absent
+    pub seed: Option<u64>,
+    /// Parameter of Richardson iteration
+    pub tau: Option<Decimal>,
+    /// Chebyshev acceleration or Aitken extrapolation of stationary method
+    pub acceleration: Option<Acceleration>,
+    /// Preconditioner of Krylov methods
+    pub preconditioning: Option<Preconditioning>,
+    /// Iterations after which GMRES restarts
+    pub restart: Option<usize>,
+    /// Rounding of products in row sums
+    pub rounding: Option<Rounding>,
+    /// Overrides tolerance mode of input
+    pub tolerance_mode: Option<ToleranceMode>,
+    /// Overrides norm of input
+    pub norm: Option<Norm>,
+    /// Overrides initial guess of input
+    pub initial_guess: Option<InitialGuess>,
+    /// Keep chosen method even when faster one applies to the matrix
+    pub no_fast_path: bool,
+    /// Agree to transformations of the system without asking
+    pub yes: bool,
+    /// Epsilons to converge to one after another, the last one replaces epsilon of input
+    pub epsilon_schedule: Vec<Decimal>,
+    /// Iterations to write approximation at
+    pub snapshot_at: Option<Vec<usize>>,
+    pub snapshot_dir: Option<PathBuf>,
+    /// Event log of solve, see [`guess_zeidel_method::replay`]
+    pub record: Option<PathBuf>,
+    /// Solve templated input for every parameter value of range instead of once
+    pub param_range: Option<ParamRange>,
+    /// Solve by this direct method besides iterative one and compare, see
+    /// [`guess_zeidel_method::comparison`]
+    pub compare: Option<Method>,
+    /// Directory splitting, iteration matrix and factors are written to
+    pub dump_decomposition: Option<PathBuf>,
+    pub dump_format: DumpFormat,
+}
+
+#[derive(Debug, Default)]
+pub struct RedactArguments {
+    pub input: InputOptions,
+    pub output: Option<String>,
+    pub compress: Option<Compression>,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct LintArguments {
+    pub input: InputOptions,
+}
+
+#[derive(Debug, Default)]
+pub struct HashArguments {
+    pub input: InputOptions,
+    /// Print canonical form instead of its hash
+    pub canonical: bool,
+    pub options: CanonicalOptions,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayArguments {
+    pub log: PathBuf,
+    pub format: ReportFormat,
+    pub number_format: NumberFormat,
+    pub output: Option<String>,
+    /// See [`SolveArguments::width`]
+    pub width: Option<usize>,
+    /// Print table of logged iterations instead of report
+    pub history: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct DiffArguments {
+    pub first: String,
+    pub second: String,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Default)]
+pub struct BatchArguments {
+    /// Input files and directories holding them
+    pub inputs: Vec<String>,
+    pub settings: ServiceSettings,
+    /// NDJSON file progress events are appended to
+    pub events: Option<PathBuf>,
+    /// Directory per-input result files are written to
+    pub output_dir: Option<PathBuf>,
+    /// File which keeps track of finished inputs across runs
+    pub resume_state: Option<PathBuf>,
+    /// Inputs are sent to these instead of being solved here
+    pub workers: Vec<WorkerSpec>,
+}
+
+#[derive(Debug, Default)]
+pub struct WorkerArguments {
+    /// TCP address to serve on, stdin and stdout are served when absent
+    pub listen: Option<String>,
+    pub settings: ServiceSettings,
+}
+
+#[derive(Debug, Default)]
+pub struct DaemonArguments {
+    pub socket: Option<String>,
+    pub settings: ServiceSettings,
+}
+
+pub const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:8080";
+
+/// Narrowest `--width` besides 0
+const MIN_WIDTH: usize = 20;
+
+#[derive(Debug)]
+pub struct ServeArguments {
+    pub listen: String,
+    /// Amount of jobs solved at the same time
+    pub concurrency: usize,
+    pub settings: ServiceSettings,
+    pub policy: AccessPolicy,
+}
+
+impl Default for ServeArguments {
+    fn default() -> Self {
+        ServeArguments {
+            listen: DEFAULT_LISTEN_ADDRESS.to_owned(),
+            concurrency: 1,
+            settings: ServiceSettings::default(),
+            policy: AccessPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ArgumentError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue {
+        option: String,
+        value: String,
+        message: String,
+    },
+    UnexpectedArgument(String),
+    MissingOption(&'static str),
+    MissingArgument(&'static str),
+}
+
+impl fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgumentError::UnknownOption(option) => writeln!(f, "Unknown option {option}!"),
+            ArgumentError::MissingValue(option) => {
+                writeln!(f, "Option {option} requires a value!")
+            }
+            ArgumentError::InvalidValue {
+                option,
+                value,
+                message,
+            } => writeln!(f, "Invalid value \"{value}\" for option {option}! {message}"),
+            ArgumentError::UnexpectedArgument(argument) => {
+                writeln!(f, "Unexpected argument \"{argument}\"!")
+            }
+            ArgumentError::MissingOption(option) => writeln!(f, "Option {option} is required!"),
+            ArgumentError::MissingArgument(what) => writeln!(f, "Expected {what}!"),
+        }
+    }
+}
+
+/// Walks over command line arguments, handing out option values
+struct ArgumentCursor<I: Iterator<Item = String>> {
+    arguments: I,
+}
+
+impl<I: Iterator<Item = String>> ArgumentCursor<I> {
+    fn next_argument(&mut self) -> Option<String> {
+        self.arguments.next()
+    }
+
+    fn value_for(&mut self, option: &str) -> Result<String, ArgumentError> {
+        self.arguments
+            .next()
+            .ok_or_else(|| ArgumentError::MissingValue(option.to_owned()))
+    }
+
+    fn value_with<T>(
+        &mut self,
+        option: &str,
+        parser: impl FnOnce(&str) -> Result<T, String>,
+    ) -> Result<T, ArgumentError> {
+        let value = self.value_for(option)?;
+        parser(&value).map_err(|message| ArgumentError::InvalidValue {
+            option: option.to_owned(),
+            value,
+            message,
+        })
+    }
+
+    fn parsed_value_for<T>(&mut self, option: &str) -> Result<T, ArgumentError>
+    where
+        T: std::str::FromStr,
+        T::Err: fmt::Display,
+    {
+        let value = self.value_for(option)?;
+        value
+            .parse()
+            .map_err(|err: T::Err| ArgumentError::InvalidValue {
+                option: option.to_owned(),
+                value,
+                message: err.to_string(),
+            })
+    }
+}
+
+/// Columns of output, 0 turns fitting off. Narrower output leaves no room for tables
+fn parse_width(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(columns) if columns == 0 || columns >= MIN_WIDTH => Ok(columns),
+        _ => Err(format!("Expected 0 or at least {MIN_WIDTH} columns")),
+    }
+}
+
+/// Accepts `5s`, `500ms`, `2m` or plain amount of seconds
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (amount, unit) = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .map_or((value, "s"), |position| value.split_at(position));
+
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| "Expected duration like 5s, 500ms or 2m".to_owned())?;
+
+    let seconds = match unit {
+        "ms" => amount / 1000.0,
+        "s" => amount,
+        "m" => amount * 60.0,
+        _ => return Err(format!("Unknown duration unit \"{unit}\"")),
+    };
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Accepts both plain (`0.001`) and scientific (`1e-6`) notation
+fn parse_decimal(value: &str) -> Result<Decimal, String> {
+    Decimal::from_str(value)
+        .or_else(|_| Decimal::from_scientific(value))
+        .map_err(|err| err.to_string())
+}
+
+/// Relative perturbation, above 0 and below 1
+fn parse_jitter(value: &str) -> Result<Decimal, String> {
+    match parse_decimal(value) {
+        Ok(relative) if relative > Decimal::ZERO && relative < Decimal::ONE => Ok(relative),
+        _ => Err("Expected relative perturbation above 0 and below 1, like 1e-9".to_owned()),
+    }
+}
+
+/// Method of direct side of `--compare`
+fn parse_direct_method(value: &str) -> Result<Method, String> {
+    match Method::from_str(value)? {
+        method if method.is_direct() => Ok(method),
+        _ => Err("Expected one of direct methods: gauss, gauss-jordan, lu, cholesky, qr".to_owned()),
+    }
+}
+
+fn parse_positive(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err("Expected positive number".to_owned()),
+        Ok(amount) => Ok(amount),
+    }
+}
+
+fn parse_aitken_period(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(period) if period >= 3 => Ok(period),
+        _ => Err("Aitken extrapolation needs at least 3 iterations between extrapolations".to_owned()),
+    }
+}
+
+/// `<lower>:<upper>`, bounds of real spectrum of iteration matrix
+fn parse_spectrum(value: &str) -> Result<(Decimal, Decimal), String> {
+    let (lower, upper) = value
+        .split_once(':')
+        .ok_or_else(|| "Expected <lower>:<upper>".to_owned())?;
+    let (lower, upper) = (parse_decimal(lower)?, parse_decimal(upper)?);
+    if lower >= upper || upper >= Decimal::ONE {
+        return Err("Expected lower bound below upper one, and upper one below 1".to_owned());
+    }
+    Ok((lower, upper))
+}
+
+fn parse_separator(value: &str) -> Result<char, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(separator), None) if !separator.is_ascii_digit() => Ok(separator),
+        _ => Err("Expected single character which is not digit".to_owned()),
+    }
+}
+
+/// Comma separated positive numbers like `1e-2,1e-4`
+fn parse_decimal_list(value: &str) -> Result<Vec<Decimal>, String> {
+    value
+        .split(',')
+        .map(|item| match parse_decimal(item.trim()) {
+            Ok(value) if value.is_sign_positive() && !value.is_zero() => Ok(value),
+            Ok(_) => Err("Expected positive numbers".to_owned()),
+            Err(err) => Err(err),
+        })
+        .collect()
+}
+
+/// Comma separated iteration numbers like `1,5,10`
+fn parse_iteration_list(value: &str) -> Result<Vec<usize>, String> {
+    value
+        .split(',')
+        .map(|item| parse_positive(item.trim()))
+        .collect()
+}
+
+/// Returns `false` if argument is not one of settings shared by long-lived modes
+fn parse_service_argument<I: Iterator<Item = String>>(
+    cursor: &mut ArgumentCursor<I>,
+    settings: &mut ServiceSettings,
+    argument: &str,
+) -> Result<bool, ArgumentError> {
+    match argument {
+        "--max-memory" => {
+            settings.max_memory = Some(cursor.value_with(argument, parse_byte_size)?)
+        }
+        "--max-matrix-size" => {
+            settings.limits.max_matrix_size = Some(cursor.value_with(argument, parse_positive)?)
+        }
+        "--max-iterations" => {
+            settings.limits.max_iterations = Some(cursor.value_with(argument, parse_positive)?)
+        }
+        "--request-timeout" => {
+            settings.limits.timeout = Some(cursor.value_with(argument, parse_duration)?)
+        }
+        _ => return parse_parallelism_argument(cursor, &mut settings.parallelism, argument),
+    }
+
+    Ok(true)
+}
+
+/// Returns `false` if argument is not related to parallelism
+fn parse_parallelism_argument<I: Iterator<Item = String>>(
+    cursor: &mut ArgumentCursor<I>,
+    parallelism: &mut Parallelism,
+    argument: &str,
+) -> Result<bool, ArgumentError> {
+    match argument {
+        "--threads" => {
+            let threads = cursor.value_with(argument, parse_positive)?;
+            // one pool per invocation, so jobs of daemon and server share its workers
+            *parallelism = SolverPool::new(threads).parallelism(parallelism.deterministic);
+        }
+        "--deterministic" => parallelism.deterministic = true,
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Handles options related to reading of input. Everything else not
+/// looking like an option is treated as input file path.
+/// Only one input is accepted
+fn parse_input_argument<I: Iterator<Item = String>>(
+    cursor: &mut ArgumentCursor<I>,
+    input: &mut InputOptions,
+    argument: String,
+) -> Result<(), ArgumentError> {
+    match argument.as_str() {
+        "--stdin-timeout" => {
+            input.stdin_timeout = Some(cursor.value_with(&argument, parse_duration)?);
+            return Ok(());
+        }
+        "--max-memory" => {
+            input.max_memory = Some(cursor.value_with(&argument, parse_byte_size)?);
+            return Ok(());
+        }
+        "--input-filter" => {
+            input.filter = Some(cursor.value_for(&argument)?);
+            return Ok(());
+        }
+        "--param" => {
+            let (name, value) = cursor.value_with(&argument, parse_param)?;
+            input.params.insert(name, value);
+            return Ok(());
+        }
+        _ => (),
+    }
+
+    if argument.starts_with("--") {
+        return Err(ArgumentError::UnknownOption(argument));
+    }
+
+    if input.path.is_some() {
+        return Err(ArgumentError::UnexpectedArgument(argument));
+    }
+
+    input.path = Some(argument);
+    Ok(())
+}
+
+/// Expects arguments without program name
+pub fn parse_arguments(
+    arguments: impl Iterator<Item = String>,
+) -> Result<Command, ArgumentError> {
+    let mut arguments = arguments.peekable();
+
+    match arguments.peek().map(String::as_str) {
+        Some("redact") => {
+            arguments.next();
+            parse_redact(ArgumentCursor { arguments }).map(Command::Redact)
+        }
+        Some("daemon") => {
+            arguments.next();
+            parse_daemon(ArgumentCursor { arguments }).map(Command::Daemon)
+        }
+        Some("openapi") => {
+            arguments.next();
+            match arguments.next() {
+                Some(argument) => Err(ArgumentError::UnexpectedArgument(argument)),
+                None => Ok(Command::OpenApi),
+            }
+        }
+        Some("lint") => {
+            arguments.next();
+            parse_lint(ArgumentCursor { arguments }).map(Command::Lint)
+        }
+        Some("hash") => {
+            arguments.next();
+            parse_hash(ArgumentCursor { arguments }).map(Command::Hash)
+        }
+        Some("replay") => {
+            arguments.next();
+            parse_replay(ArgumentCursor { arguments }).map(Command::Replay)
+        }
+        Some("diff") => {
+            arguments.next();
+            parse_diff(ArgumentCursor { arguments }).map(Command::Diff)
+        }
+        Some("serve") => {
+            arguments.next();
+            parse_serve(ArgumentCursor { arguments }).map(Command::Serve)
+        }
+        Some("batch") => {
+            arguments.next();
+            parse_batch(ArgumentCursor { arguments }).map(Command::Batch)
+        }
+        Some("worker") => {
+            arguments.next();
+            parse_worker(ArgumentCursor { arguments }).map(Command::Worker)
+        }
+        _ => parse_solve(ArgumentCursor { arguments })
+            .map(Box::new)
+            .map(Command::Solve),
+    }
+}
+
+fn parse_solve<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<SolveArguments, ArgumentError> {
+    let mut parsed = SolveArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--dry-run" => parsed.dry_run = true,
+            "--parametric" => parsed.parametric = true,
+            "--order-by-dominance" => parsed.reordering = Some(Reordering::Dominance),
+            "--reorder" => parsed.reordering = Some(cursor.parsed_value_for(&argument)?),
+            "--split-components" => parsed.split_components = true,
+            "--equilibrate" => parsed.equilibrate = true,
+            "--method" => parsed.method = Some(cursor.parsed_value_for(&argument)?),
+            "--inverse" => parsed.inverse = true,
+            "--determinant" => parsed.determinant = true,
+            "--refine" => parsed.refinement_steps = Some(cursor.parsed_value_for(&argument)?),
+            "--jitter" => parsed.jitter = Some(cursor.value_with(&argument, parse_jitter)?),
+            "--seed" => parsed.seed = Some(cursor.parsed_value_for(&argument)?),
+            // ranges of method parameters depend on method, see Method::parameters
+            "--omega" => parsed.relaxation = Some(cursor.value_with(&argument, parse_decimal)?),
+            "--tau" => parsed.tau = Some(cursor.value_with(&argument, parse_decimal)?),
+            "--chebyshev" => {
+                if parsed.acceleration.is_none() {
+                    parsed.acceleration = Some(Acceleration::Chebyshev { spectrum: None })
+                }
+            }
+            "--spectrum" => {
+                let spectrum = cursor.value_with(&argument, parse_spectrum)?;
+                parsed.acceleration = Some(Acceleration::Chebyshev {
+                    spectrum: Some(spectrum),
+                })
+            }
+            "--aitken" => {
+                if parsed.acceleration.is_none() {
+                    parsed.acceleration = Some(Acceleration::Aitken {
+                        period: DEFAULT_AITKEN_PERIOD,
+                    })
+                }
+            }
+            "--aitken-period" => {
+                let period = cursor.value_with(&argument, parse_aitken_period)?;
+                parsed.acceleration = Some(Acceleration::Aitken { period })
+            }
+            "--preconditioner" => parsed.preconditioning = Some(cursor.parsed_value_for(&argument)?),
+            "--restart" => parsed.restart = Some(cursor.parsed_value_for(&argument)?),
+            "--rounding" => parsed.rounding = Some(cursor.parsed_value_for(&argument)?),
+            "--tolerance-mode" => parsed.tolerance_mode = Some(cursor.parsed_value_for(&argument)?),
+            "--norm" => parsed.norm = Some(cursor.parsed_value_for(&argument)?),
+            "--initial-guess" => parsed.initial_guess = Some(cursor.parsed_value_for(&argument)?),
+            "--no-fast-path" => parsed.no_fast_path = true,
+            "--yes" | "-y" => parsed.yes = true,
+            "--epsilon-schedule" => {
+                parsed.epsilon_schedule = cursor.value_with(&argument, parse_decimal_list)?
+            }
+            "--snapshot-at" => {
+                parsed.snapshot_at = Some(cursor.value_with(&argument, parse_iteration_list)?)
+            }
+            "--record" => parsed.record = Some(cursor.value_for(&argument)?.into()),
+            "--snapshot-dir" => parsed.snapshot_dir = Some(cursor.value_for(&argument)?.into()),
+            "--param-range" => parsed.param_range = Some(cursor.parsed_value_for(&argument)?),
+            "--compare" => parsed.compare = Some(cursor.value_with(&argument, parse_direct_method)?),
+            "--dump-decomposition" => {
+                parsed.dump_decomposition = Some(cursor.value_for(&argument)?.into())
+            }
+            "--dump-format" => parsed.dump_format = cursor.parsed_value_for(&argument)?,
+            "--format" => parsed.format = cursor.parsed_value_for(&argument)?,
+            "--width" => parsed.width = Some(cursor.value_with(&argument, parse_width)?),
+            "--schema-version" => parsed.schema = cursor.parsed_value_for(&argument)?,
+            "--locale" => {
+                let locale = cursor.value_with(&argument, NumberFormat::for_locale)?;
+                parsed.number_format.thousands_separator = locale.thousands_separator;
+                parsed.number_format.decimal_separator = locale.decimal_separator;
+            }
+            "--thousands-separator" => {
+                parsed.number_format.thousands_separator =
+                    Some(cursor.value_with(&argument, parse_separator)?)
+            }
+            "--decimal-comma" => parsed.number_format.decimal_separator = ',',
+            "--notation" => parsed.number_format.notation = cursor.parsed_value_for(&argument)?,
+            "--output" => parsed.output = Some(cursor.value_for(&argument)?),
+            "--output-template" => parsed.output_template = Some(cursor.value_for(&argument)?.into()),
+            "--assert-matches" => parsed.assert_matches = Some(cursor.value_for(&argument)?),
+            "--tol" => parsed.tolerance = Some(cursor.value_with(&argument, parse_decimal)?),
+            _ if parse_parallelism_argument(&mut cursor, &mut parsed.parallelism, &argument)? => {}
+            _ => parse_input_argument(&mut cursor, &mut parsed.input, argument)?,
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_redact<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<RedactArguments, ArgumentError> {
+    let mut parsed = RedactArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--seed" => parsed.seed = Some(cursor.parsed_value_for(&argument)?),
+            "--output" => parsed.output = Some(cursor.value_for(&argument)?),
+            "--compress" => parsed.compress = Some(cursor.parsed_value_for(&argument)?),
+            _ => parse_input_argument(&mut cursor, &mut parsed.input, argument)?,
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_daemon<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<DaemonArguments, ArgumentError> {
+    let mut parsed = DaemonArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--socket" => parsed.socket = Some(cursor.value_for(&argument)?),
+            _ if parse_service_argument(&mut cursor, &mut parsed.settings, &argument)? => {}
+            _ if argument.starts_with("--") => return Err(ArgumentError::UnknownOption(argument)),
+            _ => return Err(ArgumentError::UnexpectedArgument(argument)),
+        }
+    }
+
+    if parsed.socket.is_none() {
+        return Err(ArgumentError::MissingOption("--socket"));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_serve<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<ServeArguments, ArgumentError> {
+    let mut parsed = ServeArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--listen" => parsed.listen = cursor.value_for(&argument)?,
+            "--concurrency" => parsed.concurrency = cursor.value_with(&argument, parse_positive)?,
+            "--api-token" => parsed.policy.api_token = Some(cursor.value_for(&argument)?),
+            "--token-required" => parsed.policy.token_required = true,
+            _ if parse_service_argument(&mut cursor, &mut parsed.settings, &argument)? => {}
+            _ if argument.starts_with("--") => return Err(ArgumentError::UnknownOption(argument)),
+            _ => return Err(ArgumentError::UnexpectedArgument(argument)),
+        }
+    }
+
+    if parsed.policy.token_required && parsed.policy.api_token.is_none() {
+        return Err(ArgumentError::MissingOption("--api-token"));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_diff<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<DiffArguments, ArgumentError> {
+    let mut parsed = DiffArguments::default();
+    let mut files = Vec::new();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--format" => parsed.format = cursor.parsed_value_for(&argument)?,
+            _ if argument.starts_with("--") => return Err(ArgumentError::UnknownOption(argument)),
+            _ if files.len() == 2 => return Err(ArgumentError::UnexpectedArgument(argument)),
+            _ => files.push(argument),
+        }
+    }
+
+    let mut files = files.into_iter();
+    match (files.next(), files.next()) {
+        (Some(first), Some(second)) => {
+            parsed.first = first;
+            parsed.second = second;
+            Ok(parsed)
+        }
+        _ => Err(ArgumentError::MissingArgument("two result files to compare")),
+    }
+}
+
+fn parse_batch<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<BatchArguments, ArgumentError> {
+    let mut parsed = BatchArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--events" => parsed.events = Some(cursor.value_for(&argument)?.into()),
+            "--output-dir" => parsed.output_dir = Some(cursor.value_for(&argument)?.into()),
+            "--resume-batch" => parsed.resume_state = Some(cursor.value_for(&argument)?.into()),
+            "--worker" => parsed.workers.push(cursor.parsed_value_for(&argument)?),
+            _ if parse_service_argument(&mut cursor, &mut parsed.settings, &argument)? => {}
+            _ if argument.starts_with("--") => return Err(ArgumentError::UnknownOption(argument)),
+            _ => parsed.inputs.push(argument),
+        }
+    }
+
+    if parsed.inputs.is_empty() {
+        return Err(ArgumentError::MissingArgument("input files or directories"));
+    }
+
+    Ok(parsed)
+}
+
+fn parse_worker<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<WorkerArguments, ArgumentError> {
+    let mut parsed = WorkerArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--listen" => parsed.listen = Some(cursor.value_for(&argument)?),
+            _ if parse_service_argument(&mut cursor, &mut parsed.settings, &argument)? => {}
+            _ if argument.starts_with("--") => return Err(ArgumentError::UnknownOption(argument)),
+            _ => return Err(ArgumentError::UnexpectedArgument(argument)),
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_hash<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<HashArguments, ArgumentError> {
+    let mut parsed = HashArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--canonical" => parsed.canonical = true,
+            "--permutation-invariant" => parsed.options.permutation_invariant = true,
+            _ => parse_input_argument(&mut cursor, &mut parsed.input, argument)?,
+        }
+    }
+
+    Ok(parsed)
+}
+
+fn parse_replay<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<ReplayArguments, ArgumentError> {
+    let mut parsed = ReplayArguments::default();
+    let mut log = None;
+
+    while let Some(argument) = cursor.next_argument() {
+        match argument.as_str() {
+            "--format" => parsed.format = cursor.parsed_value_for(&argument)?,
+            "--notation" => parsed.number_format.notation = cursor.parsed_value_for(&argument)?,
+            "--decimal-comma" => parsed.number_format.decimal_separator = ',',
+            "--output" => parsed.output = Some(cursor.value_for(&argument)?),
+            "--width" => parsed.width = Some(cursor.value_with(&argument, parse_width)?),
+            "--history" => parsed.history = true,
+            _ if argument.starts_with("--") => return Err(ArgumentError::UnknownOption(argument)),
+            _ if log.is_some() => return Err(ArgumentError::UnexpectedArgument(argument)),
+            _ => log = Some(argument),
+        }
+    }
+
+    parsed.log = log.ok_or(ArgumentError::MissingArgument("event log to replay"))?.into();
+    Ok(parsed)
+}
+
+fn parse_lint<I: Iterator<Item = String>>(
+    mut cursor: ArgumentCursor<I>,
+) -> Result<LintArguments, ArgumentError> {
+    let mut parsed = LintArguments::default();
+
+    while let Some(argument) = cursor.next_argument() {
+        parse_input_argument(&mut cursor, &mut parsed.input, argument)?;
+    }
+
+    Ok(parsed)
+}
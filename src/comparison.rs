@@ -0,0 +1,124 @@
+//! The same system solved by direct and by iterative method, side by side:
+//! how far iterations stopped from elimination, at what residual and cost
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use crate::analysis::residual_vector;
+use crate::input::fixed_name;
+use crate::norm::Norm;
+use crate::solver::{Equation, Method};
+
+/// Solve of one method of [`Comparison`]
+pub struct MethodRun {
+    pub method: Method,
+    pub outcome: Result<DVector<Decimal>, String>,
+    pub iterations: usize,
+    pub elapsed: Duration,
+    /// `A x - b` of solution, in norm of residual of equation
+    pub residual_norm: Option<Decimal>,
+}
+
+impl MethodRun {
+    fn solve(equation: &Equation, method: Method) -> Self {
+        let mut equation = equation.clone();
+        equation.method = method;
+        let mut iterations = 0;
+        let started = Instant::now();
+        let outcome = equation
+            .solve_with_progress(|progress| {
+                iterations = progress.iteration;
+                ControlFlow::Continue(())
+            })
+            .map_err(|err| err.to_string());
+        let elapsed = started.elapsed();
+        let norm = equation.norm.unwrap_or(Norm::L2);
+        let residual_norm = (outcome.as_ref().ok())
+            .map(|solution| norm.of(residual_vector(&equation, solution).iter().copied()));
+        MethodRun {
+            method,
+            outcome,
+            iterations,
+            elapsed,
+            residual_norm,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub direct: MethodRun,
+    pub iterative: MethodRun,
+    /// Iterative solution minus direct one, when both were found
+    pub difference: Option<DVector<Decimal>>,
+}
+
+/// Solves `equation` by `direct` method and by method it is configured for
+pub fn compare(equation: &Equation, direct: Method) -> Comparison {
+    let iterative = MethodRun::solve(equation, equation.method);
+    let direct = MethodRun::solve(equation, direct);
+    let difference = match (&direct.outcome, &iterative.outcome) {
+        (Ok(direct), Ok(iterative)) => Some(iterative - direct),
+        _ => None,
+    };
+    Comparison {
+        direct,
+        iterative,
+        difference,
+    }
+}
+
+impl Comparison {
+    /// Header and rows of table: unknowns with solutions of both methods
+    /// and their difference, then status, residual norm, iterations, time
+    /// and every norm of difference
+    pub fn table(&self, number: impl Fn(Decimal) -> String) -> (Vec<String>, Vec<Vec<String>>) {
+        let header = vec![
+            "value".to_owned(),
+            format!("direct ({})", self.direct.method),
+            format!("iterative ({})", self.iterative.method),
+            "difference".to_owned(),
+        ];
+        let unknowns = [&self.direct, &self.iterative]
+            .iter()
+            .filter_map(|run| run.outcome.as_ref().ok())
+            .map(DVector::len)
+            .max()
+            .unwrap_or(0);
+        let missing = || "-".to_owned();
+
+        let mut rows: Vec<Vec<String>> = (0..unknowns)
+            .map(|index| {
+                let value = |run: &MethodRun| (run.outcome.as_ref()).map_or_else(|_| missing(), |solution| number(solution[index]));
+                let difference = (self.difference.as_ref()).map_or_else(missing, |difference| number(difference[index]));
+                vec![fixed_name(index), value(&self.direct), value(&self.iterative), difference]
+            })
+            .collect();
+
+        let row = |name: &str, cell: &dyn Fn(&MethodRun) -> String| {
+            vec![name.to_owned(), cell(&self.direct), cell(&self.iterative), String::new()]
+        };
+        rows.push(row("status", &|run| match &run.outcome {
+            Ok(_) => "solved".to_owned(),
+            Err(_) => "failed".to_owned(),
+        }));
+        rows.push(row("residual norm", &|run| {
+            run.residual_norm.map_or_else(missing, |norm| number(norm.normalize()))
+        }));
+        rows.push(row("iterations", &|run| run.iterations.to_string()));
+        rows.push(row("time", &|run| format!("{:?}", run.elapsed)));
+        if let Some(difference) = &self.difference {
+            for norm in Norm::ALL {
+                let mut norm_row = row(&format!("difference {}", norm.name()), &|_| String::new());
+                norm_row[3] = number(norm.of(difference.iter().copied()).normalize());
+                rows.push(norm_row);
+            }
+        }
+        (header, rows)
+    }
+
+    /// Both methods found solution
+    pub fn is_solved(&self) -> bool {
+        self.direct.outcome.is_ok() && self.iterative.outcome.is_ok()
+    }
+}
@@ -0,0 +1,136 @@
+use core::fmt;
+use std::io;
+use std::str::FromStr;
+
+mod bits;
+mod fse;
+mod gzip;
+mod huffman;
+mod literals;
+mod lz;
+mod zstd;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression formats are handled in process, so reading compressed input
+/// needs no `gzip` or `zstd` tools installed. Anything those tools write is
+/// read, dictionaries of zstd aside. Written files favour speed over size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Recognizes compressed data by its magic bytes, so file extension does not matter
+    pub fn detect(content: &[u8]) -> Option<Compression> {
+        if content.starts_with(GZIP_MAGIC) {
+            return Some(Compression::Gzip);
+        }
+
+        if content.starts_with(ZSTD_MAGIC) {
+            return Some(Compression::Zstd);
+        }
+
+        None
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    pub fn compress(&self, content: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(match self {
+            Compression::Gzip => gzip::compress(content),
+            Compression::Zstd => zstd::compress(content),
+        })
+    }
+
+    pub fn decompress(&self, content: &[u8]) -> io::Result<Vec<u8>> {
+        self.unpack(content, None)
+    }
+
+    /// Same as [`Compression::decompress`], refusing to unpack more than `limit` bytes
+    pub fn decompress_limited(&self, content: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+        self.unpack(content, Some(limit))
+    }
+
+    fn unpack(&self, content: &[u8], limit: Option<usize>) -> io::Result<Vec<u8>> {
+        let mut sink = Sink { data: Vec::new(), limit };
+        match self {
+            Compression::Gzip => gzip::decompress(content, &mut sink)?,
+            Compression::Zstd => zstd::decompress(content, &mut sink)?,
+        }
+        Ok(sink.data)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Unpacked content, which may not grow past limit
+struct Sink {
+    data: Vec<u8>,
+    limit: Option<usize>,
+}
+
+impl Sink {
+    fn check(&self) -> io::Result<()> {
+        match self.limit {
+            Some(limit) if self.data.len() > limit => Err(invalid(format!("Content unpacks to more than {limit} bytes"))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Repeats `length` bytes starting `distance` back, they may overlap the copy
+    fn copy_match(&mut self, distance: usize, length: usize) {
+        let start = self.data.len() - distance;
+        if distance >= length {
+            self.data.extend_from_within(start..start + length);
+        } else {
+            for index in start..start + length {
+                let byte = self.data[index];
+                self.data.push(byte);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            _ => Err("Expected one of: gzip, zstd".to_owned()),
+        }
+    }
+}
+
+/// Transparently unpacks compressed content, plain content is returned as is
+pub fn decompress_if_needed(content: Vec<u8>) -> io::Result<Vec<u8>> {
+    match Compression::detect(&content) {
+        Some(compression) => compression.decompress(&content),
+        None => Ok(content),
+    }
+}
+
+/// Same as [`decompress_if_needed`], refusing compressed content which unpacks to more than `limit` bytes
+pub fn decompress_within(content: Vec<u8>, limit: usize) -> io::Result<Vec<u8>> {
+    match Compression::detect(&content) {
+        Some(compression) => compression.decompress_limited(&content, limit),
+        None => Ok(content),
+    }
+}
@@ -0,0 +1,168 @@
+use std::io;
+
+use super::invalid;
+
+fn mask(bits: u32) -> u64 {
+    (1u64 << bits) - 1
+}
+
+/// Packs values starting from least significant bit of each byte, the way
+/// both deflate and zstd lay out their streams
+#[derive(Default)]
+pub(super) struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    /// Appends `bits` lowest bits of `value`, at most 56 of them
+    pub fn write(&mut self, value: u64, bits: u32) {
+        self.buffer |= (value & mask(bits)) << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Pads with zero bits up to whole byte
+    pub fn align(&mut self) {
+        if self.count > 0 {
+            self.write(0, 8 - self.count);
+        }
+    }
+
+    /// Bytes written so far, stream has to be aligned
+    pub fn bytes(&mut self) -> &mut Vec<u8> {
+        debug_assert_eq!(self.count, 0, "stream is aligned");
+        &mut self.bytes
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+/// Reads values packed by [`BitWriter`] from the start of stream
+pub(super) struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// Next byte to load into buffer
+    position: usize,
+    buffer: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        BitReader {
+            bytes,
+            position: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    /// Next `bits` bits without consuming them, at most 32. Bits past the
+    /// end of stream read as zeros
+    pub fn peek(&mut self, bits: u32) -> u64 {
+        while self.count <= 56 && self.position < self.bytes.len() {
+            self.buffer |= u64::from(self.bytes[self.position]) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        self.buffer & mask(bits)
+    }
+
+    pub fn consume(&mut self, bits: u32) -> io::Result<()> {
+        if bits > self.count {
+            return Err(invalid("Compressed content is truncated"));
+        }
+        self.buffer >>= bits;
+        self.count -= bits;
+        Ok(())
+    }
+
+    pub fn read(&mut self, bits: u32) -> io::Result<u64> {
+        let value = self.peek(bits);
+        self.consume(bits)?;
+        Ok(value)
+    }
+
+    /// Skips to the start of next byte
+    pub fn align(&mut self) -> io::Result<()> {
+        self.consume(self.count % 8)
+    }
+
+    /// Bytes touched so far, partly read one included
+    pub fn bytes_read(&self) -> usize {
+        self.position - (self.count / 8) as usize
+    }
+
+    /// `length` whole bytes, stream has to be aligned
+    pub fn take_bytes(&mut self, length: usize) -> io::Result<&'a [u8]> {
+        let start = self.bytes_read();
+        let end = start
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| invalid("Compressed content is truncated"))?;
+        self.position = end;
+        self.buffer = 0;
+        self.count = 0;
+        Ok(&self.bytes[start..end])
+    }
+}
+
+/// Reads zstd entropy coded stream, which is written forward and read from
+/// its end. Last byte is padded by zero bits followed by single 1
+pub(super) struct BackwardReader<'a> {
+    bytes: &'a [u8],
+    /// Bits left to read, negative when reader went past the start
+    position: i64,
+}
+
+impl<'a> BackwardReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> io::Result<Self> {
+        match bytes.last() {
+            Some(&last) if last != 0 => Ok(BackwardReader {
+                bytes,
+                position: bytes.len() as i64 * 8 - i64::from(last.leading_zeros()) - 1,
+            }),
+            _ => Err(invalid("Invalid zstd data: bitstream has no end mark")),
+        }
+    }
+
+    /// Next `bits` bits without consuming them, at most 56. Bits before the
+    /// start of stream read as zeros
+    pub fn peek(&self, bits: u32) -> u64 {
+        let bits = i64::from(bits);
+        let (start, taken) = match self.position - bits {
+            start if start >= 0 => (start, bits),
+            _ if self.position > 0 => (0, self.position),
+            _ => return 0,
+        };
+        let first = (start / 8) as usize;
+        let mut word = [0; 8];
+        let available = &self.bytes[first..self.bytes.len().min(first + 8)];
+        word[..available.len()].copy_from_slice(available);
+        let value = (u64::from_le_bytes(word) >> (start % 8)) & mask(taken as u32);
+        value << (bits - taken)
+    }
+
+    pub fn consume(&mut self, bits: u32) {
+        self.position -= i64::from(bits);
+    }
+
+    pub fn read(&mut self, bits: u32) -> u64 {
+        let value = self.peek(bits);
+        self.consume(bits);
+        value
+    }
+
+    /// Bits left, negative after reading past the start
+    pub fn remaining(&self) -> i64 {
+        self.position
+    }
+}
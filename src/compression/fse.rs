@@ -0,0 +1,174 @@
+//! Finite state entropy tables of zstd
+
+use std::io;
+
+use super::bits::{BackwardReader, BitReader};
+use super::invalid;
+
+fn corrupt(reason: &str) -> io::Error {
+    invalid(format!("Invalid zstd data: {reason}"))
+}
+
+/// What state decodes to: its symbol and how next state is found
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Entry {
+    pub symbol: u8,
+    /// Bits read to add to `base`
+    pub bits: u8,
+    pub base: u16,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Table {
+    pub log: u32,
+    pub entries: Vec<Entry>,
+}
+
+impl Table {
+    /// Table of normalized counts adding up to `1 << log`, where `-1` stands
+    /// for symbol less probable than any other
+    pub fn new(counts: &[i16], log: u32) -> io::Result<Table> {
+        let size = 1usize << log;
+        let total: i64 = counts.iter().map(|count| i64::from(*count).abs()).sum();
+        if total != size as i64 || counts.len() > 256 {
+            return Err(corrupt("probabilities do not add up"));
+        }
+
+        let mut entries = vec![Entry::default(); size];
+        let mut next = vec![0u16; counts.len()];
+        // least probable symbols take the end of table
+        let mut high = size;
+        for (symbol, count) in counts.iter().enumerate() {
+            if *count == -1 {
+                high -= 1;
+                entries[high].symbol = symbol as u8;
+                next[symbol] = 1;
+            }
+        }
+
+        let step = (size >> 1) + (size >> 3) + 3;
+        let mask = size - 1;
+        let mut position = 0;
+        for (symbol, count) in counts.iter().enumerate().filter(|(_, count)| **count > 0) {
+            for _ in 0..*count {
+                entries[position].symbol = symbol as u8;
+                position = (position + step) & mask;
+                while position >= high {
+                    position = (position + step) & mask;
+                }
+            }
+            next[symbol] = *count as u16;
+        }
+        if position != 0 {
+            return Err(corrupt("probabilities do not spread over table"));
+        }
+
+        for entry in &mut entries {
+            let state = next[usize::from(entry.symbol)];
+            next[usize::from(entry.symbol)] += 1;
+            let bits = log - (15 - state.leading_zeros());
+            entry.bits = bits as u8;
+            entry.base = ((u32::from(state) << bits) - size as u32) as u16;
+        }
+        Ok(Table { log, entries })
+    }
+
+    /// Every state decodes to `symbol` without reading bits
+    pub fn rle(symbol: u8) -> Table {
+        Table {
+            log: 0,
+            entries: vec![Entry {
+                symbol,
+                bits: 0,
+                base: 0,
+            }],
+        }
+    }
+
+    /// Reads description of normalized counts from the start of `bytes`.
+    /// Returns table and bytes it took
+    pub fn read(bytes: &[u8], max_log: u32, max_symbol: usize) -> io::Result<(Table, usize)> {
+        let mut reader = BitReader::new(bytes);
+        let log = reader.read(4)? as u32 + 5;
+        if log > max_log {
+            return Err(corrupt("table is too large"));
+        }
+
+        let mut remaining = 1i32 << log;
+        let mut counts = Vec::new();
+        while remaining > 0 {
+            // values up to `remaining + 1`, the smaller ones take one bit less
+            let bits = 32 - (remaining + 1).leading_zeros();
+            let value = reader.peek(bits) as i32;
+            let lower = (1 << (bits - 1)) - 1;
+            let threshold = (1 << bits) - 1 - (remaining + 1);
+            let value = if value & lower < threshold {
+                reader.consume(bits - 1)?;
+                value & lower
+            } else {
+                reader.consume(bits)?;
+                if value > lower {
+                    value - threshold
+                } else {
+                    value
+                }
+            };
+
+            let count = value - 1;
+            remaining -= count.abs();
+            counts.push(count as i16);
+            if count == 0 {
+                loop {
+                    let repeat = reader.read(2)?;
+                    counts.extend(std::iter::repeat_n(0, repeat as usize));
+                    if repeat < 3 {
+                        break;
+                    }
+                }
+            }
+            if counts.len() > max_symbol + 1 {
+                return Err(corrupt("table has too many symbols"));
+            }
+        }
+        if remaining != 0 {
+            return Err(corrupt("probabilities do not add up"));
+        }
+        Ok((Table::new(&counts, log)?, reader.bytes_read()))
+    }
+
+    /// For every symbol and next state, state which decodes to this symbol
+    /// and leads to that next state. Encoder goes through states backwards
+    pub fn encoding(&self) -> Vec<Vec<u16>> {
+        let symbols = self.entries.iter().map(|entry| usize::from(entry.symbol) + 1).max().unwrap_or(0);
+        let mut previous = vec![vec![0; self.entries.len()]; symbols];
+        for (state, entry) in self.entries.iter().enumerate() {
+            let base = usize::from(entry.base);
+            previous[usize::from(entry.symbol)][base..base + (1 << entry.bits)].fill(state as u16);
+        }
+        previous
+    }
+}
+
+/// Decoder going through states of table
+pub(super) struct State<'a> {
+    table: &'a Table,
+    state: usize,
+}
+
+impl<'a> State<'a> {
+    pub fn new(table: &'a Table, reader: &mut BackwardReader) -> Self {
+        State {
+            table,
+            state: reader.read(table.log) as usize,
+        }
+    }
+
+    pub fn symbol(&self) -> u8 {
+        self.table.entries[self.state].symbol
+    }
+
+    pub fn update(&mut self, reader: &mut BackwardReader) {
+        let entry = self.table.entries[self.state];
+        self.state = usize::from(entry.base) + reader.read(u32::from(entry.bits)) as usize;
+    }
+}
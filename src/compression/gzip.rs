@@ -0,0 +1,462 @@
+//! Gzip members (RFC 1952) holding deflate streams (RFC 1951)
+
+use std::io;
+
+use super::bits::{BitReader, BitWriter};
+use super::huffman::code_lengths;
+use super::lz::{Match, Matcher};
+use super::{invalid, Sink, GZIP_MAGIC};
+
+/// Deflate distances reach this far back
+const WINDOW: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// Symbols per block, every block gets codes fitted to its own content
+const BLOCK_SYMBOLS: usize = 1 << 16;
+
+const END_OF_BLOCK: usize = 256;
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097,
+    6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// Order code lengths of code length alphabet are stored in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const FLAG_HEADER_CRC: u8 = 0x02;
+const FLAG_EXTRA: u8 = 0x04;
+const FLAG_NAME: u8 = 0x08;
+const FLAG_COMMENT: u8 = 0x10;
+
+fn corrupt(reason: &str) -> io::Error {
+    invalid(format!("Invalid gzip data: {reason}"))
+}
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, byte| CRC_TABLE[((crc ^ u32::from(*byte)) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// Unpacks every member of gzip file into `sink`
+pub(super) fn decompress(content: &[u8], sink: &mut Sink) -> io::Result<()> {
+    let mut rest = content;
+    loop {
+        rest = member(rest, sink)?;
+        // some tools pad files with zeros, which gzip itself ignores
+        if rest.iter().all(|byte| *byte == 0) {
+            return Ok(());
+        }
+        if !rest.starts_with(GZIP_MAGIC) {
+            return Err(corrupt("unexpected data after the end of compressed content"));
+        }
+    }
+}
+
+/// Unpacks single member, returns what follows it
+fn member<'a>(content: &'a [u8], sink: &mut Sink) -> io::Result<&'a [u8]> {
+    let truncated = || corrupt("content is truncated");
+    let header = content.get(..10).ok_or_else(truncated)?;
+    if header[2] != 8 {
+        return Err(corrupt("compression method is not deflate"));
+    }
+    let flags = header[3];
+    let mut position = 10;
+    if flags & FLAG_EXTRA != 0 {
+        let length = content.get(position..position + 2).ok_or_else(truncated)?;
+        position += 2 + usize::from(u16::from_le_bytes([length[0], length[1]]));
+    }
+    for flag in [FLAG_NAME, FLAG_COMMENT] {
+        if flags & flag != 0 {
+            let text = content.get(position..).ok_or_else(truncated)?;
+            position += text.iter().position(|byte| *byte == 0).ok_or_else(truncated)? + 1;
+        }
+    }
+    if flags & FLAG_HEADER_CRC != 0 {
+        position += 2;
+    }
+    let stream = content.get(position..).ok_or_else(truncated)?;
+
+    let start = sink.data.len();
+    let mut reader = BitReader::new(stream);
+    inflate(&mut reader, sink, start)?;
+    reader.align()?;
+    let rest = &stream[reader.bytes_read()..];
+
+    let trailer = rest.get(..8).ok_or_else(truncated)?;
+    let unpacked = &sink.data[start..];
+    if u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]) != crc32(unpacked) {
+        return Err(corrupt("checksum does not match content"));
+    }
+    if u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]) != unpacked.len() as u32 {
+        return Err(corrupt("size does not match content"));
+    }
+    Ok(&rest[8..])
+}
+
+/// Decoding table of canonical Huffman code, indexed by next bits of stream
+struct Decoder {
+    /// `symbol << 4 | length`, zero for bits no code starts with
+    table: Vec<u16>,
+    bits: u32,
+}
+
+impl Decoder {
+    fn new(lengths: &[u8]) -> io::Result<Decoder> {
+        let bits = u32::from(lengths.iter().copied().max().unwrap_or(0));
+        let mut counts = [0u32; 16];
+        for length in lengths {
+            counts[usize::from(*length)] += 1;
+        }
+        counts[0] = 0;
+
+        // codes left unused are fine, more codes than fit are not
+        let mut left = 1i64;
+        let mut next = [0u32; 16];
+        for length in 1..16 {
+            left = (left << 1) - i64::from(counts[length]);
+            if left < 0 {
+                return Err(corrupt("Huffman code is oversubscribed"));
+            }
+            next[length] = (next[length - 1] + counts[length - 1]) << 1;
+        }
+
+        let mut table = vec![0u16; 1 << bits];
+        for (symbol, length) in lengths.iter().enumerate().filter(|(_, length)| **length > 0) {
+            let length = u32::from(*length);
+            let code = next[length as usize];
+            next[length as usize] += 1;
+            let reversed = (code.reverse_bits() >> (32 - length)) as usize;
+            for entry in table.iter_mut().skip(reversed).step_by(1 << length) {
+                *entry = ((symbol as u16) << 4) | length as u16;
+            }
+        }
+        Ok(Decoder { table, bits })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> io::Result<usize> {
+        let entry = self.table[reader.peek(self.bits) as usize];
+        if entry == 0 {
+            return Err(corrupt("invalid Huffman code"));
+        }
+        reader.consume(u32::from(entry & 0xF))?;
+        Ok(usize::from(entry >> 4))
+    }
+}
+
+fn fixed_lengths() -> (Vec<u8>, Vec<u8>) {
+    let literals = (0..288).map(|symbol| match symbol {
+        0..=143 => 8,
+        144..=255 => 9,
+        256..=279 => 7,
+        _ => 8,
+    });
+    (literals.collect(), vec![5; 30])
+}
+
+/// Unpacks deflate stream, matches may not reach before `start` of `sink`
+fn inflate(reader: &mut BitReader, sink: &mut Sink, start: usize) -> io::Result<()> {
+    loop {
+        let last = reader.read(1)? == 1;
+        match reader.read(2)? {
+            0 => {
+                reader.align()?;
+                let length = reader.read(16)?;
+                if reader.read(16)? != !length & 0xFFFF {
+                    return Err(corrupt("length of stored block is damaged"));
+                }
+                sink.data.extend_from_slice(reader.take_bytes(length as usize)?);
+                sink.check()?;
+            }
+            1 => {
+                let (literals, distances) = fixed_lengths();
+                inflate_block(reader, &Decoder::new(&literals)?, &Decoder::new(&distances)?, sink, start)?;
+            }
+            2 => {
+                let (literals, distances) = read_code_lengths(reader)?;
+                inflate_block(reader, &Decoder::new(&literals)?, &Decoder::new(&distances)?, sink, start)?;
+            }
+            _ => return Err(corrupt("block type is reserved")),
+        }
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+/// Code lengths of literal/length and distance alphabets of dynamic block
+fn read_code_lengths(reader: &mut BitReader) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let literals = reader.read(5)? as usize + 257;
+    let distances = reader.read(5)? as usize + 1;
+    let code_lengths = reader.read(4)? as usize + 4;
+
+    let mut lengths = [0u8; 19];
+    for symbol in &CODE_LENGTH_ORDER[..code_lengths] {
+        lengths[*symbol] = reader.read(3)? as u8;
+    }
+    let decoder = Decoder::new(&lengths)?;
+
+    let mut lengths = Vec::with_capacity(literals + distances);
+    while lengths.len() < literals + distances {
+        let (value, repeat) = match decoder.decode(reader)? {
+            length @ 0..=15 => (length as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or_else(|| corrupt("code length repeats nothing"))?;
+                (previous, 3 + reader.read(2)?)
+            }
+            17 => (0, 3 + reader.read(3)?),
+            _ => (0, 11 + reader.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() > literals + distances {
+        return Err(corrupt("code lengths run past the end of alphabets"));
+    }
+    if lengths[END_OF_BLOCK] == 0 {
+        return Err(corrupt("block has no end code"));
+    }
+    let distances = lengths.split_off(literals);
+    Ok((lengths, distances))
+}
+
+fn inflate_block(
+    reader: &mut BitReader,
+    literals: &Decoder,
+    distances: &Decoder,
+    sink: &mut Sink,
+    start: usize,
+) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(reader)?;
+        if symbol < END_OF_BLOCK {
+            sink.data.push(symbol as u8);
+            continue;
+        }
+        if symbol == END_OF_BLOCK {
+            return sink.check();
+        }
+
+        let code = symbol - 257;
+        if code >= LENGTH_BASE.len() {
+            return Err(corrupt("invalid length code"));
+        }
+        let length = usize::from(LENGTH_BASE[code]) + reader.read(u32::from(LENGTH_EXTRA[code]))? as usize;
+        let code = distances.decode(reader)?;
+        if code >= DISTANCE_BASE.len() {
+            return Err(corrupt("invalid distance code"));
+        }
+        let distance = usize::from(DISTANCE_BASE[code]) + reader.read(u32::from(DISTANCE_EXTRA[code]))? as usize;
+        if distance > sink.data.len() - start {
+            return Err(corrupt("distance reaches before the start of content"));
+        }
+        sink.copy_match(distance, length);
+        sink.check()?;
+    }
+}
+
+/// Piece of deflate stream before it gets its codes
+#[derive(Clone, Copy)]
+enum Token {
+    Literal(u8),
+    Match(Match),
+}
+
+/// Gzip member with `content` compressed by deflate
+pub(super) fn compress(content: &[u8]) -> Vec<u8> {
+    // no file name and modification time, unknown operating system
+    let mut writer = BitWriter::default();
+    writer.bytes().extend_from_slice(&[GZIP_MAGIC[0], GZIP_MAGIC[1], 8, 0, 0, 0, 0, 0, 0, 255]);
+
+    let mut matcher = Matcher::new(WINDOW, MIN_MATCH, MAX_MATCH);
+    let mut tokens = Vec::with_capacity(BLOCK_SYMBOLS);
+    let mut position = 0;
+    while position < content.len() {
+        let found = matcher.find(content, position, content.len());
+        match found {
+            Some(found) => {
+                for inserted in position..position + found.length {
+                    matcher.insert(content, inserted);
+                }
+                tokens.push(Token::Match(found));
+                position += found.length;
+            }
+            None => {
+                matcher.insert(content, position);
+                tokens.push(Token::Literal(content[position]));
+                position += 1;
+            }
+        }
+        if tokens.len() == BLOCK_SYMBOLS {
+            write_block(&mut writer, &tokens, false);
+            tokens.clear();
+        }
+    }
+    write_block(&mut writer, &tokens, true);
+
+    let mut member = writer.finish();
+    member.extend_from_slice(&crc32(content).to_le_bytes());
+    member.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    member
+}
+
+/// Index of the last base not above `value`
+fn code_of(bases: &[u16], value: usize) -> usize {
+    bases.partition_point(|base| usize::from(*base) <= value) - 1
+}
+
+/// Canonical codes for `lengths`, bit reversed since deflate stores codes
+/// from their most significant bit
+fn codes(lengths: &[u8]) -> Vec<u16> {
+    let mut counts = [0u16; 16];
+    for length in lengths {
+        counts[usize::from(*length)] += 1;
+    }
+    counts[0] = 0;
+    let mut next = [0u16; 16];
+    for length in 1..16 {
+        next[length] = (next[length - 1] + counts[length - 1]) << 1;
+    }
+    lengths
+        .iter()
+        .map(|length| match *length {
+            0 => 0,
+            length => {
+                let code = next[usize::from(length)];
+                next[usize::from(length)] += 1;
+                code.reverse_bits() >> (16 - length)
+            }
+        })
+        .collect()
+}
+
+/// Frequencies with at least two symbols used, as some decoders refuse
+/// codes of single symbol
+fn with_two_symbols(mut frequencies: Vec<u64>) -> Vec<u64> {
+    for symbol in 0..2 {
+        if frequencies.iter().filter(|frequency| **frequency > 0).count() < 2 {
+            frequencies[symbol] = frequencies[symbol].max(1);
+        }
+    }
+    frequencies
+}
+
+/// Dynamic Huffman block of `tokens`
+fn write_block(writer: &mut BitWriter, tokens: &[Token], last: bool) {
+    let mut literal_frequencies = vec![0u64; 286];
+    let mut distance_frequencies = vec![0u64; 30];
+    literal_frequencies[END_OF_BLOCK] = 1;
+    for token in tokens {
+        match token {
+            Token::Literal(byte) => literal_frequencies[usize::from(*byte)] += 1,
+            Token::Match(found) => {
+                literal_frequencies[257 + code_of(&LENGTH_BASE, found.length)] += 1;
+                distance_frequencies[code_of(&DISTANCE_BASE, found.distance)] += 1;
+            }
+        }
+    }
+    let literal_lengths = code_lengths(&with_two_symbols(literal_frequencies), 15);
+    let distance_lengths = code_lengths(&with_two_symbols(distance_frequencies), 15);
+    let literals = 257.max(literal_lengths.iter().rposition(|length| *length > 0).map_or(0, |last| last + 1));
+    let distances = distance_lengths.iter().rposition(|length| *length > 0).map_or(0, |last| last + 1).max(1);
+
+    // code lengths of both alphabets, run length encoded as (symbol, extra bits)
+    let mut runs: Vec<(usize, u64)> = Vec::new();
+    let all: Vec<u8> = literal_lengths[..literals].iter().chain(&distance_lengths[..distances]).copied().collect();
+    let mut index = 0;
+    while index < all.len() {
+        let value = all[index];
+        let run = all[index..].iter().take_while(|length| **length == value).count();
+        let mut left = run;
+        if value == 0 {
+            while left >= 11 {
+                let taken = left.min(138);
+                runs.push((18, (taken - 11) as u64));
+                left -= taken;
+            }
+            if left >= 3 {
+                runs.push((17, (left - 3) as u64));
+                left = 0;
+            }
+        } else {
+            runs.push((usize::from(value), 0));
+            left -= 1;
+            while left >= 3 {
+                let taken = left.min(6);
+                runs.push((16, (taken - 3) as u64));
+                left -= taken;
+            }
+        }
+        runs.extend(std::iter::repeat_n((usize::from(value), 0), left));
+        index += run;
+    }
+
+    let mut run_frequencies = vec![0u64; 19];
+    for (symbol, _) in &runs {
+        run_frequencies[*symbol] += 1;
+    }
+    let run_lengths = code_lengths(&with_two_symbols(run_frequencies), 7);
+    let stored = 4.max(CODE_LENGTH_ORDER.iter().rposition(|symbol| run_lengths[*symbol] > 0).map_or(0, |last| last + 1));
+
+    writer.write(u64::from(last), 1);
+    writer.write(2, 2);
+    writer.write((literals - 257) as u64, 5);
+    writer.write((distances - 1) as u64, 5);
+    writer.write((stored - 4) as u64, 4);
+    for symbol in &CODE_LENGTH_ORDER[..stored] {
+        writer.write(u64::from(run_lengths[*symbol]), 3);
+    }
+    let run_codes = codes(&run_lengths);
+    for (symbol, extra) in runs {
+        writer.write(u64::from(run_codes[symbol]), u32::from(run_lengths[symbol]));
+        match symbol {
+            16 => writer.write(extra, 2),
+            17 => writer.write(extra, 3),
+            18 => writer.write(extra, 7),
+            _ => (),
+        }
+    }
+
+    let literal_codes = codes(&literal_lengths);
+    let distance_codes = codes(&distance_lengths);
+    let literal = |writer: &mut BitWriter, symbol: usize| {
+        writer.write(u64::from(literal_codes[symbol]), u32::from(literal_lengths[symbol]));
+    };
+    for token in tokens {
+        match token {
+            Token::Literal(byte) => literal(writer, usize::from(*byte)),
+            Token::Match(found) => {
+                let code = code_of(&LENGTH_BASE, found.length);
+                literal(writer, 257 + code);
+                writer.write((found.length - usize::from(LENGTH_BASE[code])) as u64, u32::from(LENGTH_EXTRA[code]));
+                let code = code_of(&DISTANCE_BASE, found.distance);
+                writer.write(u64::from(distance_codes[code]), u32::from(distance_lengths[code]));
+                writer.write(
+                    (found.distance - usize::from(DISTANCE_BASE[code])) as u64,
+                    u32::from(DISTANCE_EXTRA[code]),
+                );
+            }
+        }
+    }
+    literal(writer, END_OF_BLOCK);
+}
@@ -0,0 +1,56 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Lengths of prefix codes for symbols of given frequencies, none longer than
+/// `limit`. Unused symbols get 0, single used symbol gets 1. When tree grows
+/// too deep, frequencies are halved and tree is built again, which costs
+/// little against optimal length limiting
+pub(super) fn code_lengths(frequencies: &[u64], limit: u8) -> Vec<u8> {
+    let mut lengths = vec![0; frequencies.len()];
+    let used: Vec<usize> = (0..frequencies.len()).filter(|&symbol| frequencies[symbol] > 0).collect();
+    match used.as_slice() {
+        [] => return lengths,
+        [symbol] => {
+            lengths[*symbol] = 1;
+            return lengths;
+        }
+        _ => (),
+    }
+
+    let mut weights: Vec<u64> = used.iter().map(|&symbol| frequencies[symbol]).collect();
+    loop {
+        let depths = leaf_depths(&weights);
+        if depths.iter().all(|depth| *depth <= limit) {
+            for (symbol, depth) in used.iter().zip(depths) {
+                lengths[*symbol] = depth;
+            }
+            return lengths;
+        }
+        for weight in &mut weights {
+            *weight = weight.div_ceil(2);
+        }
+    }
+}
+
+/// Depths of leaves of Huffman tree, at least two leaves
+fn leaf_depths(weights: &[u64]) -> Vec<u8> {
+    let leaves = weights.len();
+    // nodes past leaves are internal, each one knows its parent
+    let mut parents = vec![0; 2 * leaves - 1];
+    let mut heap: BinaryHeap<_> = weights.iter().enumerate().map(|(node, weight)| Reverse((*weight, node))).collect();
+    let mut next = leaves;
+    while let (Some(Reverse((first, a))), Some(Reverse((second, b)))) = (heap.pop(), heap.pop()) {
+        parents[a] = next;
+        parents[b] = next;
+        heap.push(Reverse((first + second, next)));
+        next += 1;
+    }
+
+    // parents come after children, so depth of parent is known first
+    let mut depths = vec![0u8; 2 * leaves - 1];
+    for node in (0..2 * leaves - 2).rev() {
+        depths[node] = depths[parents[node]].saturating_add(1);
+    }
+    depths.truncate(leaves);
+    depths
+}
@@ -0,0 +1,300 @@
+//! Literals section of zstd blocks and its Huffman codes
+
+use std::io;
+
+use super::bits::{BackwardReader, BitWriter};
+use super::fse::{State, Table};
+use super::huffman::code_lengths;
+use super::invalid;
+
+/// Longest Huffman code of literals
+const MAX_BITS: u32 = 11;
+
+/// Literals up to this many take single stream, longer ones four
+const SINGLE_STREAM_MAX: usize = 1023;
+
+fn corrupt(reason: &str) -> io::Error {
+    invalid(format!("Invalid zstd data: {reason}"))
+}
+
+/// Decoding table of literals, indexed by next `bits` bits of stream
+#[derive(Debug, Clone)]
+pub(super) struct Huffman {
+    bits: u32,
+    /// Symbol and length of its code
+    entries: Vec<(u8, u8)>,
+}
+
+impl Huffman {
+    /// Table of code weights, weight of the last symbol is not stored
+    /// since it completes the code
+    fn from_weights(mut weights: Vec<u8>) -> io::Result<Huffman> {
+        if weights.iter().any(|weight| u32::from(*weight) > MAX_BITS) {
+            return Err(corrupt("literal weight is too large"));
+        }
+        let total: u32 = weights.iter().filter(|weight| **weight > 0).map(|weight| 1 << (weight - 1)).sum();
+        if total == 0 {
+            return Err(corrupt("literals have no codes"));
+        }
+        let bits = 32 - total.leading_zeros();
+        let left = (1 << bits) - total;
+        if bits > MAX_BITS || !left.is_power_of_two() {
+            return Err(corrupt("literal codes are not complete"));
+        }
+        weights.push(left.trailing_zeros() as u8 + 1);
+        if weights.len() > 256 {
+            return Err(corrupt("literals have too many symbols"));
+        }
+
+        // longer codes come first, symbols of the same length in their order
+        let lengths: Vec<u32> = weights.iter().map(|weight| if *weight > 0 { bits + 1 - u32::from(*weight) } else { 0 }).collect();
+        let mut starts = rank_starts(&lengths, bits);
+        let mut entries = vec![(0, 0); 1 << bits];
+        for (symbol, length) in lengths.iter().enumerate().filter(|(_, length)| **length > 0) {
+            let span = 1 << (bits - length);
+            let start = starts[*length as usize];
+            entries[start..start + span].fill((symbol as u8, *length as u8));
+            starts[*length as usize] += span;
+        }
+        Ok(Huffman { bits, entries })
+    }
+
+    /// Reads tree description from the start of `bytes`, returns table and
+    /// bytes it took
+    pub fn read(bytes: &[u8]) -> io::Result<(Huffman, usize)> {
+        let truncated = || corrupt("literals are truncated");
+        let header = usize::from(*bytes.first().ok_or_else(truncated)?);
+        if header >= 128 {
+            // weights stored as they are, 4 bits each
+            let count = header - 127;
+            let packed = bytes.get(1..1 + count.div_ceil(2)).ok_or_else(truncated)?;
+            let weights = (0..count)
+                .map(|index| match index % 2 {
+                    0 => packed[index / 2] >> 4,
+                    _ => packed[index / 2] & 0xF,
+                })
+                .collect();
+            return Ok((Huffman::from_weights(weights)?, 1 + packed.len()));
+        }
+
+        let compressed = bytes.get(1..1 + header).ok_or_else(truncated)?;
+        let (table, taken) = Table::read(compressed, 6, 255)?;
+        let mut reader = BackwardReader::new(compressed.get(taken..).ok_or_else(truncated)?)?;
+        let mut first = State::new(&table, &mut reader);
+        let mut second = State::new(&table, &mut reader);
+        let mut weights = Vec::new();
+        // states take turns until bits run out, the other state gives the last weight
+        loop {
+            weights.push(first.symbol());
+            first.update(&mut reader);
+            if reader.remaining() < 0 {
+                weights.push(second.symbol());
+                break;
+            }
+            weights.push(second.symbol());
+            second.update(&mut reader);
+            if reader.remaining() < 0 {
+                weights.push(first.symbol());
+                break;
+            }
+            if weights.len() > 255 {
+                return Err(corrupt("literals have too many symbols"));
+            }
+        }
+        Ok((Huffman::from_weights(weights)?, 1 + header))
+    }
+
+    /// Decodes `count` literals of single stream
+    fn decode(&self, stream: &[u8], count: usize, output: &mut Vec<u8>) -> io::Result<()> {
+        let mut reader = BackwardReader::new(stream)?;
+        for _ in 0..count {
+            let (symbol, length) = self.entries[reader.peek(self.bits) as usize];
+            reader.consume(u32::from(length));
+            output.push(symbol);
+        }
+        match reader.remaining() {
+            0 => Ok(()),
+            _ => Err(corrupt("literal stream does not end with its last literal")),
+        }
+    }
+}
+
+/// First table entry of codes of every length, longer codes go first
+fn rank_starts(lengths: &[u32], bits: u32) -> Vec<usize> {
+    let mut counts = vec![0usize; bits as usize + 1];
+    for length in lengths.iter().filter(|length| **length > 0) {
+        counts[*length as usize] += 1;
+    }
+    let mut starts = vec![0; bits as usize + 1];
+    for length in (1..bits as usize).rev() {
+        starts[length] = starts[length + 1] + (counts[length + 1] << (bits as usize - length - 1));
+    }
+    starts
+}
+
+/// Reads literals section from the start of block, returns bytes it took.
+/// Huffman table of compressed literals is kept for blocks reusing it
+pub(super) fn read(block: &[u8], huffman: &mut Option<Huffman>, literals: &mut Vec<u8>) -> io::Result<usize> {
+    let truncated = || corrupt("literals are truncated");
+    let first = *block.first().ok_or_else(truncated)?;
+    let kind = first & 3;
+    let format = (first >> 2) & 3;
+    literals.clear();
+
+    if kind < 2 {
+        let (size, header) = match format {
+            0 | 2 => (usize::from(first >> 3), 1),
+            1 => (usize::from(first >> 4) | (usize::from(*block.get(1).ok_or_else(truncated)?) << 4), 2),
+            _ => {
+                let bytes = block.get(..3).ok_or_else(truncated)?;
+                (usize::from(first >> 4) | (usize::from(bytes[1]) << 4) | (usize::from(bytes[2]) << 12), 3)
+            }
+        };
+        return match kind {
+            0 => {
+                literals.extend_from_slice(block.get(header..header + size).ok_or_else(truncated)?);
+                Ok(header + size)
+            }
+            _ => {
+                literals.resize(size, *block.get(header).ok_or_else(truncated)?);
+                Ok(header + 1)
+            }
+        };
+    }
+
+    let (header, size_bits) = match format {
+        0 | 1 => (3, 10),
+        2 => (4, 14),
+        _ => (5, 18),
+    };
+    let mut value = [0; 8];
+    value[..header].copy_from_slice(block.get(..header).ok_or_else(truncated)?);
+    let value = u64::from_le_bytes(value) >> 4;
+    let size = (value & ((1 << size_bits) - 1)) as usize;
+    let compressed = ((value >> size_bits) & ((1 << size_bits) - 1)) as usize;
+    let mut content = block.get(header..header + compressed).ok_or_else(truncated)?;
+
+    if kind == 2 {
+        let (table, taken) = Huffman::read(content)?;
+        *huffman = Some(table);
+        content = &content[taken..];
+    }
+    let table = huffman.as_ref().ok_or_else(|| corrupt("literals reuse Huffman table never given"))?;
+
+    if format == 0 {
+        table.decode(content, size, literals)?;
+    } else {
+        let jumps = content.get(..6).ok_or_else(truncated)?;
+        let lengths: Vec<usize> = jumps.chunks(2).map(|pair| usize::from(u16::from_le_bytes([pair[0], pair[1]]))).collect();
+        let mut streams = &content[6..];
+        let segment = size.div_ceil(4);
+        for (index, length) in lengths.iter().enumerate() {
+            let stream = streams.get(..*length).ok_or_else(truncated)?;
+            table.decode(stream, segment, literals)?;
+            streams = &streams[*length..];
+            debug_assert_eq!(literals.len(), segment * (index + 1));
+        }
+        let last = size.checked_sub(3 * segment).ok_or_else(|| corrupt("literals are too few for four streams"))?;
+        table.decode(streams, last, literals)?;
+    }
+    Ok(header + compressed)
+}
+
+/// Header of raw or run length literals section
+fn plain_header(kind: u8, size: usize, output: &mut Vec<u8>) {
+    match size {
+        0..=31 => output.push(kind | (size << 3) as u8),
+        32..=4095 => output.extend_from_slice(&[kind | (1 << 2) | (size << 4) as u8, (size >> 4) as u8]),
+        _ => output.extend_from_slice(&[kind | (3 << 2) | (size << 4) as u8, (size >> 4) as u8, (size >> 12) as u8]),
+    }
+}
+
+/// Writes literals section of block, the smallest of raw, run length and
+/// Huffman coded ones
+pub(super) fn write(literals: &[u8], output: &mut Vec<u8>) {
+    if literals.len() > 1 && literals.iter().all(|byte| *byte == literals[0]) {
+        plain_header(1, literals.len(), output);
+        output.push(literals[0]);
+        return;
+    }
+    match compress(literals) {
+        Some(compressed) if compressed.len() < literals.len() => output.extend_from_slice(&compressed),
+        _ => {
+            plain_header(0, literals.len(), output);
+            output.extend_from_slice(literals);
+        }
+    }
+}
+
+/// Huffman coded literals section. `None` when there are too few of them,
+/// or symbols above 128, whose weights need to be compressed themselves
+fn compress(literals: &[u8]) -> Option<Vec<u8>> {
+    let mut frequencies = vec![0u64; 256];
+    for byte in literals {
+        frequencies[usize::from(*byte)] += 1;
+    }
+    let last = frequencies.iter().rposition(|frequency| *frequency > 0)?;
+    if last > 128 || frequencies.iter().filter(|frequency| **frequency > 0).count() < 2 {
+        return None;
+    }
+
+    let lengths: Vec<u32> = code_lengths(&frequencies[..=last], MAX_BITS as u8).into_iter().map(u32::from).collect();
+    let bits = lengths.iter().copied().max()?;
+    let mut starts = rank_starts(&lengths, bits);
+    let codes: Vec<u64> = lengths
+        .iter()
+        .map(|length| {
+            if *length == 0 {
+                return 0;
+            }
+            let start = starts[*length as usize];
+            starts[*length as usize] += 1 << (bits - length);
+            (start >> (bits - length)) as u64
+        })
+        .collect();
+
+    let mut section = vec![128 + last as u8 - 1];
+    let weights: Vec<u8> = lengths[..last].iter().map(|length| if *length > 0 { (bits + 1 - length) as u8 } else { 0 }).collect();
+    for pair in weights.chunks(2) {
+        section.push((pair[0] << 4) | pair.get(1).copied().unwrap_or(0));
+    }
+
+    // decoder reads stream from its end, so literals are written last first
+    let stream = |literals: &[u8]| {
+        let mut writer = BitWriter::default();
+        for byte in literals.iter().rev() {
+            writer.write(codes[usize::from(*byte)], lengths[usize::from(*byte)]);
+        }
+        writer.write(1, 1);
+        writer.finish()
+    };
+    let single = literals.len() <= SINGLE_STREAM_MAX;
+    if single {
+        section.extend(stream(literals));
+    } else {
+        let segment = literals.len().div_ceil(4);
+        let streams: Vec<Vec<u8>> = literals.chunks(segment).map(stream).collect();
+        if streams.len() != 4 || streams.iter().any(|stream| stream.len() > usize::from(u16::MAX)) {
+            return None;
+        }
+        for stream in &streams[..3] {
+            section.extend_from_slice(&(stream.len() as u16).to_le_bytes());
+        }
+        section.extend(streams.concat());
+    }
+
+    let size = literals.len() as u64;
+    let compressed = section.len() as u64;
+    let (format, header_size, size_bits) = match size.max(compressed) {
+        _ if single && compressed > 1023 => return None,
+        _ if single => (0, 3, 10),
+        0..=1023 => (1, 3, 10),
+        1024..=16383 => (2, 4, 14),
+        _ => (3, 5, 18),
+    };
+    let value: u64 = 2 | (format << 2) | (size << 4) | (compressed << (4 + size_bits));
+    let mut output = Vec::with_capacity(section.len() + 5);
+    output.extend_from_slice(&value.to_le_bytes()[..header_size]);
+    output.extend(section);
+    Some(output)
+}
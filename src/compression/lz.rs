@@ -0,0 +1,97 @@
+/// Bits of hash of match prefix
+const HASH_BITS: u32 = 16;
+
+/// Candidates looked at for every position, more finds longer matches slower
+const CHAIN_DEPTH: usize = 32;
+
+/// Earlier occurrence of data at current position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Match {
+    pub distance: usize,
+    pub length: usize,
+}
+
+/// Finds repeated data for LZ77 style compressors by chains of positions
+/// with the same hash of their first `min` bytes
+pub(super) struct Matcher {
+    window: usize,
+    min: usize,
+    max: usize,
+    /// Latest position of every hash, plus one so zero means none
+    head: Vec<usize>,
+    /// Previous position of the same hash, by position modulo window
+    chain: Vec<usize>,
+}
+
+impl Matcher {
+    /// Matches are `min..=max` bytes long and start at most `window` bytes
+    /// back, `window` is power of two
+    pub fn new(window: usize, min: usize, max: usize) -> Self {
+        Matcher {
+            window,
+            min,
+            max,
+            head: vec![0; 1 << HASH_BITS],
+            chain: vec![0; window],
+        }
+    }
+
+    fn hash(&self, data: &[u8], position: usize) -> usize {
+        let key = data[position..position + self.min]
+            .iter()
+            .fold(0u32, |hash, byte| hash.wrapping_mul(0x9E37_79B1).wrapping_add(u32::from(*byte) + 1));
+        (key.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    }
+
+    /// Remembers position as candidate for later matches
+    pub fn insert(&mut self, data: &[u8], position: usize) {
+        if position + self.min > data.len() {
+            return;
+        }
+        let hash = self.hash(data, position);
+        self.chain[position & (self.window - 1)] = self.head[hash];
+        self.head[hash] = position + 1;
+    }
+
+    /// Longest match of data at `position` which ends before `end`.
+    /// Position itself is not inserted
+    pub fn find(&self, data: &[u8], position: usize, end: usize) -> Option<Match> {
+        let longest = self.max.min(end - position);
+        if longest < self.min {
+            return None;
+        }
+
+        let mut best: Option<Match> = None;
+        let mut candidate = self.head[self.hash(data, position)];
+        for _ in 0..CHAIN_DEPTH {
+            // positions are stored plus one, zero ends the chain
+            let Some(start) = candidate.checked_sub(1) else {
+                break;
+            };
+            if start >= position || position - start > self.window {
+                break;
+            }
+            let length = data[start..start + longest]
+                .iter()
+                .zip(&data[position..position + longest])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if length >= self.min && best.is_none_or(|best| length > best.length) {
+                best = Some(Match {
+                    distance: position - start,
+                    length,
+                });
+                if length == longest {
+                    break;
+                }
+            }
+            let next = self.chain[start & (self.window - 1)];
+            // slot was taken over by newer position, chain is broken
+            if next > start {
+                break;
+            }
+            candidate = next;
+        }
+        best
+    }
+}
@@ -0,0 +1,512 @@
+//! Zstandard frames (RFC 8878), without dictionaries
+
+use std::io;
+use std::sync::OnceLock;
+
+use super::bits::{BackwardReader, BitWriter};
+use super::fse::{State, Table};
+use super::literals::{self, Huffman};
+use super::lz::Matcher;
+use super::{invalid, Sink, ZSTD_MAGIC};
+
+/// Skippable frames carry data for other tools, any low nibble of first byte
+const SKIPPABLE_MAGIC: u32 = 0x184D_2A50;
+
+/// Largest content of single block
+const BLOCK_MAX: usize = 128 * 1024;
+
+/// Window of frames written here, small enough for chains of matcher to stay in cache
+const WINDOW_LOG: u32 = 17;
+const MIN_MATCH: usize = 4;
+
+const LITERALS_LENGTH_BASE: [u32; 36] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 18, 20, 22, 24, 28, 32, 40, 48, 64, 128, 256, 512, 1024,
+    2048, 4096, 8192, 16384, 32768, 65536,
+];
+const LITERALS_LENGTH_BITS: [u8; 36] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 3, 3, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+];
+const MATCH_LENGTH_BASE: [u32; 53] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32,
+    33, 34, 35, 37, 39, 41, 43, 47, 51, 59, 67, 83, 99, 131, 259, 515, 1027, 2051, 4099, 8195, 16387, 32771, 65539,
+];
+const MATCH_LENGTH_BITS: [u8; 53] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2,
+    3, 3, 4, 4, 5, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+];
+
+const LITERALS_LENGTH_DEFAULT: [i16; 36] = [
+    4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 3, 2, 1, 1, 1, 1, 1, -1, -1, -1, -1,
+];
+const MATCH_LENGTH_DEFAULT: [i16; 53] = [
+    1, 4, 3, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1, -1, -1,
+];
+const OFFSET_DEFAULT: [i16; 29] = [
+    1, 1, 1, 1, 1, 1, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1,
+];
+
+fn corrupt(reason: &str) -> io::Error {
+    invalid(format!("Invalid zstd data: {reason}"))
+}
+
+/// Literal length, offset and match length tables of predefined distributions
+fn default_tables() -> &'static [Table; 3] {
+    static TABLES: OnceLock<[Table; 3]> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let table = |counts: &[i16], log| Table::new(counts, log).expect("predefined distribution is valid");
+        [table(&LITERALS_LENGTH_DEFAULT, 6), table(&OFFSET_DEFAULT, 5), table(&MATCH_LENGTH_DEFAULT, 6)]
+    })
+}
+
+fn read_u32(bytes: &[u8], position: usize) -> io::Result<u32> {
+    let bytes = bytes.get(position..position + 4).ok_or_else(|| corrupt("content is truncated"))?;
+    Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Unpacks every frame of `content` into `sink`
+pub(super) fn decompress(content: &[u8], sink: &mut Sink) -> io::Result<()> {
+    let mut position = 0;
+    while position < content.len() {
+        let magic = read_u32(content, position)?;
+        if magic & 0xFFFF_FFF0 == SKIPPABLE_MAGIC {
+            let size = read_u32(content, position + 4)? as usize;
+            position = (position + 8).checked_add(size).filter(|end| *end <= content.len()).ok_or_else(|| corrupt("content is truncated"))?;
+            continue;
+        }
+        if magic.to_le_bytes() != ZSTD_MAGIC {
+            return Err(corrupt("unexpected data after the end of compressed content"));
+        }
+        position = frame(content, position + 4, sink)?;
+    }
+    Ok(())
+}
+
+/// Tables frame blocks may reuse from blocks before them
+#[derive(Default)]
+struct FrameState {
+    huffman: Option<Huffman>,
+    /// Literal length, offset and match length tables
+    tables: [Option<Table>; 3],
+    offsets: [usize; 3],
+    literals: Vec<u8>,
+}
+
+/// Unpacks frame which starts at `position` after magic, returns where it ends
+fn frame(content: &[u8], mut position: usize, sink: &mut Sink) -> io::Result<usize> {
+    let truncated = || corrupt("content is truncated");
+    let descriptor = *content.get(position).ok_or_else(truncated)?;
+    position += 1;
+    let single_segment = descriptor & 0x20 != 0;
+    let checksum = descriptor & 0x04 != 0;
+    if descriptor & 0x08 != 0 {
+        return Err(corrupt("reserved bit of frame header is set"));
+    }
+    if !single_segment {
+        position += 1;
+    }
+    let dictionary = [0, 1, 2, 4][usize::from(descriptor & 3)];
+    let dictionary_id = content.get(position..position + dictionary).ok_or_else(truncated)?;
+    if dictionary_id.iter().any(|byte| *byte != 0) {
+        return Err(corrupt("frames needing dictionary are not supported"));
+    }
+    position += dictionary;
+    let size_bytes = match descriptor >> 6 {
+        0 if single_segment => 1,
+        0 => 0,
+        1 => 2,
+        2 => 4,
+        _ => 8,
+    };
+    let mut size = [0; 8];
+    size[..size_bytes].copy_from_slice(content.get(position..position + size_bytes).ok_or_else(truncated)?);
+    let declared = match size_bytes {
+        0 => None,
+        2 => Some(u64::from_le_bytes(size) + 256),
+        _ => Some(u64::from_le_bytes(size)),
+    };
+    position += size_bytes;
+
+    let start = sink.data.len();
+    let mut state = FrameState {
+        offsets: [1, 4, 8],
+        ..FrameState::default()
+    };
+    loop {
+        let header = content.get(position..position + 3).ok_or_else(truncated)?;
+        let header = u32::from(header[0]) | (u32::from(header[1]) << 8) | (u32::from(header[2]) << 16);
+        position += 3;
+        let last = header & 1 == 1;
+        let size = (header >> 3) as usize;
+        match (header >> 1) & 3 {
+            0 => {
+                sink.data.extend_from_slice(content.get(position..position + size).ok_or_else(truncated)?);
+                position += size;
+            }
+            1 => {
+                if size > BLOCK_MAX {
+                    return Err(corrupt("block is too large"));
+                }
+                let byte = *content.get(position).ok_or_else(truncated)?;
+                sink.data.resize(sink.data.len() + size, byte);
+                position += 1;
+            }
+            2 => {
+                if size > BLOCK_MAX {
+                    return Err(corrupt("block is too large"));
+                }
+                let block = content.get(position..position + size).ok_or_else(truncated)?;
+                decode_block(block, &mut state, sink, start)?;
+                position += size;
+            }
+            _ => return Err(corrupt("block type is reserved")),
+        }
+        sink.check()?;
+        if last {
+            break;
+        }
+    }
+
+    let unpacked = &sink.data[start..];
+    if declared.is_some_and(|declared| declared != unpacked.len() as u64) {
+        return Err(corrupt("size does not match content"));
+    }
+    if checksum {
+        if read_u32(content, position)? != xxh64(unpacked) as u32 {
+            return Err(corrupt("checksum does not match content"));
+        }
+        position += 4;
+    }
+    Ok(position)
+}
+
+/// Table of sequences section by its mode, remembered for blocks repeating it.
+/// Returns bytes of block it took
+fn read_table(
+    block: &[u8],
+    mode: u8,
+    kind: usize,
+    tables: &mut [Option<Table>; 3],
+) -> io::Result<usize> {
+    const MAX_LOG: [u32; 3] = [9, 8, 9];
+    const MAX_SYMBOL: [usize; 3] = [35, 31, 52];
+    let (table, taken) = match mode {
+        0 => (default_tables()[kind].clone(), 0),
+        1 => {
+            let symbol = *block.first().ok_or_else(|| corrupt("sequences are truncated"))?;
+            if usize::from(symbol) > MAX_SYMBOL[kind] {
+                return Err(corrupt("sequence code is out of range"));
+            }
+            (Table::rle(symbol), 1)
+        }
+        2 => Table::read(block, MAX_LOG[kind], MAX_SYMBOL[kind])?,
+        _ => match &tables[kind] {
+            Some(_) => return Ok(0),
+            None => return Err(corrupt("sequences repeat table never given")),
+        },
+    };
+    tables[kind] = Some(table);
+    Ok(taken)
+}
+
+fn decode_block(block: &[u8], state: &mut FrameState, sink: &mut Sink, start: usize) -> io::Result<()> {
+    let truncated = || corrupt("block is truncated");
+    let mut position = literals::read(block, &mut state.huffman, &mut state.literals)?;
+
+    let first = usize::from(*block.get(position).ok_or_else(truncated)?);
+    let (count, taken) = match first {
+        0..=127 => (first, 1),
+        128..=254 => (((first - 128) << 8) + usize::from(*block.get(position + 1).ok_or_else(truncated)?), 2),
+        _ => {
+            let bytes = block.get(position + 1..position + 3).ok_or_else(truncated)?;
+            (usize::from(bytes[0]) + (usize::from(bytes[1]) << 8) + 0x7F00, 3)
+        }
+    };
+    position += taken;
+    if count == 0 {
+        sink.data.extend_from_slice(&state.literals);
+        return Ok(());
+    }
+
+    let modes = *block.get(position).ok_or_else(truncated)?;
+    position += 1;
+    if modes & 3 != 0 {
+        return Err(corrupt("reserved bits of sequences are set"));
+    }
+    for (kind, shift) in [(0, 6), (1, 4), (2, 2)] {
+        position += read_table(block.get(position..).ok_or_else(truncated)?, (modes >> shift) & 3, kind, &mut state.tables)?;
+    }
+
+    let [Some(literal_table), Some(offset_table), Some(match_table)] = &state.tables else {
+        unreachable!("every table is read above");
+    };
+    let mut reader = BackwardReader::new(block.get(position..).ok_or_else(truncated)?)?;
+    let mut literal_state = State::new(literal_table, &mut reader);
+    let mut offset_state = State::new(offset_table, &mut reader);
+    let mut match_state = State::new(match_table, &mut reader);
+
+    let mut literals = &state.literals[..];
+    for sequence in 0..count {
+        let literal_code = usize::from(literal_state.symbol());
+        let offset_code = u32::from(offset_state.symbol());
+        let match_code = usize::from(match_state.symbol());
+        if literal_code >= LITERALS_LENGTH_BASE.len() || match_code >= MATCH_LENGTH_BASE.len() || offset_code > 31 {
+            return Err(corrupt("sequence code is out of range"));
+        }
+        let offset_value = (1u64 << offset_code) + reader.read(offset_code);
+        let match_length = MATCH_LENGTH_BASE[match_code] as usize + reader.read(u32::from(MATCH_LENGTH_BITS[match_code])) as usize;
+        let literal_length =
+            LITERALS_LENGTH_BASE[literal_code] as usize + reader.read(u32::from(LITERALS_LENGTH_BITS[literal_code])) as usize;
+        if sequence + 1 < count {
+            literal_state.update(&mut reader);
+            match_state.update(&mut reader);
+            offset_state.update(&mut reader);
+        }
+
+        let offsets = &mut state.offsets;
+        let offset = match offset_value {
+            1..=3 => {
+                // without literals repeated offsets shift by one
+                let index = offset_value as usize - 1 + usize::from(literal_length == 0);
+                let offset = match index {
+                    3 => offsets[0].wrapping_sub(1),
+                    index => offsets[index],
+                };
+                if index > 0 {
+                    if index > 1 {
+                        offsets[2] = offsets[1];
+                    }
+                    offsets[1] = offsets[0];
+                    offsets[0] = offset;
+                }
+                offset
+            }
+            _ => {
+                let offset = (offset_value - 3) as usize;
+                *offsets = [offset, offsets[0], offsets[1]];
+                offset
+            }
+        };
+
+        let (copied, rest) = literals.split_at_checked(literal_length).ok_or_else(|| corrupt("sequence takes more literals than block has"))?;
+        sink.data.extend_from_slice(copied);
+        literals = rest;
+        if offset == 0 || offset > sink.data.len() - start {
+            return Err(corrupt("offset reaches before the start of content"));
+        }
+        sink.copy_match(offset, match_length);
+    }
+    if reader.remaining() != 0 {
+        return Err(corrupt("sequences do not end with their last one"));
+    }
+    sink.data.extend_from_slice(literals);
+    Ok(())
+}
+
+const PRIME_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME_4: u64 = 0x85EB_CA77_C2B2_AE63;
+const PRIME_5: u64 = 0x27D4_EB2F_1656_67C5;
+
+fn xxh64_round(accumulator: u64, lane: u64) -> u64 {
+    accumulator.wrapping_add(lane.wrapping_mul(PRIME_2)).rotate_left(31).wrapping_mul(PRIME_1)
+}
+
+fn xxh64_merge(hash: u64, accumulator: u64) -> u64 {
+    (hash ^ xxh64_round(0, accumulator)).wrapping_mul(PRIME_1).wrapping_add(PRIME_4)
+}
+
+/// XXH64 with zero seed, lower half of it is checksum of zstd frames
+fn xxh64(data: &[u8]) -> u64 {
+    let u64_at = |bytes: &[u8]| u64::from_le_bytes(bytes[..8].try_into().expect("lane is 8 bytes"));
+    let mut stripes = data.chunks_exact(32);
+    let mut hash = if data.len() >= 32 {
+        let mut lanes = [PRIME_1.wrapping_add(PRIME_2), PRIME_2, 0, 0u64.wrapping_sub(PRIME_1)];
+        for stripe in &mut stripes {
+            for (lane, bytes) in lanes.iter_mut().zip(stripe.chunks_exact(8)) {
+                *lane = xxh64_round(*lane, u64_at(bytes));
+            }
+        }
+        let [a, b, c, d] = lanes;
+        let hash = a.rotate_left(1).wrapping_add(b.rotate_left(7)).wrapping_add(c.rotate_left(12)).wrapping_add(d.rotate_left(18));
+        lanes.iter().fold(hash, |hash, lane| xxh64_merge(hash, *lane))
+    } else {
+        PRIME_5
+    };
+    hash = hash.wrapping_add(data.len() as u64);
+
+    let mut rest = stripes.remainder();
+    while rest.len() >= 8 {
+        hash = (hash ^ xxh64_round(0, u64_at(rest))).rotate_left(27).wrapping_mul(PRIME_1).wrapping_add(PRIME_4);
+        rest = &rest[8..];
+    }
+    if rest.len() >= 4 {
+        let word = u64::from(u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]));
+        hash = (hash ^ word.wrapping_mul(PRIME_1)).rotate_left(23).wrapping_mul(PRIME_2).wrapping_add(PRIME_3);
+        rest = &rest[4..];
+    }
+    for byte in rest {
+        hash = (hash ^ u64::from(*byte).wrapping_mul(PRIME_5)).rotate_left(11).wrapping_mul(PRIME_1);
+    }
+
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME_3);
+    hash ^ (hash >> 32)
+}
+
+/// Literals before match, and the match
+struct Sequence {
+    literals: usize,
+    offset: usize,
+    length: usize,
+}
+
+/// Index of the last base not above `value`
+fn code_of(bases: &[u32], value: usize) -> usize {
+    bases.partition_point(|base| *base as usize <= value) - 1
+}
+
+/// Single frame of `content` with checksum
+pub(super) fn compress(content: &[u8]) -> Vec<u8> {
+    let mut output = ZSTD_MAGIC.to_vec();
+    // 8 byte content size, window descriptor and checksum
+    output.push(0xC4);
+    output.push(((WINDOW_LOG - 10) << 3) as u8);
+    output.extend_from_slice(&(content.len() as u64).to_le_bytes());
+
+    let mut matcher = Matcher::new(1 << WINDOW_LOG, MIN_MATCH, BLOCK_MAX);
+    let mut start = 0;
+    loop {
+        let end = content.len().min(start + BLOCK_MAX);
+        let last = end == content.len();
+        let block = compress_block(content, start, end, &mut matcher);
+        let (kind, size, body) = match block {
+            Some(block) if block.len() < end - start => (2, block.len(), block),
+            _ => (0, end - start, content[start..end].to_vec()),
+        };
+        let header = u32::from(last) | (kind << 1) | ((size as u32) << 3);
+        output.extend_from_slice(&header.to_le_bytes()[..3]);
+        output.extend(body);
+        if last {
+            break;
+        }
+        start = end;
+    }
+
+    output.extend_from_slice(&(xxh64(content) as u32).to_le_bytes());
+    output
+}
+
+/// Compressed block of `content[start..end]`, matches may reach into
+/// blocks before it. `None` for empty block
+fn compress_block(content: &[u8], start: usize, end: usize, matcher: &mut Matcher) -> Option<Vec<u8>> {
+    if start == end {
+        return None;
+    }
+    let mut literals = Vec::new();
+    let mut sequences = Vec::new();
+    let mut pending = 0;
+    let mut position = start;
+    while position < end {
+        match matcher.find(content, position, end) {
+            Some(found) => {
+                for inserted in position..position + found.length {
+                    matcher.insert(content, inserted);
+                }
+                sequences.push(Sequence {
+                    literals: pending,
+                    offset: found.distance,
+                    length: found.length,
+                });
+                pending = 0;
+                position += found.length;
+            }
+            None => {
+                matcher.insert(content, position);
+                literals.push(content[position]);
+                pending += 1;
+                position += 1;
+            }
+        }
+    }
+
+    let mut output = Vec::new();
+    literals::write(&literals, &mut output);
+    match sequences.len() {
+        count @ 0..=127 => output.push(count as u8),
+        count @ 128..=0x7EFF => output.extend_from_slice(&[(count >> 8) as u8 + 128, count as u8]),
+        count => output.extend_from_slice(&[255, (count - 0x7F00) as u8, ((count - 0x7F00) >> 8) as u8]),
+    }
+    if sequences.is_empty() {
+        return Some(output);
+    }
+    // predefined tables for all three codes
+    output.push(0);
+    output.extend(encode_sequences(&sequences));
+    Some(output)
+}
+
+/// Bitstream of sequences coded by predefined tables. Decoder reads it from
+/// the end, so sequences are written last first and every field in reverse
+/// order of reading
+fn encode_sequences(sequences: &[Sequence]) -> Vec<u8> {
+    struct Coded {
+        codes: [u8; 3],
+        /// Extra bits of literal length, match length and offset
+        extra: [(u64, u32); 3],
+    }
+    let coded: Vec<Coded> = sequences
+        .iter()
+        .map(|sequence| {
+            let literal = code_of(&LITERALS_LENGTH_BASE, sequence.literals);
+            let matched = code_of(&MATCH_LENGTH_BASE, sequence.length);
+            // offsets are never written as repeated ones
+            let offset_value = sequence.offset as u64 + 3;
+            let offset = 63 - offset_value.leading_zeros();
+            Coded {
+                codes: [literal as u8, offset as u8, matched as u8],
+                extra: [
+                    ((sequence.literals - LITERALS_LENGTH_BASE[literal] as usize) as u64, u32::from(LITERALS_LENGTH_BITS[literal])),
+                    ((sequence.length - MATCH_LENGTH_BASE[matched] as usize) as u64, u32::from(MATCH_LENGTH_BITS[matched])),
+                    (offset_value - (1 << offset), offset),
+                ],
+            }
+        })
+        .collect();
+
+    // states of literal length, offset and match length tables, found from the last sequence
+    let tables = default_tables();
+    let encodings: Vec<Vec<Vec<u16>>> = tables.iter().map(Table::encoding).collect();
+    let mut states: Vec<[usize; 3]> = vec![[0; 3]; coded.len()];
+    for (index, sequence) in coded.iter().enumerate().rev() {
+        for kind in 0..3 {
+            let symbol = usize::from(sequence.codes[kind]);
+            let next = states.get(index + 1).map_or(0, |next| next[kind]);
+            states[index][kind] = usize::from(encodings[kind][symbol][next]);
+        }
+    }
+
+    let mut writer = BitWriter::default();
+    for (index, sequence) in coded.iter().enumerate().rev() {
+        if let Some(next) = states.get(index + 1) {
+            // read as literal length, match length, offset
+            for kind in [1, 2, 0] {
+                let entry = tables[kind].entries[states[index][kind]];
+                writer.write((next[kind] - usize::from(entry.base)) as u64, u32::from(entry.bits));
+            }
+        }
+        // read as offset, match length, literal length
+        for (value, bits) in sequence.extra {
+            writer.write(value, bits);
+        }
+    }
+    // read as literal length, offset, match length
+    for kind in [2, 1, 0] {
+        writer.write(states[0][kind] as u64, tables[kind].log);
+    }
+    writer.write(1, 1);
+    writer.finish()
+}
@@ -0,0 +1,111 @@
+//! Condition number of matrix `|A| |A^-1|` in max norm: how much relative
+//! error of right hand side, including rounding and stopping early, may grow
+//! in solution. Huge condition number explains why epsilon is not reached,
+//! or iterations diverge, on system which has solution
+
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::norm::Norm;
+use crate::solver::{Equation, LuFactorization, DIRECT_MAX_SIZE};
+
+/// Larger systems are not estimated: two factorizations would cost
+/// more than iterations do
+pub const CONDITION_MAX_SIZE: usize = 4 * DIRECT_MAX_SIZE;
+
+/// Steps of Hager's estimator, it usually stops after two
+const ESTIMATE_STEPS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    /// `Decimal::MAX` for singular matrix
+    pub value: Decimal,
+    /// Norm of inverse is estimated from below, not computed
+    pub estimated: bool,
+}
+
+impl Condition {
+    pub fn is_singular(&self) -> bool {
+        self.value == Decimal::MAX
+    }
+}
+
+/// Max norm of matrix, largest sum of absolute values of row
+pub fn matrix_norm(matrix: &DMatrix<Decimal>) -> Decimal {
+    (matrix.row_iter())
+        .map(|row| Norm::L1.of(row.iter().copied()))
+        .fold(dec!(0), Decimal::max)
+}
+
+/// Computed through inverse for systems up to [`DIRECT_MAX_SIZE`], estimated
+/// beyond that. `None` for systems above [`CONDITION_MAX_SIZE`]
+pub fn condition_number(equation: &Equation) -> Option<Condition> {
+    let matrix = &equation.input_matrix;
+    let size = matrix.nrows();
+    let norm = matrix_norm(matrix);
+    let singular = Condition {
+        value: Decimal::MAX,
+        estimated: false,
+    };
+    let (inverse_norm, estimated) = if size <= DIRECT_MAX_SIZE {
+        match equation.inverse() {
+            Ok(inverse) => (matrix_norm(&inverse), false),
+            Err(_) => return Some(singular),
+        }
+    } else if size <= CONDITION_MAX_SIZE {
+        match estimate_inverse_norm(matrix) {
+            Some(inverse_norm) => (inverse_norm, true),
+            None => return Some(singular),
+        }
+    } else {
+        return None;
+    };
+    Some(Condition {
+        value: norm.checked_mul(inverse_norm).unwrap_or(Decimal::MAX),
+        estimated,
+    })
+}
+
+/// `|A^-1|` in max norm, which is `|A^-T|` in 1-norm, by Hager's estimator:
+/// climbs towards column of `A^-T` with largest sum, one solve with `A^T`
+/// and one with `A` per step. Never above true norm, usually equal to it.
+/// `None` when matrix is singular
+fn estimate_inverse_norm(matrix: &DMatrix<Decimal>) -> Option<Decimal> {
+    let size = matrix.nrows();
+    let factors = LuFactorization::new(matrix).ok()?;
+    let transposed = LuFactorization::new(&matrix.transpose()).ok()?;
+
+    let mut x = DVector::from_element(size, Decimal::ONE / Decimal::from(size));
+    let mut estimate = dec!(0);
+    for _ in 0..ESTIMATE_STEPS {
+        let y = transposed.solve(&x).ok()?;
+        estimate = estimate.max(Norm::L1.of(y.iter().copied()));
+        let signs = y.map(|value| if value.is_sign_negative() { -Decimal::ONE } else { Decimal::ONE });
+        let z = factors.solve(&signs).ok()?;
+        let (column, largest) = (z.iter().enumerate())
+            .map(|(column, value)| (column, value.abs()))
+            .max_by_key(|(_, value)| *value)
+            .expect("matrix is not empty");
+        if largest <= z.dot(&x) {
+            break;
+        }
+        x = DVector::from_element(size, dec!(0));
+        x[column] = Decimal::ONE;
+    }
+    Some(estimate)
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_singular() {
+            return write!(f, "infinite, matrix is singular or nearly so");
+        }
+        let value = self.value.round_sf(3).unwrap_or(self.value).normalize();
+        match self.estimated {
+            true => write!(f, "about {value} in max norm, estimated"),
+            false => write!(f, "{value} in max norm"),
+        }
+    }
+}
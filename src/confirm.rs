@@ -0,0 +1,33 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Lists changes solver is going to make to the system and asks user to agree.
+/// Without terminal to ask on, like in scripts, changes are made with listing
+/// above left as warning
+pub fn confirm_changes(changes: &[String], assume_yes: bool) -> bool {
+    if changes.is_empty() {
+        return true;
+    }
+
+    eprintln!("Input system will be transformed before solving:");
+    for change in changes {
+        eprintln!("  - {change}");
+    }
+
+    if assume_yes {
+        return true;
+    }
+
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        eprintln!("Warning: no terminal to ask for confirmation on, proceeding");
+        return true;
+    }
+
+    eprint!("Continue? [y/N] ");
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    if stdin.lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
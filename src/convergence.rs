@@ -0,0 +1,93 @@
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Deltas of linearly converging iterations shrink roughly as `delta_k = c * rate^k`.
+/// Rate is fit by least squares of `ln(delta_k)` against `k`
+#[derive(Debug, Clone, Copy)]
+pub struct ConvergenceRate {
+    /// Delta is multiplied by this every iteration, below 1 when iterations converge
+    pub rate: Decimal,
+    /// Amount of deltas rate is fit to
+    pub samples: usize,
+}
+
+impl ConvergenceRate {
+    /// Needs at least two non-zero deltas. Deltas are expected to come from the same
+    /// kind of iteration, otherwise rate mixes different methods
+    pub fn estimate(deltas: &[Decimal]) -> Option<Self> {
+        let points: Vec<(Decimal, Decimal)> = deltas
+            .iter()
+            .enumerate()
+            .filter(|(_, delta)| delta.is_sign_positive() && !delta.is_zero())
+            .map(|(k, delta)| (Decimal::from(k), delta.ln()))
+            .collect();
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = Decimal::from(points.len());
+        let (sum_k, sum_y, sum_kk, sum_ky) = points.iter().fold(
+            (dec!(0), dec!(0), dec!(0), dec!(0)),
+            |(sum_k, sum_y, sum_kk, sum_ky), (k, y)| {
+                (sum_k + k, sum_y + y, sum_kk + k * k, sum_ky + k * y)
+            },
+        );
+        let slope = (n * sum_ky - sum_k * sum_y).checked_div(n * sum_kk - sum_k * sum_k)?;
+
+        Some(ConvergenceRate {
+            rate: slope.checked_exp()?,
+            samples: points.len(),
+        })
+    }
+
+    /// Iterations it takes to gain one more correct decimal digit
+    pub fn iterations_per_digit(&self) -> Option<Decimal> {
+        if self.rate >= Decimal::ONE || self.rate.is_zero() {
+            return None;
+        }
+        Some(dec!(-1) * Decimal::TEN.ln() / self.rate.ln())
+    }
+}
+
+/// Non-monotone course of deltas: delta sometimes grows from one iteration to the next
+#[derive(Debug, Clone, Copy)]
+pub struct Oscillation {
+    /// Iterations whose delta is larger than delta of previous one
+    pub rises: usize,
+    /// Average distance in iterations between local maxima of delta,
+    /// absent when there are less than two of them
+    pub period: Option<Decimal>,
+    /// Local maxima shrink (below 1) or grow (above 1) by this factor
+    /// from one to the next
+    pub amplitude_rate: Option<Decimal>,
+}
+
+impl Oscillation {
+    /// `None` when deltas never grow
+    pub fn detect(deltas: &[Decimal]) -> Option<Self> {
+        let rises = deltas.windows(2).filter(|pair| pair[1] > pair[0]).count();
+        if rises == 0 {
+            return None;
+        }
+
+        let peaks: Vec<(usize, Decimal)> = (1..deltas.len().saturating_sub(1))
+            .filter(|&k| deltas[k] > deltas[k - 1] && deltas[k] >= deltas[k + 1])
+            .map(|k| (k, deltas[k]))
+            .collect();
+
+        let period = match (peaks.first(), peaks.last()) {
+            (Some((first, _)), Some((last, _))) if peaks.len() > 1 => {
+                Some(Decimal::from(last - first) / Decimal::from(peaks.len() - 1))
+            }
+            _ => None,
+        };
+        let heights: Vec<Decimal> = peaks.iter().map(|(_, delta)| *delta).collect();
+
+        Some(Oscillation {
+            rises,
+            period,
+            amplitude_rate: ConvergenceRate::estimate(&heights).map(|rate| rate.rate),
+        })
+    }
+}
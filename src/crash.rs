@@ -0,0 +1,142 @@
+use guess_zeidel_method::fingerprint::{fingerprint, CanonicalOptions};
+use guess_zeidel_method::service;
+use guess_zeidel_method::solver::{Equation, Progress};
+use serde_json::{json, Value};
+use std::backtrace::Backtrace;
+use std::panic::{self, PanicHookInfo};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, process};
+
+/// What was being solved when solver crashed. Coefficients are never kept:
+/// problem is identified by its hash, which is enough to tell whether two
+/// reports are about the same input
+struct CrashContext {
+    problem: Value,
+    /// Iteration, delta and stage of last progress
+    last: Option<(usize, String, String)>,
+}
+
+static CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+
+/// Bundles written by this process, keeps names of bundles of the same moment apart
+static BUNDLES: AtomicUsize = AtomicUsize::new(0);
+
+/// On panic, bug report bundle is written to temporary directory and its
+/// path is printed after usual panic message. Panics of solves which service
+/// catches and reports as error results are not crashes, they get no bundle
+pub fn install() {
+    let default = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default(info);
+        if !service::catching_crash() {
+            report("panic", &panic_message(info), Backtrace::force_capture());
+        }
+    }));
+}
+
+/// Problem solved from now on, replaces previous one
+pub fn record_problem(equation: &Equation) {
+    let problem = json!({
+        "fingerprint": fingerprint(equation, CanonicalOptions::default()),
+        "matrix_size": equation.input_matrix.nrows(),
+        "method": equation.method.to_string(),
+        "epsilon": equation.epsilon.to_string(),
+        "tolerance_mode": equation.tolerance_mode.to_string(),
+        "norm": equation.norm.map(|norm| norm.to_string()),
+        "max_iterations": equation.max_iterations,
+        "relaxation": equation.relaxation.to_string(),
+        "tau": equation.tau.map(|tau| tau.to_string()),
+        "restart": equation.restart,
+        "fast_path": equation.fast_path,
+        "parallelism": format!("{:?}", equation.parallelism),
+        "acceleration": format!("{:?}", equation.acceleration),
+        "preconditioning": equation.preconditioning.to_string(),
+        "schur_block": equation.schur_block,
+        "blocks": equation.blocks,
+        "fixed_unknowns": equation.fixed.len(),
+        "bounded_unknowns": equation.bounds.len(),
+        "equations_reordered": !equation.row_order.is_empty(),
+        "refinement_steps": equation.refinement_steps,
+        "rounding": equation.rounding.to_string(),
+    });
+    if let Ok(mut context) = CONTEXT.lock() {
+        *context = Some(CrashContext { problem, last: None });
+    }
+}
+
+pub fn record_progress(progress: &Progress) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        if let Some(context) = context.as_mut() {
+            context.last = Some((progress.iteration, progress.delta.to_string(), progress.stage.to_string()));
+        }
+    }
+}
+
+/// Error which checks of input should have ruled out, it is a bug of solver
+pub fn report_internal_error(message: impl ToString) {
+    report("internal error", &message.to_string(), Backtrace::force_capture());
+}
+
+fn report(kind: &str, message: &str, backtrace: Backtrace) {
+    match write_bundle(kind, message, backtrace) {
+        Ok(path) => eprintln!(
+            "This is a bug of solver. Bug report is written to {}, please attach it to issue. \
+             It holds hash of input but not its coefficients: add output of `solver redact` \
+             if the bug needs input to reproduce",
+            path.display()
+        ),
+        Err(err) => eprintln!("This is a bug of solver. Can't write bug report: {err}"),
+    }
+}
+
+fn write_bundle(kind: &str, message: &str, backtrace: Backtrace) -> Result<PathBuf, String> {
+    let since = (SystemTime::now().duration_since(UNIX_EPOCH)).unwrap_or_default();
+    let seconds = since.as_secs();
+    // panic while context is locked must not hang the hook
+    let (problem, last) = match CONTEXT.try_lock() {
+        Ok(context) => match context.as_ref() {
+            Some(context) => (context.problem.clone(), context.last.clone()),
+            None => (Value::Null, None),
+        },
+        Err(_) => (Value::Null, None),
+    };
+    let bundle = json!({
+        "kind": kind,
+        "message": message,
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": env::consts::OS,
+        "arch": env::consts::ARCH,
+        "created": seconds,
+        "problem": problem,
+        "last_iteration": last.map(|(iteration, delta, stage)| json!({
+            "iteration": iteration,
+            "delta": delta,
+            "stage": stage,
+        })),
+        "backtrace": backtrace.to_string().lines().collect::<Vec<_>>(),
+    });
+
+    let sequence = BUNDLES.fetch_add(1, Ordering::Relaxed);
+    let path = env::temp_dir().join(format!(
+        "solver-bug-{seconds}.{:09}-{}-{sequence}.json",
+        since.subsec_nanos(),
+        process::id()
+    ));
+    let content = serde_json::to_string_pretty(&bundle).expect("bundle consists of plain data") + "\n";
+    fs::write(&path, content).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(path)
+}
+
+fn panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = (payload.downcast_ref::<&str>().map(|message| message.to_string()))
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic without message".to_owned());
+    match info.location() {
+        Some(location) => format!("{message} at {location}"),
+        None => message,
+    }
+}
@@ -0,0 +1,149 @@
+//! Comma separated input, as `--dump-format csv` writes matrices: row per
+//! line, values in the same string notation as JSON input.
+//!
+//! Whole document is augmented matrix, every row ends with its right hand
+//! side value. Settings go to comment lines before it, other settings than
+//! these two need JSON document or manifest:
+//!
+//! ```text
+//! # max_iterations: 64
+//! # epsilon: 0.001
+//! 4,1,5
+//! 1,3,4
+//! ```
+
+use core::fmt;
+use std::collections::BTreeMap;
+
+use crate::input::EquesionInput;
+
+#[derive(Debug)]
+pub struct CsvError {
+    /// Counted from 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+/// CSV starts with `#` comment or number, anything else (empty and broken
+/// documents too) is left to JSON parser to report
+pub fn is_csv(content: &[u8]) -> bool {
+    let start = content.iter().position(|byte| !byte.is_ascii_whitespace());
+    match start.map(|start| &content[start..]) {
+        Some([b'#', ..]) => true,
+        Some([b'+' | b'-' | b'.', rest @ ..]) => rest.first().is_some_and(|next| next.is_ascii_digit() || *next == b'.'),
+        Some([first, ..]) => first.is_ascii_digit(),
+        _ => false,
+    }
+}
+
+fn text(content: &[u8]) -> Result<&str, CsvError> {
+    std::str::from_utf8(content).map_err(|err| CsvError {
+        line: content[..err.valid_up_to()].iter().filter(|byte| **byte == b'\n').count() + 1,
+        message: "Content is not UTF-8 text".to_owned(),
+    })
+}
+
+/// Rows of values with their line numbers, blank and `#` comment lines skipped
+fn rows(text: &str) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut rows = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let row: Vec<String> = line.split(',').map(|value| value.trim().to_owned()).collect();
+        if let Some(column) = row.iter().position(String::is_empty) {
+            return Err(CsvError {
+                line: index + 1,
+                message: format!("Value in column {} is empty", column + 1),
+            });
+        }
+        rows.push((index + 1, row));
+    }
+    Ok(rows)
+}
+
+/// Matrix part of manifest
+pub fn matrix(content: &[u8]) -> Result<Vec<Vec<String>>, CsvError> {
+    Ok(rows(text(content)?)?.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Right hand side part of manifest, value per line or all on single line
+pub fn values(content: &[u8]) -> Result<Vec<String>, CsvError> {
+    let mut rows = rows(text(content)?)?;
+    if rows.len() == 1 {
+        return Ok(rows.remove(0).1);
+    }
+    rows.into_iter()
+        .map(|(line, mut row)| match row.len() {
+            1 => Ok(row.remove(0)),
+            _ => Err(CsvError {
+                line,
+                message: "Expected single value per line, or all values on single line".to_owned(),
+            }),
+        })
+        .collect()
+}
+
+/// Augmented matrix document with settings in its comments
+pub fn document(content: &[u8]) -> Result<EquesionInput, CsvError> {
+    let text = text(content)?;
+    let mut settings = BTreeMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let Some((name, value)) = line.trim().strip_prefix('#').and_then(|comment| comment.split_once(':')) else {
+            continue;
+        };
+        settings.insert(name.trim().to_owned(), (index + 1, value.trim().to_owned()));
+    }
+    let setting = |name: &str| {
+        settings.get(name).cloned().ok_or_else(|| CsvError {
+            line: 1,
+            message: format!("Expected \"# {name}: <value>\" comment before matrix"),
+        })
+    };
+    let (line, max_iterations) = setting("max_iterations")?;
+    let max_iterations = max_iterations.parse().map_err(|err| CsvError {
+        line,
+        message: format!("max_iterations is invalid! {err}"),
+    })?;
+    let (_, epsilon) = setting("epsilon")?;
+
+    let rows = rows(text)?;
+    let size = rows.len();
+    let mut input_matrix = Vec::with_capacity(size);
+    let mut expression_rhs = Vec::with_capacity(size);
+    for (line, mut row) in rows {
+        if row.len() != size + 1 {
+            return Err(CsvError {
+                line,
+                message: format!("Expected {} values, {size} coefficients and right hand side, got {}", size + 1, row.len()),
+            });
+        }
+        expression_rhs.push(row.pop().expect("row is not empty"));
+        input_matrix.push(row);
+    }
+
+    Ok(EquesionInput {
+        input_matrix,
+        expression_rhs,
+        max_iterations,
+        epsilon,
+        schur_block: None,
+        fixed: BTreeMap::new(),
+        lower: BTreeMap::new(),
+        upper: BTreeMap::new(),
+        blocks: Vec::new(),
+        grid: None,
+        method: None,
+        refinement_steps: None,
+        tolerance_mode: None,
+        norm: None,
+        initial_guess: None,
+    })
+}
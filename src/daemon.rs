@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::result::{SchemaVersion, SolveResult};
+use crate::service::{self, solve_document, ConnectionSlot, Connections, ServiceSettings};
+
+/// Rest of the line over limit is skipped in chunks of this size
+const SKIP_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Connection which neither sends nor takes anything for this long is closed
+pub(crate) const IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Serves solve requests over Unix domain socket.
+///
+/// Every line received is one request in the usual input schema, every line
+/// sent back is JSON response to it. Connection may carry any amount of
+/// requests, so clients avoid paying for process startup on each solve.
+/// Connections idle for `IDLE_TIMEOUT` are closed, ones over
+/// `MAX_CONNECTIONS` of service are refused
+pub fn run(socket_path: &Path, settings: ServiceSettings) -> io::Result<()> {
+    remove_stale_socket(socket_path)?;
+    let listener = UnixListener::bind(socket_path)?;
+    eprintln!("Listening on {}", socket_path.display());
+
+    let connections = Arc::new(Connections::default());
+    let settings = Arc::new(settings);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let Some(slot) = connections.acquire() else {
+                    let _ = stream.set_write_timeout(Some(IDLE_TIMEOUT));
+                    refuse(&stream);
+                    continue;
+                };
+                let settings = Arc::clone(&settings);
+                thread::spawn(move || {
+                    if let Err(err) = serve_connection(stream, slot, &settings) {
+                        eprintln!("Connection failed: {err}");
+                    }
+                });
+            }
+            Err(err) => eprintln!("Can't accept connection: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Socket file left by previous daemon run prevents binding
+fn remove_stale_socket(socket_path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(socket_path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(socket_path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", socket_path.display()),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn serve_connection(stream: UnixStream, _slot: ConnectionSlot, settings: &ServiceSettings) -> io::Result<()> {
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    stream.set_write_timeout(Some(IDLE_TIMEOUT))?;
+    let writer = stream.try_clone()?;
+    serve_lines(BufReader::new(stream), writer, settings)
+}
+
+/// Tells connection over the limit why it is closed
+pub(crate) fn refuse(mut writer: impl Write) {
+    let _ = write_result(&mut writer, &SolveResult::error("Too many connections, try again later".to_owned()));
+}
+
+fn write_result(writer: &mut impl Write, result: &SolveResult) -> io::Result<()> {
+    let mut encoded =
+        serde_json::to_vec(&result.document(SchemaVersion::default())).expect("response consists of plain strings");
+    encoded.push(b'\n');
+    writer.write_all(&encoded)?;
+    writer.flush()
+}
+
+/// Line protocol of daemon over any stream, also spoken by batch workers.
+/// Stream whose read times out is told so and closed
+pub(crate) fn serve_lines(mut reader: impl BufRead, mut writer: impl Write, settings: &ServiceSettings) -> io::Result<()> {
+    let limit = settings.limits.max_document_size();
+    loop {
+        let line = match read_line(&mut reader, limit) {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) if matches!(err.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                let message = format!("No request arrived within {IDLE_TIMEOUT:?}, closing connection");
+                return write_result(&mut writer, &SolveResult::error(message));
+            }
+            Err(err) => return Err(err),
+        };
+        let response = match line {
+            Line::Request(line) if line.iter().all(u8::is_ascii_whitespace) => continue,
+            Line::Request(line) => service::catch_crash(|| solve_document(line, settings, &settings.limits)),
+            Line::TooLong => SolveResult::error(format!(
+                "Request is longer than {} bytes this service accepts",
+                limit.expect("only limited lines are too long")
+            )),
+        };
+        write_result(&mut writer, &response)?;
+    }
+
+    Ok(())
+}
+
+enum Line {
+    Request(Vec<u8>),
+    TooLong,
+}
+
+/// Next line of `reader`, `None` at the end of stream. Line longer than
+/// `limit` is skipped without being kept in memory
+fn read_line(reader: &mut impl BufRead, limit: Option<usize>) -> io::Result<Option<Line>> {
+    let mut line = Vec::new();
+    let read = match limit {
+        // one byte more than allowed besides newline tells that line does not fit
+        Some(limit) => reader.take(limit as u64 + 2).read_until(b'\n', &mut line)?,
+        None => reader.read_until(b'\n', &mut line)?,
+    };
+    if read == 0 {
+        return Ok(None);
+    }
+
+    let content = line.strip_suffix(b"\n").unwrap_or(&line);
+    if limit.is_none_or(|limit| content.len() <= limit) {
+        return Ok(Some(Line::Request(line)));
+    }
+
+    while !line.ends_with(b"\n") {
+        line.clear();
+        if reader.take(SKIP_CHUNK_SIZE).read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+    }
+    Ok(Some(Line::TooLong))
+}
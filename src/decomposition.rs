@@ -0,0 +1,198 @@
+use nalgebra::DMatrix;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::solver::{ESolveError, Equation, Method};
+
+/// How matrices are written by [`dump`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DumpFormat {
+    /// Array of rows of strings, like `input_matrix` of input file
+    #[default]
+    Json,
+    /// Row per line, values separated by commas
+    Csv,
+}
+
+impl FromStr for DumpFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(DumpFormat::Json),
+            "csv" => Ok(DumpFormat::Csv),
+            _ => Err("Expected one of: json, csv".to_owned()),
+        }
+    }
+}
+
+impl DumpFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            DumpFormat::Json => "json",
+            DumpFormat::Csv => "csv",
+        }
+    }
+
+    fn render(&self, matrix: &DMatrix<Decimal>) -> String {
+        let rows = matrix
+            .row_iter()
+            .map(|row| row.iter().map(|value| value.normalize().to_string()).collect::<Vec<_>>());
+        match self {
+            // row per line, nested arrays of pretty printer take line per value
+            DumpFormat::Json => {
+                let rows: Vec<String> = rows
+                    .map(|row| serde_json::to_string(&row).expect("row consists of plain strings"))
+                    .collect();
+                format!("[\n  {}\n]\n", rows.join(",\n  "))
+            }
+            DumpFormat::Csv => rows.map(|row| row.join(",") + "\n").collect(),
+        }
+    }
+}
+
+/// `A = D + L + U`: diagonal, strictly lower and strictly upper triangular parts
+pub struct Splitting {
+    pub diagonal: DMatrix<Decimal>,
+    pub lower: DMatrix<Decimal>,
+    pub upper: DMatrix<Decimal>,
+}
+
+impl Splitting {
+    pub fn new(matrix: &DMatrix<Decimal>) -> Self {
+        let part = |keep: fn(usize, usize) -> bool| {
+            DMatrix::from_fn(matrix.nrows(), matrix.ncols(), |row, column| {
+                if keep(row, column) {
+                    matrix[(row, column)]
+                } else {
+                    dec!(0)
+                }
+            })
+        };
+        Splitting {
+            diagonal: part(|row, column| row == column),
+            lower: part(|row, column| row > column),
+            upper: part(|row, column| row < column),
+        }
+    }
+}
+
+/// Matrix `M` of stationary method, `x_(k+1) = M x_k + c`. Method converges
+/// for every start exactly when spectral radius of `M` is below 1.
+/// Krylov and direct methods have no such matrix
+pub fn iteration_matrix(equation: &Equation) -> Option<DMatrix<Decimal>> {
+    let Splitting {
+        diagonal,
+        lower,
+        upper,
+    } = Splitting::new(&equation.input_matrix);
+    let omega = equation.relaxation;
+    // (D + w L)^-1 ((1 - w) D - w U), forward sweep of SOR
+    let forward = || {
+        solve_triangular(
+            &(&diagonal + &lower * omega),
+            &(&diagonal * (Decimal::ONE - omega) - &upper * omega),
+            true,
+        )
+    };
+
+    match equation.method {
+        Method::Jacobi => {
+            // (1 - w) I + w D^-1 (-(L + U)), weighted Jacobi
+            let size = equation.input_matrix.nrows();
+            let jacobi = solve_triangular(&diagonal, &-(&lower + &upper), true);
+            Some(DMatrix::identity(size, size) * (Decimal::ONE - omega) + jacobi * omega)
+        }
+        Method::Hybrid => Some(solve_triangular(&diagonal, &-(&lower + &upper), true)),
+        Method::GaussSeidel => Some(forward()),
+        Method::Richardson => {
+            let size = equation.input_matrix.nrows();
+            let tau = equation.richardson_tau()?;
+            Some(DMatrix::identity(size, size) - &equation.input_matrix * tau)
+        }
+        Method::Ssor => {
+            let backward = solve_triangular(
+                &(&diagonal + &upper * omega),
+                &(&diagonal * (Decimal::ONE - omega) - &lower * omega),
+                false,
+            );
+            Some(backward * forward())
+        }
+        _ => None,
+    }
+}
+
+/// `T^-1 B` for triangular `T` with non-zero diagonal, column by column
+fn solve_triangular(triangular: &DMatrix<Decimal>, rhs: &DMatrix<Decimal>, lower: bool) -> DMatrix<Decimal> {
+    let size = triangular.nrows();
+    let mut out = DMatrix::from_element(size, rhs.ncols(), dec!(0));
+    let rows: Vec<usize> = if lower {
+        (0..size).collect()
+    } else {
+        (0..size).rev().collect()
+    };
+
+    for column in 0..rhs.ncols() {
+        for &row in &rows {
+            let known = (0..size)
+                .filter(|&index| index != row)
+                .fold(dec!(0), |acc, index| acc + triangular[(row, index)] * out[(index, column)]);
+            out[(row, column)] = (rhs[(row, column)] - known) / triangular[(row, row)];
+        }
+    }
+    out
+}
+
+/// Writes `D`, `L`, `U`, iteration matrix of stationary methods and factors of
+/// direct ones (inverse for Gauss-Jordan) to `directory`, returns written files
+pub fn dump(equation: &Equation, directory: &Path, format: DumpFormat) -> Result<Vec<PathBuf>, String> {
+    let splitting = Splitting::new(&equation.input_matrix);
+    let mut matrices = vec![
+        ("D", splitting.diagonal),
+        ("L", splitting.lower),
+        ("U", splitting.upper),
+    ];
+    if let Some(matrix) = iteration_matrix(equation) {
+        matrices.push(("iteration", matrix));
+    }
+
+    let failed = |err: ESolveError| format!("Can't factor matrix for dump: {err}");
+    match equation.method {
+        Method::Gauss | Method::Lu => {
+            let factors = equation.factor_lu().map_err(failed)?;
+            let size = factors.permutation.len();
+            let permutation = DMatrix::from_fn(size, size, |row, column| {
+                if factors.permutation[row] == column {
+                    dec!(1)
+                } else {
+                    dec!(0)
+                }
+            });
+            matrices.push(("LU-P", permutation));
+            matrices.push(("LU-L", factors.lower));
+            matrices.push(("LU-U", factors.upper));
+        }
+        Method::Cholesky => matrices.push(("Cholesky-L", equation.factor_cholesky().map_err(failed)?.lower)),
+        Method::Qr => {
+            let factors = equation.factor_qr().map_err(failed)?;
+            matrices.push(("QR-Q", factors.orthogonal));
+            matrices.push(("QR-R", factors.upper));
+        }
+        Method::GaussJordan => matrices.push(("inverse", equation.inverse().map_err(failed)?)),
+        _ => (),
+    }
+
+    fs::create_dir_all(directory).map_err(|err| format!("Can't create {}: {err}", directory.display()))?;
+    matrices
+        .into_iter()
+        .map(|(name, matrix)| {
+            let path = directory.join(format!("{name}.{}", format.extension()));
+            fs::write(&path, format.render(&matrix))
+                .map_err(|err| format!("Can't write {}: {err}", path.display()))?;
+            Ok(path)
+        })
+        .collect()
+}
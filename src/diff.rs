@@ -0,0 +1,342 @@
+use core::fmt;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::compression;
+use crate::norm::Norm;
+
+/// Result file as written by `--format json`. Metadata is kept as is,
+/// so files written by other versions of solver can still be compared
+#[derive(Deserialize, Debug)]
+struct ResultFile {
+    status: String,
+    #[serde(default)]
+    solution: Vec<String>,
+    #[serde(default)]
+    metadata: BTreeMap<String, json::Value>,
+    message: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum DiffError {
+    Read { path: String, message: String },
+    InvalidValue { path: String, position: usize },
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::Read { path, message } => {
+                writeln!(f, "Can't read result file {path}! Error: {message}")
+            }
+            DiffError::InvalidValue { path, position } => writeln!(
+                f,
+                "Solution component at position {position} in {path} is not a number"
+            ),
+        }
+    }
+}
+
+fn read_result(path: &str) -> Result<ResultFile, DiffError> {
+    let wrap = |message: String| DiffError::Read {
+        path: path.to_owned(),
+        message,
+    };
+
+    let content = fs::read(path).map_err(|err| wrap(err.to_string()))?;
+    let content = compression::decompress_if_needed(content).map_err(|err| wrap(err.to_string()))?;
+    json::from_slice(&content).map_err(|err| wrap(err.to_string()))
+}
+
+fn parse_solution(path: &str, result: &ResultFile) -> Result<Vec<Decimal>, DiffError> {
+    result
+        .solution
+        .iter()
+        .enumerate()
+        .map(|(index, value)| {
+            Decimal::from_str(value).map_err(|_| DiffError::InvalidValue {
+                path: path.to_owned(),
+                position: index + 1,
+            })
+        })
+        .collect()
+}
+
+/// Component of solution which deviates from expected one beyond tolerance
+#[derive(Debug)]
+pub struct Deviation {
+    pub position: usize,
+    pub expected: Decimal,
+    pub actual: Decimal,
+}
+
+/// Outcome of checking solution against golden result file
+#[derive(Debug)]
+pub enum MatchReport {
+    Matches,
+    LengthMismatch { expected: usize, actual: usize },
+    /// Expected file records failed solve
+    ExpectedFailure { status: String },
+    Deviates {
+        tolerance: Decimal,
+        deviations: Vec<Deviation>,
+    },
+}
+
+impl MatchReport {
+    pub fn is_match(&self) -> bool {
+        matches!(self, MatchReport::Matches)
+    }
+}
+
+/// Every component must be within `tolerance` of expected one
+pub fn check_matches(
+    solution: &[Decimal],
+    expected_path: &str,
+    tolerance: Decimal,
+) -> Result<MatchReport, DiffError> {
+    let expected = read_result(expected_path)?;
+    if expected.status != "solved" {
+        return Ok(MatchReport::ExpectedFailure {
+            status: expected.status,
+        });
+    }
+
+    let expected = parse_solution(expected_path, &expected)?;
+    if expected.len() != solution.len() {
+        return Ok(MatchReport::LengthMismatch {
+            expected: expected.len(),
+            actual: solution.len(),
+        });
+    }
+
+    let deviations: Vec<Deviation> = expected
+        .iter()
+        .zip(solution)
+        .enumerate()
+        .filter(|(_, (expected, actual))| (*actual - *expected).abs() > tolerance)
+        .map(|(index, (expected, actual))| Deviation {
+            position: index + 1,
+            expected: *expected,
+            actual: *actual,
+        })
+        .collect();
+
+    if deviations.is_empty() {
+        return Ok(MatchReport::Matches);
+    }
+
+    Ok(MatchReport::Deviates {
+        tolerance,
+        deviations,
+    })
+}
+
+impl fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchReport::Matches => writeln!(f, "Solution matches expected result"),
+            MatchReport::LengthMismatch { expected, actual } => writeln!(
+                f,
+                "Solution has {actual} components, expected result has {expected}"
+            ),
+            MatchReport::ExpectedFailure { status } => writeln!(
+                f,
+                "Expected result has status \"{status}\", but solution was found"
+            ),
+            MatchReport::Deviates {
+                tolerance,
+                deviations,
+            } => {
+                writeln!(f, "Solution deviates from expected beyond {tolerance}:")?;
+                for deviation in deviations {
+                    writeln!(
+                        f,
+                        "  x{}: expected {}, got {} (off by {})",
+                        deviation.position,
+                        deviation.expected,
+                        deviation.actual,
+                        (deviation.actual - deviation.expected).abs().normalize()
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ComponentDiff {
+    pub position: usize,
+    pub first: Option<String>,
+    pub second: Option<String>,
+    /// `second - first`, absent when component exists in one file only
+    pub difference: Option<String>,
+    #[serde(skip)]
+    same: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MetadataDiff {
+    pub key: String,
+    pub first: Option<json::Value>,
+    pub second: Option<json::Value>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ResultDiff {
+    pub first_status: String,
+    pub second_status: String,
+    pub components: Vec<ComponentDiff>,
+    /// Norms of difference vector over components present in both files
+    pub difference_norms: BTreeMap<&'static str, String>,
+    /// Difference norm divided by norm of first solution
+    pub relative_difference: BTreeMap<&'static str, String>,
+    pub metadata: Vec<MetadataDiff>,
+    pub messages: Vec<String>,
+}
+
+impl ResultDiff {
+    /// Nothing differs at all
+    pub fn is_identical(&self) -> bool {
+        self.first_status == self.second_status
+            && self.metadata.is_empty()
+            && self.components.iter().all(|component| component.same)
+    }
+}
+
+pub fn compare(first_path: &str, second_path: &str) -> Result<ResultDiff, DiffError> {
+    let first = read_result(first_path)?;
+    let second = read_result(second_path)?;
+    let first_solution = parse_solution(first_path, &first)?;
+    let second_solution = parse_solution(second_path, &second)?;
+
+    let length = first_solution.len().max(second_solution.len());
+    let components = (0..length)
+        .map(|index| {
+            let a = first_solution.get(index);
+            let b = second_solution.get(index);
+            ComponentDiff {
+                position: index + 1,
+                first: a.map(Decimal::to_string),
+                second: b.map(Decimal::to_string),
+                difference: a.zip(b).map(|(a, b)| (b - a).normalize().to_string()),
+                same: a == b,
+            }
+        })
+        .collect();
+
+    let differences: Vec<Decimal> = first_solution
+        .iter()
+        .zip(&second_solution)
+        .map(|(a, b)| b - a)
+        .collect();
+
+    let mut difference_norms = BTreeMap::new();
+    let mut relative_difference = BTreeMap::new();
+    for norm in Norm::ALL {
+        let difference = norm.of(differences.iter().copied());
+        let base = norm.of(first_solution.iter().copied());
+        difference_norms.insert(norm.name(), difference.normalize().to_string());
+        if !base.is_zero() {
+            relative_difference.insert(norm.name(), (difference / base).normalize().to_string());
+        }
+    }
+
+    let keys: std::collections::BTreeSet<&String> =
+        first.metadata.keys().chain(second.metadata.keys()).collect();
+    let metadata = keys
+        .into_iter()
+        .filter(|key| first.metadata.get(*key) != second.metadata.get(*key))
+        .map(|key| MetadataDiff {
+            key: key.clone(),
+            first: first.metadata.get(key).cloned(),
+            second: second.metadata.get(key).cloned(),
+        })
+        .collect();
+
+    let messages = [(first_path, &first), (second_path, &second)]
+        .into_iter()
+        .filter_map(|(path, result)| {
+            result
+                .message
+                .as_ref()
+                .map(|message| format!("{path}: {message}"))
+        })
+        .collect();
+
+    Ok(ResultDiff {
+        first_status: first.status,
+        second_status: second.status,
+        components,
+        difference_norms,
+        relative_difference,
+        metadata,
+        messages,
+    })
+}
+
+fn display_value(value: &Option<impl fmt::Display>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
+
+impl fmt::Display for ResultDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.first_status != self.second_status {
+            writeln!(
+                f,
+                "Status differs: {} vs {}",
+                self.first_status, self.second_status
+            )?;
+        }
+        for message in &self.messages {
+            writeln!(f, "{message}")?;
+        }
+
+        if !self.components.is_empty() {
+            writeln!(f, "Components (difference is second - first):")?;
+            for component in &self.components {
+                writeln!(
+                    f,
+                    "  x{}: {} | {} | {}",
+                    component.position,
+                    display_value(&component.first),
+                    display_value(&component.second),
+                    display_value(&component.difference)
+                )?;
+            }
+
+            writeln!(f, "Difference norms:")?;
+            for (norm, value) in &self.difference_norms {
+                let relative = self
+                    .relative_difference
+                    .get(norm)
+                    .map_or_else(String::new, |relative| format!(" (relative {relative})"));
+                writeln!(f, "  {norm}: {value}{relative}")?;
+            }
+        }
+
+        if self.metadata.is_empty() {
+            writeln!(f, "Metadata is the same")?;
+        } else {
+            writeln!(f, "Metadata differences:")?;
+            for entry in &self.metadata {
+                writeln!(
+                    f,
+                    "  {}: {} -> {}",
+                    entry.key,
+                    display_value(&entry.first),
+                    display_value(&entry.second)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
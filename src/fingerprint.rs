@@ -0,0 +1,107 @@
+//! Canonical form of problem and stable hash of it. Inputs differing only in
+//! formatting (`"1.50"` and `"1.5"`, key order, whitespace, compression,
+//! manifest or inline) have the same form, so the hash identifies problem
+//! for caches and duplicate detection. Hash does not depend on platform
+//! or version of Rust
+
+use rust_decimal::Decimal;
+
+use crate::input::fixed_name;
+use crate::solver::{Acceleration, Equation, InitialGuess, Preconditioning, ToleranceMode};
+
+/// FNV-1a, 128 bit: simple enough to reimplement wherever hash is checked
+const FNV_OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CanonicalOptions {
+    /// Order of equations does not matter: rows are sorted together with right
+    /// hand side. Has no effect when fixed unknowns, bounds, `schur_block` or
+    /// `blocks` tie equations to unknowns by position. Note that Gauss-Seidel
+    /// over reordered equations may converge differently, or not at all
+    pub permutation_invariant: bool,
+}
+
+fn number(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// Line per fact: size, matrix rows, right hand side, then settings
+/// which change the result. Unknowns are named like in input, `x1` first
+pub fn canonical_form(equation: &Equation, options: CanonicalOptions) -> String {
+    let size = equation.input_matrix.nrows();
+    let mut rows: Vec<(Vec<Decimal>, Decimal)> = (0..size)
+        .map(|row| {
+            let values = equation.input_matrix.row(row).iter().map(|value| value.normalize()).collect();
+            (values, equation.expression_rhs[row].normalize())
+        })
+        .collect();
+    let positional = !equation.fixed.is_empty()
+        || !equation.bounds.is_empty()
+        || equation.schur_block.is_some()
+        || !equation.blocks.is_empty();
+    if options.permutation_invariant && !positional {
+        rows.sort();
+    }
+
+    let mut out = format!("size {size}\n");
+    for (values, rhs) in &rows {
+        let values: Vec<String> = values.iter().map(|value| number(*value)).collect();
+        out += &format!("row {} = {}\n", values.join(" "), number(*rhs));
+    }
+    out += &format!("epsilon {}\n", number(equation.epsilon));
+    out += &format!("max_iterations {}\n", equation.max_iterations);
+    out += &format!("method {}\n", equation.method);
+    if equation.tolerance_mode != ToleranceMode::default() {
+        out += &format!("tolerance_mode {}\n", equation.tolerance_mode);
+    }
+    if let Some(norm) = equation.norm {
+        out += &format!("norm {norm}\n");
+    }
+    match &equation.initial_guess {
+        InitialGuess::Ones => (),
+        InitialGuess::Given(guess) => {
+            let values: Vec<String> = guess.iter().map(|value| number(*value)).collect();
+            out += &format!("initial_guess {}\n", values.join(" "));
+        }
+        strategy => out += &format!("initial_guess {strategy}\n"),
+    }
+    match equation.acceleration {
+        Acceleration::None => (),
+        Acceleration::Chebyshev { spectrum: Some((lower, upper)) } => {
+            out += &format!("chebyshev {} {}\n", number(lower), number(upper))
+        }
+        Acceleration::Chebyshev { spectrum: None } => out += "chebyshev\n",
+        Acceleration::Aitken { period } => out += &format!("aitken {period}\n"),
+    }
+    if equation.preconditioning != Preconditioning::None {
+        out += &format!("preconditioner {}\n", equation.preconditioning);
+    }
+    if let Some(block) = equation.schur_block {
+        out += &format!("schur_block {block}\n");
+    }
+    if !equation.blocks.is_empty() {
+        let blocks: Vec<String> = equation.blocks.iter().map(ToString::to_string).collect();
+        out += &format!("blocks {}\n", blocks.join(" "));
+    }
+    for (index, value) in &equation.fixed {
+        out += &format!("fixed {} {}\n", fixed_name(*index), number(*value));
+    }
+    for (index, bounds) in &equation.bounds {
+        if let Some(lower) = bounds.lower {
+            out += &format!("lower {} {}\n", fixed_name(*index), number(lower));
+        }
+        if let Some(upper) = bounds.upper {
+            out += &format!("upper {} {}\n", fixed_name(*index), number(upper));
+        }
+    }
+    out
+}
+
+/// 32 hex digits of hash of [`canonical_form`]
+pub fn fingerprint(equation: &Equation, options: CanonicalOptions) -> String {
+    let hash = canonical_form(equation, options)
+        .bytes()
+        .fold(FNV_OFFSET, |hash, byte| (hash ^ u128::from(byte)).wrapping_mul(FNV_PRIME));
+    format!("{hash:032x}")
+}
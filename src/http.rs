@@ -0,0 +1,160 @@
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+/// Limit on request line and headers, bodies are limited separately
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+
+/// Just enough of HTTP/1.1 to serve JSON API: one request per connection,
+/// body length is taken from `Content-Length`
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Header names are compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn json(status: u16, body: &impl serde::Serialize) -> Self {
+        Response {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(body).expect("responses consist of plain data"),
+        }
+    }
+}
+
+/// Reads of stream fail with `TimedOut` once `deadline` passes, however
+/// slowly bytes trickle in until then
+pub struct DeadlineReader {
+    pub stream: TcpStream,
+    pub deadline: Instant,
+}
+
+impl Read for DeadlineReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left == Duration::ZERO {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf).map_err(|err| match err.kind() {
+            // timed out socket reads report `WouldBlock` on Unix
+            io::ErrorKind::WouldBlock => io::ErrorKind::TimedOut.into(),
+            _ => err,
+        })
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+/// Body limit is chosen by `max_body_size` once head is read, `None` lets
+/// any body through. Body is read as it arrives, never allocated upfront
+pub fn read_request(
+    reader: &mut impl BufRead,
+    max_body_size: impl FnOnce(&Request) -> Option<usize>,
+) -> io::Result<Request> {
+    let mut head_size = 0;
+    let mut read_line = |reader: &mut dyn BufRead| -> io::Result<String> {
+        let mut line = String::new();
+        reader
+            .take((MAX_HEAD_SIZE - head_size) as u64)
+            .read_line(&mut line)?;
+        head_size += line.len();
+        if !line.ends_with('\n') {
+            return Err(invalid_data("Request head is too large or truncated"));
+        }
+        Ok(line.trim_end().to_owned())
+    };
+
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_owned(), path.to_owned()),
+        _ => return Err(invalid_data("Malformed request line")),
+    };
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| invalid_data("Malformed header"))?;
+        headers.push((name.trim().to_owned(), value.trim().to_owned()));
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    let length: usize = match request.header("Content-Length") {
+        Some(length) => length
+            .parse()
+            .map_err(|_| invalid_data("Malformed Content-Length"))?,
+        None => 0,
+    };
+    if max_body_size(&request).is_some_and(|limit| length > limit) {
+        return Err(invalid_data("Request body is too large"));
+    }
+
+    reader.take(length as u64).read_to_end(&mut request.body)?;
+    if request.body.len() < length {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Request body is truncated"));
+    }
+    Ok(request)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+pub fn write_response(writer: &mut impl Write, response: &Response) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    writer.write_all(&response.body)?;
+    writer.flush()
+}
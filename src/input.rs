@@ -3,28 +3,121 @@ use nalgebra::{DMatrix, DVector};
 use rust_decimal::prelude::*;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json as json;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fs;
-use std::io::{self, IsTerminal, Read};
+use std::io::{self, IsTerminal, Read, Write};
 use std::iter::Iterator;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::compression;
+use crate::csv::{self, CsvError};
+use crate::manifest::{self, InputManifest};
+use crate::memory::{ByteSize, MemoryEstimate};
+use crate::preprocess::improving_row_order;
+use crate::template;
+use crate::norm::Norm;
+use crate::solver::{Bounds, Equation, InitialGuess, Method, ToleranceMode};
 
-use crate::solver::Equation;
-
-const DECIMAL_PARSE_ERROR_MESSAGE: &str = "Can't represent such precise value";
 const ZERO_ON_DIAGONAL_ERROR_MESSAGE: &str =
     "Zero on diagonal detected! Expected non-zero value on diagonal!";
 
-fn build_decimal_from_string(input: &String) -> Result<Decimal, rust_decimal::Error> {
+fn build_decimal_from_string(input: &str) -> Result<Decimal, rust_decimal::Error> {
     Decimal::from_str(input)
 }
 
-pub fn build_configuration() -> Result<Equation, NonInteractiveError> {
+/// Value `Decimal` can't hold exactly, like one with more than 28 digits
+/// after point. It is rounded rather than rejected
+#[derive(Debug)]
+pub struct PrecisionLoss {
+    pub location: String,
+    pub written: String,
+    pub stored: Decimal,
+}
+
+impl fmt::Display for PrecisionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} \"{}\" has more digits than Decimal holds, {} is used instead",
+            self.location, self.written, self.stored
+        )
+    }
+}
+
+/// Coefficients and right hand side of raw input, with description of where they are
+pub(crate) fn located_values(input: &EquesionInput) -> impl Iterator<Item = (String, &String)> {
+    let matrix = input
+        .input_matrix
+        .iter()
+        .enumerate()
+        .flat_map(|(row, values)| {
+            values.iter().enumerate().map(move |(column, value)| {
+                (format!("Value in row {} column {}", row + 1, column + 1), value)
+            })
+        });
+    let rhs = input
+        .expression_rhs
+        .iter()
+        .enumerate()
+        .map(|(row, value)| (format!("Right hand side of row {}", row + 1), value));
+    matrix.chain(rhs)
+}
+
+/// Values which are rounded when equation is built. Values which can't be
+/// parsed at all are left to [`build_equation`]
+pub fn precision_losses(input: &EquesionInput) -> Vec<PrecisionLoss> {
+    located_values(input)
+        .filter(|(_, value)| Decimal::from_str_exact(value).is_err())
+        .filter_map(|(location, value)| {
+            let stored = build_decimal_from_string(value).ok()?;
+            Some(PrecisionLoss {
+                location,
+                written: value.clone(),
+                stored,
+            })
+        })
+        .collect()
+}
+
+/// Where and how equation input is read from
+#[derive(Debug, Default, Clone)]
+pub struct InputOptions {
+    pub path: Option<String>,
+    /// Give up waiting for stdin data after this long. Waits forever if absent
+    pub stdin_timeout: Option<Duration>,
+    /// Refuse inputs which are estimated to need more bytes than this
+    pub max_memory: Option<u64>,
+    /// Values of `${name}` placeholders in input document
+    pub params: BTreeMap<String, String>,
+    /// Shell command raw input is piped through before parsing. It reads
+    /// input on stdin and prints input document on stdout
+    pub filter: Option<String>,
+}
+
+pub fn build_configuration(input: &InputOptions) -> Result<Equation, NonInteractiveError> {
     // try non interactive
-    let parsed = try_non_interactive()?;
+    let parsed = try_non_interactive(input)?;
+    build_equation(parsed, input.max_memory)
+}
+
+pub fn build_equation(
+    parsed: EquesionInput,
+    max_memory: Option<u64>,
+) -> Result<Equation, NonInteractiveError> {
     let matrix_size = compute_matrix_size(&parsed.input_matrix, &parsed.expression_rhs)?;
 
+    let estimate = MemoryEstimate::new(matrix_size, std::mem::size_of::<Decimal>());
+    if let Some(limit) = max_memory.filter(|limit| estimate.total() > *limit) {
+        return Err(NonInteractiveError::MemoryLimitExceeded { estimate, limit });
+    }
+
     let input_matrix: Result<Vec<_>, _> = parsed
         .input_matrix
         .iter()
@@ -39,10 +132,61 @@ pub fn build_configuration() -> Result<Equation, NonInteractiveError> {
         })
         .collect();
 
+    if let Some(block) = parsed.schur_block.filter(|block| !(1..matrix_size).contains(block)) {
+        return Err(NonInteractiveError::InvalidSchurBlock { block, matrix_size });
+    }
+
+    let fixed = parse_fixed(&parsed.fixed, matrix_size)?;
+    if parsed.schur_block.is_some() && !fixed.is_empty() {
+        return Err(NonInteractiveError::InvalidFixedVariable {
+            variable: fixed_name(fixed[0].0),
+            message: "fixed variables can't be combined with schur_block".to_owned(),
+        });
+    }
+
+    let bounds = parse_bounds(&parsed.lower, &parsed.upper, matrix_size)?;
+    if let Some(&(index, _)) = bounds.first().filter(|_| parsed.schur_block.is_some()) {
+        return Err(NonInteractiveError::InvalidBound {
+            variable: fixed_name(index),
+            message: "bounds can't be combined with schur_block".to_owned(),
+        });
+    }
+
+    // plain (`0.001`) and scientific (`1e-6`) notation
+    let epsilon = Decimal::from_str(&parsed.epsilon)
+        .or_else(|_| Decimal::from_scientific(&parsed.epsilon))
+        .map_err(|err| NonInteractiveError::InvalidEpsilon {
+            epsilon: parsed.epsilon.clone(),
+            message: err.to_string(),
+        })?;
+    let initial_guess = parse_initial_guess(parsed.initial_guess.as_ref(), matrix_size)?;
+    let blocks = grid_blocks(&parsed)?;
+    check_blocks(&parsed, &blocks, matrix_size, !fixed.is_empty() || !bounds.is_empty())?;
+
+    let method = match &parsed.method {
+        Some(method) => method.parse().map_err(|message| NonInteractiveError::InvalidMethod {
+            method: method.clone(),
+            message,
+        })?,
+        None => Method::default(),
+    };
+    let tolerance_mode = match &parsed.tolerance_mode {
+        Some(mode) => mode.parse().map_err(|message| NonInteractiveError::InvalidToleranceMode {
+            mode: mode.clone(),
+            message,
+        })?,
+        None => ToleranceMode::default(),
+    };
+    let norm = match &parsed.norm {
+        Some(norm) => Some(norm.parse::<Norm>().map_err(|message| NonInteractiveError::InvalidNorm {
+            norm: norm.clone(),
+            message,
+        })?),
+        None => None,
+    };
+
     let input_matrix = input_matrix?;
-    let matrix = DMatrix::from_row_iterator(matrix_size, matrix_size, input_matrix);
-    check_for_zeroes_on_diagonal((&matrix, matrix_size))
-        .map_err(|err| NonInteractiveError::MatrixInputError(err))?;
+    let mut matrix = DMatrix::from_row_iterator(matrix_size, matrix_size, input_matrix);
 
     let raw_expression_rhs = parsed
         .expression_rhs
@@ -54,21 +198,181 @@ pub fn build_configuration() -> Result<Equation, NonInteractiveError> {
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let expression_rhs: DVector<Decimal> = DVector::from_vec(raw_expression_rhs);
+    let mut expression_rhs: DVector<Decimal> = DVector::from_vec(raw_expression_rhs);
+
+    // fixed unknowns, Schur complement and blocks tie equations to positions
+    let movable = fixed.is_empty() && parsed.schur_block.is_none() && blocks.is_empty();
+    let row_order = match movable {
+        true => improving_row_order(&matrix).unwrap_or_default(),
+        false => Vec::new(),
+    };
+    if !row_order.is_empty() {
+        matrix = DMatrix::from_fn(matrix_size, matrix_size, |row, column| matrix[(row_order[row], column)]);
+        expression_rhs = DVector::from_fn(matrix_size, |row, _| expression_rhs[row_order[row]]);
+    }
+
+    // only leading block is solved by iterations directly, trailing one
+    // is often zero (saddle point systems). Equations of fixed unknowns are not used.
+    // Diagonal blocks are solved directly, so their diagonal may have zeros
+    let iterated_rows = if blocks.is_empty() {
+        parsed.schur_block.unwrap_or(matrix_size)
+    } else {
+        0
+    };
+    let solved_rows = (0..iterated_rows).filter(|row| !fixed.iter().any(|(index, _)| index == row));
+    check_for_zeroes_on_diagonal(&matrix, solved_rows)
+        .map_err(NonInteractiveError::MatrixInputError)?;
 
     Ok(Equation {
-        input_matrix: matrix,
-        expression_rhs,
-        max_iterations: parsed.max_iterations,
-        epsilon: Decimal::from_str(&parsed.epsilon).expect(DECIMAL_PARSE_ERROR_MESSAGE),
+        schur_block: parsed.schur_block,
+        method,
+        fixed,
+        bounds,
+        blocks,
+        row_order,
+        refinement_steps: parsed.refinement_steps.unwrap_or(0),
+        tolerance_mode,
+        norm,
+        initial_guess,
+        ..Equation::new(matrix, expression_rhs, parsed.max_iterations, epsilon)
+    })
+}
+
+/// `grid` is turned into block per grid line, otherwise `blocks` are taken as is
+fn grid_blocks(parsed: &EquesionInput) -> Result<Vec<usize>, NonInteractiveError> {
+    match parsed.grid {
+        None => Ok(parsed.blocks.clone()),
+        Some(_) if !parsed.blocks.is_empty() => Err(NonInteractiveError::InvalidBlocks(
+            "grid can't be combined with blocks".to_owned(),
+        )),
+        Some([lines, line]) => Ok(vec![line; lines]),
+    }
+}
+
+/// Block sizes must be positive and add up to matrix size. Blocks replace
+/// iterations over single unknowns, so they go with no other structure
+fn check_blocks(
+    parsed: &EquesionInput,
+    blocks: &[usize],
+    matrix_size: usize,
+    constrained: bool,
+) -> Result<(), NonInteractiveError> {
+    let invalid = |message: &str| Err(NonInteractiveError::InvalidBlocks(message.to_owned()));
+    if blocks.is_empty() {
+        return match parsed.grid {
+            Some(_) => invalid("grid must have at least one line"),
+            None => Ok(()),
+        };
+    }
+    if blocks.contains(&0) {
+        return invalid("block sizes must be positive");
+    }
+    if blocks.iter().sum::<usize>() != matrix_size {
+        return invalid(&format!("block sizes must add up to matrix size {matrix_size}"));
+    }
+    if parsed.schur_block.is_some() || constrained {
+        return invalid("blocks can't be combined with schur_block, fixed variables or bounds");
+    }
+    Ok(())
+}
+
+/// Name of unknown with given index in `"fixed"` object
+pub(crate) fn fixed_name(index: usize) -> String {
+    format!("x{}", index + 1)
+}
+
+/// `{"x3": "2.5"}` into `[(2, 2.5)]`, sorted by index
+fn parse_fixed(
+    fixed: &BTreeMap<String, String>,
+    matrix_size: usize,
+) -> Result<Vec<(usize, Decimal)>, NonInteractiveError> {
+    parse_variable_values(fixed, matrix_size, |variable, message| {
+        NonInteractiveError::InvalidFixedVariable { variable, message }
     })
 }
 
+/// Strategy by name, or one value per unknown. All ones if absent
+fn parse_initial_guess(
+    guess: Option<&GuessInput>,
+    matrix_size: usize,
+) -> Result<InitialGuess, NonInteractiveError> {
+    let guess = match guess {
+        None => return Ok(InitialGuess::default()),
+        Some(GuessInput::Strategy(name)) => {
+            return name.parse().map_err(|message| {
+                NonInteractiveError::InvalidInitialGuess(format!("Strategy \"{name}\" is unknown. {message}"))
+            })
+        }
+        Some(GuessInput::Values(values)) => values,
+    };
+    if guess.len() != matrix_size {
+        return Err(NonInteractiveError::InvalidInitialGuess(format!(
+            "Expected {matrix_size} values, one per unknown, got {}",
+            guess.len()
+        )));
+    }
+    let values = guess.iter().enumerate().map(|(index, value)| {
+        build_decimal_from_string(value).map_err(|err| {
+            NonInteractiveError::InvalidInitialGuess(format!("Value of {}: {err}", fixed_name(index)))
+        })
+    });
+    Ok(InitialGuess::Given(DVector::from_vec(values.collect::<Result<_, _>>()?)))
+}
+
+/// `"lower"` and `"upper"` objects into bounds of each mentioned unknown, sorted by index
+fn parse_bounds(
+    lower: &BTreeMap<String, String>,
+    upper: &BTreeMap<String, String>,
+    matrix_size: usize,
+) -> Result<Vec<(usize, Bounds)>, NonInteractiveError> {
+    let invalid = |variable, message| NonInteractiveError::InvalidBound { variable, message };
+    let mut bounds = BTreeMap::<usize, Bounds>::new();
+    for (index, value) in parse_variable_values(lower, matrix_size, invalid)? {
+        bounds.entry(index).or_default().lower = Some(value);
+    }
+    for (index, value) in parse_variable_values(upper, matrix_size, invalid)? {
+        bounds.entry(index).or_default().upper = Some(value);
+    }
+
+    if let Some((&index, _)) = (bounds.iter())
+        .find(|(_, bounds)| matches!((bounds.lower, bounds.upper), (Some(lower), Some(upper)) if lower > upper))
+    {
+        return Err(invalid(fixed_name(index), "lower bound is above upper one".to_owned()));
+    }
+    Ok(bounds.into_iter().collect())
+}
+
+/// Object keyed by names of unknowns into values by index, sorted by index
+fn parse_variable_values(
+    values: &BTreeMap<String, String>,
+    matrix_size: usize,
+    error: impl Fn(String, String) -> NonInteractiveError,
+) -> Result<Vec<(usize, Decimal)>, NonInteractiveError> {
+    let mut parsed = values
+        .iter()
+        .map(|(variable, value)| {
+            let invalid = |message: String| error(variable.clone(), message);
+            let index = variable
+                .strip_prefix('x')
+                .and_then(|number| number.parse::<usize>().ok())
+                .filter(|number| (1..=matrix_size).contains(number))
+                .ok_or_else(|| invalid(format!("expected name from x1 to x{matrix_size}")))?;
+            let value = build_decimal_from_string(value).map_err(|err| invalid(err.to_string()))?;
+            Ok((index - 1, value))
+        })
+        .collect::<Result<Vec<_>, NonInteractiveError>>()?;
+
+    parsed.sort_unstable_by_key(|(index, _)| *index);
+    Ok(parsed)
+}
+
 /// Guass-Seidel method requires non-zero values on diagonal
 /// source: https://www3.nd.edu/~zxu2/acms60212-40212-S12/Lec-09-4.pdf slide 10
-fn check_for_zeroes_on_diagonal(matrix: (&DMatrix<Decimal>, usize)) -> Result<(), PositionalError> {
-    let (matrix, matrix_size) = matrix;
-    for i in 0..matrix_size {
+fn check_for_zeroes_on_diagonal(
+    matrix: &DMatrix<Decimal>,
+    rows: impl Iterator<Item = usize>,
+) -> Result<(), PositionalError> {
+    for i in rows {
         if matrix[(i, i)] == dec!(0) {
             let error = PositionalError {
                 row: i,
@@ -79,14 +383,14 @@ fn check_for_zeroes_on_diagonal(matrix: (&DMatrix<Decimal>, usize)) -> Result<()
         }
     }
 
-    return Ok(());
+    Ok(())
 }
 
 fn compute_matrix_size(
-    input_matrix: &Vec<Vec<String>>,
-    expression_rhs: &Vec<String>,
+    input_matrix: &[Vec<String>],
+    expression_rhs: &[String],
 ) -> Result<usize, MatrixSizeError> {
-    let row_sizes: Vec<_> = input_matrix.into_iter().map(|row| row.len()).collect();
+    let row_sizes: Vec<_> = input_matrix.iter().map(|row| row.len()).collect();
     let matrix_size = *row_sizes.iter().max().ok_or(MatrixSizeError::EmptyMatrix)?;
 
     if let Some(incorrect_row) = row_sizes
@@ -170,6 +474,22 @@ pub enum NonInteractiveError {
     MatrixInputError(PositionalError),
     RightHandSideError(usize, String),
     NoInputProvided,
+    StdinTimeout(Duration),
+    ManifestPartError { part: String, message: String },
+    MemoryLimitExceeded { estimate: MemoryEstimate, limit: u64 },
+    InvalidSchurBlock { block: usize, matrix_size: usize },
+    InvalidFixedVariable { variable: String, message: String },
+    InvalidBound { variable: String, message: String },
+    InvalidMethod { method: String, message: String },
+    InvalidToleranceMode { mode: String, message: String },
+    InvalidNorm { norm: String, message: String },
+    InvalidEpsilon { epsilon: String, message: String },
+    InvalidBlocks(String),
+    InvalidInitialGuess(String),
+    TemplateError(String),
+    /// Input filter failed to run or exited with error
+    InputFilterError { command: String, message: String },
+    CsvError(CsvError),
     ParseError(serde_json::Error),
     IOError(io::Error),
 }
@@ -216,6 +536,49 @@ impl fmt::Display for NonInteractiveError {
                 writeln!(f, "{}", err)
             }
             NonInteractiveError::NoInputProvided => writeln!(f, "No input provided!"),
+            NonInteractiveError::StdinTimeout(timeout) => writeln!(
+                f,
+                "No data arrived on stdin within {:?}! Is input wired correctly?",
+                timeout
+            ),
+            NonInteractiveError::ManifestPartError { part, message } => {
+                writeln!(f, "Can't read {part} referenced by manifest! Error: {message}")
+            }
+            NonInteractiveError::MemoryLimitExceeded { estimate, limit } => {
+                writeln!(f, "Solve would need more memory than allowed!")?;
+                writeln!(f, "Estimated: {estimate}")?;
+                writeln!(f, "Limit: {}", ByteSize(*limit))
+            }
+            NonInteractiveError::InvalidSchurBlock { block, matrix_size } => writeln!(
+                f,
+                "Schur block size {block} is out of range! Expected from 1 to {}",
+                matrix_size - 1
+            ),
+            NonInteractiveError::InvalidFixedVariable { variable, message } => {
+                writeln!(f, "Fixed variable \"{variable}\" is invalid! {message}")
+            }
+            NonInteractiveError::InvalidBound { variable, message } => {
+                writeln!(f, "Bound of variable \"{variable}\" is invalid! {message}")
+            }
+            NonInteractiveError::InvalidMethod { method, message } => {
+                writeln!(f, "Method \"{method}\" is unknown! {message}")
+            }
+            NonInteractiveError::InvalidToleranceMode { mode, message } => {
+                writeln!(f, "Tolerance mode \"{mode}\" is unknown! {message}")
+            }
+            NonInteractiveError::InvalidNorm { norm, message } => {
+                writeln!(f, "Norm \"{norm}\" is unknown! {message}")
+            }
+            NonInteractiveError::InvalidEpsilon { epsilon, message } => {
+                writeln!(f, "Epsilon \"{epsilon}\" is not a number! {message}")
+            }
+            NonInteractiveError::InvalidBlocks(message) => writeln!(f, "Blocks are invalid! {message}"),
+            NonInteractiveError::InvalidInitialGuess(message) => writeln!(f, "Initial guess is invalid! {message}"),
+            NonInteractiveError::TemplateError(message) => writeln!(f, "{message}"),
+            NonInteractiveError::InputFilterError { command, message } => {
+                writeln!(f, "Input filter \"{command}\" failed! {message}")
+            }
+            NonInteractiveError::CsvError(err) => writeln!(f, "Invalid CSV input! {err}"),
             NonInteractiveError::ParseError(err) => {
                 writeln!(f, "error during parsing occured! Error: {}", err)
             }
@@ -251,39 +614,292 @@ impl From<Option<&str>> for InputMethod {
     }
 }
 
-fn try_non_interactive() -> Result<EquesionInput, NonInteractiveError> {
-    let content = match determine_input_method() {
-        InputMethod::Argument(filepath) => fs::read_to_string(filepath),
-        InputMethod::Stdin => read_from_stdin(),
-        InputMethod::None => return Err(NonInteractiveError::NoInputProvided),
-    }?;
+/// Input document as read, before templates are substituted and it is parsed.
+/// Kept when one document is parsed many times with different parameters
+pub struct RawInput {
+    pub content: Vec<u8>,
+    /// Where files referenced by manifest are looked up
+    pub base: PathBuf,
+    /// Documents declaring larger matrix than fits are refused before parsing
+    pub max_memory: Option<u64>,
+}
+
+impl RawInput {
+    pub fn read(input: &InputOptions) -> Result<Self, NonInteractiveError> {
+        let input_method = determine_input_method(input.path.as_deref());
+        let content = match &input_method {
+            InputMethod::Argument(filepath) => fs::read(filepath)?,
+            InputMethod::Stdin => match input.stdin_timeout {
+                Some(timeout) => read_from_stdin_with_timeout(timeout)?,
+                None => read_from_stdin()?,
+            },
+            InputMethod::None => return Err(NonInteractiveError::NoInputProvided),
+        };
+        let content = match &input.filter {
+            Some(command) => run_filter(command, content)?,
+            None => content,
+        };
+
+        let base = match &input_method {
+            InputMethod::Argument(filepath) => Path::new(filepath).parent(),
+            _ => None,
+        }
+        .unwrap_or(Path::new(""))
+        .to_owned();
+
+        Ok(RawInput {
+            content: compression::decompress_if_needed(content)?,
+            base,
+            max_memory: input.max_memory,
+        })
+    }
+
+    pub fn has_placeholder(&self, name: &str) -> bool {
+        std::str::from_utf8(&self.content).is_ok_and(|text| text.contains(&format!("${{{name}}}")))
+    }
+
+    pub fn parse(&self, params: &BTreeMap<String, String>) -> Result<EquesionInput, NonInteractiveError> {
+        let content = match std::str::from_utf8(&self.content) {
+            Ok(text) if text.contains("${") => template::substitute(text, params)
+                .map_err(NonInteractiveError::TemplateError)?
+                .into_bytes(),
+            _ => self.content.clone(),
+        };
+        check_declared_size(&content, self.max_memory)?;
+        parse_document(content, &self.base)
+    }
+}
+
+/// Refuses document whose matrix would not fit into `max_memory` by rows it
+/// declares, before its values are parsed. Compressed documents and
+/// manifests are left to check of [`build_equation`]
+pub fn check_declared_size(content: &[u8], max_memory: Option<u64>) -> Result<(), NonInteractiveError> {
+    let Some(limit) = max_memory else {
+        return Ok(());
+    };
+    let Ok(DeclaredRows { input_matrix: RowCount(rows) }) = json::from_slice(content) else {
+        return Ok(());
+    };
+
+    let estimate = MemoryEstimate::new(rows, std::mem::size_of::<Decimal>());
+    match estimate.total() > limit {
+        true => Err(NonInteractiveError::MemoryLimitExceeded { estimate, limit }),
+        false => Ok(()),
+    }
+}
+
+/// Equation configured for solve is refused when its method would take
+/// more than `max_memory` along with the system
+pub fn check_memory(equation: &Equation, max_memory: Option<u64>) -> Result<(), NonInteractiveError> {
+    let estimate = MemoryEstimate::of(equation, std::mem::size_of::<Decimal>());
+    match max_memory {
+        Some(limit) if estimate.total() > limit => Err(NonInteractiveError::MemoryLimitExceeded { estimate, limit }),
+        _ => Ok(()),
+    }
+}
+
+/// Only size of input document, other fields are skipped
+#[derive(Deserialize)]
+struct DeclaredRows {
+    input_matrix: RowCount,
+}
+
+/// Rows of matrix, counted and dropped as they are read
+struct RowCount(usize);
+
+impl<'de> Deserialize<'de> for RowCount {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Rows;
+
+        impl<'de> serde::de::Visitor<'de> for Rows {
+            type Value = RowCount;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "array of rows")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<RowCount, A::Error> {
+                let mut rows = 0;
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {
+                    rows += 1;
+                }
+                Ok(RowCount(rows))
+            }
+        }
+
+        deserializer.deserialize_seq(Rows)
+    }
+}
+
+/// Pipes `content` through `command` run by shell, returns what it printed
+fn run_filter(command: &str, content: Vec<u8>) -> Result<Vec<u8>, NonInteractiveError> {
+    let failed = |message: String| NonInteractiveError::InputFilterError {
+        command: command.to_owned(),
+        message,
+    };
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| failed(format!("Can't start it: {err}")))?;
+
+    // written from another thread, so filter printing its output before it
+    // read the whole input does not block both sides
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || stdin.write_all(&content));
+    let output = child
+        .wait_with_output()
+        .map_err(|err| failed(format!("Can't read its output: {err}")))?;
+    // filter may exit without reading all input, which is its own business
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let status = match output.status.code() {
+            Some(code) => format!("It exited with code {code}"),
+            None => "It was killed by signal".to_owned(),
+        };
+        return Err(failed(match stderr.trim_end() {
+            "" => status,
+            stderr => format!("{status}: {stderr}"),
+        }));
+    }
+    Ok(output.stdout)
+}
 
-    json::from_str::<EquesionInput>(&content).map_err(|err| err.into())
+pub fn try_non_interactive(
+    input: &InputOptions,
+) -> Result<EquesionInput, NonInteractiveError> {
+    RawInput::read(input)?.parse(&input.params)
 }
 
-fn determine_input_method() -> InputMethod {
-    let arguments: Vec<String> = std::env::args().collect();
+/// Turns input document, already decompressed, into equation input. It is
+/// JSON document, manifest or CSV one (see [`csv`]). Files referenced by
+/// manifest are looked up relative to `base`
+pub fn parse_document(
+    content: Vec<u8>,
+    base: &Path,
+) -> Result<EquesionInput, NonInteractiveError> {
+    if manifest::is_manifest(&content) {
+        return json::from_slice::<InputManifest>(&content)?.resolve(base);
+    }
+    if csv::is_csv(&content) {
+        return csv::document(&content).map_err(NonInteractiveError::CsvError);
+    }
 
-    if let Some(filepath) = arguments.get(1) {
-        return InputMethod::Argument(filepath.clone());
+    json::from_slice::<EquesionInput>(&content).map_err(|err| err.into())
+}
+
+fn determine_input_method(input: Option<&str>) -> InputMethod {
+    if let InputMethod::Argument(filepath) = InputMethod::from(input) {
+        return InputMethod::Argument(filepath);
     }
 
     if !io::stdin().lock().is_terminal() {
         return InputMethod::Stdin;
     }
 
-    return InputMethod::None;
+    InputMethod::None
 }
 
-#[derive(Deserialize, Debug)]
-struct EquesionInput {
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EquesionInput {
     pub input_matrix: Vec<Vec<String>>,
     pub expression_rhs: Vec<String>,
     pub max_iterations: usize,
     pub epsilon: String,
+    /// Size of leading block for Schur complement solve, see [`Equation::schur_block`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schur_block: Option<usize>,
+    /// Unknowns with known values, like `{"x3": "2.5"}`. Their own equations
+    /// are ignored and may be left empty (all zeros)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fixed: BTreeMap<String, String>,
+    /// Lower bounds of unknowns, like `{"x1": "0"}`, applied by projection
+    /// every iteration
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lower: BTreeMap<String, String>,
+    /// Upper bounds of unknowns, see `lower`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub upper: BTreeMap<String, String>,
+    /// Sizes of diagonal blocks for block Gauss-Seidel, like `[2, 3]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<usize>,
+    /// Lines and unknowns per line of 2D grid problem, like `[4, 5]`.
+    /// Same as `blocks` of one grid line each
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<[usize; 2]>,
+    /// Name of method, like `"jacobi"`. Gauss-Seidel if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    /// Steps of iterative refinement after solve, see [`Equation::refine`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refinement_steps: Option<usize>,
+    /// `"absolute"`, `"relative"` or `"mixed"`, see [`ToleranceMode`]. Absolute if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance_mode: Option<String>,
+    /// `"l1"`, `"l2"` or `"linf"`, norm of change of iteration and of
+    /// residual, see [`Equation::norm`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub norm: Option<String>,
+    /// Approximation iterations start from, see [`Equation::initial_guess`].
+    /// All ones if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_guess: Option<GuessInput>,
 }
 
-fn read_from_stdin() -> Result<String, io::Error> {
-    let mut content = String::new();
-    io::stdin().read_to_string(&mut content).map(|_| content)
+/// `initial_guess` of input
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum GuessInput {
+    /// One value per unknown, like `["0", "1.5"]`
+    Values(Vec<String>),
+    /// Name of strategy, like `"diagonal"`, see [`InitialGuess`]
+    Strategy(String),
+}
+
+fn read_from_stdin() -> Result<Vec<u8>, io::Error> {
+    let mut content = Vec::new();
+    io::stdin().read_to_end(&mut content).map(|_| content)
+}
+
+/// Reads stdin on background thread, so waiting for the first chunk of data
+/// can be abandoned. Once data starts flowing, the rest is awaited without limit
+fn read_from_stdin_with_timeout(timeout: Duration) -> Result<Vec<u8>, NonInteractiveError> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        let mut buffer = [0u8; 8192];
+        loop {
+            let chunk = match stdin.read(&mut buffer) {
+                Ok(0) => return,
+                Ok(read) => Ok(buffer[..read].to_vec()),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => Err(err),
+            };
+
+            let failed = chunk.is_err();
+            if sender.send(chunk).is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    let mut content = match receiver.recv_timeout(timeout) {
+        Ok(chunk) => chunk?,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            return Err(NonInteractiveError::StdinTimeout(timeout))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => Vec::new(),
+    };
+
+    for chunk in receiver {
+        content.extend(chunk?);
+    }
+
+    Ok(content)
 }
@@ -0,0 +1,115 @@
+//! Random perturbation of coefficients, which shows how far solution moves
+//! when matrix is known only up to tiny relative error
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+
+use crate::condition::matrix_norm;
+use crate::norm::Norm;
+use crate::solver::{Equation, RoundingLog};
+
+/// Random share of `relative` is drawn with this many decimal places
+const SHARE_PLACES: u32 = 9;
+
+/// Seed for `--jitter` when none is given
+pub fn random_seed() -> u64 {
+    StdRng::from_entropy().gen()
+}
+
+/// Copy of `equation` whose every non-zero coefficient of matrix is
+/// multiplied by `1 + e`, with `e` drawn uniformly from `[-relative,
+/// relative]`. Zeros stay, so structure fast paths rely on is kept, and
+/// symmetric matrix stays symmetric for methods which need it
+pub fn perturb(equation: &Equation, relative: Decimal, seed: u64) -> Equation {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let scale = 10_i64.pow(SHARE_PLACES);
+    let matrix = &equation.input_matrix;
+    let symmetric = matrix == &matrix.transpose();
+    let mut perturbed = equation.clone();
+    // products of perturbed solve are not ones of solve reported
+    perturbed.rounding_log = RoundingLog::default();
+    for row in 0..matrix.nrows() {
+        for column in 0..matrix.ncols() {
+            let value = matrix[(row, column)];
+            perturbed.input_matrix[(row, column)] = match symmetric && column < row {
+                true => perturbed.input_matrix[(column, row)],
+                false if value.is_zero() => value,
+                false => {
+                    let share = Decimal::new(rng.gen_range(-scale..=scale), SHARE_PLACES);
+                    value + value * relative * share
+                }
+            };
+        }
+    }
+    perturbed
+}
+
+/// How solution responded to perturbation of [`perturb`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jitter {
+    /// Largest relative change of coefficient asked for
+    pub relative: Decimal,
+    pub seed: u64,
+    /// `|dA|inf` and `|dA|inf / |A|inf`
+    pub matrix_change: Decimal,
+    pub relative_matrix_change: Option<Decimal>,
+    /// `|dx|inf` and `|dx|inf / |x|inf` between solutions of original and
+    /// perturbed systems
+    pub solution_change: Decimal,
+    pub relative_solution_change: Option<Decimal>,
+}
+
+impl Jitter {
+    pub fn measure(
+        original: &Equation,
+        perturbed: &Equation,
+        relative: Decimal,
+        seed: u64,
+        solution: &DVector<Decimal>,
+        perturbed_solution: &DVector<Decimal>,
+    ) -> Self {
+        let change: DMatrix<Decimal> = &perturbed.input_matrix - &original.input_matrix;
+        let matrix_change = matrix_norm(&change);
+        let solution_change = Norm::LInf.of((perturbed_solution - solution).iter().copied());
+        Jitter {
+            relative,
+            seed,
+            matrix_change,
+            relative_matrix_change: matrix_change.checked_div(matrix_norm(&original.input_matrix)),
+            solution_change,
+            relative_solution_change: solution_change.checked_div(Norm::LInf.of(solution.iter().copied())),
+        }
+    }
+
+    /// Relative change of solution over relative change of matrix, an
+    /// empirical lower bound of condition number
+    pub fn amplification(&self) -> Option<Decimal> {
+        self.relative_solution_change?.checked_div(self.relative_matrix_change?)
+    }
+}
+
+impl fmt::Display for Jitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = |value: Decimal| value.round_sf(3).unwrap_or(value).normalize();
+        write!(
+            f,
+            "coefficients moved by up to {} of their value (seed {}), |dA|inf {}",
+            self.relative.normalize(),
+            self.seed,
+            number(self.matrix_change)
+        )?;
+        if let Some(relative) = self.relative_matrix_change {
+            write!(f, " ({} of |A|inf)", number(relative))?;
+        }
+        write!(f, ", solution moved by |dx|inf {}", number(self.solution_change))?;
+        if let Some(relative) = self.relative_solution_change {
+            write!(f, " ({} of |x|inf)", number(relative))?;
+        }
+        match self.amplification() {
+            Some(amplification) => write!(f, ", {} times relative change of matrix", number(amplification)),
+            None => Ok(()),
+        }
+    }
+}
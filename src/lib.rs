@@ -0,0 +1,47 @@
+//! Gauss-Seidel solver for systems of linear equations over `Decimal`.
+//!
+//! Command line application is thin layer over this crate: host applications
+//! can build [`solver::Equation`] themselves, or go through [`service`] which
+//! accepts the same documents as `solver` binary, or pass plain slices to
+//! [`slice`] functions. Parallel reductions run on worker threads owned by
+//! [`parallel::SolverPool`]. Systems of size
+//! known at compile time can be solved without std by [`kernel`]
+
+pub mod accuracy;
+pub mod analysis;
+pub mod backend;
+pub mod batch;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod comparison;
+pub mod compression;
+pub mod condition;
+pub mod convergence;
+pub mod csv;
+pub mod daemon;
+pub mod decomposition;
+pub mod diff;
+pub mod fingerprint;
+pub mod http;
+pub mod input;
+pub mod jitter;
+pub mod lint;
+pub mod manifest;
+pub mod memory;
+pub mod norm;
+pub mod parallel;
+pub mod preprocess;
+pub mod redact;
+pub mod replay;
+pub mod report;
+pub mod result;
+pub mod server;
+pub mod service;
+pub mod slice;
+pub mod snapshot;
+pub mod solver;
+pub mod study;
+pub mod template;
+pub mod worker;
+
+pub use seidel_kernel as kernel;
@@ -0,0 +1,156 @@
+use core::fmt;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::analysis::{find_proportional_rows, ProportionalRows};
+use crate::input::{located_values, precision_losses, EquesionInput, PrecisionLoss};
+use crate::solver::Equation;
+
+/// Rows whose largest coefficients differ more than this many times
+/// are prone to losing digits of the smaller row during iteration
+const ROW_MAGNITUDE_DISPARITY: Decimal = dec!(1_000_000);
+
+/// Diagonal entry smaller than this share of largest entry in its row
+const NEAR_ZERO_DIAGONAL_SHARE: Decimal = dec!(0.000001);
+
+/// More significant digits than `f64` carries are most likely
+/// artifacts of printing floats, not real data
+const MAX_SENSIBLE_DIGITS: usize = 15;
+
+#[derive(Debug)]
+pub enum LintIssue {
+    RowMagnitudeDisparity {
+        largest_row: usize,
+        smallest_row: usize,
+        ratio: Decimal,
+    },
+    NearZeroDiagonal {
+        row: usize,
+        value: Decimal,
+        row_max: Decimal,
+    },
+    ProportionalRows(ProportionalRows),
+    ExcessivePrecision {
+        location: String,
+        value: String,
+        digits: usize,
+    },
+    /// Value is rounded when parsed
+    PrecisionLoss(PrecisionLoss),
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintIssue::RowMagnitudeDisparity {
+                largest_row,
+                smallest_row,
+                ratio,
+            } => write!(
+                f,
+                "Coefficients of row {largest_row} are {} times larger than of row {smallest_row}. Consider scaling rows",
+                ratio.round_dp(2).normalize()
+            ),
+            LintIssue::NearZeroDiagonal {
+                row,
+                value,
+                row_max,
+            } => write!(
+                f,
+                "Diagonal entry of row {row} is {value} while row holds {row_max}. Iteration divides by it"
+            ),
+            LintIssue::ProportionalRows(rows) => write!(f, "{rows}"),
+            LintIssue::ExcessivePrecision {
+                location,
+                value,
+                digits,
+            } => write!(
+                f,
+                "{location} \"{value}\" has {digits} significant digits, probably a float printing artifact"
+            ),
+            LintIssue::PrecisionLoss(loss) => write!(f, "{loss}"),
+        }
+    }
+}
+
+/// Counts digits from first non-zero one, ignoring sign, point and exponent
+fn significant_digits(value: &str) -> usize {
+    let mantissa = value.split(['e', 'E']).next().unwrap_or_default();
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let trimmed = digits.trim_start_matches('0');
+    let trimmed = if mantissa.contains('.') {
+        trimmed
+    } else {
+        trimmed.trim_end_matches('0')
+    };
+    trimmed.len()
+}
+
+/// Looks at raw input values, before they are turned into numbers
+pub fn lint_input(input: &EquesionInput) -> Vec<LintIssue> {
+    let excessive = located_values(input).filter_map(|(location, value)| {
+        let digits = significant_digits(value);
+        (digits > MAX_SENSIBLE_DIGITS).then(|| LintIssue::ExcessivePrecision {
+            location,
+            value: value.clone(),
+            digits,
+        })
+    });
+
+    excessive
+        .chain(precision_losses(input).into_iter().map(LintIssue::PrecisionLoss))
+        .collect()
+}
+
+/// Looks at numeric properties of valid system
+pub fn lint_equation(equation: &Equation) -> Vec<LintIssue> {
+    let matrix = &equation.input_matrix;
+    let matrix_size = matrix.nrows();
+    let mut issues = Vec::new();
+
+    let row_max: Vec<Decimal> = matrix
+        .row_iter()
+        .map(|row| row.iter().map(|value| value.abs()).fold(dec!(0), Decimal::max))
+        .collect();
+
+    let largest = (0..matrix_size).max_by_key(|row| row_max[*row]);
+    let smallest = (0..matrix_size)
+        .filter(|row| !row_max[*row].is_zero())
+        .min_by_key(|row| row_max[*row]);
+    if let (Some(largest), Some(smallest)) = (largest, smallest) {
+        let ratio = row_max[largest] / row_max[smallest];
+        if ratio > ROW_MAGNITUDE_DISPARITY {
+            issues.push(LintIssue::RowMagnitudeDisparity {
+                largest_row: largest + 1,
+                smallest_row: smallest + 1,
+                ratio,
+            });
+        }
+    }
+
+    // trailing Schur block is never iterated on directly, equations of fixed unknowns are dropped
+    let iterated = equation.schur_block.unwrap_or(row_max.len());
+    for (row, max) in row_max
+        .iter()
+        .enumerate()
+        .take(iterated)
+        .filter(|(row, _)| !equation.fixed.iter().any(|(index, _)| index == row))
+    {
+        let value = matrix[(row, row)];
+        if value.abs() < *max * NEAR_ZERO_DIAGONAL_SHARE {
+            issues.push(LintIssue::NearZeroDiagonal {
+                row: row + 1,
+                value,
+                row_max: *max,
+            });
+        }
+    }
+
+    issues.extend(
+        find_proportional_rows(equation)
+            .into_iter()
+            .map(LintIssue::ProportionalRows),
+    );
+
+    issues
+}
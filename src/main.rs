@@ -1,11 +1,44 @@
 use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::process::ExitCode;
+use std::thread;
 
-use input::build_configuration;
+use cli::{
+    BatchArguments, Command, DaemonArguments, DiffArguments, HashArguments, LintArguments, OutputFormat,
+    RedactArguments, ReplayArguments, ServeArguments, SolveArguments, WorkerArguments,
+};
+use guess_zeidel_method::backend::F64System;
+use guess_zeidel_method::batch::{self, ResumeState};
+use guess_zeidel_method::comparison;
+use guess_zeidel_method::compression::Compression;
+use guess_zeidel_method::input::{
+    build_configuration, build_equation, check_memory, precision_losses, try_non_interactive, RawInput,
+};
+use guess_zeidel_method::jitter::{self, Jitter};
+use guess_zeidel_method::memory::MemoryEstimate;
+use guess_zeidel_method::preprocess::{
+    magnitude_spread, DominanceProfile, Equilibration, Reordering, Split, SymmetricPermutation,
+};
+use guess_zeidel_method::replay::{EventLog, Replay};
+use guess_zeidel_method::report::{render_table, Report, ReportFormat};
+use guess_zeidel_method::result::{LastIteration, SolveTrace};
+use guess_zeidel_method::snapshot::Snapshots;
+use guess_zeidel_method::study::{self, ParamRange};
+use guess_zeidel_method::solver::{ESolveError, Equation, Method, Progress, RankAnalysis, DIRECT_MAX_SIZE};
+use guess_zeidel_method::{
+    analysis, daemon, decomposition, diff, fingerprint, lint, preprocess, redact, server, worker,
+};
+use rust_decimal::Decimal;
+use nalgebra::DVector;
 
-use crate::{input::NonInteractiveError, solver::ESolveError};
-
-mod input;
-mod solver;
+mod cli;
+mod confirm;
+mod crash;
+mod status;
+mod terminal;
 
 fn pad_string(displayable: impl Display, padding: usize) -> String {
     let string = displayable.to_string();
@@ -16,32 +49,1255 @@ fn pad_string(displayable: impl Display, padding: usize) -> String {
         .unwrap_or_default()
 }
 
-const USAGE_INFORMATION: &str = r#"solver <file-path>
-solver < file-path
+const USAGE_INFORMATION: &str = r#"solver [solve options] <file-path>
+solver [solve options] < file-path
+solver redact [--seed <number>] [--output <file-path>] [--compress gzip|zstd] <file-path>
+solver daemon --socket <socket-path> [service options]
+solver lint <file-path>
+solver hash [--canonical] [--permutation-invariant] <file-path>
+solver batch [--events <file-path>] [--output-dir <dir-path>] [--resume-batch <file-path>]
+             [--worker <command>|tcp://<address>]... [service options] <path>...
+solver worker [--listen <address>] [service options]
+solver diff [--format plain|json] <first-result> <second-result>
+solver replay [--format <format>] [--history] [--width <columns>] [--output <file-path>] <log-path>
+solver openapi
+solver serve [--listen <address>] [--concurrency <count>] [--api-token <token> [--token-required]] [service options]
+
+<file-path> is any valid path to a file. Gzip and zstd compressed inputs
+are unpacked automatically. File may be a manifest referencing matrix
+and right hand side through "input_matrix_file" and "expression_rhs_file",
+JSON or CSV files like --dump-format csv writes, or binary *.f64 files of
+little endian floats, matrix row by row, like numpy tofile writes
+File may be CSV too, augmented matrix with right hand side ending every row,
+which takes only the two settings, from lines like `# max_iterations: 64`
+and `# epsilon: 0.001` before it
+Optional "schur_block": <rows> splits system into 2x2 blocks [[A, B], [C, D]]
+with A of that size and solves it through Schur complement of A, so D may
+have zeros on diagonal (saddle point systems)
+Optional "fixed": {"x3": "2.5"} gives known values of unknowns. They are
+eliminated before solving, so their own equations may be all zeros
+Optional "lower": {"x1": "0"} and "upper": {"x2": "1"} bound unknowns. Every
+update is projected onto bounds (projected Gauss-Seidel), which solves
+complementarity problems. Only gauss-seidel, jacobi, richardson and ssor
+apply bounds
+Optional "blocks": [2, 3] splits unknowns into diagonal blocks adding up to
+matrix size. Gauss-Seidel solves every block directly per sweep (block
+Gauss-Seidel), so blocks may have zeros on diagonal
+Optional "grid": [4, 5] tags system of 2D grid problem with 4 lines of 5
+unknowns each, numbered line by line, and is blocks of one grid line each.
+Block tridiagonal systems, like those of heat equation, are solved directly by
+block Thomas algorithm. Pass --no-fast-path to iterate
+Optional "method": "jacobi" chooses method like --method does, the option
+overrides it
+Optional "refinement_steps": 2 refines solution of any method by that many
+steps of iterative refinement, like --refine does, the option overrides it
+Optional "tolerance_mode": "relative" chooses what epsilon is compared with,
+like --tolerance-mode does, the option overrides it
+Optional "norm": "l2" chooses norm of change of iteration and of residual, like
+--norm does, the option overrides it
+Optional "initial_guess": ["0", "1.5", "2"] starts iterations from these values
+of unknowns instead of all ones, like from solution of similar system solved
+before. Direct methods do not use it. It may name strategy instead, like
+"diagonal", as --initial-guess does, the option overrides it
+Before iterating, spectral radius of iteration matrix of jacobi, gauss-seidel,
+ssor and richardson is estimated by power iteration for systems up to 400
+unknowns, and it is told whether convergence is guaranteed and how many
+iterations it should take. It is shown in result too
+Iterations stop before max_iterations once 50 of them in a row bring delta no
+lower than the smallest one so far: solve fails as stagnated, with that delta
+and its iteration, or as diverging when delta grew 1000 times over it.
+Approximation which outgrows Decimal fails solve with row and iteration
+where it overflowed
+Equations with zeros on diagonal, or in order which is not diagonally dominant
+while another one is, are swapped before solving and the order is reported.
+Unknowns keep their numbers. Systems with "fixed", "schur_block", "blocks" or
+"grid" are kept in input order
+On Unix, SIGUSR1 (kill -USR1 <pid>) prints iteration, delta and elapsed time
+of running solve to stderr
+If solver panics or meets error its input checks should have ruled out, bug
+report is written to temporary directory and its path is printed. Report holds
+version, settings, hash of input, last iteration and backtrace, but not
+coefficients of input
+<duration> is like 5s, 500ms or 2m
+<size> is like 512M or 2G
+
+solve options:
+  --dry-run                     validate input and print memory estimate without solving,
+                                along with norms of matrix and right hand side and
+                                coefficients which lose digits in f64
+  --parametric                  if system has no unique solution, describe all of them
+                                through particular solution and null space basis
+  --reorder dominance|cm|rcm    renumber unknowns before solving, solution is still
+                                printed in original order:
+                                dominance visits rows with strongest diagonal
+                                dominance first and prints dominance of rows,
+                                cm and rcm apply (reverse) Cuthill-McKee ordering to
+                                reduce bandwidth and print bandwidth
+  --order-by-dominance          same as --reorder dominance
+  --equilibrate                 scale rows and columns by powers of 10 so that
+                                the largest entry of every row and column is
+                                between 1 and 10, for matrices mixing tiny and
+                                huge coefficients. Digits are not lost, solution
+                                is unscaled and epsilon still holds for it.
+                                Skipped for systems with fixed or bounded unknowns
+  --yes                         do not ask for confirmation when --reorder,
+                                --split-components, --equilibrate or hybrid
+                                method change the system. Without terminal
+                                solver does not ask, changes are listed as warning
+  --method <method>             gauss-seidel (default), jacobi, richardson, ssor,
+                                gauss, gauss-jordan, lu, cholesky, qr, cg, gmres,
+                                bicgstab, hybrid or auto, overrides "method" of
+                                input.
+                                Jacobi computes every component from previous
+                                approximation. richardson is simple iteration
+                                x + tau (b - A x). ssor does forward and backward
+                                Gauss-Seidel sweep every iteration, which suits
+                                symmetric systems. gauss solves directly by Gaussian
+                                elimination with partial pivoting, gauss-jordan by
+                                Gauss-Jordan elimination, lu through LU
+                                factorization with partial pivoting, cholesky
+                                through Cholesky factorization, which only applies
+                                to symmetric positive definite matrices.
+                                For such matrices cholesky is suggested unless
+                                the system is large. qr solves through Householder
+                                QR factorization, which stays accurate for nearly
+                                singular matrices. cg runs conjugate gradient
+                                iterations, which converge much faster than
+                                Gauss-Seidel for symmetric positive definite
+                                matrices and do not apply to other ones. gmres
+                                runs restarted GMRES, which needs neither symmetry
+                                nor diagonal dominance. bicgstab does the same
+                                with memory not growing between restarts. Every
+                                10 iterations all three compare residual they
+                                carry with true b - Ax and start over from the
+                                true one when they drifted apart, which is
+                                reported as restart
+                                hybrid starts with Jacobi iterations and switches
+                                to Gauss-Seidel, then to direct elimination, when
+                                convergence stalls. auto picks method by symmetry,
+                                positive definiteness, diagonal dominance,
+                                bandwidth and size of matrix and tells why
+  --inverse                     also report inverse of matrix, computed by
+                                Gauss-Jordan elimination
+  --determinant                 also report determinant of matrix, computed by
+                                LU factorization
+  --refine <steps>              after solve, solve residual for correction through
+                                LU factorization up to this many times, stopping
+                                once residual no longer shrinks. Residual norm
+                                before and after is reported. Up to 100 unknowns
+  --jitter <relative>           after solve, multiply every non-zero coefficient of
+                                matrix by random 1 + e, |e| up to relative, like
+                                1e-9, and solve again. Change of matrix and of
+                                solution is reported, and how many times relative
+                                change of solution exceeds that of matrix. Change
+                                below epsilon is noise of iterations
+  --seed <number>               seed of --jitter, random and reported by default
+  --omega <number>              relaxation factor in (0, 2) of gauss-seidel, ssor and
+                                Gauss-Seidel stage of hybrid, 1 by default. Above 1
+                                gives SOR. For jacobi it is damping in (0, 1]
+                                (weighted Jacobi). Methods taking no such parameter
+                                refuse it
+  --tau <number>                parameter of richardson, estimated from Gershgorin
+                                discs of matrix by default
+  --chebyshev                   accelerate jacobi, gauss-seidel or ssor by Chebyshev
+                                semi-iteration, which needs far fewer iterations on
+                                slowly converging systems. Base of gauss-seidel and
+                                ssor is forward and backward sweep, so matrix should
+                                be symmetric. Spectrum of iteration matrix is
+                                estimated from Gershgorin discs or first iterations
+  --spectrum <lower>:<upper>    same as --chebyshev with given bounds of eigenvalues
+                                of iteration matrix, upper one below 1
+  --aitken                      apply Aitken delta-squared extrapolation to
+                                approximation every 5 iterations of jacobi,
+                                gauss-seidel, richardson, ssor or hybrid, which
+                                shortens slow but steady convergence
+  --aitken-period <count>       same as --aitken, every given number of iterations,
+                                at least 3
+  --preconditioner none|jacobi|ilu0
+                                preconditioner of cg, gmres and bicgstab: jacobi
+                                divides by diagonal, ilu0 applies incomplete LU
+                                factors keeping non-zero pattern of matrix. Both
+                                cut iterations on poorly conditioned matrices.
+                                With auto method, cg or gmres is chosen
+  --restart <count>             iterations after which gmres restarts, at least 1,
+                                30 by default
+  --rounding native|rescale:<places>|wide
+                                rounding of products a_ij x_j summed by sweeps and
+                                products by matrix. native lets Decimal round
+                                product wherever its 28 digits run out, rescale:20
+                                rounds every product to 20 decimal places, wide
+                                keeps products exact in two parts and rounds only
+                                the sum, on one thread. The coarsest rounding
+                                applied is reported
+  --no-fast-path                gauss-seidel over tridiagonal matrix is replaced with
+                                Thomas algorithm, unless this is passed
+  --initial-guess zeros|ones|diagonal|random:<seed>
+                                approximation iterations start from: all zeros,
+                                all ones (default), b_i / a_ii, which often saves
+                                many iterations over ones, or random components
+                                in [-1, 1], the same for the same seed
+  --tolerance-mode absolute|relative|mixed
+                                iterations stop once largest change of component
+                                is below epsilon (absolute, default), epsilon times
+                                largest component of approximation (relative), or
+                                epsilon times one plus it (mixed), which suits
+                                solutions with huge or tiny components
+  --norm l1|l2|linf             norm of change of iteration compared with epsilon,
+                                of approximation relative tolerance scales by, and
+                                of reported residual. By default change is largest
+                                change of component and residual norm is Euclidean
+  --epsilon-schedule <list>     solve in phases to each epsilon in turn (like
+                                1e-2,1e-4,1e-6), every phase starts from result of
+                                previous one. The last epsilon replaces input one
+  --snapshot-at <list>          write approximation at given iterations (like 1,5,10)
+                                to iterate-<iteration>.json files
+  --snapshot-dir <dir-path>     where snapshot files are written, current directory
+                                by default
+  --record <file-path>          write event log of solve: settings, every iteration
+                                with its approximation, decisions and result, one
+                                JSON object per line. solver replay renders it
+  --dump-decomposition <dir-path>
+                                write D, L and U of A = D + L + U, iteration matrix
+                                of jacobi, richardson, gauss-seidel and ssor, factors of
+                                gauss, lu, cholesky and qr and inverse computed
+                                by gauss-jordan to files in directory
+  --dump-format json|csv        format of dumped matrices, json by default
+  --split-components            if system is reducible, solve its independent
+                                subsystems separately (in parallel with --threads)
+  --param <name>=<value>        replace ${name} placeholders of input file with value
+                                before parsing, may be repeated
+  --param-range <name>=<start>:<end>:<step>
+                                solve templated input for every value of parameter
+                                from start to end and print table of iterations
+                                and solutions against the parameter
+  --compare <direct-method>     solve by given direct method (gauss, gauss-jordan,
+                                lu, cholesky or qr) and by iterative --method,
+                                gauss-seidel by default, and print table of both
+                                solutions, their difference, residual norm,
+                                iterations and time of each
+  --stdin-timeout <duration>    fail if no data arrives on stdin in time
+  --input-filter <command>      pipe input file through shell command before
+                                parsing, like "my-export-to-json --strict". It reads
+                                input on stdin and must print input document in
+                                the format above, which supports other input formats
+  --max-memory <size>           refuse inputs estimated to need more memory
+  --threads <count>             spread row sums of large systems across pool of
+                                worker threads, which jobs of serve and daemon share
+  --deterministic               bit-identical results across runs and thread counts
+  --format <format>             plain, table, json, latex or markdown. All of them
+                                show solution with iteration count, epsilon, last
+                                delta, residual Ax - b with its norm and how
+                                many digits of solution are trustworthy, judged by
+                                residual, condition number and epsilon.
+                                Solution of singular matrix exits with 1.
+                                Condition number is shown on its own too, also when
+                                solve fails: computed up to 100 unknowns, estimated
+                                up to 400, next to norms |A|1, |A|inf, Frobenius
+                                norm of A and norm of b. Multiplications and
+                                divisions of solve are counted too, by kind of
+                                iteration, to compare cost of methods. json result
+                                can be compared by diff
+  --schema-version result/v1    schema of json result, written to its "schema"
+                                field. Within a version fields are only added,
+                                never removed, renamed or changed in meaning, so
+                                tools reading it should skip unknown fields. The
+                                latest one by default, daemon, batch, HTTP and C
+                                API write it too
+  --locale en|ru|de|fr          thousands and decimal separators of locale
+  --thousands-separator <char>  group digits of integer part by three
+  --decimal-comma               write decimal comma instead of point
+  --notation fixed|scientific   scientific writes numbers like 1.25e-3
+                                Number options do not affect json
+  --width <columns>             fit plain and table output into this many
+                                columns: long lines wrap, middle columns of wide
+                                tables are left out. Width of terminal by default,
+                                0 does not fit. Files of --output are never fitted
+  --output <file-path>          write result to file instead of stdout
+  --output-template <file-path> fill template instead of --format: {{x1}},
+                                {{solution}}, {{iterations}}, {{last_delta}},
+                                {{residual1}}, {{residual_norm}}, {{epsilon}},
+                                {{accuracy}}, {{condition}},
+                                {{determinant}} (with --determinant), {{status}},
+                                {{error}} and {{size}} are replaced
+                                by values, {{#unknowns}}{{name}} = {{value}}{{/unknowns}}
+                                and {{#equations}}{{index}}: {{residual}}{{/equations}}
+                                repeat for every unknown or equation
+  --assert-matches <file-path>  exit with 1 unless solution matches result file
+                                written with --format json
+  --tol <number>                allowed deviation for --assert-matches, epsilon by default
+
+redact rescales and renumbers equations so system can be shared
+without revealing original coefficients. Sparsity pattern, bandwidth and
+ratios of coefficients within an equation are kept, so original is recovered
+up to scale of every equation and order of unknowns: it hides very little
+
+lint warns about inputs which are valid but likely to cause trouble:
+rows of very different magnitude, near-zero diagonal entries, repeating
+or contradicting equations, values with suspiciously many digits and values
+rounded because Decimal can't hold them
+
+hash prints stable hash of input: inputs differing only in formatting of
+numbers, key order or file layout hash equally. --canonical prints the form
+hash is taken of. --permutation-invariant ignores order of equations
+
+replay renders report of solve from its --record log without solving
+again, in any --format. --history prints table of logged iterations
+instead. Logs may be compressed with gzip or zstd
 
-<file-path> is any valid path to a file
+diff compares two results written with --format json: componentwise
+differences, norms of difference and metadata changes. Exits with 1
+if results differ
+
+batch solves every input file, directories stand for files they hold.
+Batch file {"defaults": {...}, "problems": [...]} holds several problems,
+each given inline by "input_matrix" and "expression_rhs" or by "input_file".
+Problems may set "epsilon", "max_iterations" and "method", the rest is taken
+from "defaults"
+Progress of every input is printed to stderr, --events appends it as one
+JSON object per line ("started" and "finished" events with status and time).
+--output-dir receives <input file name>.result.json for every input.
+--resume-batch records every finished input to the given state file and
+skips inputs recorded there by earlier runs, so interrupted run can be
+continued by running the same command again.
+--worker sends inputs to worker instead of solving them here, every free
+worker takes the next input. Worker is command serving "solver worker" on
+its stdin and stdout, like "ssh node1 solver worker", or address of
+"solver worker --listen", like tcp://node1:7878. Give the same worker several
+times to solve several inputs on it at once. Inputs, manifests included, are
+read here, so workers need no files. Service options then apply to workers
+started with them, not to batch. Worker which fails is dropped and its input
+goes to another one
+Exits with 1 unless all inputs are solved
+
+worker solves inputs sent by batch --worker, one JSON input per line like
+daemon. TCP workers accept anyone who can connect, listen on cluster network
+only
+
+service options:
+  --max-memory <size>, --threads <count>, --deterministic  same as for solve
+  --max-matrix-size <rows>      reject larger systems
+  --max-iterations <count>      reject inputs asking for more iterations
+  --request-timeout <duration>  stop solves taking longer
+Inputs sent to service carry matrix inline, manifests are not followed
+Services take at most 256 connections at a time and refuse further ones.
+Daemon and worker close connections idle for 5 minutes, HTTP requests must
+arrive whole within 60 seconds
+
+daemon serves solve requests over Unix socket: one JSON input per line,
+one JSON response per line
+
+serve starts HTTP API (127.0.0.1:8080 by default):
+  POST /solve       solve input from request body, failed solve gets 422
+  POST /analyze     method, norms, conditioning and warnings of input, not solved
+  POST /jobs        queue input from request body, responds with job id
+  GET /jobs/<id>    status, progress and result of queued job
+  GET /openapi.json description of the API, also printed by "solver openapi"
+--concurrency sets how many queued jobs are solved at the same time
+Requests with "Authorization: Bearer <token>" matching --api-token are not
+limited. With --token-required other requests are rejected
 "#;
 
-fn main() {
-    let config = build_configuration();
-    match config {
+fn report_error(error: impl Display) -> ExitCode {
+    eprintln!("Error occured:");
+    eprintln!("{}", pad_string(error, 2));
+    ExitCode::FAILURE
+}
+
+fn report_usage() -> ExitCode {
+    eprintln!("{}", pad_string(USAGE_INFORMATION, 2));
+    ExitCode::FAILURE
+}
+
+/// Exit code when solve finished without solution or with solution of
+/// singular matrix, or compared results differ
+const UNSUCCESSFUL: u8 = 1;
+/// Checking matrix for Cholesky suggestion factors it, which is not free
+const CHOLESKY_SUGGESTION_MAX_SIZE: usize = 100;
+
+fn main() -> ExitCode {
+    crash::install();
+    let command = match cli::parse_arguments(std::env::args().skip(1)) {
+        Ok(command) => command,
         Err(err) => {
-            let error_string: &dyn Display = if err.is_no_input_provided() {
-                &USAGE_INFORMATION
-            } else {
-                eprintln!("Error occured:");
-                &err
-            };
-
-            eprintln!("{}", pad_string(error_string, 2));
-        }
-        Ok(config) => match config.solve() {
-            Ok(result) => println!("Solution: {}", result),
-            Err(error) => match error {
-                ESolveError::Diverge => {
-                    eprintln!("Solution approximation diverges. Equesions do not have solution")
-                }
-            },
+            report_error(err);
+            return report_usage();
+        }
+    };
+
+    match command {
+        Command::Solve(arguments) => solve(*arguments),
+        Command::Redact(arguments) => redact(arguments),
+        Command::Daemon(arguments) => daemon(arguments),
+        Command::Serve(arguments) => serve(arguments),
+        Command::OpenApi => {
+            print!("{}", server::OPENAPI_SPEC);
+            ExitCode::SUCCESS
+        }
+        Command::Diff(arguments) => diff(arguments),
+        Command::Lint(arguments) => lint(arguments),
+        Command::Hash(arguments) => hash(arguments),
+        Command::Replay(arguments) => replay(arguments),
+        Command::Batch(arguments) => batch(arguments),
+        Command::Worker(arguments) => worker(arguments),
+    }
+}
+
+fn solve(arguments: SolveArguments) -> ExitCode {
+    if let Some(range) = &arguments.param_range {
+        return study(&arguments, range);
+    }
+
+    let parsed = match try_non_interactive(&arguments.input) {
+        Ok(parsed) => parsed,
+        Err(err) if err.is_no_input_provided() => return report_usage(),
+        Err(err) => return report_error(err),
+    };
+    for loss in precision_losses(&parsed) {
+        eprintln!("warning: {loss}");
+    }
+    let mut config = match build_equation(parsed, arguments.input.max_memory) {
+        Ok(config) => config,
+        Err(err) => return report_error(err),
+    };
+
+    if arguments.dry_run {
+        // estimate covers method given on command line
+        configure(&mut config, &arguments);
+        let matrix_size = config.input_matrix.nrows();
+        let estimate = MemoryEstimate::of(&config, std::mem::size_of::<Decimal>());
+        println!("Input is valid");
+        println!("Matrix size: {matrix_size}x{matrix_size}");
+        println!("Max iterations: {}", config.max_iterations);
+        println!("Epsilon: {}", config.epsilon);
+        println!("Norms: {}", analysis::system_norms(&config));
+        println!("Estimated memory: {estimate}");
+        let (_, losses) = F64System::from_equation_lossy(&config);
+        println!("Coefficients losing digits in f64: {}", losses.len());
+        for loss in &losses {
+            println!("  {loss}");
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    // iterative methods may still converge to one of many solutions,
+    // so rank is checked before iterating
+    if arguments.parametric {
+        match config.analyze_rank() {
+            RankAnalysis::Parametric(solution) => {
+                let content = Report::parametric(&config, solution)
+                    .with_number_format(arguments.number_format)
+                    .with_schema(arguments.schema)
+                    .with_width(terminal::output_width(arguments.width, arguments.output.as_deref()))
+                    .render(arguments.format);
+                return match write_output(arguments.output.as_deref(), None, content) {
+                    Ok(()) => ExitCode::SUCCESS,
+                    Err(err) => report_error(err),
+                };
+            }
+            RankAnalysis::Inconsistent { rank } => eprintln!(
+                "System matrix has rank {rank}, but augmented matrix has higher rank: no solution exists"
+            ),
+            RankAnalysis::FullRank => (),
+        }
+    }
+
+    configure(&mut config, &arguments);
+    // decisions are kept for event log too
+    let mut notes = Vec::new();
+    if config.method == Method::Auto {
+        let selection = config.select_method();
+        notes.push(format!("Method {} is chosen: {}", selection.method, selection.reason));
+        config.method = selection.method;
+    }
+    if let Err(err) = config.check_parameters() {
+        return report_error(err);
+    }
+    if let Err(err) = check_memory(&config, arguments.input.max_memory) {
+        return report_error(err);
+    }
+    crash::record_problem(&config);
+    if config.uses_thomas() {
+        notes.push(
+            "Matrix is tridiagonal, it is solved by Thomas algorithm. Pass --no-fast-path to iterate"
+                .to_owned(),
+        );
+    }
+    if config.uses_block_thomas() {
+        notes.push(
+            "Matrix is block tridiagonal, it is solved by block Thomas algorithm. Pass --no-fast-path to iterate"
+                .to_owned(),
+        );
+    }
+    if !config.row_order.is_empty() {
+        let order: Vec<String> = config.row_order.iter().map(|row| (row + 1).to_string()).collect();
+        let purpose = match config.is_diagonally_dominant() {
+            true => "make matrix diagonally dominant",
+            false => "put non-zero entries on diagonal",
+        };
+        notes.push(format!(
+            "Equations are swapped to {purpose}, they are solved in order {}",
+            order.join(", ")
+        ));
+    }
+    if let Some(warning) = config.dominance_warning() {
+        notes.push(format!("warning: {warning}"));
+    }
+    let spectral_radius = config.spectral_radius();
+    if let Some(radius) = &spectral_radius {
+        let iterations = match config.method {
+            Method::Hybrid => "Jacobi stage of hybrid".to_owned(),
+            method => method.to_string(),
+        };
+        // hybrid method leaves diverging Jacobi iterations by itself
+        let warning = match radius.diverges() && config.method != Method::Hybrid {
+            true => "warning: ",
+            false => "",
+        };
+        notes.push(format!("{warning}Spectral radius of {iterations} iteration matrix is {radius}"));
+        match radius.predicted_iterations(config.epsilon) {
+            Some(predicted) if predicted > config.max_iterations && config.method != Method::Hybrid => {
+                notes.push(format!(
+                    "warning: about {predicted} iterations are needed to reach epsilon, but max_iterations is {}",
+                    config.max_iterations
+                ))
+            }
+            _ => (),
+        }
+    }
+    for note in &notes {
+        eprintln!("{note}");
+    }
+    if let Some(direct) = arguments.compare {
+        return compare(&config, direct, &arguments);
+    }
+    if !config.uses_thomas() && !config.uses_block_thomas() {
+        suggest_cholesky(&config);
+    }
+    if let Some(directory) = &arguments.dump_decomposition {
+        if let Err(err) = decomposition::dump(&config, directory, arguments.dump_format) {
+            return report_error(err);
+        }
+    }
+    let mut split = arguments.split_components;
+    let mut reordering = arguments.reordering;
+    let blocked = config.schur_block.is_some() || !config.blocks.is_empty();
+    if blocked && (split || reordering.is_some()) {
+        eprintln!("Input is split into blocks, reordering and splitting are skipped");
+        split = false;
+        reordering = None;
+    }
+
+    let permutation = reordering.map(|reordering| {
+        let permutation = reordering.permutation(&config);
+        report_reordering(&config, reordering, &permutation);
+        (reordering, permutation)
+    });
+
+    let equilibration = match arguments.equilibrate {
+        true if !config.fixed.is_empty() || !config.bounds.is_empty() => {
+            eprintln!("Input has fixed or bounded unknowns, equilibration is skipped");
+            None
+        }
+        true => Some(Equilibration::new(&config.input_matrix)).filter(|equilibration| {
+            let needed = !equilibration.is_identity();
+            if !needed {
+                eprintln!("Matrix is already equilibrated");
+            }
+            needed
+        }),
+        false => None,
+    };
+    let scaled = equilibration.as_ref().map(|equilibration| {
+        let scaled = equilibration.apply(&config);
+        eprintln!(
+            "Rows and columns are scaled by powers of 10: entries spanned {} orders of magnitude, now {}",
+            magnitude_spread(&config.input_matrix),
+            magnitude_spread(&scaled.input_matrix)
+        );
+        scaled
+    });
+    let system = scaled.as_ref().unwrap_or(&config);
+
+    let mut changes = Vec::new();
+    if let Some((reordering, permutation)) = &permutation {
+        if !permutation.is_identity() {
+            changes.push(format!(
+                "unknowns are renumbered by {reordering} ordering, solution is reported in original order"
+            ));
+        }
+    }
+    if equilibration.is_some() {
+        changes.push("rows and columns are scaled, solution is reported unscaled".to_owned());
+    }
+    if split {
+        changes.push("independent subsystems, if there are such, are solved separately".to_owned());
+    }
+    if config.method == Method::Hybrid {
+        changes.push(
+            "iterations may switch from Jacobi to Gauss-Seidel and to direct elimination".to_owned(),
+        );
+    }
+    if !confirm::confirm_changes(&changes, arguments.yes) {
+        return report_error("Solve was not confirmed");
+    }
+    let permutation = permutation.map(|(_, permutation)| permutation);
+
+    let mut snapshots = arguments
+        .snapshot_at
+        .clone()
+        .map(|at| Snapshots::new(at, arguments.snapshot_dir.clone().unwrap_or_else(|| ".".into())));
+    if snapshots.is_some() && (split || config.schur_block.is_some()) {
+        eprintln!("Snapshots are taken only when system is solved as a whole by iterations");
+        snapshots = None;
+    }
+
+    let mut log = match arguments.record.as_deref() {
+        Some(path) => match EventLog::create(path, &config, &notes) {
+            Ok(log) => Some(log),
+            Err(err) => return report_error(format!("Can't write {}: {err}", path.display())),
         },
+        None => None,
+    };
+
+    let mut snapshot_error = None;
+    let mut log_error = None;
+    let mut trace = SolveTrace::default();
+    let status = status::watch();
+    let mut on_iteration = |progress: &Progress, permutation: Option<&SymmetricPermutation>| {
+        trace.record(&config, progress);
+        status.update(progress.iteration, progress.delta);
+        crash::record_progress(progress);
+
+        let wanted = snapshots
+            .as_ref()
+            .is_some_and(|snapshots| snapshots.is_wanted(progress.iteration));
+        if !wanted && log.is_none() {
+            return ControlFlow::Continue(());
+        }
+        let iterate = match permutation {
+            Some(permutation) => permutation.restore(progress.iterate),
+            None => progress.iterate.clone(),
+        };
+        let iterate = match &equilibration {
+            Some(equilibration) => equilibration.restore(&iterate),
+            None => iterate,
+        };
+        if let Some(log) = &mut log {
+            if let Err(err) = log.iteration(progress, &iterate) {
+                log_error = Some(err);
+                return ControlFlow::Break(());
+            }
+        }
+        let Some(snapshots) = snapshots.as_mut().filter(|_| wanted) else {
+            return ControlFlow::Continue(());
+        };
+        match snapshots.take(progress.iteration, progress.delta, &iterate) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(err) => {
+                snapshot_error = Some(err);
+                ControlFlow::Break(())
+            }
+        }
+    };
+
+    let mut last = LastIteration::default();
+    let mut outcome = match &permutation {
+        Some(permutation) if !permutation.is_identity() => solve_prepared(
+            &permutation.apply(system),
+            split,
+            &mut last,
+            |progress| on_iteration(progress, Some(permutation)),
+        )
+        .map(|solution| permutation.restore(&solution)),
+        _ => solve_prepared(system, split, &mut last, |progress| {
+            on_iteration(progress, None)
+        }),
+    };
+    if let (Some(equilibration), Ok(solution)) = (&equilibration, &mut outcome) {
+        *solution = equilibration.restore(solution);
+    }
+
+    if let Some(err) = snapshot_error {
+        return report_error(err);
+    }
+    if let Some(err) = log_error {
+        return report_error(format!("Can't write event log: {err}"));
+    }
+    if let Some(snapshots) = &snapshots {
+        let missed: Vec<String> = snapshots.missed().iter().map(ToString::to_string).collect();
+        if !missed.is_empty() {
+            eprintln!(
+                "Solve finished before iterations {}, no snapshots for them",
+                missed.join(", ")
+            );
+        }
+    }
+    let refinement = match &mut outcome {
+        Ok(solution) if config.refinement_steps > 0 => {
+            let refinement = config.refine(solution, config.refinement_steps);
+            if refinement.is_none() {
+                eprintln!("Refinement is skipped: it needs regular matrix of at most {DIRECT_MAX_SIZE} unknowns without fixed or bounded ones");
+            }
+            refinement
+        }
+        _ => None,
+    };
+    let jitter = match (&outcome, arguments.jitter) {
+        (Ok(solution), Some(relative)) => {
+            let seed = arguments.seed.unwrap_or_else(jitter::random_seed);
+            let perturbed = jitter::perturb(&config, relative, seed);
+            let outcome = solve_prepared(&perturbed, split, &mut LastIteration::default(), |_| {
+                ControlFlow::Continue(())
+            });
+            match outcome {
+                Ok(mut perturbed_solution) => {
+                    // both solutions are refined alike, so that refinement is not taken for response
+                    if refinement.is_some() {
+                        perturbed.refine(&mut perturbed_solution, config.refinement_steps);
+                    }
+                    Some(Jitter::measure(&config, &perturbed, relative, seed, solution, &perturbed_solution))
+                }
+                Err(err) => {
+                    eprintln!("Perturbed system with seed {seed} was not solved: {err}");
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+    let mut exit_code = match &outcome {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            if err.is_internal() {
+                crash::report_internal_error(err);
+            }
+            ExitCode::from(UNSUCCESSFUL)
+        }
+    };
+
+    if let (Some(expected), Ok(solution)) = (&arguments.assert_matches, &outcome) {
+        let tolerance = arguments.tolerance.unwrap_or(config.epsilon);
+        match diff::check_matches(solution.as_slice(), expected, tolerance) {
+            Ok(report) if report.is_match() => (),
+            Ok(report) => {
+                eprint!("{report}");
+                exit_code = ExitCode::from(UNSUCCESSFUL);
+            }
+            Err(err) => return report_error(err),
+        }
+    }
+
+    let solved = outcome.is_ok();
+    let advice = outcome.as_ref().err().and_then(failure_advice);
+    let mut report = Report::from_solve(&config, outcome, last, &trace)
+        .with_number_format(arguments.number_format)
+        .with_schema(arguments.schema)
+        .with_width(terminal::output_width(arguments.width, arguments.output.as_deref()))
+        .with_spectral_radius(spectral_radius)
+        .with_refinement(refinement)
+        .with_jitter(jitter);
+    if arguments.inverse && solved {
+        match config.inverse() {
+            Ok(inverse) => report = report.with_inverse(inverse),
+            Err(err) => return report_error(format!("Can't invert matrix: {err}")),
+        }
+    }
+    if arguments.determinant {
+        report = report.with_determinant(config.determinant());
+    }
+    if report.is_singular_solution() {
+        eprintln!("warning: matrix is singular, solution is not unique or satisfies system only up to rounding");
+        exit_code = ExitCode::from(UNSUCCESSFUL);
+    }
+    if let Some(log) = &mut log {
+        if let Err(err) = log.finish(&report) {
+            return report_error(format!("Can't write event log: {err}"));
+        }
+    }
+    let content = match &arguments.output_template {
+        Some(path) => {
+            let rendered = fs::read_to_string(path)
+                .map_err(|err| format!("Can't read output template {}: {err}", path.display()))
+                .and_then(|template| report.render_template(&template));
+            match rendered {
+                Ok(content) => content,
+                Err(err) => return report_error(err),
+            }
+        }
+        None => report.render(arguments.format),
+    };
+    // plain failure is diagnostics rather than result
+    if report.is_failed() && arguments.format == ReportFormat::Plain && arguments.output_template.is_none() {
+        eprint!("{content}");
+        if let Some(advice) = advice {
+            eprintln!("{advice}");
+        }
+        explain_failure(&config);
+        return exit_code;
+    }
+
+    match write_output(arguments.output.as_deref(), None, content) {
+        Ok(()) => exit_code,
+        Err(err) => report_error(err),
+    }
+}
+
+/// With `split`, independent subsystems are solved one by one, or in parallel
+/// when several threads are allowed. Iteration count and delta reported
+/// are the largest ones among subsystems. `on_iteration` is only called
+/// when system is solved as a whole
+fn solve_prepared(
+    equation: &Equation,
+    split: bool,
+    last: &mut LastIteration,
+    mut on_iteration: impl FnMut(&Progress) -> ControlFlow<()>,
+) -> Result<DVector<Decimal>, ESolveError> {
+    fn record(last: &mut LastIteration) -> impl FnMut(&Progress) -> ControlFlow<()> + '_ {
+        move |progress| {
+            last.record(progress);
+            ControlFlow::Continue(())
+        }
+    }
+
+    let mut solve_whole = |last: &mut LastIteration| {
+        equation.solve_with_progress(|progress| {
+            last.record(progress);
+            on_iteration(progress)
+        })
+    };
+
+    let components = if split {
+        Split::new(&equation.input_matrix)
+    } else {
+        return solve_whole(last);
+    };
+    if components.components.len() < 2 {
+        eprintln!("System is irreducible, solving as a whole");
+        return solve_whole(last);
+    }
+
+    let subsystems = components.subsystems(equation);
+    eprintln!(
+        "System splits into {} independent subsystems of sizes {}",
+        subsystems.len(),
+        components
+            .components
+            .iter()
+            .map(|component| component.len().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let solve_one = |subsystem: &Equation| {
+        let mut last = LastIteration::default();
+        subsystem
+            .solve_with_progress(record(&mut last))
+            .map(|solution| (solution, last))
+    };
+    let threads = equation.parallelism.threads.min(subsystems.len());
+    let outcomes: Vec<_> = if threads > 1 {
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|worker| {
+                    let subsystems = &subsystems;
+                    scope.spawn(move || {
+                        (worker..subsystems.len())
+                            .step_by(threads)
+                            .map(|index| (index, solve_one(&subsystems[index])))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            let mut outcomes: Vec<_> = handles
+                .into_iter()
+                .flat_map(|handle| handle.join().expect("subsystem solve does not panic"))
+                .collect();
+            outcomes.sort_by_key(|(index, _)| *index);
+            outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+        })
+    } else {
+        subsystems.iter().map(solve_one).collect()
+    };
+
+    let mut solutions = Vec::with_capacity(outcomes.len());
+    for outcome in outcomes {
+        let (solution, subsystem_last) = outcome?;
+        last.iteration = last.iteration.max(subsystem_last.iteration);
+        last.delta = last.delta.max(subsystem_last.delta);
+        solutions.push(solution);
+    }
+    Ok(components.combine(&solutions))
+}
+
+/// Applies solve options of command line over settings of input
+fn configure(config: &mut Equation, arguments: &SolveArguments) {
+    config.parallelism = arguments.parallelism.clone();
+    if let Some(method) = arguments.method {
+        config.method = method;
+    }
+    if let Some(relaxation) = arguments.relaxation {
+        config.relaxation = relaxation;
+    }
+    if let Some(tau) = arguments.tau {
+        config.tau = Some(tau);
+    }
+    if let Some(acceleration) = arguments.acceleration {
+        config.acceleration = acceleration;
+    }
+    if let Some(preconditioning) = arguments.preconditioning {
+        config.preconditioning = preconditioning;
+    }
+    if let Some(restart) = arguments.restart {
+        config.restart = restart;
+    }
+    if let Some(rounding) = arguments.rounding {
+        config.rounding = rounding;
+    }
+    if let Some(mode) = arguments.tolerance_mode {
+        config.tolerance_mode = mode;
+    }
+    if let Some(norm) = arguments.norm {
+        config.norm = Some(norm);
+    }
+    if let Some(guess) = &arguments.initial_guess {
+        config.initial_guess = guess.clone();
+    }
+    if let Some(steps) = arguments.refinement_steps {
+        config.refinement_steps = steps;
+    }
+    config.fast_path = !arguments.no_fast_path;
+    if let Some((last, looser)) = arguments.epsilon_schedule.split_last() {
+        config.epsilon = *last;
+        config.epsilon_schedule = looser.to_vec();
+    }
+}
+
+fn study(arguments: &SolveArguments, range: &ParamRange) -> ExitCode {
+    let raw = match RawInput::read(&arguments.input) {
+        Ok(raw) => raw,
+        Err(err) if err.is_no_input_provided() => return report_usage(),
+        Err(err) => return report_error(err),
+    };
+    if !raw.has_placeholder(&range.name) {
+        return report_error(format!(
+            "Input has no ${{{}}} placeholder to vary",
+            range.name
+        ));
+    }
+
+    let points = study::run(&raw, &arguments.input.params, range, arguments.input.max_memory, |config| {
+        configure(config, arguments)
+    });
+
+    let (header, rows) = study::table(range, &points, |value| arguments.number_format.format(value));
+    let width = terminal::output_width(arguments.width, arguments.output.as_deref());
+    let content = render_table(&header, &rows, arguments.format, width);
+    if let Err(err) = write_output(arguments.output.as_deref(), None, content) {
+        return report_error(err);
+    }
+    if points.iter().all(|point| point.outcome.is_ok()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(UNSUCCESSFUL)
+    }
+}
+
+fn compare(equation: &Equation, direct: Method, arguments: &SolveArguments) -> ExitCode {
+    if equation.method.is_direct() {
+        return report_error(format!(
+            "--compare needs iterative method to compare {direct} with, but method is {}",
+            equation.method
+        ));
+    }
+    let comparison = comparison::compare(equation, direct);
+    for run in [&comparison.direct, &comparison.iterative] {
+        if let Err(err) = &run.outcome {
+            eprintln!("Method {} failed: {err}", run.method);
+        }
+    }
+    let (header, rows) = comparison.table(|value| arguments.number_format.format(value));
+    let width = terminal::output_width(arguments.width, arguments.output.as_deref());
+    let content = render_table(&header, &rows, arguments.format, width);
+    if let Err(err) = write_output(arguments.output.as_deref(), None, content) {
+        return report_error(err);
+    }
+    if comparison.is_solved() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(UNSUCCESSFUL)
+    }
+}
+
+fn suggest_cholesky(equation: &Equation) {
+    let suggested = matches!(
+        equation.method,
+        Method::GaussSeidel | Method::Gauss | Method::GaussJordan | Method::Lu
+    );
+    if suggested
+        && equation.bounds.is_empty()
+        && equation.input_matrix.nrows() <= CHOLESKY_SUGGESTION_MAX_SIZE
+        && equation.is_symmetric_positive_definite()
+    {
+        eprintln!("Matrix is symmetric positive definite, --method cholesky solves it directly");
+    }
+}
+
+fn report_reordering(equation: &Equation, reordering: Reordering, permutation: &SymmetricPermutation) {
+    match reordering {
+        Reordering::Dominance => {
+            let identity = SymmetricPermutation::identity(equation.input_matrix.nrows());
+            eprintln!("Dominance before: {}", DominanceProfile::new(equation, &identity));
+            eprintln!("Dominance after:  {}", DominanceProfile::new(equation, permutation));
+        }
+        Reordering::CuthillMcKee | Reordering::ReverseCuthillMcKee => eprintln!(
+            "Bandwidth before: {}, after: {}",
+            preprocess::bandwidth(&equation.input_matrix),
+            preprocess::bandwidth(&permutation.apply(equation).input_matrix)
+        ),
+    }
+}
+
+/// What to change when iterations failed to converge
+fn failure_advice(err: &ESolveError) -> Option<&'static str> {
+    match err {
+        ESolveError::MaxIterationsExceeded { .. } => Some(
+            "Delta was still shrinking: raise max_iterations or epsilon, or speed iterations up by --chebyshev, --omega above 1 or a Krylov method like --method gmres",
+        ),
+        ESolveError::Diverged { .. } => Some(
+            "Iterations of this method do not converge for this matrix: try --reorder dominance, --method auto or a direct method like --method lu",
+        ),
+        ESolveError::Stagnated { .. } => Some(
+            "Delta no longer shrinks, epsilon may be below rounding noise of the system: try larger epsilon, --refine or a direct method like --method lu",
+        ),
+        ESolveError::NumericOverflow { .. } => Some(
+            "Iterations grew past largest Decimal: scale equations down by --equilibrate, reorder them by --reorder dominance, or try a direct method like --method lu",
+        ),
+        _ => None,
+    }
+}
+
+/// Points at equations which make the system unsolvable, if there are such
+fn explain_failure(equation: &Equation) {
+    let conflicts = analysis::find_proportional_rows(equation);
+    if conflicts.is_empty() {
+        return;
+    }
+
+    eprintln!("Possible reasons:");
+    for conflict in conflicts {
+        eprintln!("  {conflict}");
+    }
+}
+
+fn diff(arguments: DiffArguments) -> ExitCode {
+    let diff = match diff::compare(&arguments.first, &arguments.second) {
+        Ok(diff) => diff,
+        Err(err) => return report_error(err),
+    };
+
+    match arguments.format {
+        OutputFormat::Plain => print!("{diff}"),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&diff).expect("diff consists of plain data")
+        ),
+    }
+
+    if diff.is_identical() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(UNSUCCESSFUL)
+    }
+}
+
+fn lint(arguments: LintArguments) -> ExitCode {
+    let parsed = match try_non_interactive(&arguments.input) {
+        Ok(parsed) => parsed,
+        Err(err) if err.is_no_input_provided() => return report_usage(),
+        Err(err) => return report_error(err),
+    };
+
+    let mut issues = lint::lint_input(&parsed);
+    match build_equation(parsed, arguments.input.max_memory) {
+        Ok(equation) => issues.extend(lint::lint_equation(&equation)),
+        Err(err) => return report_error(err),
+    }
+
+    if issues.is_empty() {
+        println!("No issues found");
+        return ExitCode::SUCCESS;
+    }
+
+    for issue in &issues {
+        println!("warning: {issue}");
+    }
+    ExitCode::from(UNSUCCESSFUL)
+}
+
+fn hash(arguments: HashArguments) -> ExitCode {
+    let equation = match build_configuration(&arguments.input) {
+        Ok(equation) => equation,
+        Err(err) if err.is_no_input_provided() => return report_usage(),
+        Err(err) => return report_error(err),
+    };
+
+    if arguments.canonical {
+        print!("{}", fingerprint::canonical_form(&equation, arguments.options));
+    } else {
+        println!("{}", fingerprint::fingerprint(&equation, arguments.options));
+    }
+    ExitCode::SUCCESS
+}
+
+fn replay(arguments: ReplayArguments) -> ExitCode {
+    let replay = match Replay::read(&arguments.log) {
+        Ok(replay) => replay,
+        Err(err) => return report_error(err),
+    };
+
+    let width = terminal::output_width(arguments.width, arguments.output.as_deref());
+    let content = if arguments.history {
+        let header = ["Iteration", "Phase", "Stage", "Delta"].map(ToOwned::to_owned);
+        let rows: Vec<Vec<String>> = replay
+            .iterations
+            .iter()
+            .map(|logged| {
+                vec![
+                    logged.iteration.to_string(),
+                    (logged.phase + 1).to_string(),
+                    logged.stage.to_string(),
+                    arguments.number_format.format(logged.delta),
+                ]
+            })
+            .collect();
+        render_table(&header, &rows, arguments.format, width)
+    } else {
+        for note in &replay.notes {
+            eprintln!("{note}");
+        }
+        replay
+            .report()
+            .with_number_format(arguments.number_format)
+            .with_width(width)
+            .render(arguments.format)
+    };
+
+    let exit_code = match replay.outcome {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::from(UNSUCCESSFUL),
+    };
+    match write_output(arguments.output.as_deref(), None, content) {
+        Ok(()) => exit_code,
+        Err(err) => report_error(err),
+    }
+}
+
+fn batch(arguments: BatchArguments) -> ExitCode {
+    let inputs = match batch::collect_inputs(&arguments.inputs) {
+        Ok(inputs) => inputs,
+        Err(err) => return report_error(err),
+    };
+
+    let resume = match arguments.resume_state.as_deref().map(ResumeState::open) {
+        Some(Ok(resume)) => Some(resume),
+        Some(Err(err)) => return report_error(err),
+        None => None,
+    };
+
+    let events = arguments.events.as_deref();
+    let output_dir = arguments.output_dir.as_deref();
+    let summary = match arguments.workers.is_empty() {
+        true => batch::run(inputs, &arguments.settings, events, output_dir, resume),
+        false => batch::run_distributed(inputs, &arguments.workers, events, output_dir, resume),
+    };
+    match summary {
+        Ok(summary) if summary.solved == summary.total => {
+            println!("{summary}");
+            ExitCode::SUCCESS
+        }
+        Ok(summary) => {
+            println!("{summary}");
+            ExitCode::from(UNSUCCESSFUL)
+        }
+        Err(err) => report_error(err),
+    }
+}
+
+fn redact(arguments: RedactArguments) -> ExitCode {
+    // grid is turned into blocks by parsing, so it is taken from input
+    let parsed = try_non_interactive(&arguments.input)
+        .and_then(|parsed| Ok((parsed.grid, build_equation(parsed, arguments.input.max_memory)?)));
+    let (grid, equation) = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) if err.is_no_input_provided() => return report_usage(),
+        Err(err) => return report_error(err),
+    };
+
+    let redacted = match redact::redact(&equation, grid, arguments.seed) {
+        Ok(redacted) => redacted,
+        Err(err) => return report_error(err),
+    };
+    let content = serde_json::to_string_pretty(&redacted)
+        .expect("redacted input consists of plain strings and numbers")
+        + "\n";
+
+    match write_output(arguments.output.as_deref(), arguments.compress, content) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(err),
+    }
+}
+
+fn worker(arguments: WorkerArguments) -> ExitCode {
+    let served = match &arguments.listen {
+        Some(address) => worker::listen(address, arguments.settings),
+        None => worker::serve_stdio(&arguments.settings),
+    };
+    match served {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(format!("Worker stopped: {err}")),
+    }
+}
+
+fn daemon(arguments: DaemonArguments) -> ExitCode {
+    let socket = arguments.socket.expect("socket is required by argument parser");
+    match daemon::run(Path::new(&socket), arguments.settings) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(format!("Daemon stopped: {err}")),
+    }
+}
+
+fn serve(arguments: ServeArguments) -> ExitCode {
+    match server::run(
+        &arguments.listen,
+        arguments.concurrency,
+        arguments.settings,
+        arguments.policy,
+    ) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => report_error(format!("Server stopped: {err}")),
+    }
+}
+
+/// Writes to file or stdout, compressing content when asked to
+fn write_output(
+    filepath: Option<&str>,
+    compress: Option<Compression>,
+    content: String,
+) -> Result<(), String> {
+    let content = match compress {
+        Some(compression) => compression
+            .compress(content.as_bytes())
+            .map_err(|err| err.to_string())?,
+        None => content.into_bytes(),
+    };
+
+    match filepath {
+        Some(filepath) => {
+            fs::write(filepath, content).map_err(|err| format!("Can't write {filepath}: {err}"))
+        }
+        None => io::stdout()
+            .write_all(&content)
+            .map_err(|err| format!("Can't write output: {err}")),
     }
 }
@@ -0,0 +1,166 @@
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use serde_json as json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::compression;
+use crate::csv;
+use crate::input::{EquesionInput, GuessInput, NonInteractiveError};
+
+/// Input which keeps matrix and right hand side in separate files,
+/// so the same (possibly huge) matrix can be shared by several problems.
+///
+/// ```json
+/// {
+///   "input_matrix_file": "matrix.json.gz",
+///   "expression_rhs_file": "rhs.json",
+///   "max_iterations": 64,
+///   "epsilon": "0.001"
+/// }
+/// ```
+///
+/// Matrix file holds array of rows, right hand side file holds array of values,
+/// both in the same string notation as inline input. Parts starting with
+/// number or `#` comment are read as CSV (see [`csv`]): row per line, right
+/// hand side value per line or all on one. Parts named `*.f64` (like `matrix.f64.zst`) are binary:
+/// little endian 64 bit floats one after another, matrix row by row, as numpy
+/// `array.astype("<f8").tofile(path)` writes them. Their values are taken in
+/// the shortest notation reading back to the same float. Part files may be
+/// compressed. Relative paths are resolved against manifest location
+#[derive(Deserialize, Debug)]
+pub(crate) struct InputManifest {
+    pub input_matrix_file: String,
+    pub expression_rhs_file: String,
+    pub max_iterations: usize,
+    pub epsilon: String,
+    #[serde(default)]
+    pub schur_block: Option<usize>,
+    #[serde(default)]
+    pub fixed: BTreeMap<String, String>,
+    #[serde(default)]
+    pub lower: BTreeMap<String, String>,
+    #[serde(default)]
+    pub upper: BTreeMap<String, String>,
+    #[serde(default)]
+    pub blocks: Vec<usize>,
+    #[serde(default)]
+    pub grid: Option<[usize; 2]>,
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub refinement_steps: Option<usize>,
+    #[serde(default)]
+    pub tolerance_mode: Option<String>,
+    #[serde(default)]
+    pub norm: Option<String>,
+    #[serde(default)]
+    pub initial_guess: Option<GuessInput>,
+}
+
+/// Cheap look at document to find out whether it is a manifest
+#[derive(Deserialize)]
+struct ManifestProbe {
+    input_matrix_file: Option<IgnoredAny>,
+}
+
+pub(crate) fn is_manifest(content: &[u8]) -> bool {
+    json::from_slice::<ManifestProbe>(content)
+        .map(|probe| probe.input_matrix_file.is_some())
+        .unwrap_or(false)
+}
+
+impl InputManifest {
+    /// `base` is directory of manifest itself
+    pub fn resolve(self, base: &Path) -> Result<EquesionInput, NonInteractiveError> {
+        Ok(EquesionInput {
+            input_matrix: read_part(base, &self.input_matrix_file)?,
+            expression_rhs: read_part(base, &self.expression_rhs_file)?,
+            max_iterations: self.max_iterations,
+            epsilon: self.epsilon,
+            schur_block: self.schur_block,
+            fixed: self.fixed,
+            lower: self.lower,
+            upper: self.upper,
+            blocks: self.blocks,
+            grid: self.grid,
+            method: self.method,
+            refinement_steps: self.refinement_steps,
+            tolerance_mode: self.tolerance_mode,
+            norm: self.norm,
+            initial_guess: self.initial_guess,
+        })
+    }
+}
+
+/// Content of part file, in any of formats it may take
+trait Part: Sized + for<'de> Deserialize<'de> {
+    fn from_csv(content: &[u8]) -> Result<Self, csv::CsvError>;
+    fn from_values(values: Vec<String>) -> Result<Self, String>;
+}
+
+impl Part for Vec<Vec<String>> {
+    fn from_csv(content: &[u8]) -> Result<Self, csv::CsvError> {
+        csv::matrix(content)
+    }
+
+    /// Binary matrix has no shape of its own, it is square
+    fn from_values(values: Vec<String>) -> Result<Self, String> {
+        let size = values.len().isqrt();
+        if size * size != values.len() {
+            return Err(format!("{} values do not make square matrix", values.len()));
+        }
+        Ok(values.chunks(size.max(1)).map(<[String]>::to_vec).collect())
+    }
+}
+
+impl Part for Vec<String> {
+    fn from_csv(content: &[u8]) -> Result<Self, csv::CsvError> {
+        csv::values(content)
+    }
+
+    fn from_values(values: Vec<String>) -> Result<Self, String> {
+        Ok(values)
+    }
+}
+
+/// Values of binary part, see [`InputManifest`]
+fn binary_values(content: &[u8]) -> Result<Vec<String>, String> {
+    if !content.len().is_multiple_of(8) {
+        return Err(format!("Size of {} bytes is not whole number of 8 byte floats", content.len()));
+    }
+    (content.chunks_exact(8).enumerate())
+        .map(|(index, bytes)| {
+            let value = f64::from_le_bytes(bytes.try_into().expect("chunk is 8 bytes"));
+            match value.is_finite() {
+                true => Ok(value.to_string()),
+                false => Err(format!("Value {} is {value}, expected finite number", index + 1)),
+            }
+        })
+        .collect()
+}
+
+fn is_binary(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let name = name.trim_end_matches(".gz").trim_end_matches(".zst");
+    name.ends_with(".f64")
+}
+
+fn read_part<T: Part>(base: &Path, filepath: &str) -> Result<T, NonInteractiveError> {
+    let path: PathBuf = base.join(filepath);
+    let wrap = |message: String| NonInteractiveError::ManifestPartError {
+        part: path.display().to_string(),
+        message,
+    };
+
+    let content = fs::read(&path).map_err(|err| wrap(err.to_string()))?;
+    let content = compression::decompress_if_needed(content).map_err(|err| wrap(err.to_string()))?;
+    if is_binary(&path) {
+        return binary_values(&content).and_then(T::from_values).map_err(wrap);
+    }
+    if csv::is_csv(&content) {
+        return T::from_csv(&content).map_err(|err| wrap(format!("Invalid CSV! {err}")));
+    }
+    json::from_slice(&content).map_err(|err| wrap(err.to_string()))
+}
@@ -0,0 +1,143 @@
+use core::fmt;
+
+use crate::solver::{Equation, Method};
+
+/// Rough amount of memory solve is going to take, in bytes
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryEstimate {
+    /// Dense coefficient matrix
+    pub matrix: u64,
+    /// Coefficients are collected into flat buffer before matrix is built,
+    /// so for a moment they exist twice
+    pub staging: u64,
+    /// Values of input document as parsed strings, alive until matrix is built
+    pub document: u64,
+    /// Right hand side and iterate vectors
+    pub vectors: u64,
+    /// Factors, bases and copies of the system kept by method
+    pub workspace: u64,
+}
+
+/// Vectors alive during solve: right hand side and result
+const WORKING_VECTORS: u64 = 2;
+
+/// Bytes of one parsed value besides its `String`, most values are short
+const VALUE_LENGTH: u64 = 16;
+
+impl MemoryEstimate {
+    pub fn new(matrix_size: usize, element_size: usize) -> Self {
+        let size = matrix_size as u64;
+        let element_size = element_size as u64;
+        let matrix = size.saturating_mul(size).saturating_mul(element_size);
+        // matrix and right hand side
+        let values = size.saturating_mul(size.saturating_add(1));
+
+        MemoryEstimate {
+            matrix,
+            staging: matrix,
+            document: values.saturating_mul(std::mem::size_of::<String>() as u64 + VALUE_LENGTH),
+            vectors: WORKING_VECTORS.saturating_mul(size).saturating_mul(element_size),
+            workspace: 0,
+        }
+    }
+
+    /// Estimate of `equation` as configured, with workspace of its method
+    pub fn of(equation: &Equation, element_size: usize) -> Self {
+        let size = equation.input_matrix.nrows();
+        MemoryEstimate {
+            workspace: workspace(equation.method, size as u64, equation.restart as u64)
+                .saturating_mul(element_size as u64),
+            ..MemoryEstimate::new(size, element_size)
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.matrix
+            .saturating_add(self.staging)
+            .saturating_add(self.document)
+            .saturating_add(self.vectors)
+            .saturating_add(self.workspace)
+    }
+}
+
+/// Elements `method` keeps besides matrix and working vectors of size `n`
+fn workspace(method: Method, n: u64, restart: u64) -> u64 {
+    let square = n.saturating_mul(n);
+    match method {
+        Method::GaussSeidel | Method::Jacobi | Method::Hybrid | Method::Richardson | Method::Ssor => n,
+        // eliminated copy of matrix
+        Method::Gauss | Method::GaussJordan => square.saturating_add(n),
+        // both factors
+        Method::Lu | Method::Qr => square.saturating_mul(2).saturating_add(n),
+        Method::Cholesky => square.saturating_add(n),
+        Method::ConjugateGradient => n.saturating_mul(4),
+        Method::BiCgStab => n.saturating_mul(8),
+        // basis of restart cycle and its Hessenberg matrix
+        Method::Gmres => (restart.saturating_add(1))
+            .saturating_mul(n.saturating_add(restart))
+            .saturating_add(n.saturating_mul(2)),
+        // system is cloned for the method selection picks, LU needs most of them
+        Method::Auto => (square.saturating_add(n)).saturating_add(workspace(Method::Lu, n, restart)),
+    }
+}
+
+impl fmt::Display for MemoryEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (matrix {}, parsing buffer {}, input values {}, vectors {}, method workspace {})",
+            ByteSize(self.total()),
+            ByteSize(self.matrix),
+            ByteSize(self.staging),
+            ByteSize(self.document),
+            ByteSize(self.vectors),
+            ByteSize(self.workspace)
+        )
+    }
+}
+
+/// Displays amount of bytes with binary unit prefix
+#[derive(Debug, Clone, Copy)]
+pub struct ByteSize(pub u64);
+
+const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut value = self.0 as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            write!(f, "{} {}", self.0, UNITS[unit])
+        } else {
+            write!(f, "{:.1} {}", value, UNITS[unit])
+        }
+    }
+}
+
+/// Accepts plain amount of bytes or amount with `K`, `M`, `G`, `T` suffix
+/// (binary multiples, optional trailing `B` or `iB`)
+pub fn parse_byte_size(value: &str) -> Result<u64, String> {
+    let error = || "Expected size like 512M, 2G or amount of bytes".to_owned();
+    let upper = value.trim().to_ascii_uppercase();
+    let digits_end = upper
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(upper.len());
+    let (amount, unit) = upper.split_at(digits_end);
+
+    let amount: u64 = amount.parse().map_err(|_| error())?;
+    let shift = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => return Err(error()),
+    };
+
+    amount.checked_mul(1 << shift).ok_or_else(error)
+}
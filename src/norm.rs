@@ -0,0 +1,61 @@
+use core::fmt;
+use core::str::FromStr;
+
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Vector norms used to measure distances between approximations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Norm {
+    /// Sum of absolute values
+    L1,
+    /// Euclidean
+    L2,
+    /// Max absolute value
+    LInf,
+}
+
+impl Norm {
+    pub const ALL: [Norm; 3] = [Norm::L1, Norm::L2, Norm::LInf];
+
+    /// Norm too large for Decimal is [`Decimal::MAX`]
+    pub fn of(&self, values: impl IntoIterator<Item = Decimal>) -> Decimal {
+        let values = values.into_iter().map(|value| value.abs());
+        match self {
+            Norm::L1 => values.fold(dec!(0), |acc, value| acc.saturating_add(value)),
+            Norm::L2 => values
+                .fold(dec!(0), |acc, value| acc.saturating_add(value.saturating_mul(value)))
+                .sqrt()
+                .expect("sum of squares is not negative"),
+            Norm::LInf => values.fold(dec!(0), Decimal::max),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Norm::L1 => "L1",
+            Norm::L2 => "L2",
+            Norm::LInf => "Linf",
+        }
+    }
+}
+
+impl FromStr for Norm {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "l1" => Ok(Norm::L1),
+            "l2" => Ok(Norm::L2),
+            "linf" => Ok(Norm::LInf),
+            _ => Err("Expected one of: l1, l2, linf".to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Norm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -0,0 +1,303 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::any::Any;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+
+/// Terms in one chunk of deterministic reduction. Chunk borders depend only on
+/// amount of terms, never on amount of threads
+const DETERMINISTIC_CHUNK: usize = 64;
+
+/// Shorter reductions are not worth spawning threads for
+const MIN_PARALLEL_TERMS: usize = 1024;
+
+/// Piece of reduction sent to worker. Borrows of reduction are erased from
+/// its type, see [`SolverPool::run`]
+type Task = Box<dyn FnOnce() + Send + 'static>;
+
+/// Worker threads shared by every solve pool is given to.
+///
+/// Host application creates pool once and hands it to equations with
+/// [`SolverPool::parallelism`], so concurrent solves together never run more
+/// than `threads` workers. Workers are started on first parallel reduction
+/// and live as long as pool does, so sweeps do not pay for spawning threads.
+/// When pool is busy, reduction takes whatever workers are left, down to
+/// doing the work on calling thread alone. Cloning pool shares the same workers
+#[derive(Debug, Clone)]
+pub struct SolverPool {
+    threads: usize,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    idle: Mutex<usize>,
+    /// Queue of started workers, `None` until they are needed or when none
+    /// of them could be started
+    tasks: Mutex<Option<mpsc::Sender<Task>>>,
+    started: OnceLock<()>,
+}
+
+impl SolverPool {
+    pub fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        SolverPool {
+            threads,
+            shared: Arc::new(Shared {
+                idle: Mutex::new(threads),
+                tasks: Mutex::new(None),
+                started: OnceLock::new(),
+            }),
+        }
+    }
+
+    /// Pool of reductions not given pool of their own, as many workers as
+    /// there are cores
+    pub fn shared() -> &'static SolverPool {
+        static SHARED: OnceLock<SolverPool> = OnceLock::new();
+        SHARED.get_or_init(|| SolverPool::new(thread::available_parallelism().map_or(1, |threads| threads.get())))
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Settings for [`crate::solver::Equation::parallelism`] which take workers from this pool
+    pub fn parallelism(&self, deterministic: bool) -> Parallelism {
+        Parallelism {
+            threads: self.threads,
+            deterministic,
+            pool: Some(self.clone()),
+        }
+    }
+
+    /// Takes up to `wanted` idle workers, they are given back when lease is dropped
+    fn lease(&self, wanted: usize) -> Lease<'_> {
+        let mut idle = self.shared.idle.lock().expect("pool lock is not poisoned");
+        let workers = wanted.min(*idle);
+        *idle -= workers;
+        Lease {
+            pool: self,
+            workers,
+        }
+    }
+
+    /// Starts workers, once per pool. Workers stop when every clone of pool is dropped
+    fn start(&self) {
+        self.shared.started.get_or_init(|| {
+            let (sender, receiver) = mpsc::channel::<Task>();
+            let receiver = Arc::new(Mutex::new(receiver));
+            let mut started = 0;
+            for worker in 0..self.threads {
+                let receiver = Arc::clone(&receiver);
+                let spawned = thread::Builder::new()
+                    .name(format!("solver-worker-{worker}"))
+                    .spawn(move || loop {
+                        let task = receiver.lock().expect("pool lock is not poisoned").recv();
+                        match task {
+                            // panic reaches calling thread through dropped result sender
+                            Ok(task) => drop(panic::catch_unwind(AssertUnwindSafe(task))),
+                            Err(_) => break,
+                        }
+                    });
+                if spawned.is_ok() {
+                    started += 1;
+                }
+            }
+            if started > 0 {
+                *self.shared.tasks.lock().expect("pool lock is not poisoned") = Some(sender);
+            }
+        });
+    }
+
+    /// Runs `piece(0)`, ..., `piece(pieces - 1)` on workers and waits for all
+    /// of them. Results come in order pieces finish, paired with their index.
+    /// Pieces are run on calling thread when no worker could be started
+    fn run<R: Send>(&self, pieces: usize, piece: impl Fn(usize) -> R + Sync) -> Vec<(usize, R)> {
+        self.start();
+        let tasks = self.shared.tasks.lock().expect("pool lock is not poisoned").clone();
+        let Some(tasks) = tasks else {
+            return (0..pieces).map(|index| (index, piece(index))).collect();
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let waiter = Waiter {
+            sender: Some(sender),
+            receiver,
+        };
+        let piece = &piece;
+        for index in 0..pieces {
+            let sender = waiter.sender.clone().expect("sender is dropped only by waiting");
+            let task: Box<dyn FnOnce() + Send + '_> = Box::new(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| piece(index)));
+                let _ = sender.send((index, result));
+            });
+            // SAFETY: task borrows `piece` only, and `waiter` does not let
+            // this function return or unwind before every task is finished:
+            // it waits until the last sender is dropped, which happens when
+            // task returns or unwinds. Task that is never sent is run right here
+            let task = unsafe { mem::transmute::<Box<dyn FnOnce() + Send + '_>, Task>(task) };
+            if let Err(mpsc::SendError(task)) = tasks.send(task) {
+                task();
+            }
+        }
+
+        let mut results = Vec::with_capacity(pieces);
+        for (index, result) in waiter.wait() {
+            match result {
+                Ok(result) => results.push((index, result)),
+                // every task is finished by now, so nothing borrows `piece` any more
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+        assert_eq!(results.len(), pieces, "every task sends its result");
+        results
+    }
+}
+
+/// Result of piece of [`SolverPool::run`], or payload of its panic
+type Outcome<R> = (usize, Result<R, Box<dyn Any + Send>>);
+
+/// Receives results of tasks sent to workers. Dropped, it waits until every
+/// task is finished, so tasks never outlive borrows they hold even when
+/// calling thread unwinds
+struct Waiter<R> {
+    sender: Option<mpsc::Sender<Outcome<R>>>,
+    receiver: mpsc::Receiver<Outcome<R>>,
+}
+
+impl<R> Waiter<R> {
+    fn wait(mut self) -> Vec<Outcome<R>> {
+        self.sender = None;
+        self.receiver.iter().collect()
+    }
+}
+
+impl<R> Drop for Waiter<R> {
+    fn drop(&mut self) {
+        self.sender = None;
+        for _ in self.receiver.iter() {}
+    }
+}
+
+struct Lease<'a> {
+    pool: &'a SolverPool,
+    workers: usize,
+}
+
+impl Drop for Lease<'_> {
+    fn drop(&mut self) {
+        *self.pool.shared.idle.lock().expect("pool lock is not poisoned") += self.workers;
+    }
+}
+
+/// How reductions inside solver are spread across threads.
+///
+/// `Decimal` rounds every sum to 28 significant digits, so sum of the same
+/// terms may differ in last digits depending on order of additions.
+/// In deterministic mode terms are always grouped into fixed chunks and chunk
+/// sums are combined in chunk order, which gives bit-identical results across
+/// runs and thread counts (single threaded run included)
+#[derive(Debug, Clone)]
+pub struct Parallelism {
+    /// Most workers single reduction may use
+    pub threads: usize,
+    pub deterministic: bool,
+    /// Workers reductions run on. Without it they are taken from
+    /// [`SolverPool::shared`]
+    pub pool: Option<SolverPool>,
+}
+
+impl Default for Parallelism {
+    fn default() -> Self {
+        Parallelism {
+            threads: 1,
+            deterministic: false,
+            pool: None,
+        }
+    }
+}
+
+impl Parallelism {
+    /// Sums `term(0) + term(1) + ... + term(len - 1)`, `None` when a term
+    /// or sum of them does not fit into Decimal. Panic of term reaches caller
+    /// once every worker is done with reduction, and workers stay usable:
+    ///
+    /// ```
+    /// use guess_zeidel_method::parallel::SolverPool;
+    /// use rust_decimal::Decimal;
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// let parallelism = SolverPool::new(4).parallelism(false);
+    /// let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+    ///     parallelism.sum(4096, |index| if index == 4000 { panic!("bad term") } else { Some(Decimal::ONE) })
+    /// }));
+    /// assert_eq!(panicked.unwrap_err().downcast_ref::<&str>(), Some(&"bad term"));
+    /// assert_eq!(parallelism.sum(4096, |_| Some(Decimal::ONE)), Some(Decimal::from(4096)));
+    /// ```
+    pub fn sum(&self, len: usize, term: impl Fn(usize) -> Option<Decimal> + Sync) -> Option<Decimal> {
+        let wanted = if len >= MIN_PARALLEL_TERMS { self.threads } else { 1 };
+        let pool = self.pool.as_ref().unwrap_or_else(|| SolverPool::shared());
+        let lease = (wanted > 1).then(|| pool.lease(wanted));
+        let threads = lease.as_ref().map_or(1, |lease| lease.workers);
+
+        match (self.deterministic, threads > 1) {
+            (true, false) => (0..len)
+                .step_by(DETERMINISTIC_CHUNK)
+                .map(|start| sum_range(&term, start, len.min(start + DETERMINISTIC_CHUNK)))
+                .try_fold(dec!(0), |acc, chunk| acc.checked_add(chunk?)),
+            (true, true) => deterministic_parallel_sum(pool, threads, len, &term),
+            (false, false) => sum_range(&term, 0, len),
+            (false, true) => unordered_parallel_sum(pool, threads, len, &term),
+        }
+    }
+}
+
+/// Workers take chunks in round-robin, partial sums are put back in chunk order
+fn deterministic_parallel_sum(
+    pool: &SolverPool,
+    threads: usize,
+    len: usize,
+    term: &(impl Fn(usize) -> Option<Decimal> + Sync),
+) -> Option<Decimal> {
+    let chunks = len.div_ceil(DETERMINISTIC_CHUNK);
+    let mut partial = vec![Some(dec!(0)); chunks];
+
+    let sums = pool.run(threads, |worker| {
+        (worker..chunks)
+            .step_by(threads)
+            .map(|chunk| {
+                let start = chunk * DETERMINISTIC_CHUNK;
+                let end = len.min(start + DETERMINISTIC_CHUNK);
+                (chunk, sum_range(term, start, end))
+            })
+            .collect::<Vec<_>>()
+    });
+    for (chunk, sum) in sums.into_iter().flat_map(|(_, sums)| sums) {
+        partial[chunk] = sum;
+    }
+
+    partial.into_iter().try_fold(dec!(0), |acc, chunk| acc.checked_add(chunk?))
+}
+
+/// Each worker sums contiguous slice, slices are combined in order they finish
+fn unordered_parallel_sum(
+    pool: &SolverPool,
+    threads: usize,
+    len: usize,
+    term: &(impl Fn(usize) -> Option<Decimal> + Sync),
+) -> Option<Decimal> {
+    let slice = len.div_ceil(threads);
+    let sums = pool.run(len.div_ceil(slice), |index| {
+        let start = index * slice;
+        sum_range(term, start, len.min(start + slice))
+    });
+
+    sums.into_iter().try_fold(dec!(0), |acc, (_, sum)| acc.checked_add(sum?))
+}
+
+fn sum_range(term: &impl Fn(usize) -> Option<Decimal>, start: usize, end: usize) -> Option<Decimal> {
+    (start..end).try_fold(dec!(0), |acc, index| acc.checked_add(term(index)?))
+}
@@ -0,0 +1,516 @@
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use crate::solver::{Equation, InitialGuess};
+
+/// Renumbering of unknowns applied to equations in the same order
+/// (`P A P^T`, `P b`). Diagonal entries stay on diagonal, only order
+/// in which Gauss-Seidel visits them changes
+#[derive(Debug, Clone)]
+pub struct SymmetricPermutation {
+    /// `order[i]` is original index of row placed at position `i`
+    pub order: Vec<usize>,
+}
+
+impl SymmetricPermutation {
+    pub fn identity(size: usize) -> Self {
+        SymmetricPermutation {
+            order: (0..size).collect(),
+        }
+    }
+
+    pub fn apply(&self, equation: &Equation) -> Equation {
+        let size = self.order.len();
+        Equation {
+            input_matrix: DMatrix::from_fn(size, size, |row, column| {
+                equation.input_matrix[(self.order[row], self.order[column])]
+            }),
+            expression_rhs: DVector::from_fn(size, |row, _| equation.expression_rhs[self.order[row]]),
+            max_iterations: equation.max_iterations,
+            epsilon: equation.epsilon,
+            tolerance_mode: equation.tolerance_mode,
+            norm: equation.norm,
+            parallelism: equation.parallelism.clone(),
+            // renumbering mixes blocks, so block structure does not survive it
+            schur_block: None,
+            epsilon_schedule: equation.epsilon_schedule.clone(),
+            method: equation.method,
+            relaxation: equation.relaxation,
+            tau: equation.tau,
+            restart: equation.restart,
+            fast_path: equation.fast_path,
+            acceleration: equation.acceleration,
+            preconditioning: equation.preconditioning,
+            fixed: self.renumber(&equation.fixed),
+            bounds: self.renumber(&equation.bounds),
+            // renumbering mixes blocks too
+            blocks: Vec::new(),
+            row_order: match equation.row_order.is_empty() {
+                true => Vec::new(),
+                false => self.order.iter().map(|row| equation.row_order[*row]).collect(),
+            },
+            refinement_steps: equation.refinement_steps,
+            rounding: equation.rounding,
+            rounding_log: equation.rounding_log.clone(),
+            initial_guess: equation.initial_guess.select(&self.order),
+        }
+    }
+
+    /// Values attached to unknowns (fixed values, bounds) in new numbering,
+    /// unknowns left out of `order` are dropped
+    fn renumber<T: Copy>(&self, values: &[(usize, T)]) -> Vec<(usize, T)> {
+        let mut renumbered: Vec<_> = values
+            .iter()
+            .filter_map(|&(original, value)| {
+                let index = self.order.iter().position(|&index| index == original)?;
+                Some((index, value))
+            })
+            .collect();
+        renumbered.sort_unstable_by_key(|(index, _)| *index);
+        renumbered
+    }
+
+    /// Puts components of solution of permuted system back in original order
+    pub fn restore(&self, solution: &DVector<Decimal>) -> DVector<Decimal> {
+        let mut restored = solution.clone();
+        for (position, original) in self.order.iter().enumerate() {
+            restored[*original] = solution[position];
+        }
+        restored
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.order.iter().enumerate().all(|(position, row)| position == *row)
+    }
+}
+
+/// Passes of alternating row and column scaling, after two of them row and
+/// column maxima are within factor 10 of each other for most matrices
+const EQUILIBRATION_PASSES: usize = 3;
+
+/// Scaling of rows and columns `R A C y = R b`, `x = C y`, with diagonal
+/// `R` and `C` of powers of 10 chosen so that the largest entry of every row
+/// and column is between 1 and 10. Multiplying Decimal by power of 10 only
+/// moves its point, so scaled system loses no digits, unless they move
+/// beyond 28 decimal places
+#[derive(Debug, Clone)]
+pub struct Equilibration {
+    pub rows: DVector<Decimal>,
+    pub columns: DVector<Decimal>,
+}
+
+impl Equilibration {
+    pub fn new(matrix: &DMatrix<Decimal>) -> Self {
+        let size = matrix.nrows();
+        let mut rows = DVector::from_element(size, Decimal::ONE);
+        let mut columns = DVector::from_element(size, Decimal::ONE);
+        let scaled = |rows: &DVector<Decimal>, columns: &DVector<Decimal>, row: usize, column: usize| {
+            (matrix[(row, column)] * rows[row]) * columns[column]
+        };
+        for _ in 0..EQUILIBRATION_PASSES {
+            for row in 0..size {
+                let largest = (0..size).map(|column| scaled(&rows, &columns, row, column).abs()).max();
+                rows[row] *= inverse_power(largest.unwrap_or_default());
+            }
+            for column in 0..size {
+                let largest = (0..size).map(|row| scaled(&rows, &columns, row, column).abs()).max();
+                columns[column] *= inverse_power(largest.unwrap_or_default());
+            }
+        }
+        Equilibration { rows, columns }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.rows.iter().chain(self.columns.iter()).all(|scale| *scale == Decimal::ONE)
+    }
+
+    /// Scaled system. Epsilon is divided by the largest column scale, so that
+    /// change of `y` below it still means change of `x` below epsilon of input
+    pub fn apply(&self, equation: &Equation) -> Equation {
+        let size = self.rows.len();
+        let largest_column = self.columns.iter().copied().max().unwrap_or(Decimal::ONE);
+        let epsilon = |epsilon: Decimal| epsilon / largest_column;
+        Equation {
+            input_matrix: DMatrix::from_fn(size, size, |row, column| {
+                (equation.input_matrix[(row, column)] * self.rows[row]) * self.columns[column]
+            }),
+            expression_rhs: equation.expression_rhs.component_mul(&self.rows),
+            epsilon: epsilon(equation.epsilon),
+            epsilon_schedule: equation.epsilon_schedule.iter().copied().map(epsilon).collect(),
+            initial_guess: match &equation.initial_guess {
+                InitialGuess::Given(guess) => InitialGuess::Given(guess.component_div(&self.columns)),
+                strategy => strategy.clone(),
+            },
+            ..equation.clone()
+        }
+    }
+
+    /// Solution of original system from solution of scaled one
+    pub fn restore(&self, solution: &DVector<Decimal>) -> DVector<Decimal> {
+        solution.component_mul(&self.columns)
+    }
+}
+
+/// Orders of magnitude between the largest and the smallest non-zero entry
+pub fn magnitude_spread(matrix: &DMatrix<Decimal>) -> u32 {
+    let exponents = matrix.iter().filter(|value| !value.is_zero()).map(|value| exponent(value.abs()));
+    let (low, high) = exponents.fold((i32::MAX, i32::MIN), |(low, high), exponent| {
+        (low.min(exponent), high.max(exponent))
+    });
+    high.checked_sub(low).map_or(0, |spread| spread.unsigned_abs())
+}
+
+/// `e` such that `10^e <= value < 10^(e + 1)`, for positive value
+fn exponent(mut value: Decimal) -> i32 {
+    let mut exponent = 0;
+    while value >= dec!(10) {
+        value /= dec!(10);
+        exponent += 1;
+    }
+    while value < Decimal::ONE {
+        value *= dec!(10);
+        exponent -= 1;
+    }
+    exponent
+}
+
+/// `10^-e` bringing value to `[1, 10)`, 1 for zero and where Decimal can't
+/// hold the power
+fn inverse_power(value: Decimal) -> Decimal {
+    if value.is_zero() {
+        return Decimal::ONE;
+    }
+    let exponent = exponent(value);
+    match exponent >= 0 {
+        true => Decimal::try_new(1, exponent.unsigned_abs()).unwrap_or(Decimal::ONE),
+        false => dec!(10).checked_powu(u64::from(exponent.unsigned_abs())).unwrap_or(Decimal::ONE),
+    }
+}
+
+/// `|a_ii| / sum |a_ij|, j != i`. `None` when off-diagonal entries are all zero,
+/// which is the best possible dominance. Ratio out of Decimal range saturates
+/// to zero or [`Decimal::MAX`]
+pub fn dominance_ratio(matrix: &DMatrix<Decimal>, row: usize) -> Option<Decimal> {
+    let off_diagonal = (0..matrix.ncols())
+        .filter(|column| *column != row)
+        .try_fold(dec!(0), |acc, column| acc.checked_add(matrix[(row, column)].abs()))
+        // sum out of range is above any diagonal entry
+        .unwrap_or(Decimal::MAX);
+
+    if off_diagonal.is_zero() {
+        return None;
+    }
+    let diagonal = matrix[(row, row)].abs();
+    Some(diagonal.checked_div(off_diagonal).unwrap_or(match diagonal > off_diagonal {
+        true => Decimal::MAX,
+        false => Decimal::ZERO,
+    }))
+}
+
+/// Dominance ratios of rows in order solver visits them
+pub struct DominanceProfile(Vec<(usize, Option<Decimal>)>);
+
+impl DominanceProfile {
+    pub fn new(equation: &Equation, permutation: &SymmetricPermutation) -> Self {
+        let size = equation.input_matrix.nrows();
+        DominanceProfile(
+            (0..size)
+                .map(|position| {
+                    let row = permutation.order[position];
+                    (row, dominance_ratio(&equation.input_matrix, row))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl fmt::Display for DominanceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<String> = self
+            .0
+            .iter()
+            .map(|(row, ratio)| match ratio {
+                Some(ratio) => format!("row {}: {}", row + 1, ratio.round_dp(4).normalize()),
+                None => format!("row {}: inf", row + 1),
+            })
+            .collect();
+        write!(f, "{}", entries.join(", "))
+    }
+}
+
+/// Visits rows with strongest diagonal dominance first
+pub fn order_by_dominance(equation: &Equation) -> SymmetricPermutation {
+    let size = equation.input_matrix.nrows();
+    let ratios: Vec<Option<Decimal>> = (0..size)
+        .map(|row| dominance_ratio(&equation.input_matrix, row))
+        .collect();
+
+    let mut order: Vec<usize> = (0..size).collect();
+    // `None` is unbounded ratio, so it goes first. Sort is stable,
+    // rows with equal ratio keep original order
+    order.sort_by(|a, b| match (ratios[*a], ratios[*b]) {
+        (None, None) => std::cmp::Ordering::Equal,
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (Some(a), Some(b)) => b.cmp(&a),
+    });
+
+    SymmetricPermutation { order }
+}
+
+/// Order of equations (rows together with right hand side) to solve in, when
+/// order of input has zeros on diagonal or is not diagonally dominant while
+/// another one is. Unlike [`SymmetricPermutation`] unknowns keep their numbers,
+/// equations are only swapped, so solution does not change. `order[i]` is
+/// original row placed at position `i`. `None` when input order is kept:
+/// nothing is better, or no order avoids zeros on diagonal at all
+pub fn improving_row_order(matrix: &DMatrix<Decimal>) -> Option<Vec<usize>> {
+    let size = matrix.nrows();
+    let has_zero = (0..size).any(|row| matrix[(row, row)].is_zero());
+    let dominant = |row: usize, column: usize| {
+        let off_diagonal = (0..size)
+            .filter(|other| *other != column)
+            .fold(dec!(0), |acc, other| acc + matrix[(row, other)].abs());
+        matrix[(row, column)].abs() > off_diagonal
+    };
+    if !has_zero && (0..size).all(|row| dominant(row, row)) {
+        return None;
+    }
+
+    // row is strictly dominant in at most one column, so dominant order
+    // exists exactly when every column is dominant column of some row
+    let mut by_column = vec![None; size];
+    for row in 0..size {
+        if let Some(column) = (0..size).find(|column| dominant(row, *column)) {
+            by_column[column] = Some(row);
+        }
+    }
+    if let Some(order) = by_column.into_iter().collect::<Option<Vec<usize>>>() {
+        return Some(order);
+    }
+    if !has_zero {
+        return None;
+    }
+
+    // otherwise any order with non-zero diagonal, found by augmenting paths
+    // of bipartite matching. Larger entries are tried first
+    let candidates: Vec<Vec<usize>> = (0..size)
+        .map(|row| {
+            let mut columns: Vec<usize> = (0..size).filter(|column| !matrix[(row, *column)].is_zero()).collect();
+            columns.sort_by_key(|column| std::cmp::Reverse(matrix[(row, *column)].abs()));
+            columns
+        })
+        .collect();
+    let mut row_at: Vec<Option<usize>> = vec![None; size];
+    for row in 0..size {
+        let mut visited = vec![false; size];
+        if !augment(row, &candidates, &mut row_at, &mut visited) {
+            return None;
+        }
+    }
+    row_at.into_iter().collect()
+}
+
+/// Places `row` into one of its columns, moving rows already placed
+/// into other columns of theirs when needed
+fn augment(row: usize, candidates: &[Vec<usize>], row_at: &mut [Option<usize>], visited: &mut [bool]) -> bool {
+    for &column in &candidates[row] {
+        if visited[column] {
+            continue;
+        }
+        visited[column] = true;
+        let free = match row_at[column] {
+            None => true,
+            Some(placed) => augment(placed, candidates, row_at, visited),
+        };
+        if free {
+            row_at[column] = Some(row);
+            return true;
+        }
+    }
+    false
+}
+
+/// Ways to renumber unknowns before solving
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reordering {
+    /// See [`order_by_dominance`]
+    Dominance,
+    /// See [`cuthill_mckee`]
+    CuthillMcKee,
+    /// Cuthill-McKee order reversed, which usually gives less fill-in
+    /// for direct solves at the same bandwidth
+    ReverseCuthillMcKee,
+}
+
+impl Reordering {
+    pub fn permutation(&self, equation: &Equation) -> SymmetricPermutation {
+        match self {
+            Reordering::Dominance => order_by_dominance(equation),
+            Reordering::CuthillMcKee => cuthill_mckee(&equation.input_matrix),
+            Reordering::ReverseCuthillMcKee => {
+                let mut permutation = cuthill_mckee(&equation.input_matrix);
+                permutation.order.reverse();
+                permutation
+            }
+        }
+    }
+}
+
+impl fmt::Display for Reordering {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reordering::Dominance => write!(f, "diagonal dominance"),
+            Reordering::CuthillMcKee => write!(f, "Cuthill-McKee"),
+            Reordering::ReverseCuthillMcKee => write!(f, "reverse Cuthill-McKee"),
+        }
+    }
+}
+
+impl FromStr for Reordering {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "dominance" => Ok(Reordering::Dominance),
+            "cm" | "cuthill-mckee" => Ok(Reordering::CuthillMcKee),
+            "rcm" | "reverse-cuthill-mckee" => Ok(Reordering::ReverseCuthillMcKee),
+            _ => Err("Expected one of: dominance, cm, rcm".to_owned()),
+        }
+    }
+}
+
+/// Largest distance of non-zero entry from main diagonal
+pub fn bandwidth(matrix: &DMatrix<Decimal>) -> usize {
+    let size = matrix.nrows();
+    (0..size)
+        .flat_map(|row| (0..size).map(move |column| (row, column)))
+        .filter(|position| !matrix[*position].is_zero())
+        .map(|(row, column)| row.abs_diff(column))
+        .max()
+        .unwrap_or(0)
+}
+
+/// `i` and `j` are adjacent when either `a_ij` or `a_ji` is not zero
+fn adjacency(matrix: &DMatrix<Decimal>) -> Vec<Vec<usize>> {
+    let size = matrix.nrows();
+    (0..size)
+        .map(|row| {
+            (0..size)
+                .filter(|column| {
+                    *column != row
+                        && !(matrix[(row, *column)].is_zero() && matrix[(*column, row)].is_zero())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Breadth-first numbering of adjacency graph of matrix, starting from
+/// vertex of the lowest degree and visiting neighbours from the lowest degree.
+/// Coupled unknowns get close numbers, so non-zero entries gather around diagonal
+pub fn cuthill_mckee(matrix: &DMatrix<Decimal>) -> SymmetricPermutation {
+    let size = matrix.nrows();
+    let neighbours = adjacency(matrix);
+    let degree = |vertex: usize| neighbours[vertex].len();
+
+    let mut visited = vec![false; size];
+    let mut order = Vec::with_capacity(size);
+
+    // every connected component gets its own traversal
+    while order.len() < size {
+        let start = (0..size)
+            .filter(|vertex| !visited[*vertex])
+            .min_by_key(|vertex| degree(*vertex))
+            .expect("not all vertices are numbered yet");
+
+        visited[start] = true;
+        let mut queue = VecDeque::from([start]);
+        while let Some(vertex) = queue.pop_front() {
+            order.push(vertex);
+
+            let mut next: Vec<usize> = neighbours[vertex]
+                .iter()
+                .copied()
+                .filter(|neighbour| !visited[*neighbour])
+                .collect();
+            next.sort_by_key(|neighbour| degree(*neighbour));
+            for neighbour in next {
+                visited[neighbour] = true;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    SymmetricPermutation { order }
+}
+
+/// Groups of unknowns which do not appear in equations of other groups.
+/// Each group forms independent subsystem, so reducible (block-diagonal
+/// up to renumbering) system can be solved group by group
+#[derive(Debug)]
+pub struct Split {
+    /// Original indices of unknowns in every group, ascending
+    pub components: Vec<Vec<usize>>,
+}
+
+impl Split {
+    /// Connected components of adjacency graph of matrix
+    pub fn new(matrix: &DMatrix<Decimal>) -> Self {
+        let size = matrix.nrows();
+        let neighbours = adjacency(matrix);
+        let mut visited = vec![false; size];
+        let mut components = Vec::new();
+
+        for start in 0..size {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            let mut component = Vec::new();
+            let mut stack = vec![start];
+            while let Some(vertex) = stack.pop() {
+                component.push(vertex);
+                for neighbour in &neighbours[vertex] {
+                    if !visited[*neighbour] {
+                        visited[*neighbour] = true;
+                        stack.push(*neighbour);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+
+        Split { components }
+    }
+
+    pub fn subsystems(&self, equation: &Equation) -> Vec<Equation> {
+        self.components
+            .iter()
+            .map(|component| {
+                SymmetricPermutation {
+                    order: component.clone(),
+                }
+                .apply(equation)
+            })
+            .collect()
+    }
+
+    /// Puts solutions of subsystems, in order of components, into one vector
+    pub fn combine(&self, solutions: &[DVector<Decimal>]) -> DVector<Decimal> {
+        let size = self.components.iter().map(Vec::len).sum();
+        let mut combined = DVector::from_element(size, dec!(0));
+        for (component, solution) in self.components.iter().zip(solutions) {
+            for (position, original) in component.iter().enumerate() {
+                combined[*original] = solution[position];
+            }
+        }
+        combined
+    }
+}
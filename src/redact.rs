@@ -0,0 +1,170 @@
+use core::fmt;
+use std::ops::Range;
+
+use nalgebra::DMatrix;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+
+use crate::input::{fixed_name, EquesionInput, GuessInput};
+use crate::solver::{Bounds, Equation, InitialGuess, Method, ToleranceMode};
+
+/// Bounds of row scale factor in hundredths. Keeping factors within `[0.5, 2]`
+/// changes condition number at most 4 times
+const MIN_ROW_FACTOR: i64 = 50;
+const MAX_ROW_FACTOR: i64 = 200;
+
+/// Scaled coefficient or right hand side of original row does not fit into Decimal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedactError {
+    pub row: usize,
+}
+
+impl fmt::Display for RedactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Row {} outgrows Decimal when scaled, redaction needs coefficients at least 2 times below its maximum",
+            self.row + 1
+        )
+    }
+}
+
+/// Hides actual coefficients of the system while keeping its numerical character.
+///
+/// Unknowns are renumbered by symmetric permutation `P A P^T`, so diagonal
+/// entries stay on diagonal. Permutation stays within blocks, Schur block
+/// and grid lines, and one widening bandwidth is replaced by reversal or
+/// by none, so banded systems take the same solver path. Then each row
+/// (together with its right hand side) is multiplied by random factor with
+/// random sign. Row scaling keeps diagonal dominance of every row intact and
+/// does not change the solution, so redacted system converges the same way
+/// as original one.
+///
+/// This hides very little. Ratios of coefficients within a row survive, so
+/// dividing every row by its largest coefficient gives the same rows for
+/// original and redacted system: original is recovered up to row scale and
+/// order of unknowns, and solution is the same up to that order. Sparsity
+/// pattern survives too. Columns are not scaled: that would change solution,
+/// bounds and meaning of epsilon, and break diagonal dominance solver reports on.
+///
+/// `grid` of original input is kept. Seed is not reported anywhere: knowing
+/// it is enough to undo redaction
+pub fn redact(equation: &Equation, grid: Option<[usize; 2]>, seed: Option<u64>) -> Result<EquesionInput, RedactError> {
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let matrix_size = equation.input_matrix.nrows();
+    let mut permutation: Vec<usize> = (0..matrix_size).collect();
+    // unknowns are shuffled within blocks, so Schur and block Gauss-Seidel
+    // structure is kept
+    let sizes = match equation.schur_block {
+        Some(block) => vec![block, matrix_size - block],
+        None if !equation.blocks.is_empty() => equation.blocks.clone(),
+        None => vec![matrix_size],
+    };
+    let ranges: Vec<Range<usize>> = (sizes.iter())
+        .scan(0, |start, size| {
+            *start += size;
+            Some(*start - size..*start)
+        })
+        .collect();
+    let original = bandwidth(&equation.input_matrix, &permutation);
+    for range in &ranges {
+        permutation[range.clone()].shuffle(&mut rng);
+    }
+    if bandwidth(&equation.input_matrix, &permutation) > original {
+        permutation.sort_unstable();
+        for range in &ranges {
+            permutation[range.clone()].reverse();
+        }
+    }
+    if bandwidth(&equation.input_matrix, &permutation) > original {
+        permutation.sort_unstable();
+    }
+
+    let factors: Vec<Decimal> = (0..matrix_size)
+        .map(|_| {
+            let factor = Decimal::new(rng.gen_range(MIN_ROW_FACTOR..=MAX_ROW_FACTOR), 2);
+            if rng.gen_bool(0.5) {
+                -factor
+            } else {
+                factor
+            }
+        })
+        .collect();
+
+    let scaled = |row: usize, value: Decimal| {
+        (value.checked_mul(factors[row])).ok_or(RedactError { row: permutation[row] })
+    };
+    let mut matrix = DMatrix::zeros(matrix_size, matrix_size);
+    for row in 0..matrix_size {
+        for column in 0..matrix_size {
+            matrix[(row, column)] = scaled(row, equation.input_matrix[(permutation[row], permutation[column])])?;
+        }
+    }
+
+    let input_matrix = matrix
+        .row_iter()
+        .map(|row| row.iter().map(|value| value.normalize().to_string()).collect())
+        .collect();
+
+    let expression_rhs = (permutation.iter().enumerate())
+        .map(|(row, &source_row)| Ok(scaled(row, equation.expression_rhs[source_row])?.normalize().to_string()))
+        .collect::<Result<_, RedactError>>()?;
+
+    let renumbered = |index: usize| {
+        let index = permutation.iter().position(|&source| source == index);
+        fixed_name(index.expect("permutation covers every unknown"))
+    };
+    let bound = |pick: fn(&Bounds) -> Option<Decimal>| {
+        (equation.bounds.iter())
+            .filter_map(|(index, bounds)| Some((renumbered(*index), pick(bounds)?.normalize().to_string())))
+            .collect()
+    };
+
+    Ok(EquesionInput {
+        input_matrix,
+        expression_rhs,
+        max_iterations: equation.max_iterations,
+        epsilon: equation.epsilon.to_string(),
+        schur_block: equation.schur_block,
+        fixed: equation
+            .fixed
+            .iter()
+            .map(|&(original, value)| (renumbered(original), value.normalize().to_string()))
+            .collect(),
+        lower: bound(|bounds| bounds.lower),
+        upper: bound(|bounds| bounds.upper),
+        blocks: match grid {
+            Some(_) => Vec::new(),
+            None => equation.blocks.clone(),
+        },
+        grid,
+        method: (equation.method != Method::default()).then(|| equation.method.to_string()),
+        refinement_steps: (equation.refinement_steps > 0).then_some(equation.refinement_steps),
+        tolerance_mode: (equation.tolerance_mode != ToleranceMode::default()).then(|| equation.tolerance_mode.to_string()),
+        norm: equation.norm.map(|norm| norm.to_string()),
+        initial_guess: match &equation.initial_guess {
+            InitialGuess::Ones => None,
+            InitialGuess::Given(guess) => Some(GuessInput::Values(
+                permutation.iter().map(|&source| guess[source].normalize().to_string()).collect(),
+            )),
+            strategy => Some(GuessInput::Strategy(strategy.to_string())),
+        },
+    })
+}
+
+/// Bandwidth of `P A P^T`, where row `i` of it is row `permutation[i]` of `A`
+fn bandwidth(matrix: &DMatrix<Decimal>, permutation: &[usize]) -> usize {
+    let size = permutation.len();
+    (0..size)
+        .flat_map(|row| (0..size).map(move |column| (row, column)))
+        .filter(|(row, column)| !matrix[(permutation[*row], permutation[*column])].is_zero())
+        .map(|(row, column)| row.abs_diff(column))
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,373 @@
+//! Event log of solve, one JSON object per line, and replay of it. Log holds
+//! solved system with its settings, every iteration with its approximation,
+//! decisions printed during solve and the result, so every report format
+//! can be rendered from it later without solving again:
+//!
+//! ```json
+//! {"event":"settings","input_matrix":[["4","1"],["1","3"]],"expression_rhs":["1","2"],...}
+//! {"event":"note","message":"Method gauss-seidel is chosen: ..."}
+//! {"event":"iteration","iteration":1,"phase":0,"stage":"Gauss-Seidel","delta":"0.5","iterate":["0","0.5"]}
+//! {"event":"switch","iteration":4,"from":"Jacobi","to":"Gauss-Seidel"}
+//! {"event":"finished","iterations":12,"last_delta":"0.00001","solution":["0.09","0.63"]}
+//! ```
+
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, LineWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::accuracy::Accuracy;
+use crate::analysis::{residual_vector, weighted_residuals};
+use crate::compression::decompress_if_needed;
+use crate::report::{Report, ReportOutcome};
+use crate::result::{tolerance_mode, LastIteration, SolveTrace};
+use crate::solver::{Equation, Progress, Stage};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum LogEvent {
+    Settings {
+        input_matrix: Vec<Vec<String>>,
+        expression_rhs: Vec<String>,
+        max_iterations: usize,
+        epsilon: String,
+        /// Absent in logs of absolute tolerance and in older logs
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tolerance_mode: Option<String>,
+        /// See [`Equation::norm`], absent in logs of default norms
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        norm: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        epsilon_schedule: Vec<String>,
+        method: String,
+        /// See [`Equation::row_order`], matrix is in this order
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        row_order: Vec<usize>,
+    },
+    /// Decision solver told about, like chosen method
+    Note { message: String },
+    Iteration {
+        iteration: usize,
+        phase: usize,
+        stage: String,
+        delta: String,
+        /// In original order of unknowns, even when solved system was renumbered
+        iterate: Vec<String>,
+        /// See [`Progress::restarted`]
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        restarted: bool,
+    },
+    /// Kind of iteration changed after `iteration`
+    Switch {
+        iteration: usize,
+        from: String,
+        to: String,
+    },
+    Finished {
+        iterations: usize,
+        last_delta: String,
+        solution: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        inverse: Option<Vec<Vec<String>>>,
+    },
+    Failed { message: String },
+}
+
+fn strings<'a>(values: impl IntoIterator<Item = &'a Decimal>) -> Vec<String> {
+    values.into_iter().map(ToString::to_string).collect()
+}
+
+fn matrix_strings(matrix: &DMatrix<Decimal>) -> Vec<Vec<String>> {
+    matrix.row_iter().map(|row| strings(row.iter())).collect()
+}
+
+/// Writes events as they happen, so log of killed solve is complete
+/// up to the last iteration
+pub struct EventLog {
+    file: LineWriter<File>,
+    last_stage: Option<Stage>,
+}
+
+impl EventLog {
+    /// Starts log with settings of `equation` and `notes` given before solve
+    pub fn create(path: &Path, equation: &Equation, notes: &[String]) -> io::Result<Self> {
+        let mut log = EventLog {
+            file: LineWriter::new(File::create(path)?),
+            last_stage: None,
+        };
+        log.write(&LogEvent::Settings {
+            input_matrix: matrix_strings(&equation.input_matrix),
+            expression_rhs: strings(equation.expression_rhs.iter()),
+            max_iterations: equation.max_iterations,
+            epsilon: equation.epsilon.to_string(),
+            tolerance_mode: tolerance_mode(equation.tolerance_mode),
+            norm: equation.norm.map(|norm| norm.to_string()),
+            epsilon_schedule: strings(&equation.epsilon_schedule),
+            method: equation.method.to_string(),
+            row_order: equation.row_order.clone(),
+        })?;
+        for message in notes {
+            log.write(&LogEvent::Note {
+                message: message.clone(),
+            })?;
+        }
+        Ok(log)
+    }
+
+    fn write(&mut self, event: &LogEvent) -> io::Result<()> {
+        let line = serde_json::to_string(event).expect("event consists of plain data");
+        writeln!(self.file, "{line}")
+    }
+
+    /// `iterate` is approximation of `progress` in original order of unknowns
+    pub fn iteration(&mut self, progress: &Progress, iterate: &DVector<Decimal>) -> io::Result<()> {
+        if let Some(from) = self.last_stage.filter(|stage| *stage != progress.stage) {
+            self.write(&LogEvent::Switch {
+                iteration: progress.iteration - 1,
+                from: from.to_string(),
+                to: progress.stage.to_string(),
+            })?;
+        }
+        self.last_stage = Some(progress.stage);
+        self.write(&LogEvent::Iteration {
+            iteration: progress.iteration,
+            phase: progress.phase,
+            stage: progress.stage.to_string(),
+            delta: progress.delta.to_string(),
+            iterate: strings(iterate.iter()),
+            restarted: progress.restarted,
+        })
+    }
+
+    /// Records outcome of `report`, the one printed for the solve
+    pub fn finish(&mut self, report: &Report) -> io::Result<()> {
+        let event = match &report.outcome {
+            ReportOutcome::Solved { solution, last, .. } => LogEvent::Finished {
+                iterations: last.iteration,
+                last_delta: last.delta.to_string(),
+                solution: strings(solution.iter()),
+                inverse: report.inverse.as_ref().map(matrix_strings),
+            },
+            ReportOutcome::Failed(message) => LogEvent::Failed {
+                message: message.clone(),
+            },
+            ReportOutcome::Parametric(_) => return Ok(()),
+        };
+        self.write(&event)
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Read(io::Error),
+    /// Line is not an event, by number from 1
+    Event { line: usize, message: String },
+    /// Log does not start with settings
+    NoSettings,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Read(err) => write!(f, "Can't read log: {err}"),
+            ReplayError::Event { line, message } => write!(f, "Line {line} of log is not an event: {message}"),
+            ReplayError::NoSettings => write!(f, "Log does not start with settings of solve"),
+        }
+    }
+}
+
+/// Iteration as recorded in log
+#[derive(Debug, Clone)]
+pub struct LoggedIteration {
+    pub iteration: usize,
+    pub phase: usize,
+    pub stage: Stage,
+    pub delta: Decimal,
+    pub iterate: DVector<Decimal>,
+    pub restarted: bool,
+}
+
+/// Solve read back from [`EventLog`]
+#[derive(Debug)]
+pub struct Replay {
+    /// System with settings which change iterations, others are defaults
+    pub equation: Equation,
+    pub notes: Vec<String>,
+    pub iterations: Vec<LoggedIteration>,
+    pub outcome: Result<(DVector<Decimal>, LastIteration), String>,
+    pub inverse: Option<DMatrix<Decimal>>,
+}
+
+impl Replay {
+    /// Accepts gzip and zstd compressed logs too
+    pub fn read(path: &Path) -> Result<Self, ReplayError> {
+        let content = fs::read(path)
+            .and_then(decompress_if_needed)
+            .map_err(ReplayError::Read)?;
+        Replay::parse(&String::from_utf8_lossy(&content))
+    }
+
+    fn parse(content: &str) -> Result<Self, ReplayError> {
+        let mut lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let event = |(index, line): (usize, &str)| {
+            let invalid = move |message: String| ReplayError::Event {
+                line: index + 1,
+                message,
+            };
+            serde_json::from_str::<LogEvent>(line)
+                .map_err(|err| invalid(err.to_string()))
+                .map(|event| (event, invalid))
+        };
+
+        let Some(first) = lines.next() else {
+            return Err(ReplayError::NoSettings);
+        };
+        let (
+            LogEvent::Settings {
+                input_matrix,
+                expression_rhs,
+                max_iterations,
+                epsilon,
+                tolerance_mode,
+                norm,
+                epsilon_schedule,
+                method,
+                row_order,
+            },
+            invalid,
+        ) = event(first)?
+        else {
+            return Err(ReplayError::NoSettings);
+        };
+        let rows = input_matrix.len();
+        let values: Vec<Decimal> = (input_matrix.iter().flatten())
+            .map(|value| decimal(value))
+            .collect::<Result<_, _>>()
+            .map_err(&invalid)?;
+        if values.len() != rows * rows {
+            return Err(invalid("Matrix is not square".to_owned()));
+        }
+        let mut equation = Equation::new(
+            DMatrix::from_row_slice(rows, rows, &values),
+            DVector::from_vec(decimals(&expression_rhs).map_err(&invalid)?),
+            max_iterations,
+            decimal(&epsilon).map_err(&invalid)?,
+        );
+        equation.epsilon_schedule = decimals(&epsilon_schedule).map_err(&invalid)?;
+        equation.method = method.parse().map_err(&invalid)?;
+        if let Some(mode) = tolerance_mode {
+            equation.tolerance_mode = mode.parse().map_err(&invalid)?;
+        }
+        if let Some(norm) = norm {
+            equation.norm = Some(norm.parse().map_err(&invalid)?);
+        }
+        if !row_order.is_empty() && (row_order.len() != rows || row_order.iter().any(|row| *row >= rows)) {
+            return Err(invalid("Order of equations does not match matrix".to_owned()));
+        }
+        equation.row_order = row_order;
+
+        let mut replay = Replay {
+            equation,
+            notes: Vec::new(),
+            iterations: Vec::new(),
+            outcome: Err("Log ends before solve finished".to_owned()),
+            inverse: None,
+        };
+        for line in lines {
+            let (event, invalid) = event(line)?;
+            match event {
+                LogEvent::Settings { .. } => return Err(invalid("Settings repeat".to_owned())),
+                LogEvent::Note { message } => replay.notes.push(message),
+                LogEvent::Iteration {
+                    iteration,
+                    phase,
+                    stage,
+                    delta,
+                    iterate,
+                    restarted,
+                } => replay.iterations.push(LoggedIteration {
+                    iteration,
+                    phase,
+                    stage: stage.parse().map_err(&invalid)?,
+                    delta: decimal(&delta).map_err(&invalid)?,
+                    iterate: DVector::from_vec(decimals(&iterate).map_err(&invalid)?),
+                    restarted,
+                }),
+                // switches follow from stages of iterations, they are in log for its readers
+                LogEvent::Switch { .. } => (),
+                LogEvent::Finished {
+                    iterations,
+                    last_delta,
+                    solution,
+                    inverse,
+                } => {
+                    let last = LastIteration {
+                        iteration: iterations,
+                        delta: decimal(&last_delta).map_err(&invalid)?,
+                        stage: replay.iterations.last().map(|logged| logged.stage),
+                    };
+                    let solution = DVector::from_vec(decimals(&solution).map_err(&invalid)?);
+                    replay.outcome = Ok((solution, last));
+                    if let Some(inverse) = inverse {
+                        let size = inverse.len();
+                        let values = decimals(&inverse.concat()).map_err(&invalid)?;
+                        replay.inverse = Some(DMatrix::from_row_slice(size, size, &values));
+                    }
+                }
+                LogEvent::Failed { message } => replay.outcome = Err(message),
+            }
+        }
+        Ok(replay)
+    }
+
+    /// Trace solve would have built from the same iterations
+    pub fn trace(&self) -> SolveTrace {
+        let mut trace = SolveTrace::default();
+        for logged in &self.iterations {
+            trace.record(
+                &self.equation,
+                &Progress {
+                    iteration: logged.iteration,
+                    phase: logged.phase,
+                    delta: logged.delta,
+                    stage: logged.stage,
+                    iterate: &logged.iterate,
+                    restarted: logged.restarted,
+                },
+            );
+        }
+        trace
+    }
+
+    /// Report of the replayed solve, the same solve printed
+    pub fn report(&self) -> Report {
+        let outcome = match &self.outcome {
+            Ok((solution, last)) => ReportOutcome::Solved {
+                residual: residual_vector(&self.equation, solution),
+                residuals: weighted_residuals(&self.equation, solution),
+                accuracy: Accuracy::assess(&self.equation, solution),
+                solution: solution.clone(),
+                last: *last,
+                trace: Box::new(self.trace()),
+            },
+            Err(message) => ReportOutcome::Failed(message.clone()),
+        };
+        let report = Report::new(&self.equation, outcome);
+        match &self.inverse {
+            Some(inverse) => report.with_inverse(inverse.clone()),
+            None => report,
+        }
+    }
+}
+
+fn decimal(value: &str) -> Result<Decimal, String> {
+    Decimal::from_str(value).map_err(|err| format!("{value}: {err}"))
+}
+
+fn decimals(values: &[String]) -> Result<Vec<Decimal>, String> {
+    values.iter().map(|value| decimal(value)).collect()
+}
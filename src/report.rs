@@ -0,0 +1,804 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use crate::accuracy::Accuracy;
+use crate::analysis::{residual_vector, system_norms, weighted_residuals, SystemNorms};
+use crate::convergence::Oscillation;
+use crate::result::{
+    equation_order, residual_strings, tolerance_mode, AccuracyMetadata, LastIteration, NormsMetadata, OscillationMetadata, JitterMetadata, RefinementMetadata, ResultMetadata, RoundingMetadata, SchemaVersion, SolveResult, SolveTrace,
+};
+use crate::condition::{condition_number, Condition};
+use crate::jitter::Jitter;
+use crate::norm::Norm;
+use crate::solver::{Determinant, ESolveError, Equation, ParametricSolution, Refinement, RoundingReport, SpectralRadius, ToleranceMode};
+
+mod layout;
+mod number;
+mod template;
+
+pub use number::{NumberFormat, Notation};
+
+/// Convergence rate is estimate, more digits would be noise
+const RATE_DIGITS: u32 = 6;
+
+/// Ways to present [`Report`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// Human readable text, the way solver always printed results
+    #[default]
+    Plain,
+    /// Columns aligned in ASCII tables
+    Table,
+    /// [`SolveResult`] document, can be compared by diff
+    Json,
+    /// Fragment to paste into LaTeX document
+    Latex,
+    Markdown,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "plain" => Ok(ReportFormat::Plain),
+            "table" => Ok(ReportFormat::Table),
+            "json" => Ok(ReportFormat::Json),
+            "latex" | "tex" => Ok(ReportFormat::Latex),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            _ => Err("Expected one of: plain, table, json, latex, markdown".to_owned()),
+        }
+    }
+}
+
+/// Everything known about finished solve. Every format is rendered from the
+/// same facts, so formats differ in layout only
+#[derive(Debug)]
+pub struct Report {
+    pub matrix_size: usize,
+    pub max_iterations: usize,
+    pub epsilon: Decimal,
+    pub tolerance_mode: ToleranceMode,
+    /// See [`Equation::norm`]
+    pub norm: Option<Norm>,
+    pub outcome: ReportOutcome,
+    /// Inverse of matrix, shown after solution when requested
+    pub inverse: Option<DMatrix<Decimal>>,
+    /// Shown after matrix size when requested
+    pub determinant: Option<Determinant>,
+    /// Used by every format except JSON
+    pub number_format: NumberFormat,
+    /// Of JSON, see [`SchemaVersion`]
+    pub schema: SchemaVersion,
+    /// Plain and table formats fit into this many columns: tables leave
+    /// out middle columns and wrap long cells, lines are wrapped
+    pub width: Option<usize>,
+    /// See [`ResultMetadata::equation_order`]
+    pub equation_order: Vec<usize>,
+    /// Shown for failed solves too, where it often explains failure
+    pub condition: Option<Condition>,
+    /// Of iteration matrix, like condition shown for failed solves too
+    pub spectral_radius: Option<SpectralRadius>,
+    /// Done after solve when requested, solution is already refined
+    pub refinement: Option<Refinement>,
+    /// Response of solution to perturbed coefficients, when requested
+    pub jitter: Option<Jitter>,
+    /// Worst rounding of products, recorded by equation during solve
+    pub rounding: RoundingReport,
+    /// Shown next to condition number, for failed solves too
+    pub norms: SystemNorms,
+    /// Why solve may fail or be inaccurate, known before it started. Only
+    /// JSON has them, text formats leave them to messages printed before solve
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum ReportOutcome {
+    Solved {
+        solution: DVector<Decimal>,
+        last: LastIteration,
+        /// Boxed, it is the largest part of report
+        trace: Box<SolveTrace>,
+        /// `A x - b`, see [`residual_vector`]
+        residual: DVector<Decimal>,
+        /// See [`weighted_residuals`]
+        residuals: Vec<Decimal>,
+        accuracy: Accuracy,
+    },
+    Parametric(ParametricSolution),
+    Failed(String),
+}
+
+impl Report {
+    /// Norm of `A x - b`, Euclidean unless other norm was chosen
+    fn residual_norm(&self, residual: &DVector<Decimal>) -> Decimal {
+        self.norm.unwrap_or(Norm::L2).of(residual.iter().copied()).normalize()
+    }
+
+    pub fn from_solve(
+        equation: &Equation,
+        outcome: Result<DVector<Decimal>, ESolveError>,
+        last: LastIteration,
+        trace: &SolveTrace,
+    ) -> Self {
+        let outcome = match outcome {
+            Ok(solution) => ReportOutcome::Solved {
+                residual: residual_vector(equation, &solution),
+                residuals: weighted_residuals(equation, &solution),
+                accuracy: Accuracy::assess(equation, &solution),
+                solution,
+                last,
+                trace: Box::new(trace.clone()),
+            },
+            Err(err) => ReportOutcome::Failed(err.to_string()),
+        };
+        Report::new(equation, outcome)
+    }
+
+    pub fn parametric(equation: &Equation, solution: ParametricSolution) -> Self {
+        Report::new(equation, ReportOutcome::Parametric(solution))
+    }
+
+    pub(crate) fn new(equation: &Equation, outcome: ReportOutcome) -> Self {
+        let condition = match &outcome {
+            ReportOutcome::Solved { accuracy, .. } => accuracy.condition,
+            ReportOutcome::Failed(_) => condition_number(equation),
+            ReportOutcome::Parametric(_) => None,
+        };
+        Report {
+            condition,
+            matrix_size: equation.input_matrix.nrows(),
+            max_iterations: equation.max_iterations,
+            epsilon: equation.epsilon,
+            tolerance_mode: equation.tolerance_mode,
+            norm: equation.norm,
+            outcome,
+            inverse: None,
+            determinant: None,
+            spectral_radius: None,
+            refinement: None,
+            jitter: None,
+            rounding: equation.rounding_report(),
+            norms: system_norms(equation),
+            number_format: NumberFormat::default(),
+            schema: SchemaVersion::default(),
+            width: None,
+            equation_order: equation_order(equation),
+            warnings: equation.dominance_warning().iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    pub fn with_inverse(mut self, inverse: DMatrix<Decimal>) -> Self {
+        self.inverse = Some(inverse);
+        self
+    }
+
+    pub fn with_determinant(mut self, determinant: Determinant) -> Self {
+        self.determinant = Some(determinant);
+        self
+    }
+
+    pub fn with_spectral_radius(mut self, radius: Option<SpectralRadius>) -> Self {
+        self.spectral_radius = radius;
+        self
+    }
+
+    pub fn with_refinement(mut self, refinement: Option<Refinement>) -> Self {
+        self.refinement = refinement;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: Option<Jitter>) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_number_format(mut self, number_format: NumberFormat) -> Self {
+        self.number_format = number_format;
+        self
+    }
+
+    pub fn with_schema(mut self, schema: SchemaVersion) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    fn number(&self, value: Decimal) -> String {
+        self.number_format.format(value)
+    }
+
+    /// In number format when determinant fits into Decimal
+    fn determinant(&self) -> Option<String> {
+        let determinant = self.determinant?;
+        Some(match determinant.value() {
+            Some(value) => self.number(value.normalize()),
+            None => determinant.to_string(),
+        })
+    }
+
+    fn oscillation(&self, oscillation: Option<Oscillation>) -> String {
+        let Some(oscillation) = oscillation else {
+            return "none, delta decreased every iteration".to_owned();
+        };
+
+        let mut description = format!("delta grew {} times", oscillation.rises);
+        if let Some(period) = oscillation.period {
+            description += &format!(", peaks every {} iterations", self.number(period.round_dp(2)));
+        }
+        if let Some(rate) = oscillation.amplitude_rate {
+            let trend = if rate < Decimal::ONE { "shrinking" } else { "growing" };
+            description += &format!(
+                ", {trend}: every peak is {} of previous one",
+                self.number(rate.round_dp(RATE_DIGITS))
+            );
+        }
+        description
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self.outcome, ReportOutcome::Failed(_))
+    }
+
+    /// Solution was found, but matrix is singular: it is one of many, or
+    /// only satisfies system up to rounding
+    pub fn is_singular_solution(&self) -> bool {
+        matches!(self.outcome, ReportOutcome::Solved { .. })
+            && self.condition.is_some_and(|condition| condition.is_singular())
+    }
+
+    /// The same report as JSON document
+    pub fn to_result(&self) -> SolveResult {
+        let strings = |vector: &DVector<Decimal>| vector.iter().map(ToString::to_string).collect();
+        let result = match &self.outcome {
+            ReportOutcome::Solved {
+                solution,
+                last,
+                trace,
+                residual,
+                residuals,
+                accuracy,
+            } => SolveResult::Solved {
+                solution: strings(solution),
+                metadata: Box::new(ResultMetadata {
+                    matrix_size: self.matrix_size,
+                    iterations: last.iteration,
+                    max_iterations: self.max_iterations,
+                    epsilon: self.epsilon.to_string(),
+                    tolerance_mode: tolerance_mode(self.tolerance_mode),
+                    norm: self.norm.map(|norm| norm.to_string()),
+                    last_delta: last.delta.to_string(),
+                    direct: last.direct().map(|stage| stage.to_string()),
+                    phases: trace.phases(),
+                    switches: trace.switches(),
+                    restarts: trace.restarts(),
+                    convergence_rate: trace
+                        .convergence_rate()
+                        .map(|rate| rate.rate.round_dp(RATE_DIGITS).to_string()),
+                    oscillation: trace.oscillation().flatten().map(|oscillation| {
+                        OscillationMetadata {
+                            rises: oscillation.rises,
+                            period: oscillation.period.map(|period| period.round_dp(2).to_string()),
+                            amplitude_rate: oscillation
+                                .amplitude_rate
+                                .map(|rate| rate.round_dp(RATE_DIGITS).to_string()),
+                        }
+                    }),
+                    residual: residual_strings(residual.as_slice()),
+                    residual_norm: Some(self.residual_norm(residual).to_string()),
+                    weighted_residuals: residual_strings(residuals),
+                    accuracy: Some(AccuracyMetadata::from(accuracy)),
+                    warnings: Vec::new(),
+                    equation_order: self.equation_order.clone(),
+                    condition_number: None,
+                    determinant: self.determinant.map(|determinant| determinant.to_string()),
+                    operations: trace.operations(),
+                    spectral_radius: None,
+                    refinement: self.refinement.as_ref().map(RefinementMetadata::from),
+                    jitter: self.jitter.as_ref().map(JitterMetadata::from),
+                    rounding: Some(RoundingMetadata::from(&self.rounding)),
+                    norms: Some(NormsMetadata::from(&self.norms)),
+                    inverse: self.inverse.as_ref().map(|inverse| {
+                        inverse.row_iter().map(|row| row.iter().map(ToString::to_string).collect()).collect()
+                    }),
+                }),
+            },
+            ReportOutcome::Parametric(solution) => SolveResult::parametric(solution),
+            ReportOutcome::Failed(message) => SolveResult::error(message),
+        };
+        result
+            .with_warnings(self.warnings.clone())
+            .with_condition(self.condition.as_ref())
+            .with_spectral_radius(self.spectral_radius.as_ref())
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Plain => self.plain(),
+            ReportFormat::Table => self.table(),
+            ReportFormat::Json => {
+                serde_json::to_string_pretty(&self.to_result().document(self.schema))
+                    .expect("result consists of plain data")
+                    + "\n"
+            }
+            ReportFormat::Latex => self.latex(),
+            ReportFormat::Markdown => self.markdown(),
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match self.outcome {
+            ReportOutcome::Solved { .. } => "solved",
+            ReportOutcome::Parametric(_) => "infinitely many solutions",
+            ReportOutcome::Failed(_) => "failed",
+        }
+    }
+
+    /// Named values describing solve, in the order they are presented
+    fn facts(&self) -> Vec<(String, String)> {
+        let mut facts = vec![
+            ("Status".to_owned(), self.status().to_owned()),
+            ("Matrix size".to_owned(), self.matrix_size.to_string()),
+        ];
+        if let Some(determinant) = self.determinant() {
+            facts.push(("Determinant".to_owned(), determinant));
+        }
+
+        match &self.outcome {
+            ReportOutcome::Solved {
+                last,
+                trace,
+                residual,
+                residuals,
+                accuracy,
+                ..
+            } => {
+                match last.direct() {
+                    Some(stage) => facts.push(("Solved".to_owned(), format!("directly, by {stage}"))),
+                    None => {
+                        facts.push((
+                            "Iterations".to_owned(),
+                            format!("{} of {}", last.iteration, self.max_iterations),
+                        ));
+                        let mut epsilon = match self.tolerance_mode {
+                            ToleranceMode::Absolute => self.number(self.epsilon),
+                            mode => format!("{}, {mode}", self.number(self.epsilon)),
+                        };
+                        if let Some(norm) = self.norm {
+                            epsilon += &format!(", {norm} norm");
+                        }
+                        facts.push(("Epsilon".to_owned(), epsilon));
+                        facts.push(("Last delta".to_owned(), self.number(last.delta)));
+                    }
+                }
+                for (index, (epsilon, iterations, delta)) in trace.phases.iter().enumerate() {
+                    facts.push((
+                        format!("Phase {}", index + 1),
+                        format!(
+                            "epsilon {}, {iterations} iterations, last delta {}",
+                            self.number(*epsilon),
+                            self.number(*delta)
+                        ),
+                    ));
+                }
+                for (iteration, from, to) in &trace.switches {
+                    facts.push((
+                        "Switch".to_owned(),
+                        format!("{from} to {to} after iteration {iteration}"),
+                    ));
+                }
+                for (iteration, stage) in &trace.restarts {
+                    facts.push((
+                        "Restart".to_owned(),
+                        format!("{stage} after iteration {iteration}, its residual drifted from b - Ax"),
+                    ));
+                }
+                if let Some(rate) = trace.convergence_rate() {
+                    let per_digit = match rate.iterations_per_digit() {
+                        Some(iterations) => {
+                            format!("{} iterations per digit", self.number(iterations.round_dp(2)))
+                        }
+                        None => "deltas do not shrink".to_owned(),
+                    };
+                    facts.push((
+                        "Convergence rate".to_owned(),
+                        format!(
+                            "{} per iteration, {per_digit} (fit to {} deltas)",
+                            self.number(rate.rate.round_dp(RATE_DIGITS)),
+                            rate.samples
+                        ),
+                    ));
+                }
+                if let Some(oscillation) = trace.oscillation() {
+                    facts.push(("Oscillation".to_owned(), self.oscillation(oscillation)));
+                }
+                if !trace.operations.is_empty() {
+                    let mut operations = trace.total_operations().to_string();
+                    if trace.operations.len() > 1 {
+                        let stages: Vec<String> = (trace.operations.iter())
+                            .map(|(stage, operations, _)| format!("{stage}: {operations}"))
+                            .collect();
+                        operations += &format!(" ({})", stages.join("; "));
+                    }
+                    facts.push(("Operations".to_owned(), operations));
+                }
+                facts.push(("Residual norm".to_owned(), self.number(self.residual_norm(residual))));
+                if let Some(refinement) = &self.refinement {
+                    facts.push(("Refinement".to_owned(), refinement.to_string()));
+                }
+                if let Some(jitter) = &self.jitter {
+                    facts.push(("Jitter".to_owned(), jitter.to_string()));
+                }
+                facts.push(("Rounding".to_owned(), self.rounding.to_string()));
+                let worst = (residuals.iter().enumerate()).max_by_key(|(_, residual)| **residual);
+                if let Some((row, residual)) = worst {
+                    facts.push((
+                        "Worst equation".to_owned(),
+                        format!("{}, weighted residual {}", row + 1, self.number(*residual)),
+                    ));
+                }
+                if let Some(condition) = &self.condition {
+                    facts.push(("Condition number".to_owned(), condition.to_string()));
+                }
+                facts.push(("Norms".to_owned(), self.norms.to_string()));
+                if let Some(radius) = &self.spectral_radius {
+                    facts.push(("Spectral radius".to_owned(), radius.to_string()));
+                }
+                facts.push(("Accuracy".to_owned(), accuracy.to_string()));
+                let poor = self.poorly_satisfied(residuals);
+                if !poor.is_empty() {
+                    let rows: Vec<String> = poor.iter().map(|row| (row + 1).to_string()).collect();
+                    facts.push((
+                        "Poorly satisfied".to_owned(),
+                        format!("equations {}, weighted residual above epsilon", rows.join(", ")),
+                    ));
+                }
+            }
+            ReportOutcome::Parametric(solution) => {
+                facts.push(("Rank".to_owned(), solution.rank.to_string()));
+                facts.push(("Free parameters".to_owned(), solution.null_space.len().to_string()));
+            }
+            ReportOutcome::Failed(message) => {
+                facts.push(("Error".to_owned(), message.clone()));
+                if let Some(condition) = &self.condition {
+                    facts.push(("Condition number".to_owned(), condition.to_string()));
+                }
+                facts.push(("Norms".to_owned(), self.norms.to_string()));
+                if let Some(radius) = &self.spectral_radius {
+                    facts.push(("Spectral radius".to_owned(), radius.to_string()));
+                }
+            }
+        }
+
+        facts
+    }
+
+    /// Equations whose weighted residual is above epsilon
+    fn poorly_satisfied(&self, residuals: &[Decimal]) -> Vec<usize> {
+        (0..residuals.len())
+            .filter(|row| residuals[*row] > self.epsilon)
+            .collect()
+    }
+
+    /// Header and rows of residual and weighted residual of every equation
+    fn equations(&self) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+        let ReportOutcome::Solved { residual, residuals, .. } = &self.outcome else {
+            return None;
+        };
+        let poor = self.poorly_satisfied(residuals);
+
+        let header = ["Equation", "Residual", "Weighted residual", "Above epsilon"].map(str::to_owned).to_vec();
+        let rows = (residuals.iter().enumerate())
+            .map(|(row, weighted)| {
+                let mark = if poor.contains(&row) { "yes" } else { "" };
+                let residual = self.number(residual[row].normalize());
+                vec![(row + 1).to_string(), residual, self.number(*weighted), mark.to_owned()]
+            })
+            .collect();
+        Some((header, rows))
+    }
+
+    /// Header and rows of inverse matrix, columns are numbered from 1
+    fn inverse(&self) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+        let inverse = self.inverse.as_ref()?;
+        let header = std::iter::once("Inverse".to_owned())
+            .chain((1..=inverse.ncols()).map(|column| column.to_string()))
+            .collect();
+        let rows = (inverse.row_iter().enumerate())
+            .map(|(row, values)| {
+                std::iter::once((row + 1).to_string())
+                    .chain(values.iter().map(|value| self.number(*value)))
+                    .collect()
+            })
+            .collect();
+        Some((header, rows))
+    }
+
+    /// Header and rows of unknowns: solution, or particular solution and
+    /// null space basis. Failed solve has no unknowns to show
+    fn unknowns(&self) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+        let columns: Vec<(String, &DVector<Decimal>)> = match &self.outcome {
+            ReportOutcome::Solved { solution, .. } => vec![("Value".to_owned(), solution)],
+            ReportOutcome::Parametric(solution) => {
+                let mut columns = vec![("Particular".to_owned(), &solution.particular)];
+                columns.extend(
+                    (solution.null_space.iter().enumerate())
+                        .map(|(index, basis)| (format!("v{}", index + 1), basis)),
+                );
+                columns
+            }
+            ReportOutcome::Failed(_) => return None,
+        };
+
+        let header = std::iter::once("Unknown".to_owned())
+            .chain(columns.iter().map(|(name, _)| name.clone()))
+            .collect();
+        let rows = (0..self.matrix_size)
+            .map(|row| {
+                std::iter::once(format!("x{}", row + 1))
+                    .chain(columns.iter().map(|(_, vector)| self.number(vector[row])))
+                    .collect()
+            })
+            .collect();
+        Some((header, rows))
+    }
+
+    fn plain(&self) -> String {
+        let vector = |vector: &DVector<Decimal>| vector.map(|value| self.number(value));
+        let mut out = match &self.outcome {
+            ReportOutcome::Solved { solution, residual, .. } => {
+                let mut out = format!("Solution: {}", vector(solution));
+                out += &format!("Residual Ax - b: {}", vector(&residual.map(|value| value.normalize())));
+                if let Some(inverse) = &self.inverse {
+                    let rendered = format!("{}", inverse.map(|value| self.number(value)));
+                    match (self.width, self.inverse()) {
+                        (Some(width), Some((header, rows)))
+                            if rendered.lines().any(|line| line.chars().count() > width) =>
+                        {
+                            out += &format!("Inverse:\n{}", ascii_table(Some(&header), &rows, self.width));
+                        }
+                        _ => out += &format!("Inverse: {rendered}"),
+                    }
+                }
+                out
+            }
+            ReportOutcome::Parametric(solution) => {
+                let size = solution.particular.len();
+                let mut out = format!(
+                    "System has infinitely many solutions: rank is {} of {size}\n",
+                    solution.rank
+                );
+                out += &format!("Particular solution: {}", vector(&solution.particular));
+                out += "Null space basis:\n";
+                for (index, basis) in solution.null_space.iter().enumerate() {
+                    out += &format!("v{}: {}", index + 1, vector(basis));
+                }
+                let parameters: String = (1..=solution.null_space.len())
+                    .map(|index| format!(" + t{index} * v{index}"))
+                    .collect();
+                return out + &format!("General solution: x = particular{parameters}\n");
+            }
+            ReportOutcome::Failed(message) => {
+                let mut out = format!("{message}\n");
+                if let Some(determinant) = self.determinant() {
+                    out += &format!("Determinant: {determinant}\n");
+                }
+                if let Some(condition) = &self.condition {
+                    out += &format!("Condition number: {condition}\n");
+                }
+                out += &format!("Norms: {}\n", self.norms);
+                if let Some(radius) = &self.spectral_radius {
+                    out += &format!("Spectral radius: {radius}\n");
+                }
+                return out;
+            }
+        };
+        // status and size are evident from solution itself
+        for (name, value) in self.facts().into_iter().skip(2) {
+            match self.width {
+                Some(width) => out += &layout::wrap_fact(&name, &value, width),
+                None => out += &format!("{name}: {value}\n"),
+            }
+        }
+        out
+    }
+
+    fn table(&self) -> String {
+        let mut out = String::new();
+        if let Some((header, rows)) = self.unknowns() {
+            out += &ascii_table(Some(&header), &rows, self.width);
+        }
+        for (header, rows) in self.inverse().into_iter().chain(self.equations()) {
+            out += &ascii_table(Some(&header), &rows, self.width);
+        }
+        let facts: Vec<Vec<String>> = self
+            .facts()
+            .into_iter()
+            .map(|(name, value)| vec![name, value])
+            .collect();
+        out + &ascii_table(None, &facts, self.width)
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = String::new();
+        let tables = self.unknowns().into_iter().chain(self.inverse()).chain(self.equations());
+        for (header, rows) in tables {
+            out += &render_table(&header, &rows, ReportFormat::Markdown, None);
+            out += "\n";
+        }
+        for (name, value) in self.facts() {
+            out += &format!("- **{name}:** {}\n", markdown_escape(&value));
+        }
+        out
+    }
+
+    fn latex(&self) -> String {
+        // separators would be typeset as punctuation and spaces dropped in math mode
+        let entry = |value: &Decimal| self.number(*value).replace(',', "{,}").replace(' ', "\\,");
+        let pmatrix = |vector: &DVector<Decimal>| {
+            let entries: Vec<String> = vector.iter().map(entry).collect();
+            format!("\\begin{{pmatrix}} {} \\end{{pmatrix}}", entries.join(" \\\\ "))
+        };
+
+        let mut out = String::new();
+        match &self.outcome {
+            ReportOutcome::Solved { solution, residual, .. } => {
+                out += &format!("\\[\nx = {}\n\\]\n", pmatrix(solution));
+                out += &format!("\\[\nAx - b = {}\n\\]\n", pmatrix(&residual.map(|value| value.normalize())));
+                if let Some(inverse) = &self.inverse {
+                    let rows: Vec<String> = inverse
+                        .row_iter()
+                        .map(|row| row.iter().map(entry).collect::<Vec<_>>().join(" & "))
+                        .collect();
+                    out += &format!(
+                        "\\[\nA^{{-1}} = \\begin{{pmatrix}} {} \\end{{pmatrix}}\n\\]\n",
+                        rows.join(" \\\\ ")
+                    );
+                }
+            }
+            ReportOutcome::Parametric(solution) => {
+                let terms: String = (solution.null_space.iter().enumerate())
+                    .map(|(index, basis)| format!(" + t_{{{}}} {}", index + 1, pmatrix(basis)))
+                    .collect();
+                out += &format!("\\[\nx = {}{terms}\n\\]\n", pmatrix(&solution.particular));
+            }
+            ReportOutcome::Failed(_) => (),
+        }
+
+        out += "\\begin{tabular}{ll}\n";
+        for (name, value) in self.facts() {
+            out += &format!("{} & {} \\\\\n", latex_escape(&name), latex_escape(&value));
+        }
+        out + "\\end{tabular}\n"
+    }
+}
+
+
+/// Table of values besides single solve report, like parameter study. Json gives
+/// array of objects keyed by header
+/// Plain and table formats fit into `width` columns, see [`Report::width`]
+pub fn render_table(header: &[String], rows: &[Vec<String>], format: ReportFormat, width: Option<usize>) -> String {
+    match format {
+        ReportFormat::Plain | ReportFormat::Table => ascii_table(Some(&header.to_vec()), rows, width),
+        ReportFormat::Markdown => {
+            let mut out = markdown_row(header);
+            out += &markdown_row(&vec!["---".to_owned(); header.len()]);
+            for row in rows {
+                out += &markdown_row(row);
+            }
+            out
+        }
+        ReportFormat::Latex => {
+            let line = |cells: &[String]| {
+                let cells: Vec<String> = cells.iter().map(|cell| latex_escape(cell)).collect();
+                cells.join(" & ") + " \\\\\n"
+            };
+            let mut out = format!("\\begin{{tabular}}{{{}}}\n", "l".repeat(header.len()));
+            out += &line(header);
+            out += "\\hline\n";
+            for row in rows {
+                out += &line(row);
+            }
+            out + "\\end{tabular}\n"
+        }
+        ReportFormat::Json => {
+            let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+                .iter()
+                .map(|row| {
+                    header
+                        .iter()
+                        .cloned()
+                        .zip(row.iter().map(|cell| serde_json::Value::String(cell.clone())))
+                        .collect()
+                })
+                .collect();
+            serde_json::to_string_pretty(&objects).expect("table consists of plain strings") + "\n"
+        }
+    }
+}
+
+fn ascii_table(header: Option<&Vec<String>>, rows: &[Vec<String>], width: Option<usize>) -> String {
+    let (mut header, mut rows) = (header.cloned(), rows.to_vec());
+    if let Some(width) = width {
+        layout::elide_columns(header.as_mut(), &mut rows, width);
+    }
+    let header = header.as_ref();
+    let rows = rows.as_slice();
+    let columns = header.map_or_else(|| rows.first().map_or(0, Vec::len), Vec::len);
+    let mut widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            header
+                .into_iter()
+                .chain(rows)
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    if let Some(width) = width {
+        layout::shrink(&mut widths, width);
+    }
+
+    let border = widths
+        .iter()
+        .map(|width| "-".repeat(width + 2))
+        .fold("+".to_owned(), |acc, dashes| acc + &dashes + "+")
+        + "\n";
+    // cells longer than their shrunk column take several lines
+    let line = |row: &Vec<String>| {
+        let cells: Vec<Vec<String>> = (row.iter().zip(&widths))
+            .map(|(cell, width)| match cell.chars().count() > *width {
+                true => layout::wrap(cell, *width),
+                false => vec![cell.clone()],
+            })
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+        (0..height)
+            .map(|index| {
+                (cells.iter().zip(&widths))
+                    .map(|(lines, width)| {
+                        let cell = lines.get(index).map_or("", String::as_str);
+                        format!(" {cell:<width$} |")
+                    })
+                    .fold("|".to_owned(), |acc, cell| acc + &cell)
+                    + "\n"
+            })
+            .collect::<String>()
+    };
+
+    let mut out = border.clone();
+    if let Some(header) = header {
+        out += &line(header);
+        out += &border;
+    }
+    for row in rows {
+        out += &line(row);
+    }
+    out + &border
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells.iter().map(|cell| markdown_escape(cell)).collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn markdown_escape(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn latex_escape(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '\\' => "\\textbackslash{}".to_owned(),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => format!("\\{c}"),
+            '\n' => " ".to_owned(),
+            c => c.to_string(),
+        })
+        .collect()
+}
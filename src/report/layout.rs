@@ -0,0 +1,117 @@
+//! Fitting tables and lines of text formats into width of terminal
+
+/// Stands for columns left out of table which does not fit
+const ELLIPSIS: &str = "...";
+
+/// Wrapped columns are not made narrower than this
+const MIN_COLUMN_WIDTH: usize = 8;
+
+/// Characters taken by table with columns of `widths`, borders included
+fn table_width(widths: &[usize]) -> usize {
+    widths.iter().map(|width| width + 3).sum::<usize>() + 1
+}
+
+/// Leaves out middle columns of table wider than `width`: the first one,
+/// which names rows, stays, followed by as many columns from both ends as
+/// fit next to column of `...`, at least one, which is wrapped if it does
+/// not fit either. Tables of two columns are kept whole
+pub(super) fn elide_columns(header: Option<&mut Vec<String>>, rows: &mut [Vec<String>], width: usize) {
+    let Some(columns) = header.as_ref().map(|header| header.len()).or(rows.first().map(Vec::len)) else {
+        return;
+    };
+    let widths: Vec<usize> = (0..columns)
+        .map(|column| {
+            (header.iter().map(|header| &header[column]))
+                .chain(rows.iter().map(|row| &row[column]))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    if columns <= 2 || table_width(&widths) <= width {
+        return;
+    }
+
+    let mut used = table_width(&[widths[0], ELLIPSIS.len()]);
+    // columns before `left` and from `right` on are kept
+    let (mut left, mut right) = (1, columns);
+    let mut from_left = true;
+    loop {
+        let next = if from_left { left } else { right - 1 };
+        if used + widths[next] + 3 > width && left > 1 {
+            break;
+        }
+        used += widths[next] + 3;
+        match from_left {
+            true => left += 1,
+            false => right -= 1,
+        }
+        from_left = !from_left;
+    }
+
+    let elide = |cells: &mut Vec<String>| {
+        cells.splice(left..right, [ELLIPSIS.to_owned()]);
+    };
+    if let Some(header) = header {
+        elide(header);
+    }
+    rows.iter_mut().for_each(elide);
+}
+
+/// Narrows the widest columns until table fits into `width`, or they
+/// reach [`MIN_COLUMN_WIDTH`]. Their cells are to be wrapped by [`wrap`]
+pub(super) fn shrink(widths: &mut [usize], width: usize) {
+    while table_width(widths) > width {
+        let Some((column, widest)) = (widths.iter().copied().enumerate()).max_by_key(|(_, width)| *width) else {
+            return;
+        };
+        if widest <= MIN_COLUMN_WIDTH {
+            return;
+        }
+        widths[column] = widest - 1;
+    }
+}
+
+/// `text` in lines of at most `width` characters, broken at spaces, and
+/// inside words longer than line
+pub(super) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for mut word in text.split(' ') {
+        loop {
+            let length = line.chars().count();
+            let needed = word.chars().count() + if length == 0 { 0 } else { length + 1 };
+            if needed <= width {
+                if length > 0 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                break;
+            }
+            if length > 0 {
+                lines.push(std::mem::take(&mut line));
+                continue;
+            }
+            let split = word.char_indices().nth(width).map_or(word.len(), |(index, _)| index);
+            lines.push(word[..split].to_owned());
+            word = &word[split..];
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Line `name: value` wrapped into `width`, continuation lines indented
+pub(super) fn wrap_fact(name: &str, value: &str, width: usize) -> String {
+    const INDENT: &str = "  ";
+    let lines = wrap(&format!("{name}: {value}"), width.saturating_sub(INDENT.len()));
+    (lines.iter().enumerate())
+        .map(|(index, line)| match index {
+            0 => format!("{line}\n"),
+            _ => format!("{INDENT}{line}\n"),
+        })
+        .collect()
+}
@@ -0,0 +1,86 @@
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// How numbers are written by human readable report formats.
+/// JSON keeps plain notation so results stay machine readable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    /// Put between groups of three digits of integer part
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+    pub notation: Notation,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            thousands_separator: None,
+            decimal_separator: '.',
+            notation: Notation::Fixed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// All digits written out, like `1234.5`
+    #[default]
+    Fixed,
+    /// Single digit before decimal separator and power of ten, like `1.2345e3`
+    Scientific,
+}
+
+impl FromStr for Notation {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "fixed" => Ok(Notation::Fixed),
+            "scientific" | "sci" => Ok(Notation::Scientific),
+            _ => Err("Expected one of: fixed, scientific".to_owned()),
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Conventional separators of locale: `en` is `1,234.5`, `ru` is `1 234,5`
+    pub fn for_locale(locale: &str) -> Result<Self, String> {
+        let (thousands_separator, decimal_separator) = match locale {
+            "en" => (',', '.'),
+            "ru" | "de" | "fr" => (' ', ','),
+            _ => return Err("Expected one of: en, ru, de, fr".to_owned()),
+        };
+
+        Ok(NumberFormat {
+            thousands_separator: Some(thousands_separator),
+            decimal_separator,
+            notation: Notation::Fixed,
+        })
+    }
+
+    pub fn format(&self, value: Decimal) -> String {
+        let written = match self.notation {
+            Notation::Fixed => value.to_string(),
+            // `Decimal` writes zero mantissa as empty string
+            Notation::Scientific if value.is_zero() => "0e0".to_owned(),
+            Notation::Scientific => format!("{value:e}"),
+        };
+
+        let (sign, unsigned) = match written.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", written.as_str()),
+        };
+        let (integer, rest) = unsigned.split_at(unsigned.find(['.', 'e']).unwrap_or(unsigned.len()));
+
+        let mut out = sign.to_owned();
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                if let Some(separator) = self.thousands_separator {
+                    out.push(separator);
+                }
+            }
+            out.push(digit);
+        }
+        out + &rest.replace('.', &self.decimal_separator.to_string())
+    }
+}
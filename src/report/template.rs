@@ -0,0 +1,155 @@
+//! Custom report from user's template, such as filled-in LaTeX homework.
+//! `{{name}}` stands for value, `{{#unknowns}}...{{/unknowns}}` and
+//! `{{#equations}}...{{/equations}}` repeat text for every unknown or
+//! equation. Braces are chosen so that LaTeX `${x}$` stays intact, extra
+//! braces around placeholder are kept as they are: `x_{{{index}}}`
+
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+
+use super::{Report, ReportOutcome};
+
+const PLACEHOLDERS: &str = "status, size, iterations, max_iterations, epsilon, last_delta, \
+    solution, x<N>, residual<N>, residual_norm, accuracy, condition, determinant, error";
+
+/// Looks up placeholder by name
+type Lookup<'a> = dyn Fn(&str) -> Result<String, String> + 'a;
+/// Renders section by name and body
+type Section<'a> = dyn Fn(&str, &str) -> Result<String, String> + 'a;
+
+impl Report {
+    /// Template with every placeholder replaced. Placeholder which is unknown,
+    /// or has no value for this outcome, like solution of failed solve, is
+    /// an error rather than empty text, so that mistakes are not silently
+    /// handed in
+    pub fn render_template(&self, template: &str) -> Result<String, String> {
+        fill(template, &|name| self.placeholder(name), &|name, body| self.section(name, body))
+    }
+
+    fn placeholder(&self, name: &str) -> Result<String, String> {
+        let value = match name {
+            "status" => self.status().to_owned(),
+            "size" => self.matrix_size.to_string(),
+            "max_iterations" => self.max_iterations.to_string(),
+            "epsilon" => self.number(self.epsilon),
+            "iterations" => self.solved(name)?.1.to_string(),
+            "last_delta" => self.number(self.solved(name)?.2),
+            "solution" => {
+                let values: Vec<String> = self.solved(name)?.0.iter().map(|value| self.number(*value)).collect();
+                values.join(", ")
+            }
+            "residual_norm" => {
+                self.solved(name)?;
+                match &self.outcome {
+                    ReportOutcome::Solved { residual, .. } => self.number(self.residual_norm(residual)),
+                    _ => unreachable!("solved outcome was checked"),
+                }
+            }
+            "accuracy" => self.solved(name)?.4.to_string(),
+            "condition" => match &self.condition {
+                Some(condition) if condition.is_singular() => "inf".to_owned(),
+                Some(condition) => self.number(condition.value.round_sf(3).unwrap_or(condition.value).normalize()),
+                None => return Err(format!("{{{{{name}}}}} has no value, condition number is not estimated for systems this large")),
+            },
+            "determinant" => match self.determinant() {
+                Some(determinant) => determinant,
+                None => return Err(format!("{{{{{name}}}}} has no value, pass --determinant to compute it")),
+            },
+            "error" => match &self.outcome {
+                ReportOutcome::Failed(message) => message.clone(),
+                _ => String::new(),
+            },
+            _ => {
+                if let Some(row) = self.numbered(name, "x")? {
+                    self.number(self.solved(name)?.0[row])
+                } else if let Some(row) = self.numbered(name, "residual")? {
+                    self.number(self.solved(name)?.3[row])
+                } else {
+                    return Err(format!("Unknown placeholder {{{{{name}}}}}, expected one of: {PLACEHOLDERS}"));
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    fn section(&self, name: &str, body: &str) -> Result<String, String> {
+        if !matches!(name, "unknowns" | "equations") {
+            return Err(format!("Unknown section {{{{#{name}}}}}, expected unknowns or equations"));
+        }
+        let nested = |_: &str, _: &str| Err(format!("Sections can't be nested in {{{{#{name}}}}}"));
+        let (solution, _, _, residuals, _) = self.solved(&format!("#{name}"))?;
+        let mut out = String::new();
+        for row in 0..self.matrix_size {
+            let index = (row + 1).to_string();
+            let item = |placeholder: &str| match (name, placeholder) {
+                (_, "index") => Ok(index.clone()),
+                ("unknowns", "name") => Ok(format!("x{index}")),
+                ("unknowns", "value") => Ok(self.number(solution[row])),
+                ("equations", "residual") => Ok(self.number(residuals[row])),
+                _ => self.placeholder(placeholder),
+            };
+            out += &fill(body, &item, &nested)?;
+        }
+        Ok(out)
+    }
+
+    /// Row of `x3`-like placeholder, counted from 0
+    fn numbered(&self, name: &str, prefix: &str) -> Result<Option<usize>, String> {
+        let Some(number) = name.strip_prefix(prefix).and_then(|number| number.parse::<usize>().ok()) else {
+            return Ok(None);
+        };
+        match number {
+            1.. if number <= self.matrix_size => Ok(Some(number - 1)),
+            _ => Err(format!(
+                "{{{{{name}}}}} is out of range, system has {} equations and unknowns",
+                self.matrix_size
+            )),
+        }
+    }
+
+    /// Solution, iterations, last delta, weighted residuals and accuracy digits
+    #[allow(clippy::type_complexity)]
+    fn solved(&self, name: &str) -> Result<(&DVector<Decimal>, usize, Decimal, &[Decimal], u32), String> {
+        match &self.outcome {
+            ReportOutcome::Solved {
+                solution,
+                last,
+                residuals,
+                accuracy,
+                ..
+            } => Ok((solution, last.iteration, last.delta, residuals, accuracy.digits)),
+            ReportOutcome::Parametric(_) => Err(format!(
+                "{{{{{name}}}}} has no value, system has infinitely many solutions"
+            )),
+            ReportOutcome::Failed(message) => Err(format!("{{{{{name}}}}} has no value, solve failed: {message}")),
+        }
+    }
+}
+
+fn fill(template: &str, lookup: &Lookup, section: &Section) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(mut start) = rest.find("{{") {
+        // `x_{{{index}}}` is LaTeX braces around placeholder
+        while rest[start + 2..].starts_with('{') {
+            start += 1;
+        }
+        out += &rest[..start];
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or("Placeholder opened by {{ is not closed by }}")?;
+        let name = after[..end].trim();
+        rest = &after[end + 2..];
+        if let Some(name) = name.strip_prefix('#') {
+            let close = format!("{{{{/{name}}}}}");
+            let body_end = rest.find(&close).ok_or(format!("Section {{{{#{name}}}}} is not closed by {close}"))?;
+            out += &section(name, &rest[..body_end])?;
+            rest = &rest[body_end + close.len()..];
+        } else if name.starts_with('/') {
+            return Err(format!("{{{{{name}}}}} closes section which is not open"));
+        } else {
+            out += &lookup(name)?;
+        }
+    }
+    out += rest;
+    Ok(out)
+}
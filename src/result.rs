@@ -0,0 +1,736 @@
+use core::fmt;
+use core::str::FromStr;
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::accuracy::Accuracy;
+use crate::analysis::{residual_vector, system_norms, weighted_residuals, SystemNorms};
+use crate::condition::{condition_number, Condition};
+use crate::convergence::{ConvergenceRate, Oscillation};
+use crate::jitter::Jitter;
+use crate::norm::Norm;
+use crate::solver::{
+    ESolveError, Equation, Method, Operations, ParametricSolution, Progress, Refinement, RoundingReport, SpectralRadius,
+    Stage, ToleranceMode, Verdict,
+};
+
+/// See [`ResultMetadata::tolerance_mode`]
+pub(crate) fn tolerance_mode(mode: ToleranceMode) -> Option<String> {
+    (mode != ToleranceMode::default()).then(|| mode.to_string())
+}
+
+/// See [`ResultMetadata::equation_order`]
+pub(crate) fn equation_order(equation: &Equation) -> Vec<usize> {
+    equation.row_order.iter().map(|row| row + 1).collect()
+}
+
+pub(crate) fn residual_strings(residuals: &[Decimal]) -> Vec<String> {
+    residuals.iter().map(|residual| residual.normalize().to_string()).collect()
+}
+
+/// Version of [`SolveResult`] documents, written to their `schema` field.
+/// Within one version fields are only added: none is removed, renamed or
+/// changes meaning, so readers should ignore fields they do not know.
+/// Anything else makes a new version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaVersion {
+    #[default]
+    V1,
+}
+
+impl FromStr for SchemaVersion {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "result/v1" | "v1" | "1" => Ok(SchemaVersion::V1),
+            _ => Err("Expected one of: result/v1".to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaVersion::V1 => write!(f, "result/v1"),
+        }
+    }
+}
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// [`SolveResult`] the way it is written, with version of its schema first
+#[derive(Serialize, Debug)]
+pub struct ResultDocument<'a> {
+    pub schema: SchemaVersion,
+    #[serde(flatten)]
+    pub result: &'a SolveResult,
+}
+
+/// Writes result nested in other document, like job of HTTP API, in
+/// current schema
+pub(crate) fn serialize_document<S: Serializer>(result: &Option<SolveResult>, serializer: S) -> Result<S::Ok, S::Error> {
+    result.as_ref().map(|result| result.document(SchemaVersion::default())).serialize(serializer)
+}
+
+/// Machine readable outcome of solve. Written by `--format json`
+/// and returned by daemon and HTTP API, as [`ResultDocument`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SolveResult {
+    Solved {
+        solution: Vec<String>,
+        metadata: Box<ResultMetadata>,
+    },
+    /// Infinitely many solutions, see [`ParametricSolution`]
+    Parametric {
+        rank: usize,
+        particular: Vec<String>,
+        null_space: Vec<Vec<String>>,
+    },
+    Error {
+        message: String,
+        /// See [`ResultMetadata::warnings`]
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        warnings: Vec<String>,
+        /// Often explains why solve failed, see [`Condition`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        condition_number: Option<ConditionMetadata>,
+        /// See [`ResultMetadata::spectral_radius`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        spectral_radius: Option<SpectralRadiusMetadata>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ResultMetadata {
+    pub matrix_size: usize,
+    pub iterations: usize,
+    pub max_iterations: usize,
+    pub epsilon: String,
+    /// What epsilon was compared with, absent for absolute tolerance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance_mode: Option<String>,
+    /// Norm of `last_delta` and `residual_norm`, absent when delta is
+    /// largest change of component and residual norm is Euclidean
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub norm: Option<String>,
+    pub last_delta: String,
+    /// Present when solution was given at once by elimination or
+    /// factorization, like `"LU decomposition"`. Then `iterations` is 1 and
+    /// `last_delta` is change from initial guess
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direct: Option<String>,
+    /// Present when solve went through epsilon schedule
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub phases: Vec<PhaseMetadata>,
+    /// Present when hybrid method changed iteration kind
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub switches: Vec<SwitchMetadata>,
+    /// Present when recurrence of Krylov method started over
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub restarts: Vec<RestartMetadata>,
+    /// Delta is multiplied by this every iteration, fit to deltas of the last phase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub convergence_rate: Option<String>,
+    /// Present when delta did not decrease monotonically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oscillation: Option<OscillationMetadata>,
+    /// `A x - b`, signed residual of every equation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub residual: Vec<String>,
+    /// Norm of `residual`, Euclidean by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub residual_norm: Option<String>,
+    /// Residual of every equation scaled by norm of its row
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub weighted_residuals: Vec<String>,
+    /// Rows of inverse of matrix, present when it was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inverse: Option<Vec<Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<AccuracyMetadata>,
+    /// Why solve may fail or be inaccurate, known before it started,
+    /// like matrix which is not diagonally dominant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Equations of input, from 1, in order they were solved in. Present when
+    /// they were swapped to put non-zero, dominant entries on diagonal
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub equation_order: Vec<usize>,
+    /// Absent for systems too large to estimate it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition_number: Option<ConditionMetadata>,
+    /// Present when it was requested, like `"-12.5"` or `"3.2e41"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub determinant: Option<String>,
+    /// Multiplications and divisions of solve, see [`Stage::operations`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operations: Option<OperationsMetadata>,
+    /// Of iteration matrix of stationary method, estimated before solve
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spectral_radius: Option<SpectralRadiusMetadata>,
+    /// Present when solution was refined, see [`Refinement`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refinement: Option<RefinementMetadata>,
+    /// Present when perturbed system was solved too, see [`Jitter`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<JitterMetadata>,
+    /// Rounding of products by sweeps, see [`RoundingReport`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<RoundingMetadata>,
+    /// Norms of matrix and right hand side, see [`SystemNorms`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub norms: Option<NormsMetadata>,
+}
+
+/// See [`SystemNorms`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NormsMetadata {
+    pub one: String,
+    pub infinity: String,
+    /// Absent when squares of entries do not fit into Decimal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frobenius: Option<String>,
+    pub rhs: String,
+    /// Norm of `rhs`, like `"L2"`
+    pub rhs_norm: String,
+}
+
+impl From<&SystemNorms> for NormsMetadata {
+    fn from(norms: &SystemNorms) -> Self {
+        NormsMetadata {
+            one: norms.one.normalize().to_string(),
+            infinity: norms.infinity.normalize().to_string(),
+            frobenius: norms.frobenius.map(|frobenius| frobenius.normalize().to_string()),
+            rhs: norms.rhs.normalize().to_string(),
+            rhs_norm: norms.rhs_norm.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoundingMetadata {
+    /// Like `"native"` or `"rescale:20"`
+    pub strategy: String,
+    /// Fewest decimal places product was rounded to, absent when every product was exact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coarsest_places: Option<u32>,
+    /// Bound of error of single rounding, like `"5e-21"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worst_error: Option<String>,
+}
+
+impl From<&RoundingReport> for RoundingMetadata {
+    fn from(report: &RoundingReport) -> Self {
+        RoundingMetadata {
+            strategy: report.rounding.to_string(),
+            coarsest_places: report.coarsest_places,
+            worst_error: report.worst_error(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RefinementMetadata {
+    pub steps: usize,
+    /// Euclidean norms of `A x - b`
+    pub residual_before: String,
+    pub residual_after: String,
+}
+
+impl From<&Refinement> for RefinementMetadata {
+    fn from(refinement: &Refinement) -> Self {
+        RefinementMetadata {
+            steps: refinement.steps,
+            residual_before: refinement.residual_before.normalize().to_string(),
+            residual_after: refinement.residual_after.normalize().to_string(),
+        }
+    }
+}
+
+/// See [`Jitter`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JitterMetadata {
+    pub relative: String,
+    pub seed: u64,
+    /// `|dA|inf`, and divided by `|A|inf` unless matrix is zero
+    pub matrix_change: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_matrix_change: Option<String>,
+    /// `|dx|inf`, and divided by `|x|inf` unless solution is zero
+    pub solution_change: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_solution_change: Option<String>,
+}
+
+impl From<&Jitter> for JitterMetadata {
+    fn from(jitter: &Jitter) -> Self {
+        let text = |value: Decimal| value.normalize().to_string();
+        JitterMetadata {
+            relative: text(jitter.relative),
+            seed: jitter.seed,
+            matrix_change: text(jitter.matrix_change),
+            relative_matrix_change: jitter.relative_matrix_change.map(text),
+            solution_change: text(jitter.solution_change),
+            relative_solution_change: jitter.relative_solution_change.map(text),
+        }
+    }
+}
+
+/// See [`SpectralRadius`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpectralRadiusMetadata {
+    pub estimate: String,
+    /// Whether `verdict` is `"converges"`, kept for readers which do not know it
+    pub converges: bool,
+    /// `"converges"`, `"uncertain"` for estimate about 1 or `"diverges"`, as
+    /// text report tells
+    #[serde(default)]
+    pub verdict: String,
+    /// Sufficient condition matrix meets, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guaranteed_by: Option<String>,
+}
+
+impl From<&SpectralRadius> for SpectralRadiusMetadata {
+    fn from(radius: &SpectralRadius) -> Self {
+        SpectralRadiusMetadata {
+            estimate: radius.estimate.round_sf(6).unwrap_or(radius.estimate).normalize().to_string(),
+            converges: radius.verdict() == Verdict::Converges,
+            verdict: radius.verdict().to_string(),
+            guaranteed_by: radius.guarantee.map(|guarantee| guarantee.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OperationsMetadata {
+    pub multiplications: u64,
+    pub divisions: u64,
+    /// Share of every kind of iteration, in order they were first done
+    pub by_stage: Vec<StageOperationsMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StageOperationsMetadata {
+    pub stage: String,
+    pub multiplications: u64,
+    pub divisions: u64,
+}
+
+/// Value of condition, `"inf"` for singular matrix
+fn condition_text(condition: &Condition) -> String {
+    match condition.is_singular() {
+        true => "inf".to_owned(),
+        false => condition.value.to_string(),
+    }
+}
+
+/// See [`Condition`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConditionMetadata {
+    /// `"inf"` for singular matrix
+    pub value: String,
+    pub norm: String,
+    pub estimated: bool,
+}
+
+impl From<&Condition> for ConditionMetadata {
+    fn from(condition: &Condition) -> Self {
+        ConditionMetadata {
+            value: condition_text(condition),
+            norm: "max".to_owned(),
+            estimated: condition.estimated,
+        }
+    }
+}
+
+/// See [`Accuracy`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccuracyMetadata {
+    pub digits: u32,
+    pub backward_error: String,
+    /// Same as `value` of [`ConditionMetadata`], `"inf"` for singular matrix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    pub limited_by: String,
+    pub verdict: String,
+}
+
+impl From<&Accuracy> for AccuracyMetadata {
+    fn from(accuracy: &Accuracy) -> Self {
+        AccuracyMetadata {
+            digits: accuracy.digits,
+            backward_error: accuracy.backward_error.to_string(),
+            condition: accuracy.condition.as_ref().map(condition_text),
+            limited_by: accuracy.limited_by.to_string(),
+            verdict: accuracy.to_string(),
+        }
+    }
+}
+
+/// See [`Oscillation`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OscillationMetadata {
+    pub rises: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amplitude_rate: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhaseMetadata {
+    pub epsilon: String,
+    pub iterations: usize,
+    pub last_delta: String,
+}
+
+/// Switch made by hybrid method after iteration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SwitchMetadata {
+    /// Last iteration done the old way
+    pub iteration: usize,
+    pub from: String,
+    pub to: String,
+}
+
+/// Krylov method started over from true residual after iteration, see
+/// [`crate::solver::RESIDUAL_CHECK_PERIOD`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RestartMetadata {
+    pub iteration: usize,
+    pub stage: String,
+}
+
+/// Collects course of solve beyond the last iteration: iteration count and last
+/// delta of every epsilon schedule phase, and switches made by hybrid method.
+/// Solves made of several inner solves (Schur complement) pass every phase
+/// several times, their iterations are summed up
+#[derive(Debug, Default, Clone)]
+pub struct SolveTrace {
+    /// Epsilon, iterations and last delta of every phase
+    pub(crate) phases: Vec<(Decimal, usize, Decimal)>,
+    /// Last iteration of old stage, old and new stage
+    pub(crate) switches: Vec<(usize, Stage, Stage)>,
+    /// Iteration after which recurrence of stage restarted
+    pub(crate) restarts: Vec<(usize, Stage)>,
+    /// Operations done by every stage, in order stages were first done, and
+    /// iterations of stage in current inner solve. Iterations tell setup
+    /// iteration from the rest and position in GMRES cycle
+    pub(crate) operations: Vec<(Stage, Operations, usize)>,
+    /// Deltas since the last change of phase or stage
+    deltas: Vec<Decimal>,
+    last: Option<(usize, usize, Stage)>,
+}
+
+impl SolveTrace {
+    pub fn record(&mut self, equation: &Equation, progress: &Progress) {
+        if !equation.epsilon_schedule.is_empty() {
+            while self.phases.len() <= progress.phase {
+                let epsilon = equation.phase_epsilon(self.phases.len());
+                self.phases.push((epsilon, 0, dec!(0)));
+            }
+            let (_, iterations, delta) = &mut self.phases[progress.phase];
+            *iterations += 1;
+            *delta = progress.delta;
+        }
+
+        if let Some((iteration, _, stage)) = self.last.filter(|(_, _, stage)| *stage != progress.stage) {
+            self.switches.push((iteration, stage, progress.stage));
+        }
+        if self.last.is_some_and(|(_, phase, stage)| (phase, stage) != (progress.phase, progress.stage)) {
+            self.deltas.clear();
+        }
+        if progress.restarted {
+            self.restarts.push((progress.iteration, progress.stage));
+        }
+        self.deltas.push(progress.delta);
+        self.last = Some((progress.iteration, progress.phase, progress.stage));
+
+        // inner solves of Schur complement or subsystems start from iteration 1
+        if progress.iteration == 1 {
+            for (_, _, iterations) in &mut self.operations {
+                *iterations = 0;
+            }
+        }
+        let size = progress.iterate.len();
+        match self.operations.iter_mut().find(|(stage, _, _)| *stage == progress.stage) {
+            Some((stage, total, iterations)) => {
+                *total += stage.operations(equation, size, *iterations);
+                *iterations += 1;
+            }
+            None => self.operations.push((progress.stage, progress.stage.operations(equation, size, 0), 1)),
+        }
+    }
+
+    /// Multiplications and divisions of all stages together
+    pub fn total_operations(&self) -> Operations {
+        (self.operations.iter()).fold(Operations::default(), |total, (_, operations, _)| total + *operations)
+    }
+
+    pub(crate) fn operations(&self) -> Option<OperationsMetadata> {
+        if self.operations.is_empty() {
+            return None;
+        }
+        let total = self.total_operations();
+        Some(OperationsMetadata {
+            multiplications: total.multiplications,
+            divisions: total.divisions,
+            by_stage: (self.operations.iter())
+                .map(|(stage, operations, _)| StageOperationsMetadata {
+                    stage: stage.to_string(),
+                    multiplications: operations.multiplications,
+                    divisions: operations.divisions,
+                })
+                .collect(),
+        })
+    }
+
+    /// Rate of the last phase, done by the last kind of iteration
+    pub fn convergence_rate(&self) -> Option<ConvergenceRate> {
+        ConvergenceRate::estimate(&self.deltas)
+    }
+
+    /// Oscillation of deltas in the same part of solve as [`SolveTrace::convergence_rate`].
+    /// Outer `None` means there are too few deltas to tell
+    pub fn oscillation(&self) -> Option<Option<Oscillation>> {
+        (self.deltas.len() > 1).then(|| Oscillation::detect(&self.deltas))
+    }
+
+    pub(crate) fn phases(&self) -> Vec<PhaseMetadata> {
+        self.phases
+            .iter()
+            .map(|(epsilon, iterations, delta)| PhaseMetadata {
+                epsilon: epsilon.to_string(),
+                iterations: *iterations,
+                last_delta: delta.to_string(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn switches(&self) -> Vec<SwitchMetadata> {
+        self.switches
+            .iter()
+            .map(|(iteration, from, to)| SwitchMetadata {
+                iteration: *iteration,
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn restarts(&self) -> Vec<RestartMetadata> {
+        self.restarts
+            .iter()
+            .map(|(iteration, stage)| RestartMetadata {
+                iteration: *iteration,
+                stage: stage.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Remembers the most recent iteration reported by solver
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LastIteration {
+    pub iteration: usize,
+    pub delta: Decimal,
+    pub stage: Option<Stage>,
+}
+
+impl LastIteration {
+    pub fn record(&mut self, progress: &Progress) {
+        self.iteration = progress.iteration;
+        self.delta = progress.delta;
+        self.stage = Some(progress.stage);
+    }
+
+    /// Stage which gave solution at once: its only iteration and delta,
+    /// change from initial guess, say nothing about convergence
+    pub fn direct(&self) -> Option<Stage> {
+        self.stage.filter(Stage::is_direct)
+    }
+}
+
+impl SolveResult {
+    pub fn error(message: impl ToString) -> Self {
+        SolveResult::Error {
+            message: message.to_string().trim_end().to_owned(),
+            warnings: Vec::new(),
+            condition_number: None,
+            spectral_radius: None,
+        }
+    }
+
+    /// Adds spectral radius to solved and failed results
+    pub fn with_spectral_radius(mut self, radius: Option<&SpectralRadius>) -> Self {
+        let radius = radius.map(SpectralRadiusMetadata::from);
+        match &mut self {
+            SolveResult::Solved { metadata, .. } => metadata.spectral_radius = radius,
+            SolveResult::Error { spectral_radius, .. } => *spectral_radius = radius,
+            SolveResult::Parametric { .. } => (),
+        }
+        self
+    }
+
+    /// Adds condition number to solved and failed results
+    pub fn with_condition(mut self, condition: Option<&Condition>) -> Self {
+        let condition = condition.map(ConditionMetadata::from);
+        match &mut self {
+            SolveResult::Solved { metadata, .. } => metadata.condition_number = condition,
+            SolveResult::Error { condition_number, .. } => *condition_number = condition,
+            SolveResult::Parametric { .. } => (),
+        }
+        self
+    }
+
+    /// Adds `warnings` to solved and failed results, parametric ones have no place for them
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        match &mut self {
+            SolveResult::Solved { metadata, .. } => metadata.warnings.extend(warnings),
+            SolveResult::Error { warnings: existing, .. } => existing.extend(warnings),
+            SolveResult::Parametric { .. } => (),
+        }
+        self
+    }
+
+    pub fn parametric(solution: &ParametricSolution) -> Self {
+        let strings =
+            |vector: &DVector<Decimal>| vector.iter().map(|value| value.to_string()).collect();
+        SolveResult::Parametric {
+            rank: solution.rank,
+            particular: strings(&solution.particular),
+            null_space: solution.null_space.iter().map(strings).collect(),
+        }
+    }
+
+    pub fn from_solve(
+        equation: &Equation,
+        outcome: Result<DVector<Decimal>, ESolveError>,
+        last: LastIteration,
+    ) -> Self {
+        let (result, condition) = match outcome {
+            Ok(solution) => {
+                let accuracy = Accuracy::assess(equation, &solution);
+                let residual = residual_vector(equation, &solution);
+                let result = SolveResult::Solved {
+                    solution: solution.iter().map(|value| value.to_string()).collect(),
+                    metadata: Box::new(ResultMetadata {
+                        matrix_size: equation.input_matrix.nrows(),
+                        iterations: last.iteration,
+                        max_iterations: equation.max_iterations,
+                        epsilon: equation.epsilon.to_string(),
+                        tolerance_mode: tolerance_mode(equation.tolerance_mode),
+                        norm: equation.norm.map(|norm| norm.to_string()),
+                        last_delta: last.delta.to_string(),
+                        direct: last.direct().map(|stage| stage.to_string()),
+                        phases: Vec::new(),
+                        switches: Vec::new(),
+                        restarts: Vec::new(),
+                        convergence_rate: None,
+                        oscillation: None,
+                        residual: residual_strings(residual.as_slice()),
+                        residual_norm: Some(
+                            (equation.norm.unwrap_or(Norm::L2).of(residual.iter().copied()))
+                                .normalize()
+                                .to_string(),
+                        ),
+                        weighted_residuals: residual_strings(&weighted_residuals(equation, &solution)),
+                        inverse: None,
+                        accuracy: Some(AccuracyMetadata::from(&accuracy)),
+                        warnings: Vec::new(),
+                        equation_order: equation_order(equation),
+                        condition_number: None,
+                        determinant: None,
+                        operations: None,
+                        spectral_radius: None,
+                        refinement: None,
+                        jitter: None,
+                        rounding: Some(RoundingMetadata::from(&equation.rounding_report())),
+                        norms: Some(NormsMetadata::from(&system_norms(equation))),
+                    }),
+                };
+                (result, accuracy.condition)
+            }
+            Err(err) => (SolveResult::error(err), condition_number(equation)),
+        };
+        result
+            .with_warnings(equation.dominance_warning().iter().map(ToString::to_string).collect())
+            .with_condition(condition.as_ref())
+    }
+
+    /// This result in schema of `version`
+    pub fn document(&self, version: SchemaVersion) -> ResultDocument<'_> {
+        ResultDocument {
+            schema: version,
+            result: self,
+        }
+    }
+
+    /// Same as `status` field of JSON representation
+    pub fn status(&self) -> &'static str {
+        match self {
+            SolveResult::Solved { .. } => "solved",
+            SolveResult::Parametric { .. } => "parametric",
+            SolveResult::Error { .. } => "error",
+        }
+    }
+}
+
+/// What is known about system before it is solved, returned by
+/// `POST /analyze` of HTTP API
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Analysis {
+    pub matrix_size: usize,
+    /// Method input names, or the one chosen for it
+    pub method: String,
+    /// Why `method` was chosen, absent when input names it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method_reason: Option<String>,
+    pub symmetric: bool,
+    pub diagonally_dominant: bool,
+    pub tridiagonal: bool,
+    pub norms: NormsMetadata,
+    /// Absent for systems too large to estimate it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition_number: Option<ConditionMetadata>,
+    /// Absent for methods which are not stationary iterations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spectral_radius: Option<SpectralRadiusMetadata>,
+    /// Same as `solver lint` reports, and dominance warning of solve
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl Analysis {
+    /// Method of `equation` is replaced with chosen one when it is automatic
+    pub fn of(equation: &mut Equation, mut warnings: Vec<String>) -> Self {
+        let mut method_reason = None;
+        if equation.method == Method::Auto {
+            let selection = equation.select_method();
+            equation.method = selection.method;
+            method_reason = Some(selection.reason);
+        }
+        warnings.extend(equation.dominance_warning().iter().map(ToString::to_string));
+        Analysis {
+            matrix_size: equation.input_matrix.nrows(),
+            method: equation.method.to_string(),
+            method_reason,
+            symmetric: equation.is_symmetric(),
+            diagonally_dominant: equation.is_diagonally_dominant(),
+            tridiagonal: equation.is_tridiagonal(),
+            norms: NormsMetadata::from(&system_norms(equation)),
+            condition_number: condition_number(equation).as_ref().map(ConditionMetadata::from),
+            spectral_radius: equation.spectral_radius().as_ref().map(SpectralRadiusMetadata::from),
+            warnings,
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::http::{self, DeadlineReader, Request, Response};
+use crate::result::{serialize_document, SchemaVersion, SolveResult};
+use crate::service::{
+    self, analyze_document, solve_document, solve_document_with_progress, ConnectionSlot, Connections, RequestLimits, ServiceSettings,
+};
+
+/// Description of the API below, kept in sync by hand
+pub const OPENAPI_SPEC: &str = include_str!("openapi.json");
+
+/// Bodies of guests are limited by the largest document their limits let
+/// through, this one applies when their matrix size is not limited
+const MAX_GUEST_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Finished jobs kept for their results, the oldest are forgotten first
+const MAX_FINISHED_JOBS: usize = 1024;
+
+/// Jobs waiting for worker, further submissions are refused
+const MAX_PENDING_JOBS: usize = 1024;
+
+/// Bodies of jobs waiting for worker, further submissions are refused
+const MAX_PENDING_BYTES: usize = 1024 * 1024 * 1024;
+
+/// Whole request, head and body, must arrive within this time
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Response client does not take within this time is abandoned
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum JobStatus {
+    Queued,
+    Running,
+    Finished,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct Progress {
+    iteration: usize,
+    max_iterations: usize,
+    delta: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct Job {
+    id: u64,
+    status: JobStatus,
+    progress: Option<Progress>,
+    #[serde(serialize_with = "serialize_document")]
+    result: Option<SolveResult>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+fn error_response(status: u16, message: &str) -> Response {
+    Response::json(status, &ErrorBody { error: message })
+}
+
+struct PendingJob {
+    id: u64,
+    content: Vec<u8>,
+    limits: RequestLimits,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: HashMap<u64, Job>,
+    pending: VecDeque<PendingJob>,
+    /// Ids of finished jobs, in order they finished
+    finished: VecDeque<u64>,
+    /// Bytes of bodies in `pending`
+    pending_bytes: usize,
+    next_id: u64,
+}
+
+/// Who is making request. Guests are held to configured limits,
+/// requests bearing API token are trusted and unlimited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Guest,
+    Trusted,
+}
+
+/// Access policy of HTTP API
+#[derive(Debug, Default, Clone)]
+pub struct AccessPolicy {
+    pub api_token: Option<String>,
+    /// Reject guests altogether instead of limiting them
+    pub token_required: bool,
+}
+
+impl AccessPolicy {
+    fn role(&self, request: &Request) -> Role {
+        let bearer = request
+            .header("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match (&self.api_token, bearer) {
+            (Some(token), Some(bearer)) if token == bearer.trim() => Role::Trusted,
+            _ => Role::Guest,
+        }
+    }
+}
+
+/// Jobs are solved by fixed amount of worker threads, so long solves
+/// never occupy threads handling requests
+#[derive(Default)]
+struct JobQueue {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+impl JobQueue {
+    /// `None` when queue is full
+    fn submit(&self, content: Vec<u8>, limits: RequestLimits) -> Option<Job> {
+        let mut state = self.state.lock().expect("queue lock is not poisoned");
+        if state.pending.len() >= MAX_PENDING_JOBS || state.pending_bytes + content.len() > MAX_PENDING_BYTES {
+            return None;
+        }
+        state.pending_bytes += content.len();
+        state.next_id += 1;
+        let job = Job {
+            id: state.next_id,
+            status: JobStatus::Queued,
+            progress: None,
+            result: None,
+        };
+        state.jobs.insert(job.id, job.clone());
+        state.pending.push_back(PendingJob {
+            id: job.id,
+            content,
+            limits,
+        });
+        self.available.notify_one();
+        Some(job)
+    }
+
+    fn get(&self, id: u64) -> Option<Job> {
+        let state = self.state.lock().expect("queue lock is not poisoned");
+        state.jobs.get(&id).cloned()
+    }
+
+    fn update(&self, id: u64, change: impl FnOnce(&mut Job)) {
+        let mut state = self.state.lock().expect("queue lock is not poisoned");
+        if let Some(job) = state.jobs.get_mut(&id) {
+            change(job);
+        }
+    }
+
+    fn finish(&self, id: u64, result: SolveResult) {
+        let mut state = self.state.lock().expect("queue lock is not poisoned");
+        if let Some(job) = state.jobs.get_mut(&id) {
+            job.status = JobStatus::Finished;
+            job.result = Some(result);
+        }
+        state.finished.push_back(id);
+        while state.finished.len() > MAX_FINISHED_JOBS {
+            let forgotten = state.finished.pop_front().expect("finished jobs are not empty");
+            state.jobs.remove(&forgotten);
+        }
+    }
+
+    fn next_pending(&self) -> PendingJob {
+        let mut state = self.state.lock().expect("queue lock is not poisoned");
+        loop {
+            if let Some(pending) = state.pending.pop_front() {
+                state.pending_bytes -= pending.content.len();
+                return pending;
+            }
+            state = self
+                .available
+                .wait(state)
+                .expect("queue lock is not poisoned");
+        }
+    }
+
+    fn work(&self, settings: &ServiceSettings) {
+        loop {
+            let PendingJob {
+                id,
+                content,
+                limits,
+            } = self.next_pending();
+            self.update(id, |job| job.status = JobStatus::Running);
+
+            // panic of one job must not take the worker down with it,
+            // or the job stays running and jobs after it stay queued
+            let result = service::catch_crash(|| solve_document_with_progress(
+                content,
+                settings,
+                &limits,
+                |max_iterations| {
+                    self.update(id, |job| {
+                        job.progress = Some(Progress {
+                            iteration: 0,
+                            max_iterations,
+                            delta: None,
+                        })
+                    })
+                },
+                |iteration, delta| {
+                    self.update(id, |job| {
+                        if let Some(progress) = &mut job.progress {
+                            progress.iteration = iteration;
+                            progress.delta = Some(delta.to_string());
+                        }
+                    })
+                },
+            ));
+
+            self.finish(id, result);
+        }
+    }
+}
+
+/// HTTP API:
+/// - `POST /solve` solves input from body right away, failed solve gets 422
+/// - `POST /analyze` reports method, norms and conditioning of input without solving it
+/// - `POST /jobs` queues input from body, responds with job id
+/// - `GET /jobs/:id` reports status, progress and result of the job
+/// - `GET /openapi.json` describes all of the above
+///
+/// Guests are limited by `settings.limits`, see [`AccessPolicy`]. Request
+/// which does not arrive within `REQUEST_TIMEOUT` gets 408, connections
+/// over `MAX_CONNECTIONS` of service get 503
+pub fn run(
+    address: &str,
+    concurrency: usize,
+    settings: ServiceSettings,
+    policy: AccessPolicy,
+) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let connections = Arc::new(Connections::default());
+    let queue = Arc::new(JobQueue::default());
+    let policy = Arc::new(policy);
+    let settings = Arc::new(settings);
+
+    for _ in 0..concurrency {
+        let queue = Arc::clone(&queue);
+        let settings = Arc::clone(&settings);
+        thread::spawn(move || queue.work(&settings));
+    }
+
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let Some(slot) = connections.acquire() else {
+                    // fresh socket takes short response without blocking
+                    let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                    let refused = error_response(503, "Too many connections, try again later");
+                    let _ = http::write_response(&mut &stream, &refused);
+                    continue;
+                };
+                let queue = Arc::clone(&queue);
+                let policy = Arc::clone(&policy);
+                let settings = Arc::clone(&settings);
+                thread::spawn(move || {
+                    if let Err(err) = serve_connection(stream, slot, &queue, &settings, &policy) {
+                        eprintln!("Connection failed: {err}");
+                    }
+                });
+            }
+            Err(err) => eprintln!("Can't accept connection: {err}"),
+        }
+    }
+
+    Ok(())
+}
+
+fn serve_connection(
+    stream: TcpStream,
+    _slot: ConnectionSlot,
+    queue: &JobQueue,
+    settings: &ServiceSettings,
+    policy: &AccessPolicy,
+) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    let max_body_size = |head: &Request| match policy.role(head) {
+        Role::Guest => Some(settings.limits.max_document_size().unwrap_or(MAX_GUEST_BODY_SIZE)),
+        Role::Trusted => None,
+    };
+    let reader = DeadlineReader {
+        stream,
+        deadline: Instant::now() + REQUEST_TIMEOUT,
+    };
+    let response = match http::read_request(&mut BufReader::new(reader), max_body_size) {
+        Ok(request) => route(request, queue, settings, policy),
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            error_response(400, &err.to_string())
+        }
+        Err(err) if err.kind() == io::ErrorKind::TimedOut => {
+            error_response(408, &format!("Request did not arrive within {REQUEST_TIMEOUT:?}"))
+        }
+        Err(err) => return Err(err),
+    };
+
+    http::write_response(&mut writer, &response)
+}
+
+fn route(
+    request: Request,
+    queue: &JobQueue,
+    settings: &ServiceSettings,
+    policy: &AccessPolicy,
+) -> Response {
+    // description must be reachable without token to find out that token is needed
+    if request.method == "GET" && request.path == "/openapi.json" {
+        return Response {
+            status: 200,
+            content_type: "application/json",
+            body: OPENAPI_SPEC.as_bytes().to_vec(),
+        };
+    }
+
+    let role = policy.role(&request);
+    if role == Role::Guest && policy.token_required {
+        return error_response(401, "Valid API token is required");
+    }
+
+    let limits = match role {
+        Role::Guest => settings.limits,
+        Role::Trusted => RequestLimits::NONE,
+    };
+
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("POST", ["solve"]) => {
+            let result = service::catch_crash(|| solve_document(request.body, settings, &limits));
+            // body of failed solve is result too, it often tells why
+            let status = match result.status() {
+                "error" => 422,
+                _ => 200,
+            };
+            Response::json(status, &result.document(SchemaVersion::default()))
+        }
+        ("POST", ["analyze"]) => {
+            let analysis = service::catch_crash_with(|| analyze_document(request.body, settings, &limits), Err);
+            match analysis {
+                Ok(analysis) => Response::json(200, &analysis),
+                Err(err) => error_response(422, err.trim_end()),
+            }
+        }
+        ("POST", ["jobs"]) => match queue.submit(request.body, limits) {
+            Some(job) => Response::json(202, &job),
+            None => error_response(503, "Too many jobs are queued, try again later"),
+        },
+        ("GET", ["jobs", id]) => match id.parse().ok().and_then(|id| queue.get(id)) {
+            Some(job) => Response::json(200, &job),
+            None => error_response(404, "No such job"),
+        },
+        (_, ["solve"] | ["analyze"] | ["jobs"] | ["jobs", _]) => error_response(405, "Method not allowed"),
+        _ => error_response(404, "No such endpoint"),
+    }
+}
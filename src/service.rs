@@ -0,0 +1,422 @@
+use rust_decimal::Decimal;
+use core::cell::Cell;
+use core::fmt;
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json as json;
+use std::fs;
+use std::ops::ControlFlow;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::compression;
+use crate::csv;
+use crate::input::{build_equation, check_declared_size, check_memory, parse_document, EquesionInput, NonInteractiveError};
+use crate::lint;
+use crate::manifest;
+use crate::parallel::Parallelism;
+use crate::result::{Analysis, LastIteration, SolveResult};
+use crate::solver::{ESolveError, Method};
+
+/// Settings shared by every request served by long-lived solver process
+#[derive(Debug, Default, Clone)]
+pub struct ServiceSettings {
+    pub max_memory: Option<u64>,
+    pub parallelism: Parallelism,
+    /// Limits for requests which are not trusted
+    pub limits: RequestLimits,
+}
+
+/// Bytes allowed per value of request document, with quotes, separators and indentation
+const MAX_VALUE_SIZE: usize = 64;
+
+/// Bytes allowed for fields of request document besides matrix and right hand side
+const DOCUMENT_OVERHEAD: usize = 64 * 1024;
+
+/// Compressed requests unpack to at most this much when matrix size is not
+/// limited, so small body can't expand without end
+const MAX_UNPACKED_REQUEST_SIZE: usize = 1024 * 1024 * 1024;
+
+/// Connections daemon, HTTP API and TCP worker serve at the same time,
+/// further ones are turned away
+pub(crate) const MAX_CONNECTIONS: usize = 256;
+
+/// Count of connections being served, see [`MAX_CONNECTIONS`]
+#[derive(Debug, Default)]
+pub(crate) struct Connections {
+    active: AtomicUsize,
+}
+
+impl Connections {
+    /// `None` when every slot is taken
+    pub fn acquire(self: &Arc<Self>) -> Option<ConnectionSlot> {
+        (self.active)
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |active| (active < MAX_CONNECTIONS).then_some(active + 1))
+            .ok()
+            .map(|_| ConnectionSlot(Arc::clone(self)))
+    }
+}
+
+/// Connection counted as served until dropped
+pub(crate) struct ConnectionSlot(Arc<Connections>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Protects shared service from requests which would hog it
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RequestLimits {
+    pub max_matrix_size: Option<usize>,
+    pub max_iterations: Option<usize>,
+    /// Solve is stopped once it takes longer than this
+    pub timeout: Option<Duration>,
+}
+
+impl RequestLimits {
+    pub const NONE: RequestLimits = RequestLimits {
+        max_matrix_size: None,
+        max_iterations: None,
+        timeout: None,
+    };
+
+    /// Size of the largest document these limits let through, `None` when
+    /// matrix size is not limited
+    pub fn max_document_size(&self) -> Option<usize> {
+        let size = self.max_matrix_size?;
+        // matrix, right hand side and initial guess
+        size.checked_mul(size + 2)?
+            .checked_mul(MAX_VALUE_SIZE)?
+            .checked_add(DOCUMENT_OVERHEAD)
+    }
+
+    fn check(&self, parsed: &EquesionInput) -> Result<(), LimitError> {
+        let matrix_size = parsed.input_matrix.len();
+        if let Some(limit) = self.max_matrix_size.filter(|limit| matrix_size > *limit) {
+            return Err(LimitError::MatrixSize { limit });
+        }
+
+        self.check_iterations(parsed.max_iterations)
+    }
+
+    fn check_iterations(&self, iterations: usize) -> Result<(), LimitError> {
+        match self.max_iterations {
+            Some(limit) if iterations > limit => Err(LimitError::Iterations {
+                actual: iterations,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks raw `content` while reading it, before matrix is materialised:
+    /// rows and values are counted and dropped, reading stops at the first one
+    /// above limit. Malformed documents pass, parsing them reports the error
+    fn check_document(&self, content: &[u8]) -> Result<(), LimitError> {
+        if self.max_matrix_size.is_none() && self.max_iterations.is_none() {
+            return Ok(());
+        }
+
+        let probe = LimitProbe {
+            limits: self,
+            exceeded: Cell::new(None),
+        };
+        let _ = (&probe).deserialize(&mut json::Deserializer::from_slice(content));
+        match probe.exceeded.into_inner() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Walks request document without keeping it, remembering the first limit exceeded
+struct LimitProbe<'a> {
+    limits: &'a RequestLimits,
+    exceeded: Cell<Option<LimitError>>,
+}
+
+impl LimitProbe<'_> {
+    fn exceed<E: de::Error>(&self, err: LimitError) -> E {
+        let message = err.to_string();
+        self.exceeded.set(Some(err));
+        E::custom(message)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for &LimitProbe<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for &LimitProbe<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "input document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "input_matrix" => map.next_value_seed(BoundedSeq { probe: self, nested: true })?,
+                "expression_rhs" => map.next_value_seed(BoundedSeq { probe: self, nested: false })?,
+                "max_iterations" => {
+                    let iterations = map.next_value::<usize>()?;
+                    self.limits.check_iterations(iterations).map_err(|err| self.exceed(err))?
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sequence of at most `max_matrix_size` elements, rows of matrix when `nested`
+struct BoundedSeq<'a, 'b> {
+    probe: &'a LimitProbe<'b>,
+    nested: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for BoundedSeq<'_, '_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for BoundedSeq<'_, '_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let limit = self.probe.limits.max_matrix_size;
+        let mut count = 0;
+        loop {
+            let element = match self.nested {
+                true => seq.next_element_seed(BoundedSeq { probe: self.probe, nested: false })?,
+                false => seq.next_element::<IgnoredAny>()?.map(|_| ()),
+            };
+            if element.is_none() {
+                return Ok(());
+            }
+            count += 1;
+            if let Some(limit) = limit.filter(|limit| count > *limit) {
+                return Err(self.probe.exceed(LimitError::MatrixSize { limit }));
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum LimitError {
+    MatrixSize { limit: usize },
+    Iterations { actual: usize, limit: usize },
+    Timeout(Duration),
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::MatrixSize { limit } => write!(
+                f,
+                "Matrix has more than {limit} rows or columns, this service accepts at most {limit}"
+            ),
+            LimitError::Iterations { actual, limit } => write!(
+                f,
+                "Requested {actual} iterations, this service allows at most {limit}"
+            ),
+            LimitError::Timeout(timeout) => {
+                write!(f, "Solve did not finish within {timeout:?} time limit")
+            }
+        }
+    }
+}
+
+/// Solves request document written in the usual input schema. Requests
+/// come from outside, so manifests are not followed and document is checked
+/// against `limits` before it is parsed
+pub fn solve_document(
+    content: Vec<u8>,
+    settings: &ServiceSettings,
+    limits: &RequestLimits,
+) -> SolveResult {
+    solve_document_with_progress(content, settings, limits, |_| {}, |_, _| {})
+}
+
+/// Same as [`solve_document`], reporting `max_iterations` of parsed input
+/// before solve starts and then every finished iteration with its delta
+pub fn solve_document_with_progress(
+    content: Vec<u8>,
+    settings: &ServiceSettings,
+    limits: &RequestLimits,
+    on_start: impl FnOnce(usize),
+    on_iteration: impl FnMut(usize, Decimal),
+) -> SolveResult {
+    match parse_request(content, settings, limits) {
+        Ok(parsed) => solve_input(parsed, None, settings, limits, on_start, on_iteration),
+        Err(err) => SolveResult::error(err),
+    }
+}
+
+/// Analyzes request document without solving it, checked the same way as
+/// by [`solve_document`]
+pub fn analyze_document(content: Vec<u8>, settings: &ServiceSettings, limits: &RequestLimits) -> Result<Analysis, String> {
+    let parsed = parse_request(content, settings, limits)?;
+    limits.check(&parsed).map_err(|err| err.to_string())?;
+    let mut warnings: Vec<String> = lint::lint_input(&parsed).iter().map(ToString::to_string).collect();
+    let mut equation = build_equation(parsed, settings.max_memory).map_err(|err| err.to_string())?;
+    warnings.extend(lint::lint_equation(&equation).iter().map(ToString::to_string));
+    equation.parallelism = settings.parallelism.clone();
+    Ok(Analysis::of(&mut equation, warnings))
+}
+
+/// Solves document of local caller, manifests are resolved relative to
+/// working directory
+pub fn solve_local_document(content: Vec<u8>, settings: &ServiceSettings) -> SolveResult {
+    solve_content(content, Path::new(""), settings, &settings.limits, |_| {}, |_, _| {})
+}
+
+fn parse_request(content: Vec<u8>, settings: &ServiceSettings, limits: &RequestLimits) -> Result<EquesionInput, String> {
+    let limit = limits.max_document_size().unwrap_or(MAX_UNPACKED_REQUEST_SIZE);
+    let content = compression::decompress_within(content, limit).map_err(|err| err.to_string())?;
+
+    if manifest::is_manifest(&content) {
+        return Err("Requests must carry matrix inline, manifests are not followed by service".to_owned());
+    }
+    if csv::is_csv(&content) {
+        return Err("Requests must be JSON documents, CSV input is read only from files".to_owned());
+    }
+
+    limits.check_document(&content).map_err(|err| err.to_string())?;
+    check_declared_size(&content, settings.max_memory).map_err(|err| err.to_string())?;
+    json::from_slice::<EquesionInput>(&content).map_err(|err| NonInteractiveError::from(err).to_string())
+}
+
+/// Solves input file with limits of `settings`. Manifests are resolved
+/// relative to the file
+pub fn solve_file(path: &Path, settings: &ServiceSettings) -> SolveResult {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(err) => return SolveResult::error(format!("Can't read {}: {err}", path.display())),
+    };
+    let base = path.parent().unwrap_or(Path::new(""));
+    solve_content(content, base, settings, &settings.limits, |_| {}, |_, _| {})
+}
+
+fn solve_content(
+    content: Vec<u8>,
+    base: &Path,
+    settings: &ServiceSettings,
+    limits: &RequestLimits,
+    on_start: impl FnOnce(usize),
+    on_iteration: impl FnMut(usize, Decimal),
+) -> SolveResult {
+    let parsed = compression::decompress_if_needed(content)
+        .map_err(NonInteractiveError::from)
+        .and_then(|content| {
+            check_declared_size(&content, settings.max_memory)?;
+            parse_document(content, base)
+        });
+    match parsed {
+        Ok(parsed) => solve_input(
+            parsed,
+            None,
+            settings,
+            limits,
+            on_start,
+            on_iteration,
+        ),
+        Err(err) => SolveResult::error(err),
+    }
+}
+
+thread_local! {
+    /// Depth of [`catch_crash`] calls on this thread
+    static CATCHING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Whether panic on this thread is going to be caught by [`catch_crash`] and
+/// reported as error result, so panic hook need not treat it as crash of process
+pub fn catching_crash() -> bool {
+    CATCHING.with(|depth| depth.get() > 0)
+}
+
+/// Solve which panics is turned into error result, so one broken input does
+/// not take down batch run or worker serving others
+pub(crate) fn catch_crash(solve: impl FnOnce() -> SolveResult) -> SolveResult {
+    catch_crash_with(solve, SolveResult::error)
+}
+
+/// Same as [`catch_crash`], `crashed` makes result of message about panic
+pub(crate) fn catch_crash_with<T>(run: impl FnOnce() -> T, crashed: impl FnOnce(String) -> T) -> T {
+    CATCHING.with(|depth| depth.set(depth.get() + 1));
+    let outcome = panic::catch_unwind(AssertUnwindSafe(run));
+    CATCHING.with(|depth| depth.set(depth.get() - 1));
+    outcome.unwrap_or_else(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|reason| reason.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        crashed(format!("Solver crashed: {reason}"))
+    })
+}
+
+/// Solves already parsed input, `method` overrides the one input names
+pub fn solve_input(
+    parsed: EquesionInput,
+    method: Option<Method>,
+    settings: &ServiceSettings,
+    limits: &RequestLimits,
+    on_start: impl FnOnce(usize),
+    mut on_iteration: impl FnMut(usize, Decimal),
+) -> SolveResult {
+    if let Err(err) = limits.check(&parsed) {
+        return SolveResult::error(err);
+    }
+
+    let mut equation = match build_equation(parsed, settings.max_memory) {
+        Ok(equation) => equation,
+        Err(err) => return SolveResult::error(err),
+    };
+
+    equation.parallelism = settings.parallelism.clone();
+    if let Some(method) = method {
+        equation.method = method;
+    }
+    if let Err(err) = check_memory(&equation, settings.max_memory) {
+        return SolveResult::error(err);
+    }
+    on_start(equation.max_iterations);
+
+    let started = Instant::now();
+    let mut last = LastIteration::default();
+    let outcome = equation.solve_with_progress(|progress| {
+        last.record(progress);
+        on_iteration(progress.iteration, progress.delta);
+        match limits.timeout {
+            Some(timeout) if started.elapsed() > timeout => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        }
+    });
+
+    match outcome {
+        Err(ESolveError::Cancelled) => SolveResult::error(LimitError::Timeout(
+            limits.timeout.expect("solve is cancelled only by timeout"),
+        )),
+        outcome => SolveResult::from_solve(&equation, outcome, last),
+    }
+}
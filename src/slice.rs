@@ -0,0 +1,146 @@
+//! Solving systems given as plain slices, for callers who would rather not
+//! build nalgebra types or input documents. Matrix is row-major, `n * n` values
+
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::solver::{ESolveError, Equation, LinearSolver, Method};
+
+#[derive(Debug, Clone)]
+pub struct SolveOptions {
+    pub epsilon: Decimal,
+    pub max_iterations: usize,
+    /// Ignored by functions naming their method
+    pub method: Method,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions {
+            epsilon: dec!(0.000001),
+            max_iterations: 1000,
+            method: Method::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SliceError {
+    /// Slice length does not match `n`
+    Length {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    EmptySystem,
+    Solve(ESolveError),
+}
+
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SliceError::Length {
+                what,
+                expected,
+                actual,
+            } => write!(f, "Expected {expected} values of {what}, got {actual}"),
+            SliceError::EmptySystem => write!(f, "System has no equations"),
+            SliceError::Solve(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<ESolveError> for SliceError {
+    fn from(err: ESolveError) -> Self {
+        SliceError::Solve(err)
+    }
+}
+
+/// Solves `a x = b` by method of `options`
+pub fn solve(
+    a: &[Decimal],
+    b: &[Decimal],
+    n: usize,
+    options: &SolveOptions,
+) -> Result<Vec<Decimal>, SliceError> {
+    if n == 0 {
+        return Err(SliceError::EmptySystem);
+    }
+    let check = |what, expected, actual| {
+        if expected == actual {
+            Ok(())
+        } else {
+            Err(SliceError::Length {
+                what,
+                expected,
+                actual,
+            })
+        }
+    };
+    check("matrix", n * n, a.len())?;
+    check("right hand side", n, b.len())?;
+
+    let mut equation = Equation::new(
+        DMatrix::from_row_slice(n, n, a),
+        DVector::from_column_slice(b),
+        options.max_iterations,
+        options.epsilon,
+    );
+    equation.method = options.method;
+    // stationary methods divide by diagonal, input documents are checked
+    // for this when read and slices are checked here
+    if matches!(
+        equation.method,
+        Method::GaussSeidel | Method::Jacobi | Method::Ssor | Method::Hybrid
+    ) {
+        if let Some(row) = (0..n).find(|row| equation.input_matrix[(*row, *row)].is_zero()) {
+            return Err(ESolveError::ZeroDiagonal { row }.into());
+        }
+    }
+
+    let solution = options.method.solve(&equation)?;
+    Ok(solution.values.iter().copied().collect())
+}
+
+pub fn solve_gauss_seidel(
+    a: &[Decimal],
+    b: &[Decimal],
+    n: usize,
+    options: &SolveOptions,
+) -> Result<Vec<Decimal>, SliceError> {
+    solve_by(Method::GaussSeidel, a, b, n, options)
+}
+
+pub fn solve_jacobi(
+    a: &[Decimal],
+    b: &[Decimal],
+    n: usize,
+    options: &SolveOptions,
+) -> Result<Vec<Decimal>, SliceError> {
+    solve_by(Method::Jacobi, a, b, n, options)
+}
+
+/// Gaussian elimination, `options.max_iterations` and `epsilon` do not matter
+pub fn solve_direct(
+    a: &[Decimal],
+    b: &[Decimal],
+    n: usize,
+    options: &SolveOptions,
+) -> Result<Vec<Decimal>, SliceError> {
+    solve_by(Method::Gauss, a, b, n, options)
+}
+
+fn solve_by(
+    method: Method,
+    a: &[Decimal],
+    b: &[Decimal],
+    n: usize,
+    options: &SolveOptions,
+) -> Result<Vec<Decimal>, SliceError> {
+    let options = SolveOptions {
+        method,
+        ..options.clone()
+    };
+    solve(a, b, n, &options)
+}
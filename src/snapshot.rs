@@ -0,0 +1,69 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// One file per chosen iteration:
+///
+/// ```json
+/// { "iteration": 5, "delta": "0.0123", "iterate": ["1.02", "1.98", "3.01"] }
+/// ```
+#[derive(Serialize)]
+struct Snapshot {
+    iteration: usize,
+    delta: String,
+    iterate: Vec<String>,
+}
+
+/// Writes approximations at chosen iterations to `<directory>/iterate-<iteration>.json`
+pub struct Snapshots {
+    at: Vec<usize>,
+    directory: PathBuf,
+    taken: Vec<usize>,
+}
+
+impl Snapshots {
+    pub fn new(at: Vec<usize>, directory: PathBuf) -> Self {
+        Snapshots {
+            at,
+            directory,
+            taken: Vec::new(),
+        }
+    }
+
+    pub fn is_wanted(&self, iteration: usize) -> bool {
+        self.at.contains(&iteration)
+    }
+
+    pub fn take(
+        &mut self,
+        iteration: usize,
+        delta: Decimal,
+        iterate: &DVector<Decimal>,
+    ) -> Result<(), String> {
+        let snapshot = Snapshot {
+            iteration,
+            delta: delta.to_string(),
+            iterate: iterate.iter().map(|value| value.to_string()).collect(),
+        };
+        let path = self.directory.join(format!("iterate-{iteration}.json"));
+        let content = serde_json::to_string_pretty(&snapshot)
+            .expect("snapshot consists of plain data")
+            + "\n";
+
+        fs::write(&path, content)
+            .map_err(|err| format!("Can't write {}: {err}", path.display()))?;
+        self.taken.push(iteration);
+        Ok(())
+    }
+
+    /// Chosen iterations solve finished before
+    pub fn missed(&self) -> Vec<usize> {
+        self.at
+            .iter()
+            .copied()
+            .filter(|iteration| !self.taken.contains(iteration))
+            .collect()
+    }
+}
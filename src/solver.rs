@@ -1,58 +1,1079 @@
+use core::fmt;
+use std::ops::ControlFlow;
+use std::str::FromStr;
 use nalgebra::{DMatrix, DVector};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-#[derive(Debug)]
+use crate::norm::Norm;
+use crate::parallel::Parallelism;
+
+mod aitken;
+mod bicgstab;
+mod block;
+mod cg;
+mod chebyshev;
+mod cholesky;
+mod determinant;
+mod direct;
+mod drift;
+mod fixed;
+mod gauss_jordan;
+mod gmres;
+mod guess;
+mod iterations;
+mod lu;
+mod operations;
+mod qr;
+mod refinement;
+mod richardson;
+mod rounding;
+mod parameters;
+mod parametric;
+mod preconditioner;
+mod projection;
+mod schur;
+mod selection;
+mod small;
+mod spectral;
+mod thomas;
+mod tolerance;
+
+use aitken::Aitken;
+use bicgstab::BiCgStab;
+use block::BlockSweep;
+use cg::ConjugateGradient;
+use chebyshev::Chebyshev;
+use gmres::Gmres;
+pub use cholesky::CholeskyFactorization;
+pub use determinant::Determinant;
+pub use drift::RESIDUAL_CHECK_PERIOD;
+pub use guess::InitialGuess;
+pub use iterations::{IterationState, Iterations};
+pub use lu::LuFactorization;
+pub use operations::Operations;
+pub use qr::QrFactorization;
+pub use refinement::Refinement;
+pub use rounding::{Rounding, RoundingLog, RoundingReport};
+pub use parameters::{Bound, Parameter, ParameterError, ParameterSpec, Range};
+pub use parametric::{ParametricSolution, RankAnalysis};
+pub use preconditioner::{DiagonalPreconditioner, IdentityPreconditioner, Ilu0, Preconditioning};
+pub use projection::Bounds;
+pub use selection::{DominanceWarning, Selection, DIRECT_MAX_SIZE};
+pub use small::{solve_small, SmallSolution, SMALL_MAX_SIZE};
+pub use spectral::{Guarantee, SpectralRadius, Verdict};
+pub use tolerance::ToleranceMode;
+
+#[derive(Debug, Clone)]
 pub struct Equation {
     pub input_matrix: DMatrix<Decimal>,
     pub expression_rhs: DVector<Decimal>,
     pub max_iterations: usize,
     pub epsilon: Decimal,
+    /// How change of iteration is compared with epsilon
+    pub tolerance_mode: ToleranceMode,
+    /// Norm of change of iteration and of residual. Largest change of
+    /// component and Euclidean norm of residual when `None`
+    pub norm: Option<Norm>,
+    pub parallelism: Parallelism,
+    /// System is split into 2x2 blocks `[[A, B], [C, D]]` with `A` of this size
+    /// and solved through Schur complement of `A`
+    pub schur_block: Option<usize>,
+    /// Looser epsilons to converge to before `epsilon`, one phase each.
+    /// Every phase starts from approximation of previous one and may take
+    /// up to `max_iterations`
+    pub epsilon_schedule: Vec<Decimal>,
+    pub method: Method,
+    /// Gauss-Seidel and SSOR updates are `x + relaxation * (x_gs - x)`:
+    /// 1 keeps plain method, values in `(1, 2)` over-relax (SOR). Damps
+    /// Jacobi updates the same way (weighted Jacobi), see [`Method::parameters`]
+    pub relaxation: Decimal,
+    /// Unknowns with known values, by index, ascending. They are eliminated
+    /// before solving, so their equations are not used at all
+    pub fixed: Vec<(usize, Decimal)>,
+    /// Box constraints of unknowns by index, ascending, see [`Bounds`]
+    pub bounds: Vec<(usize, Bounds)>,
+    /// Sizes of diagonal blocks, in order, covering the whole matrix. Gauss-Seidel
+    /// solves every block directly per sweep when given. Empty for plain method
+    pub blocks: Vec<usize>,
+    /// Parameter of Richardson iteration, estimated when absent,
+    /// see [`Equation::richardson_tau`]
+    pub tau: Option<Decimal>,
+    /// GMRES rebuilds its subspace after this many iterations
+    pub restart: usize,
+    /// Allow faster algorithm to replace chosen one when structure of matrix
+    /// permits, see [`Equation::uses_thomas`]
+    pub fast_path: bool,
+    /// Applied over stationary method, `None` keeps plain method
+    pub acceleration: Acceleration,
+    /// Applied by conjugate gradient, GMRES and BiCGSTAB
+    pub preconditioning: Preconditioning,
+    /// Equations were swapped to put non-zero, dominant entries on diagonal:
+    /// row `i` of matrix is equation `row_order[i]` of input, from 0.
+    /// Empty when equations are in input order, see [`Equation::input_row`]
+    pub row_order: Vec<usize>,
+    /// Steps of iterative refinement after solve, 0 for none. Not done by
+    /// solve itself, see [`Equation::refine`]
+    pub refinement_steps: usize,
+    /// How products of row sums are rounded
+    pub rounding: Rounding,
+    /// Worst rounding of products so far, see [`Equation::rounding_report`]
+    pub rounding_log: RoundingLog,
+    /// Approximation iterations start from. Direct methods do not use it
+    pub initial_guess: InitialGuess,
+}
+
+/// Approximate inverse of matrix, applied to residual by Krylov methods.
+/// Good preconditioner `M` is cheap to apply and makes `M^-1 A` much closer
+/// to identity than `A`, so far fewer iterations are needed
+pub trait Preconditioner {
+    /// `M^-1 r`
+    fn apply(&self, residual: &DVector<Decimal>) -> DVector<Decimal>;
+}
+
+/// Method of solving linear systems. Every [`Method`] is one, so callers can
+/// run all of [`solvers`] over the same system uniformly
+pub trait LinearSolver {
+    /// Name to tell solver by in output
+    fn name(&self) -> String;
+
+    /// Solves `equation` with its settings, whatever method it names
+    fn solve(&self, equation: &Equation) -> Result<Solution, ESolveError>;
+}
+
+/// Result of [`LinearSolver::solve`]
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub values: DVector<Decimal>,
+    /// Iterations through all phases, direct methods do a single one
+    pub iterations: usize,
+    /// Largest change of component during the last iteration
+    pub last_delta: Decimal,
+}
+
+/// Technique applied on top of stationary method to converge in fewer iterations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Acceleration {
+    #[default]
+    None,
+    /// Chebyshev semi-iteration over Jacobi, or over symmetric sweep for
+    /// Gauss-Seidel and SSOR. `spectrum` bounds real eigenvalues of iteration
+    /// matrix of base method from below and above, upper bound is below 1.
+    /// When absent it is estimated: from Gershgorin discs for Jacobi when they
+    /// allow, otherwise from convergence of first plain iterations
+    Chebyshev { spectrum: Option<(Decimal, Decimal)> },
+    /// Aitken delta-squared extrapolation of approximation every `period`
+    /// iterations of stationary method, at least 3
+    Aitken { period: usize },
+}
+
+/// Iterations between Aitken extrapolations unless asked otherwise
+pub const DEFAULT_AITKEN_PERIOD: usize = 5;
+
+impl Acceleration {
+    /// Whether acceleration applies to method and constraints of `equation`
+    fn supports(&self, equation: &Equation) -> bool {
+        match self {
+            Acceleration::None => true,
+            Acceleration::Chebyshev { .. } => {
+                matches!(equation.method, Method::Jacobi | Method::GaussSeidel | Method::Ssor)
+                    && equation.bounds.is_empty()
+                    && equation.blocks.is_empty()
+            }
+            Acceleration::Aitken { .. } => matches!(
+                equation.method,
+                Method::Jacobi | Method::GaussSeidel | Method::Richardson | Method::Ssor | Method::Hybrid
+            ),
+        }
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Method {
+    #[default]
+    GaussSeidel,
+    /// Every component of iteration is computed from previous approximation only
+    Jacobi,
+    /// Simple iteration `x + tau (b - A x)`, see [`Equation::richardson_tau`]
+    Richardson,
+    /// Symmetric SOR: every iteration is forward Gauss-Seidel sweep followed
+    /// by backward one, which suits symmetric systems
+    Ssor,
+    /// Gaussian elimination with partial pivoting instead of iterations.
+    /// Gives solution whenever matrix is not singular
+    Gauss,
+    /// Gauss-Jordan elimination, clears every column above diagonal too.
+    /// Same elimination of `[A | I]` gives inverse, see [`Equation::inverse`]
+    GaussJordan,
+    /// LU factorization with partial pivoting, see [`Equation::factor_lu`]
+    Lu,
+    /// Cholesky factorization, only for symmetric positive definite matrices
+    Cholesky,
+    /// Householder QR factorization, stable for nearly singular matrices
+    Qr,
+    /// Conjugate gradient, iterative method for symmetric positive definite matrices
+    ConjugateGradient,
+    /// Restarted GMRES, iterative method for general non-singular matrices
+    Gmres,
+    /// BiCGSTAB, iterative method for general non-singular matrices
+    BiCgStab,
+    /// Starts with Jacobi iterations, which are cheaper and trivially parallel.
+    /// When convergence stalls, switches to Gauss-Seidel, and when that stalls
+    /// too, finishes with direct elimination
+    Hybrid,
+    /// Chosen by structure of matrix when solve starts, see [`Equation::select_method`]
+    Auto,
+}
+
+impl FromStr for Method {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "gauss-seidel" | "seidel" => Ok(Method::GaussSeidel),
+            "jacobi" => Ok(Method::Jacobi),
+            "richardson" => Ok(Method::Richardson),
+            "ssor" => Ok(Method::Ssor),
+            "gauss" | "direct" => Ok(Method::Gauss),
+            "gauss-jordan" => Ok(Method::GaussJordan),
+            "lu" => Ok(Method::Lu),
+            "cholesky" => Ok(Method::Cholesky),
+            "qr" => Ok(Method::Qr),
+            "cg" => Ok(Method::ConjugateGradient),
+            "gmres" => Ok(Method::Gmres),
+            "bicgstab" => Ok(Method::BiCgStab),
+            "hybrid" => Ok(Method::Hybrid),
+            "auto" => Ok(Method::Auto),
+            _ => Err(
+                "Expected one of: gauss-seidel, jacobi, richardson, ssor, gauss, gauss-jordan, lu, cholesky, qr, cg, gmres, bicgstab, hybrid, auto".to_owned(),
+            ),
+        }
+    }
+}
+
+/// Name method is chosen by, the one [`Method::from_str`] accepts
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Method::GaussSeidel => "gauss-seidel",
+            Method::Jacobi => "jacobi",
+            Method::Richardson => "richardson",
+            Method::Ssor => "ssor",
+            Method::Gauss => "gauss",
+            Method::GaussJordan => "gauss-jordan",
+            Method::Lu => "lu",
+            Method::Cholesky => "cholesky",
+            Method::Qr => "qr",
+            Method::ConjugateGradient => "cg",
+            Method::Gmres => "gmres",
+            Method::BiCgStab => "bicgstab",
+            Method::Hybrid => "hybrid",
+            Method::Auto => "auto",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Every method except [`Method::Auto`], which picks one of them
+pub const METHODS: [Method; 13] = [
+    Method::GaussSeidel,
+    Method::Jacobi,
+    Method::Richardson,
+    Method::Ssor,
+    Method::Gauss,
+    Method::GaussJordan,
+    Method::Lu,
+    Method::Cholesky,
+    Method::Qr,
+    Method::ConjugateGradient,
+    Method::Gmres,
+    Method::BiCgStab,
+    Method::Hybrid,
+];
+
+/// Registered solvers, one per method of [`METHODS`]
+pub fn solvers() -> impl Iterator<Item = &'static dyn LinearSolver> {
+    METHODS.iter().map(|method| method as &dyn LinearSolver)
+}
+
+/// Solves with `equation` switched to this method, other settings are kept
+impl LinearSolver for Method {
+    fn name(&self) -> String {
+        self.to_string()
+    }
+
+    fn solve(&self, equation: &Equation) -> Result<Solution, ESolveError> {
+        let mut equation = equation.clone();
+        equation.method = *self;
+        let mut last = (0, dec!(0));
+        let values = equation.solve_with_progress(|progress| {
+            last = (progress.iteration, progress.delta);
+            ControlFlow::Continue(())
+        })?;
+        Ok(Solution {
+            values,
+            iterations: last.0,
+            last_delta: last.1,
+        })
+    }
+}
+
+impl Method {
+    /// Gives solution by elimination or factorization instead of iterations
+    pub fn is_direct(&self) -> bool {
+        matches!(
+            self,
+            Method::Gauss | Method::GaussJordan | Method::Lu | Method::Cholesky | Method::Qr
+        )
+    }
+}
+
+/// Kind of iteration solver is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Jacobi,
+    GaussSeidel,
+    /// Gauss-Seidel over blocks of unknowns, see [`Equation::blocks`]
+    BlockGaussSeidel,
+    Richardson,
+    /// Forward and backward Gauss-Seidel sweep
+    Ssor,
+    /// Gaussian elimination, done as single final iteration
+    Direct,
+    /// Gauss-Jordan elimination, done as single iteration
+    GaussJordan,
+    /// Solve through LU factors, done as single iteration
+    Lu,
+    /// Solve through Cholesky factor, done as single iteration
+    Cholesky,
+    /// Solve through QR factors, done as single iteration
+    Qr,
+    ConjugateGradient,
+    Gmres,
+    BiCgStab,
+    /// Gauss-Seidel replaced with tridiagonal elimination, done as single iteration
+    Thomas,
+    /// Chebyshev acceleration of Jacobi or symmetric Gauss-Seidel sweep
+    Chebyshev,
+    /// Block Gauss-Seidel replaced with block tridiagonal elimination, done as single iteration
+    BlockThomas,
+}
+
+impl Stage {
+    /// Gives solution in single step
+    pub fn is_direct(&self) -> bool {
+        matches!(
+            self,
+            Stage::Direct
+                | Stage::GaussJordan
+                | Stage::Lu
+                | Stage::Cholesky
+                | Stage::Qr
+                | Stage::Thomas
+                | Stage::BlockThomas
+        )
+    }
+
+    /// Updates approximation by the same rule every iteration
+    fn is_stationary(&self) -> bool {
+        matches!(
+            self,
+            Stage::Jacobi | Stage::GaussSeidel | Stage::BlockGaussSeidel | Stage::Richardson | Stage::Ssor
+        )
+    }
+
+    /// Stage hybrid method switches to when this one stalls
+    fn next(&self) -> Stage {
+        match self {
+            Stage::Jacobi => Stage::GaussSeidel,
+            _ => Stage::Direct,
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Jacobi => write!(f, "Jacobi"),
+            Stage::GaussSeidel => write!(f, "Gauss-Seidel"),
+            Stage::BlockGaussSeidel => write!(f, "block Gauss-Seidel"),
+            Stage::Richardson => write!(f, "Richardson"),
+            Stage::Ssor => write!(f, "SSOR"),
+            Stage::Direct => write!(f, "direct elimination"),
+            Stage::GaussJordan => write!(f, "Gauss-Jordan elimination"),
+            Stage::Lu => write!(f, "LU decomposition"),
+            Stage::Cholesky => write!(f, "Cholesky decomposition"),
+            Stage::Qr => write!(f, "QR decomposition"),
+            Stage::ConjugateGradient => write!(f, "conjugate gradient"),
+            Stage::Gmres => write!(f, "GMRES"),
+            Stage::BiCgStab => write!(f, "BiCGSTAB"),
+            Stage::Thomas => write!(f, "Thomas algorithm"),
+            Stage::Chebyshev => write!(f, "Chebyshev acceleration"),
+            Stage::BlockThomas => write!(f, "block Thomas algorithm"),
+        }
+    }
+}
+
+/// Parses names [`Stage`] is displayed with
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const STAGES: [Stage; 16] = [
+            Stage::Jacobi,
+            Stage::GaussSeidel,
+            Stage::BlockGaussSeidel,
+            Stage::Richardson,
+            Stage::Ssor,
+            Stage::Direct,
+            Stage::GaussJordan,
+            Stage::Lu,
+            Stage::Cholesky,
+            Stage::Qr,
+            Stage::ConjugateGradient,
+            Stage::Gmres,
+            Stage::BiCgStab,
+            Stage::Thomas,
+            Stage::Chebyshev,
+            Stage::BlockThomas,
+        ];
+        STAGES
+            .into_iter()
+            .find(|stage| stage.to_string() == value)
+            .ok_or_else(|| format!("Unknown stage {value}"))
+    }
+}
+
+/// Iteration counts as slow when its delta is not at least this much
+/// smaller than delta of previous one
+const STALL_RATIO: Decimal = dec!(0.9);
+
+/// Hybrid method switches after this many slow iterations in a row
+const STALL_WINDOW: usize = 3;
+
+/// Iterations stagnate when this many in a row bring delta no lower than
+/// the smallest one so far
+const STAGNATION_WINDOW: usize = 50;
+
+/// Stagnating iterations diverge instead when delta grew this many times
+/// over the smallest one
+const DIVERGENCE_GROWTH: Decimal = dec!(1000);
+
+/// Failure of iterations which did not reach epsilon, by the smallest delta
+/// and the last one: they diverge when delta grew [`DIVERGENCE_GROWTH`]
+/// times over the smallest, otherwise they ran out of iterations
+pub(crate) fn exhausted(best_delta: Decimal, last_delta: Decimal) -> ESolveError {
+    match best_delta.checked_mul(DIVERGENCE_GROWTH) {
+        Some(bound) if last_delta <= bound => ESolveError::MaxIterationsExceeded { last_delta },
+        _ => ESolveError::Diverged {
+            growth_factor: last_delta.checked_div(best_delta),
+        },
+    }
+}
+
+/// Row whose sum, or unknown expressed from it, does not fit into Decimal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Overflow {
+    pub row: usize,
+}
+
+impl From<Overflow> for ESolveError {
+    /// Iteration is not known where overflow is met, [`Equation::solve_with_progress`]
+    /// fills it in
+    fn from(Overflow { row }: Overflow) -> Self {
+        ESolveError::NumericOverflow { row, iteration: 0 }
+    }
+}
+
+/// Restart of GMRES unless asked otherwise, keeps basis of large systems small
+pub const DEFAULT_RESTART: usize = 30;
+
+/// Pivots of elimination at most this share of largest matrix entry are
+/// treated as zero: they are rounding leftovers of cancelled values, and
+/// division by them gives noise instead of solution
+const PIVOT_TOLERANCE: Decimal = dec!(0.000000000000000001);
+
+/// Largest pivot of `matrix` which [`PIVOT_TOLERANCE`] treats as zero
+fn pivot_tolerance(matrix: &DMatrix<Decimal>) -> Decimal {
+    matrix.iter().map(|value| value.abs()).fold(dec!(0), Decimal::max) * PIVOT_TOLERANCE
+}
+
+/// State of solve after finished iteration
+pub struct Progress<'a> {
+    /// Counted through all phases
+    pub iteration: usize,
+    /// Index into [`Equation::epsilon_schedule`], the last phase solves to `epsilon`
+    pub phase: usize,
+    /// Largest change of component during iteration
+    pub delta: Decimal,
+    pub stage: Stage,
+    /// Approximation after iteration, in unknown order of solved system
+    pub iterate: &'a DVector<Decimal>,
+    /// Recurrence of Krylov method started over after iteration: residual
+    /// it carried drifted from true one, see [`RESIDUAL_CHECK_PERIOD`]
+    pub restarted: bool,
+}
+
+#[derive(Debug)]
 pub enum ESolveError {
-    Diverge,
+    /// Iteration budget ran out before delta reached epsilon
+    MaxIterationsExceeded { last_delta: Decimal },
+    /// Delta grew `growth_factor` times over its smallest value. `None` when
+    /// method broke down instead, or growth does not fit into Decimal
+    Diverged { growth_factor: Option<Decimal> },
+    /// Delta stopped decreasing above epsilon: for [`STAGNATION_WINDOW`]
+    /// iterations it stayed above `best_delta` of iteration `at_iteration`
+    Stagnated { best_delta: Decimal, at_iteration: usize },
+    /// Matrix is singular or so close to it that pivot left in `column`, from
+    /// 0, is rounding noise. `None` when it was found by rank check instead
+    Singular { column: Option<usize> },
+    /// Caller asked to stop before solution was found
+    Cancelled,
+    /// Schur complement can't be solved by iterations
+    SchurZeroDiagonal { row: usize },
+    /// Cholesky factorization or conjugate gradient was asked for matrix they do not apply to
+    NotPositiveDefinite,
+    /// Bounds were given for method which does not update unknowns one by one
+    BoundsUnsupported,
+    /// Diagonal block of block Gauss-Seidel is singular, by index from 0
+    SingularBlock { block: usize },
+    /// Blocks were given for method other than Gauss-Seidel
+    BlocksUnsupported,
+    /// Acceleration was asked for method, or constraints, it does not apply to
+    AccelerationUnsupported(Acceleration),
+    /// Preconditioner was given for method other than Krylov one
+    PreconditionerUnsupported,
+    /// Preconditioner can't be built, zero pivot met in row by index from 0
+    ZeroPivot { row: usize },
+    /// Unknown can't be expressed from its equation. Systems built
+    /// through input are checked for this beforehand
+    ZeroDiagonal { row: usize },
+    /// Setting is not taken by method or is out of its range
+    InvalidParameter(ParameterError),
+    /// Given [`Equation::initial_guess`] has not one value per unknown
+    InitialGuessSize { expected: usize, actual: usize },
+    /// Sum of row, by index from 0, or unknown expressed from it outgrew
+    /// Decimal on iteration, from 1
+    NumericOverflow { row: usize, iteration: usize },
+}
+
+impl fmt::Display for ESolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ESolveError::MaxIterationsExceeded { last_delta } => write!(
+                f,
+                "Epsilon was not reached within max_iterations, last delta is {}",
+                last_delta.normalize()
+            ),
+            ESolveError::Diverged { growth_factor: Some(growth) } => write!(
+                f,
+                "Solution approximation diverges: delta grew {} times over its smallest value",
+                growth.round_sf(3).unwrap_or(*growth).normalize()
+            ),
+            ESolveError::Diverged { growth_factor: None } => write!(
+                f,
+                "Solution approximation diverges. Equesions do not have solution"
+            ),
+            ESolveError::Stagnated { best_delta, at_iteration } => write!(
+                f,
+                "Iterations stagnated: for {STAGNATION_WINDOW} iterations delta stayed above {} of iteration {at_iteration}. Epsilon is out of reach of this method",
+                best_delta.normalize()
+            ),
+            ESolveError::Singular { column: Some(column) } => write!(
+                f,
+                "Matrix is singular or numerically singular: no pivot above rounding noise is left in column {}. System has no unique solution",
+                column + 1
+            ),
+            ESolveError::Singular { column: None } => write!(
+                f,
+                "Matrix is singular or numerically singular, its rank is below its size. System has no unique solution"
+            ),
+            ESolveError::Cancelled => write!(f, "Solve was cancelled"),
+            ESolveError::SchurZeroDiagonal { row } => write!(
+                f,
+                "Schur complement has zero on diagonal in row {row}. Is coupling block rank deficient?"
+            ),
+            ESolveError::NotPositiveDefinite => write!(
+                f,
+                "Matrix is not symmetric positive definite, which chosen method requires"
+            ),
+            ESolveError::BoundsUnsupported => write!(
+                f,
+                "Bounds of unknowns are applied only by gauss-seidel, jacobi, richardson and ssor methods"
+            ),
+            ESolveError::SingularBlock { block } => {
+                write!(f, "Diagonal block {} is singular, it can't be solved directly", block + 1)
+            }
+            ESolveError::BlocksUnsupported => write!(f, "Blocks of unknowns are used only by gauss-seidel method"),
+            ESolveError::AccelerationUnsupported(Acceleration::Aitken { .. }) => write!(
+                f,
+                "Aitken extrapolation applies only to jacobi, gauss-seidel, richardson, ssor and hybrid methods"
+            ),
+            ESolveError::PreconditionerUnsupported => {
+                write!(f, "Preconditioner is applied only by cg, gmres and bicgstab methods")
+            }
+            ESolveError::ZeroPivot { row } => {
+                write!(f, "Preconditioner can't be built: zero pivot in row {}", row + 1)
+            }
+            ESolveError::AccelerationUnsupported(_) => write!(
+                f,
+                "Chebyshev acceleration applies only to jacobi, gauss-seidel and ssor methods without bounds or blocks"
+            ),
+            ESolveError::ZeroDiagonal { row } => {
+                write!(f, "Zero on diagonal in row {}, Gauss-Seidel can't be applied", row + 1)
+            }
+            ESolveError::InvalidParameter(err) => write!(f, "{err}"),
+            ESolveError::InitialGuessSize { expected, actual } => write!(
+                f,
+                "Initial guess has {actual} values, but system has {expected} unknowns"
+            ),
+            ESolveError::NumericOverflow { row, iteration } => write!(
+                f,
+                "Numbers outgrew Decimal in row {} on iteration {iteration}: coefficients or approximation are too large",
+                row + 1
+            ),
+        }
+    }
+}
+
+impl ESolveError {
+    /// Error which checks of input rule out: solve built through input and
+    /// ending with it has hit a bug
+    pub fn is_internal(&self) -> bool {
+        matches!(self, ESolveError::ZeroDiagonal { .. })
+    }
 }
 
 impl Equation {
-    pub fn solve(&self) -> Result<DVector<Decimal>, ESolveError> {
+    /// Plain Gauss-Seidel over whole system: no blocks, constraints or relaxation
+    pub fn new(
+        input_matrix: DMatrix<Decimal>,
+        expression_rhs: DVector<Decimal>,
+        max_iterations: usize,
+        epsilon: Decimal,
+    ) -> Self {
+        Equation {
+            input_matrix,
+            expression_rhs,
+            max_iterations,
+            epsilon,
+            tolerance_mode: ToleranceMode::Absolute,
+            norm: None,
+            parallelism: Parallelism::default(),
+            schur_block: None,
+            epsilon_schedule: Vec::new(),
+            method: Method::default(),
+            relaxation: Decimal::ONE,
+            fixed: Vec::new(),
+            bounds: Vec::new(),
+            blocks: Vec::new(),
+            tau: None,
+            restart: DEFAULT_RESTART,
+            fast_path: true,
+            acceleration: Acceleration::None,
+            preconditioning: Preconditioning::None,
+            row_order: Vec::new(),
+            refinement_steps: 0,
+            rounding: Rounding::Native,
+            rounding_log: RoundingLog::default(),
+            initial_guess: InitialGuess::default(),
+        }
+    }
+
+    /// Equation of input, from 0, which is `row` of matrix
+    pub fn input_row(&self, row: usize) -> usize {
+        self.row_order.get(row).copied().unwrap_or(row)
+    }
+
+    /// `on_iteration` receives [`Progress`] of every finished iteration.
+    /// Returning [`ControlFlow::Break`] stops solve with [`ESolveError::Cancelled`]
+    pub fn solve_with_progress(
+        &self,
+        on_iteration: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<DVector<Decimal>, ESolveError> {
+        if self.method == Method::Auto {
+            let mut chosen = self.clone();
+            chosen.method = self.select_method().method;
+            return chosen.solve_with_progress(on_iteration);
+        }
+        self.check_parameters().map_err(ESolveError::InvalidParameter)?;
+        match &self.initial_guess {
+            InitialGuess::Given(guess) if guess.len() != self.input_matrix.nrows() => {
+                return Err(ESolveError::InitialGuessSize {
+                    expected: self.input_matrix.nrows(),
+                    actual: guess.len(),
+                })
+            }
+            _ => (),
+        }
+        if !self.acceleration.supports(self) {
+            return Err(ESolveError::AccelerationUnsupported(self.acceleration));
+        }
+        if self.preconditioning != Preconditioning::None
+            && !matches!(self.method, Method::ConjugateGradient | Method::Gmres | Method::BiCgStab)
+        {
+            return Err(ESolveError::PreconditionerUnsupported);
+        }
+        if !self.bounds.is_empty() && !self.method.supports_bounds() {
+            return Err(ESolveError::BoundsUnsupported);
+        }
+        if !self.blocks.is_empty() && self.method != Method::GaussSeidel {
+            return Err(ESolveError::BlocksUnsupported);
+        }
+        if !self.fixed.is_empty() {
+            return self.solve_fixed(on_iteration);
+        }
+
+        match self.schur_block {
+            Some(block) => self.solve_schur(block, on_iteration),
+            None => self.iterate(on_iteration),
+        }
+    }
+
+    /// Epsilon which phase converges to
+    pub fn phase_epsilon(&self, phase: usize) -> Decimal {
+        self.epsilon_schedule
+            .get(phase)
+            .copied()
+            .unwrap_or(self.epsilon)
+    }
+
+    fn iterate(
+        &self,
+        mut on_iteration: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<DVector<Decimal>, ESolveError> {
         let matrix_size = self.input_matrix.column_iter().count();
-        let mut k: usize = 1;
-        let mut result_vector = DVector::from_element(matrix_size, dec!(1));
+        let mut result_vector = self.initial_guess.start(self);
+        let mut total = 0;
+        let mut stage = match self.method {
+            Method::Jacobi | Method::GaussSeidel | Method::Ssor
+                if matches!(self.acceleration, Acceleration::Chebyshev { .. }) =>
+            {
+                Stage::Chebyshev
+            }
+            Method::GaussSeidel if self.uses_block_thomas() => Stage::BlockThomas,
+            Method::GaussSeidel if !self.blocks.is_empty() => Stage::BlockGaussSeidel,
+            Method::GaussSeidel if self.uses_thomas() => Stage::Thomas,
+            Method::GaussSeidel => Stage::GaussSeidel,
+            Method::Jacobi | Method::Hybrid => Stage::Jacobi,
+            Method::Richardson => Stage::Richardson,
+            Method::Ssor => Stage::Ssor,
+            Method::Gauss => Stage::Direct,
+            Method::GaussJordan => Stage::GaussJordan,
+            Method::Lu => Stage::Lu,
+            Method::Cholesky => Stage::Cholesky,
+            Method::Qr => Stage::Qr,
+            Method::ConjugateGradient => Stage::ConjugateGradient,
+            Method::Gmres => Stage::Gmres,
+            Method::BiCgStab => Stage::BiCgStab,
+            Method::Auto => unreachable!("auto method is resolved before solve"),
+        };
+        let mut conjugate_gradient = None;
+        let mut gmres = None;
+        let mut bicgstab = None;
+        let mut tau = None;
+        let mut block_sweep = None;
+        let mut chebyshev = None;
+        let mut aitken = match self.acceleration {
+            Acceleration::Aitken { period } => Some(Aitken::new(period)),
+            _ => None,
+        };
+        let mut previous_delta = None;
+        let mut slow = 0;
+        // smallest delta of current stage and its iteration
+        let mut best: Option<(Decimal, usize)> = None;
+        if matches!(stage, Stage::Jacobi | Stage::GaussSeidel | Stage::Ssor | Stage::Chebyshev) {
+            self.check_diagonal()?;
+        }
 
-        loop {
-            let mut delta = dec!(0);
+        for phase in 0..=self.epsilon_schedule.len() {
+            let epsilon = self.phase_epsilon(phase);
+            let mut k: usize = 1;
 
-            for i in 0..matrix_size {
-                let mut s = dec!(0);
+            loop {
+                // methods measure change by its largest component as they go
+                let previous = self.norm.filter(|norm| *norm != Norm::LInf).map(|_| result_vector.clone());
+                // overflow is met deep inside step, which does not know iteration
+                let mut step = || -> Result<Decimal, ESolveError> {
+                    Ok(match stage {
+                        Stage::Jacobi => self.jacobi_sweep(&mut result_vector)?,
+                        Stage::GaussSeidel => self.sweep(&mut result_vector, 0..matrix_size)?,
+                        Stage::Thomas => match self.thomas_step(&mut result_vector)? {
+                            Some(delta) => delta,
+                            None => {
+                                self.check_diagonal()?;
+                                stage = Stage::GaussSeidel;
+                                self.sweep(&mut result_vector, 0..matrix_size)?
+                            }
+                        },
+                        Stage::BlockThomas => match self.block_thomas_step(&mut result_vector)? {
+                            Some(delta) => delta,
+                            None => {
+                                stage = Stage::BlockGaussSeidel;
+                                let state = block_sweep.insert(BlockSweep::new(self)?);
+                                state.step(self, &mut result_vector)?
+                            }
+                        },
+                        Stage::BlockGaussSeidel => {
+                            let state = match &mut block_sweep {
+                                Some(state) => state,
+                                None => block_sweep.insert(BlockSweep::new(self)?),
+                            };
+                            state.step(self, &mut result_vector)?
+                        }
+                        Stage::Richardson => {
+                            let tau = match tau {
+                                Some(tau) => tau,
+                                // zero matrix gives no step to take
+                                None => *tau.insert(self.richardson_tau().ok_or(ESolveError::Diverged { growth_factor: None })?),
+                            };
+                            self.richardson_step(tau, &mut result_vector)?
+                        }
+                        Stage::Ssor => self.symmetric_sweep(&mut result_vector)?,
+                        Stage::Chebyshev => {
+                            let Acceleration::Chebyshev { spectrum } = self.acceleration else {
+                                unreachable!("stage is chosen only with acceleration")
+                            };
+                            chebyshev
+                                .get_or_insert_with(|| Chebyshev::new(self, spectrum))
+                                .step(self, &mut result_vector)?
+                        }
+                        Stage::Direct => self.direct_step(&mut result_vector)?,
+                        Stage::GaussJordan => self.gauss_jordan_step(&mut result_vector)?,
+                        Stage::Lu => self.lu_step(&mut result_vector)?,
+                        Stage::Cholesky => self.cholesky_step(&mut result_vector)?,
+                        Stage::Qr => self.qr_step(&mut result_vector)?,
+                        Stage::ConjugateGradient => {
+                            let state = match &mut conjugate_gradient {
+                                Some(state) => state,
+                                None => conjugate_gradient.insert(ConjugateGradient::new(self, &result_vector)?),
+                            };
+                            state.step(self, &mut result_vector)?
+                        }
+                        Stage::Gmres => {
+                            let state = match &mut gmres {
+                                Some(state) => state,
+                                None => gmres.insert(Gmres::new(self, &result_vector)?),
+                            };
+                            state.step(self, &mut result_vector, self.restart)?
+                        }
+                        Stage::BiCgStab => {
+                            let state = match &mut bicgstab {
+                                Some(state) => state,
+                                None => bicgstab.insert(BiCgStab::new(self, &result_vector)?),
+                            };
+                            state.step(self, &mut result_vector)?
+                        }
+                    })
+                };
+                let delta = step().map_err(|err| match err {
+                    ESolveError::NumericOverflow { row, .. } => ESolveError::NumericOverflow { row, iteration: total + 1 },
+                    err => err,
+                })?;
+                let delta = match (&previous, self.norm) {
+                    (Some(previous), Some(norm)) => norm.of((&result_vector - previous).iter().copied()),
+                    _ => delta,
+                };
+                total += 1;
+                let restarted = total % RESIDUAL_CHECK_PERIOD == 0
+                    && match stage {
+                        Stage::ConjugateGradient => (conjugate_gradient.as_mut())
+                            .is_some_and(|state| state.resync(self, &result_vector)),
+                        Stage::Gmres => match &mut gmres {
+                            Some(state) => state.resync(self, &result_vector)?,
+                            None => false,
+                        },
+                        Stage::BiCgStab => bicgstab.as_mut().is_some_and(|state| state.resync(self, &result_vector)),
+                        _ => false,
+                    };
+
+                let progress = Progress {
+                    iteration: total,
+                    phase,
+                    delta,
+                    stage,
+                    iterate: &result_vector,
+                    restarted,
+                };
+                if on_iteration(&progress).is_break() {
+                    return Err(ESolveError::Cancelled);
+                }
+
+                // elimination gives solution at once, further phases have nothing to refine
+                if stage.is_direct() {
+                    return Ok(result_vector);
+                }
+
+                let norm = self.norm.unwrap_or(Norm::LInf);
+                if self.tolerance_mode.converged(delta, epsilon, &result_vector, norm) {
+                    break;
+                }
+                match best {
+                    Some((best_delta, _)) if delta >= best_delta => (),
+                    _ => best = Some((delta, total)),
+                }
+                if let Some((best_delta, at_iteration)) = best.filter(|(_, at)| total - at >= STAGNATION_WINDOW) {
+                    return Err(match self.divergence(best_delta, delta) {
+                        ESolveError::MaxIterationsExceeded { .. } => {
+                            ESolveError::Stagnated { best_delta, at_iteration }
+                        }
+                        err => err,
+                    });
+                }
+                if let Some(aitken) = aitken.as_mut().filter(|_| stage.is_stationary()) {
+                    aitken.observe(self, stage, &mut result_vector);
+                }
 
-                for j in 0..matrix_size {
-                    // skip current line
-                    if j == i {
-                        continue;
+                if self.method == Method::Hybrid {
+                    match previous_delta {
+                        Some(previous) if delta >= previous * STALL_RATIO => slow += 1,
+                        _ => slow = 0,
+                    }
+                    previous_delta = Some(delta);
+                    if slow >= STALL_WINDOW {
+                        stage = stage.next();
+                        previous_delta = None;
+                        slow = 0;
+                        best = None;
                     }
-                    s += self.input_matrix[(i, j)] * result_vector[j];
                 }
 
-                let x = (self.expression_rhs[i] - s) / self.input_matrix[(i, i)];
-                let d = (x - result_vector[i]).abs();
-                if d > delta {
-                    delta = d;
+                if k < self.max_iterations {
+                    k += 1;
+                    continue;
                 }
 
-                result_vector[i] = x;
+                let (best_delta, _) = best.unwrap_or((delta, total));
+                return Err(self.divergence(best_delta, delta));
             }
+        }
 
-            if delta < self.epsilon {
-                return Ok(result_vector);
-            }
+        Ok(result_vector)
+    }
+
+    /// Why iterations did not converge: singular matrix, when it is small
+    /// enough for its rank to be checked, or see [`exhausted`]
+    fn divergence(&self, best_delta: Decimal, last_delta: Decimal) -> ESolveError {
+        let singular = self.input_matrix.nrows() <= DIRECT_MAX_SIZE
+            && !matches!(self.analyze_rank(), RankAnalysis::FullRank);
+        match singular {
+            true => ESolveError::Singular { column: None },
+            false => exhausted(best_delta, last_delta),
+        }
+    }
+
+    /// One Jacobi iteration: every component is computed from previous approximation
+    fn jacobi_sweep(&self, result_vector: &mut DVector<Decimal>) -> Result<Decimal, Overflow> {
+        let matrix_size = result_vector.len();
+        let previous = result_vector.clone();
+        let mut delta = dec!(0);
+        // Jacobi stage of hybrid method is never damped
+        let damping = match self.method {
+            Method::Jacobi => self.relaxation,
+            _ => Decimal::ONE,
+        };
+
+        for i in 0..matrix_size {
+            let s = self.row_sum(i, &previous, |j| j == i)?;
+
+            let x = self.expressed(i, s)?;
+            let x = self.project(i, relaxed(i, x, previous[i], damping)?);
+            delta = delta.max(change(i, x, previous[i])?);
+            result_vector[i] = x;
+        }
+
+        Ok(delta)
+    }
+
+    /// Forward sweep and backward sweep, returns largest change of component
+    /// over both of them
+    fn symmetric_sweep(&self, result_vector: &mut DVector<Decimal>) -> Result<Decimal, Overflow> {
+        let matrix_size = result_vector.len();
+        let previous = result_vector.clone();
+        self.sweep(result_vector, 0..matrix_size)?;
+        self.sweep(result_vector, (0..matrix_size).rev())?;
+
+        (0..matrix_size)
+            .map(|i| change(i, result_vector[i], previous[i]))
+            .try_fold(dec!(0), |delta, change| Ok(delta.max(change?)))
+    }
 
-            if k < self.max_iterations {
-                k += 1;
-                continue;
+    /// One Gauss-Seidel iteration in place over `rows` in given order,
+    /// returns largest change of component
+    fn sweep(
+        &self,
+        result_vector: &mut DVector<Decimal>,
+        rows: impl Iterator<Item = usize>,
+    ) -> Result<Decimal, Overflow> {
+        let mut delta = dec!(0);
+
+        for i in rows {
+            // skip current line
+            let s = self.row_sum(i, result_vector, |j| j == i)?;
+
+            let x = self.expressed(i, s)?;
+            let x = self.project(i, relaxed(i, x, result_vector[i], self.relaxation)?);
+            let d = change(i, x, result_vector[i])?;
+            if d > delta {
+                delta = d;
             }
 
-            return Err(ESolveError::Diverge);
+            result_vector[i] = x;
+        }
+
+        Ok(delta)
+    }
+
+    /// Sweeps divide by diagonal, division by zero must not pass for overflow
+    fn check_diagonal(&self) -> Result<(), ESolveError> {
+        match (0..self.input_matrix.nrows()).find(|row| self.input_matrix[(*row, *row)].is_zero()) {
+            Some(row) => Err(ESolveError::ZeroDiagonal { row }),
+            None => Ok(()),
         }
     }
+
+    /// Unknown of `row` expressed from its equation, where the rest of its
+    /// terms sum to `s`
+    fn expressed(&self, row: usize, s: Decimal) -> Result<Decimal, Overflow> {
+        (self.expression_rhs[row].checked_sub(s))
+            .and_then(|rest| rest.checked_div(self.input_matrix[(row, row)]))
+            .ok_or(Overflow { row })
+    }
+}
+
+/// `x` of `row` damped by `relaxation` on its way from `current`
+fn relaxed(row: usize, x: Decimal, current: Decimal, relaxation: Decimal) -> Result<Decimal, Overflow> {
+    if relaxation == Decimal::ONE {
+        return Ok(x);
+    }
+    (x.checked_sub(current))
+        .and_then(|step| relaxation.checked_mul(step))
+        .and_then(|step| current.checked_add(step))
+        .ok_or(Overflow { row })
+}
+
+/// `|x - current|` of unknown of `row`
+fn change(row: usize, x: Decimal, current: Decimal) -> Result<Decimal, Overflow> {
+    x.checked_sub(current).map(|change| change.abs()).ok_or(Overflow { row })
+}
+
+/// `value - factor * other`, elimination of entry of `row`
+fn eliminated(row: usize, value: Decimal, factor: Decimal, other: Decimal) -> Result<Decimal, Overflow> {
+    (factor.checked_mul(other))
+        .and_then(|product| value.checked_sub(product))
+        .ok_or(Overflow { row })
+}
+
+/// Sum of `a * b` over `terms` of `row`
+fn dot(row: usize, terms: impl IntoIterator<Item = (Decimal, Decimal)>) -> Result<Decimal, Overflow> {
+    terms.into_iter().try_fold(dec!(0), |sum, (a, b)| {
+        a.checked_mul(b).and_then(|term| sum.checked_add(term)).ok_or(Overflow { row })
+    })
+}
+
+/// `(value - s) / divisor`, unknown of `row` in substitution
+fn substituted(row: usize, value: Decimal, s: Decimal, divisor: Decimal) -> Result<Decimal, Overflow> {
+    (value.checked_sub(s))
+        .and_then(|rest| rest.checked_div(divisor))
+        .ok_or(Overflow { row })
 }
@@ -0,0 +1,67 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+
+use super::{Equation, Stage};
+
+/// Aitken delta-squared extrapolation of iterates of stationary method.
+/// Error of such iterations soon shrinks by nearly the same factor every
+/// step, and from three consecutive iterates every component is moved to
+/// where that geometric sequence ends: `x - (x - x') ^ 2 / (x - 2 x' + x'')`.
+/// Jacobi error often alternates its sign, so every other iterate is taken then
+pub(super) struct Aitken {
+    period: usize,
+    /// The last iterates, oldest first
+    history: Vec<DVector<Decimal>>,
+    /// Iterations since extrapolation, or since stage changed
+    since: usize,
+    stage: Option<Stage>,
+}
+
+impl Aitken {
+    pub(super) fn new(period: usize) -> Self {
+        Aitken {
+            period,
+            history: Vec::new(),
+            since: 0,
+            stage: None,
+        }
+    }
+
+    /// Takes iterate of finished iteration, extrapolates it in place every
+    /// `period` iterations of the same stage
+    pub(super) fn observe(&mut self, equation: &Equation, stage: Stage, result_vector: &mut DVector<Decimal>) {
+        if self.stage != Some(stage) {
+            self.history.clear();
+            self.since = 0;
+            self.stage = Some(stage);
+        }
+        let spacing = if stage == Stage::Jacobi { 2 } else { 1 };
+        let kept = 2 * spacing + 1;
+        if self.history.len() == kept {
+            self.history.remove(0);
+        }
+        self.history.push(result_vector.clone());
+        self.since += 1;
+        if self.since < self.period || self.history.len() < kept {
+            return;
+        }
+
+        let (oldest, previous, current) = (&self.history[0], &self.history[spacing], &self.history[2 * spacing]);
+        for i in 0..result_vector.len() {
+            let step = current[i] - previous[i];
+            let previous_step = previous[i] - oldest[i];
+            // only component approaching its limit has one to jump to
+            if step.abs() >= previous_step.abs() {
+                continue;
+            }
+            let jump = step
+                .checked_mul(step)
+                .and_then(|square| square.checked_div(step - previous_step));
+            if let Some(jump) = jump {
+                result_vector[i] = equation.project(i, current[i] - jump);
+            }
+        }
+        self.history.clear();
+        self.since = 0;
+    }
+}
@@ -0,0 +1,130 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{ESolveError, Equation, Preconditioner};
+use crate::norm::Norm;
+
+/// State carried between BiCGSTAB iterations. Works for general non-symmetric
+/// matrices with short recurrences, so unlike GMRES memory does not grow
+/// with iterations. Preconditioner is applied from the right
+pub(super) struct BiCgStab {
+    residual: DVector<Decimal>,
+    /// Fixed shadow residual, `r` of starting approximation
+    shadow: DVector<Decimal>,
+    direction: DVector<Decimal>,
+    /// `A M^-1 p` of previous iteration
+    product: DVector<Decimal>,
+    rho: Decimal,
+    alpha: Decimal,
+    omega: Decimal,
+    /// Half step left residual within epsilon, further steps change nothing
+    solved: bool,
+    preconditioner: Box<dyn Preconditioner>,
+}
+
+impl BiCgStab {
+    /// Fails when preconditioner can't be built
+    pub(super) fn new(equation: &Equation, start: &DVector<Decimal>) -> Result<Self, ESolveError> {
+        let residual = equation.residual_of(start)?;
+        let size = residual.len();
+        Ok(BiCgStab {
+            shadow: residual.clone(),
+            residual,
+            direction: DVector::from_element(size, dec!(0)),
+            product: DVector::from_element(size, dec!(0)),
+            rho: dec!(1),
+            alpha: dec!(1),
+            omega: dec!(1),
+            solved: false,
+            preconditioner: equation.preconditioner()?,
+        })
+    }
+
+    /// Recurrence starts over from current residual when shadow residual
+    /// becomes orthogonal to it
+    fn reset(&mut self) {
+        let size = self.residual.len();
+        self.shadow = self.residual.clone();
+        self.direction = DVector::from_element(size, dec!(0));
+        self.product = DVector::from_element(size, dec!(0));
+        self.rho = dec!(1);
+        self.alpha = dec!(1);
+        self.omega = dec!(1);
+        self.solved = false;
+    }
+
+    /// Returns largest change of component. Fails with [`ESolveError::Diverged`]
+    /// when recurrence breaks down while residual is still above epsilon,
+    /// which happens for singular matrices
+    pub(super) fn step(
+        &mut self,
+        equation: &Equation,
+        result_vector: &mut DVector<Decimal>,
+    ) -> Result<Decimal, ESolveError> {
+        if self.solved || self.residual.iter().all(Decimal::is_zero) {
+            return Ok(dec!(0));
+        }
+
+        let mut rho = self.shadow.dot(&self.residual);
+        if rho.is_zero() {
+            self.reset();
+            rho = self.shadow.dot(&self.residual);
+        }
+        let beta = (rho / self.rho) * (self.alpha / self.omega);
+        self.direction = &self.residual + (&self.direction - &self.product * self.omega) * beta;
+        let direction = self.preconditioner.apply(&self.direction);
+        self.product = equation.multiply(&direction)?;
+
+        let projection = self.shadow.dot(&self.product);
+        if projection.is_zero() {
+            return Err(ESolveError::Diverged { growth_factor: None });
+        }
+        self.alpha = rho / projection;
+        let half = &self.residual - &self.product * self.alpha;
+        let mut change = direction * self.alpha;
+
+        let half_norm = equation.norm.unwrap_or(Norm::L2).of(half.iter().copied());
+        if half_norm <= equation.epsilon {
+            // half step already solves the system, omega would be rounding
+            // noise over rounding noise
+            self.residual = half;
+            self.solved = true;
+        } else {
+            let preconditioned = self.preconditioner.apply(&half);
+            let corrected = equation.multiply(&preconditioned)?;
+            let length = corrected.dot(&corrected);
+            let omega = match length.is_zero() {
+                true => dec!(0),
+                false => corrected.dot(&half) / length,
+            };
+            if omega.is_zero() {
+                return Err(ESolveError::Diverged { growth_factor: None });
+            }
+            self.omega = omega;
+            change += preconditioned * omega;
+            self.residual = half - corrected * omega;
+        }
+        self.rho = rho;
+
+        *result_vector += &change;
+        Ok(change.iter().map(|value| value.abs()).fold(dec!(0), Decimal::max))
+    }
+
+    /// Starts recurrence over from true residual of `approximation` when
+    /// the carried one drifted from it, see [`Equation::drifted`]. Returns
+    /// whether it did
+    pub(super) fn resync(&mut self, equation: &Equation, approximation: &DVector<Decimal>) -> bool {
+        // overflow fails the next step instead
+        let Ok(residual) = equation.residual_of(approximation) else {
+            return false;
+        };
+        let mismatch = Norm::LInf.of((&residual - &self.residual).iter().copied());
+        if !equation.drifted(mismatch, Norm::LInf.of(residual.iter().copied())) {
+            return false;
+        }
+        self.residual = residual;
+        self.reset();
+        true
+    }
+}
@@ -0,0 +1,77 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::ops::Range;
+
+use super::{change, relaxed, ESolveError, Equation, LuFactorization, Overflow};
+
+/// Block Gauss-Seidel: unknowns of every diagonal block are found together
+/// by direct solve of the block, using newest values of other blocks.
+/// Blocks are factored once, before the first sweep
+pub(super) struct BlockSweep {
+    ranges: Vec<Range<usize>>,
+    factors: Vec<LuFactorization>,
+}
+
+impl BlockSweep {
+    /// Fails with [`ESolveError::SingularBlock`] when diagonal block can't be solved
+    pub(super) fn new(equation: &Equation) -> Result<Self, ESolveError> {
+        let ranges = equation.block_ranges();
+        let factors = (ranges.iter().enumerate())
+            .map(|(block, range)| {
+                let size = range.len();
+                let matrix = DMatrix::from_fn(size, size, |row, column| {
+                    equation.input_matrix[(range.start + row, range.start + column)]
+                });
+                LuFactorization::new(&matrix).map_err(|err| match err {
+                    ESolveError::NumericOverflow { row, iteration } => {
+                        ESolveError::NumericOverflow { row: range.start + row, iteration }
+                    }
+                    _ => ESolveError::SingularBlock { block },
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(BlockSweep { ranges, factors })
+    }
+
+    /// Returns largest change of component
+    pub(super) fn step(&self, equation: &Equation, result_vector: &mut DVector<Decimal>) -> Result<Decimal, Overflow> {
+        let mut delta = dec!(0);
+
+        for (range, factors) in self.ranges.iter().zip(&self.factors) {
+            let rhs = range
+                .clone()
+                .map(|i| {
+                    let s = equation.row_sum(i, result_vector, |j| range.contains(&j))?;
+                    equation.expression_rhs[i].checked_sub(s).ok_or(Overflow { row: i })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let solution = (factors.substitute(&DVector::from_vec(rhs)))
+                .map_err(|Overflow { row }| Overflow { row: range.start + row })?;
+            for (row, x) in solution.iter().enumerate() {
+                let i = range.start + row;
+                let x = relaxed(i, *x, result_vector[i], equation.relaxation)?;
+                delta = delta.max(change(i, x, result_vector[i])?);
+                result_vector[i] = x;
+            }
+        }
+
+        Ok(delta)
+    }
+}
+
+impl Equation {
+    /// Unknowns of each block of [`Equation::blocks`], in order
+    pub fn block_ranges(&self) -> Vec<Range<usize>> {
+        let mut start = 0;
+        self.blocks
+            .iter()
+            .map(|size| {
+                let range = start..start + size;
+                start += size;
+                range
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,101 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{ESolveError, Equation, Overflow, Preconditioner};
+use crate::norm::Norm;
+
+/// State carried between conjugate gradient iterations. In exact arithmetic
+/// CG finishes in at most `n` iterations, usually far fewer for well
+/// conditioned symmetric positive definite systems. Preconditioner should
+/// be symmetric too
+pub(super) struct ConjugateGradient {
+    residual: DVector<Decimal>,
+    direction: DVector<Decimal>,
+    /// `r^T M^-1 r` of current residual
+    residual_norm: Decimal,
+    preconditioner: Box<dyn Preconditioner>,
+}
+
+impl ConjugateGradient {
+    /// Fails with [`ESolveError::NotPositiveDefinite`] when matrix is not symmetric
+    pub(super) fn new(equation: &Equation, start: &DVector<Decimal>) -> Result<Self, ESolveError> {
+        let matrix = &equation.input_matrix;
+        if matrix != &matrix.transpose() {
+            return Err(ESolveError::NotPositiveDefinite);
+        }
+
+        let preconditioner = equation.preconditioner()?;
+        let residual = equation.residual_of(start)?;
+        let direction = preconditioner.apply(&residual);
+        let residual_norm = residual.dot(&direction);
+        Ok(ConjugateGradient {
+            direction,
+            residual,
+            residual_norm,
+            preconditioner,
+        })
+    }
+
+    /// Moves approximation along current direction, returns largest change of component.
+    /// Non-positive curvature means matrix is not positive definite
+    pub(super) fn step(
+        &mut self,
+        equation: &Equation,
+        result_vector: &mut DVector<Decimal>,
+    ) -> Result<Decimal, ESolveError> {
+        if self.residual_norm.is_zero() {
+            return Ok(dec!(0));
+        }
+
+        let product = equation.multiply(&self.direction)?;
+        let curvature = self.direction.dot(&product);
+        if curvature <= dec!(0) {
+            return Err(ESolveError::NotPositiveDefinite);
+        }
+        let alpha = self.residual_norm / curvature;
+
+        let mut delta = dec!(0);
+        for i in 0..result_vector.len() {
+            let change = alpha * self.direction[i];
+            result_vector[i] += change;
+            delta = delta.max(change.abs());
+        }
+        self.residual -= product * alpha;
+
+        let preconditioned = self.preconditioner.apply(&self.residual);
+        let residual_norm = self.residual.dot(&preconditioned);
+        let beta = residual_norm / self.residual_norm;
+        self.direction = preconditioned + &self.direction * beta;
+        self.residual_norm = residual_norm;
+
+        Ok(delta)
+    }
+
+    /// Starts recurrence over from true residual of `approximation` when
+    /// the carried one drifted from it, see [`Equation::drifted`]. Returns
+    /// whether it did
+    pub(super) fn resync(&mut self, equation: &Equation, approximation: &DVector<Decimal>) -> bool {
+        // overflow fails the next step instead
+        let Ok(residual) = equation.residual_of(approximation) else {
+            return false;
+        };
+        let mismatch = Norm::LInf.of((&residual - &self.residual).iter().copied());
+        if !equation.drifted(mismatch, Norm::LInf.of(residual.iter().copied())) {
+            return false;
+        }
+        self.direction = self.preconditioner.apply(&residual);
+        self.residual_norm = residual.dot(&self.direction);
+        self.residual = residual;
+        true
+    }
+}
+
+impl Equation {
+    /// `A v`, row sums go through [`crate::parallel::Parallelism`] and
+    /// [`Equation::rounding`]
+    pub(super) fn multiply(&self, vector: &DVector<Decimal>) -> Result<DVector<Decimal>, Overflow> {
+        let rows = (0..vector.len()).map(|row| self.row_sum(row, vector, |_| false));
+        Ok(DVector::from_vec(rows.collect::<Result<_, _>>()?))
+    }
+}
@@ -0,0 +1,143 @@
+use nalgebra::DVector;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+use super::{change, relaxed, Equation, Method, Overflow};
+
+/// Plain iterations run to estimate spectrum when it is neither given
+/// nor follows from Gershgorin discs
+const ESTIMATE_ITERATIONS: usize = 6;
+
+/// Chebyshev semi-iterative acceleration of Jacobi or symmetric Gauss-Seidel.
+/// Iteration matrix `M` of base method must have real eigenvalues in
+/// `[lower, upper]` with `upper < 1`: every step combines base step with two
+/// previous approximations so error shrinks as Chebyshev polynomial of `M`,
+/// which takes about square root of iterations base method needs
+pub(super) struct Chebyshev {
+    /// `2 / (2 - lower - upper)`, maps spectrum onto interval symmetric around zero
+    gamma: Decimal,
+    /// Square of half-width of mapped interval
+    sigma_squared: Decimal,
+    /// Weight of the last accelerated step
+    omega: Decimal,
+    previous: Option<DVector<Decimal>>,
+    /// Deltas of plain iterations while spectrum is estimated
+    deltas: Vec<Decimal>,
+    accelerated: bool,
+}
+
+impl Chebyshev {
+    pub(super) fn new(equation: &Equation, spectrum: Option<(Decimal, Decimal)>) -> Self {
+        let mut chebyshev = Chebyshev {
+            gamma: Decimal::ONE,
+            sigma_squared: dec!(0),
+            omega: Decimal::ONE,
+            previous: None,
+            deltas: Vec::new(),
+            accelerated: false,
+        };
+        let spectrum = spectrum.or_else(|| match equation.method {
+            // eigenvalues of weighted Jacobi are 1 - w + w l for l of plain one
+            Method::Jacobi => equation.jacobi_radius_bound().map(|radius| {
+                let damping = equation.relaxation;
+                let shift = Decimal::ONE - damping;
+                (shift - damping * radius, shift + damping * radius)
+            }),
+            _ => None,
+        });
+        if let Some((lower, upper)) = spectrum {
+            chebyshev.accelerate(lower, upper);
+        }
+        chebyshev
+    }
+
+    fn accelerate(&mut self, lower: Decimal, upper: Decimal) {
+        let width = Decimal::TWO - lower - upper;
+        self.gamma = Decimal::TWO / width;
+        let sigma = (upper - lower) / width;
+        self.sigma_squared = sigma * sigma;
+        self.accelerated = true;
+    }
+
+    /// Returns largest change of component
+    pub(super) fn step(&mut self, equation: &Equation, result_vector: &mut DVector<Decimal>) -> Result<Decimal, Overflow> {
+        let mut base = result_vector.clone();
+        let base_delta = match equation.method {
+            Method::Jacobi => equation.jacobi_sweep(&mut base)?,
+            _ => equation.symmetric_sweep(&mut base)?,
+        };
+
+        if !self.accelerated {
+            // until spectrum is known, or when it turns out no acceleration
+            // applies, base method iterates alone
+            self.deltas.push(base_delta);
+            if self.deltas.len() == ESTIMATE_ITERATIONS {
+                if let Some(radius) = estimate_radius(&self.deltas) {
+                    let lower = match equation.method {
+                        Method::Jacobi => -radius,
+                        // symmetric sweep over symmetric positive definite matrix
+                        _ => dec!(0),
+                    };
+                    self.accelerate(lower, radius);
+                }
+            }
+            *result_vector = base;
+            return Ok(base_delta);
+        }
+
+        // 1 on the first step, 2 / (2 - sigma^2) on the second one
+        let omega = match &self.previous {
+            None => Decimal::ONE,
+            Some(_) if self.omega == Decimal::ONE => Decimal::TWO / (Decimal::TWO - self.sigma_squared),
+            Some(_) => Decimal::ONE / (Decimal::ONE - self.sigma_squared * self.omega / dec!(4)),
+        };
+        self.omega = omega;
+
+        let mut delta = dec!(0);
+        let mut next = base;
+        for i in 0..next.len() {
+            let current = result_vector[i];
+            let extrapolated = relaxed(i, next[i], current, self.gamma)?;
+            let x = match &self.previous {
+                Some(previous) => relaxed(i, extrapolated, previous[i], omega)?,
+                None => extrapolated,
+            };
+            delta = delta.max(change(i, x, current)?);
+            next[i] = x;
+        }
+        self.previous = Some(std::mem::replace(result_vector, next));
+        Ok(delta)
+    }
+}
+
+/// Geometric mean of the last four ratios of successive deltas, skipping the first one
+/// where error is not yet dominated by largest eigenvalue. `None` when base
+/// method does not converge, so there is nothing to accelerate
+fn estimate_radius(deltas: &[Decimal]) -> Option<Decimal> {
+    let (first, last) = (deltas[1], deltas[deltas.len() - 1]);
+    if first.is_zero() {
+        return None;
+    }
+    let radius = (last / first).sqrt()?.sqrt()?;
+    (radius < Decimal::ONE).then_some(radius)
+}
+
+impl Equation {
+    /// Largest row sum of Jacobi iteration matrix `D^-1 (L + U)`, bounds its
+    /// spectral radius. `None` when bound is not below 1
+    pub fn jacobi_radius_bound(&self) -> Option<Decimal> {
+        let matrix = &self.input_matrix;
+        let bound = (0..matrix.nrows())
+            .map(|row| {
+                if matrix[(row, row)].is_zero() {
+                    return Decimal::MAX;
+                }
+                let others = (0..matrix.ncols())
+                    .filter(|column| *column != row)
+                    .fold(dec!(0), |acc, column| acc + matrix[(row, column)].abs());
+                others / matrix[(row, row)].abs()
+            })
+            .fold(dec!(0), Decimal::max);
+        (bound < Decimal::ONE).then_some(bound)
+    }
+}
@@ -0,0 +1,83 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{ESolveError, Equation};
+
+/// `A = L L^T` of symmetric positive definite matrix. Takes half the work
+/// of LU and needs no pivoting, since all pivots of such matrix are positive
+#[derive(Debug, Clone)]
+pub struct CholeskyFactorization {
+    pub lower: DMatrix<Decimal>,
+}
+
+impl CholeskyFactorization {
+    /// Fails with [`ESolveError::NotPositiveDefinite`] unless matrix is symmetric
+    /// positive definite: factorization itself is the check
+    pub fn new(matrix: &DMatrix<Decimal>) -> Result<Self, ESolveError> {
+        let size = matrix.nrows();
+        if matrix != &matrix.transpose() {
+            return Err(ESolveError::NotPositiveDefinite);
+        }
+
+        let mut lower = DMatrix::from_element(size, size, dec!(0));
+        for column in 0..size {
+            let squares = (0..column).fold(dec!(0), |acc, k| acc + lower[(column, k)] * lower[(column, k)]);
+            let pivot = matrix[(column, column)] - squares;
+            if pivot <= dec!(0) {
+                return Err(ESolveError::NotPositiveDefinite);
+            }
+            let diagonal = pivot.sqrt().ok_or(ESolveError::NotPositiveDefinite)?;
+            lower[(column, column)] = diagonal;
+
+            for row in column + 1..size {
+                let s = (0..column).fold(dec!(0), |acc, k| acc + lower[(row, k)] * lower[(column, k)]);
+                lower[(row, column)] = (matrix[(row, column)] - s) / diagonal;
+            }
+        }
+
+        Ok(CholeskyFactorization { lower })
+    }
+
+    /// Solves `L y = rhs`, then `L^T x = y`
+    pub fn solve(&self, rhs: &DVector<Decimal>) -> DVector<Decimal> {
+        let size = rhs.len();
+        let mut y = DVector::from_element(size, dec!(0));
+        for row in 0..size {
+            let s = (0..row).fold(dec!(0), |acc, column| acc + self.lower[(row, column)] * y[column]);
+            y[row] = (rhs[row] - s) / self.lower[(row, row)];
+        }
+
+        let mut x = DVector::from_element(size, dec!(0));
+        for row in (0..size).rev() {
+            let s = (row + 1..size).fold(dec!(0), |acc, below| acc + self.lower[(below, row)] * x[below]);
+            x[row] = (y[row] - s) / self.lower[(row, row)];
+        }
+        x
+    }
+}
+
+impl Equation {
+    pub fn factor_cholesky(&self) -> Result<CholeskyFactorization, ESolveError> {
+        CholeskyFactorization::new(&self.input_matrix)
+    }
+
+    pub fn is_symmetric_positive_definite(&self) -> bool {
+        self.factor_cholesky().is_ok()
+    }
+
+    /// Replaces approximation with solution found through Cholesky factorization,
+    /// returns largest change
+    pub(super) fn cholesky_step(
+        &self,
+        result_vector: &mut DVector<Decimal>,
+    ) -> Result<Decimal, ESolveError> {
+        let solution = self.factor_cholesky()?.solve(&self.expression_rhs);
+        let delta = (0..solution.len())
+            .map(|row| (solution[row] - result_vector[row]).abs())
+            .fold(dec!(0), Decimal::max);
+        *result_vector = solution;
+        Ok(delta)
+    }
+}
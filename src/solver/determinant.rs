@@ -0,0 +1,107 @@
+use core::fmt;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+use super::{Equation, LuFactorization};
+
+/// Digits of mantissa shown in scientific notation
+const MANTISSA_DIGITS: u32 = 15;
+
+/// `mantissa * 10^exponent`, where `1 <= |mantissa| < 10` or both are zero.
+/// Determinant of even moderate system overflows Decimal, which holds
+/// numbers up to about `7.9e28`, so exponent is kept apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Determinant {
+    pub mantissa: Decimal,
+    pub exponent: i64,
+}
+
+impl Determinant {
+    pub const ZERO: Determinant = Determinant {
+        mantissa: dec!(0),
+        exponent: 0,
+    };
+
+    fn normalized(mut mantissa: Decimal, mut exponent: i64) -> Self {
+        if mantissa.is_zero() {
+            return Determinant::ZERO;
+        }
+        while mantissa.abs() >= dec!(10) {
+            mantissa /= dec!(10);
+            exponent += 1;
+        }
+        while mantissa.abs() < Decimal::ONE {
+            mantissa *= dec!(10);
+            exponent -= 1;
+        }
+        Determinant { mantissa, exponent }
+    }
+
+    /// Product of pivots of `P A = L U`, sign flipped for every row swap
+    pub fn from_lu(factors: &LuFactorization) -> Self {
+        let sign = if is_odd(&factors.permutation) { -Decimal::ONE } else { Decimal::ONE };
+        factors.upper.diagonal().iter().fold(Determinant::normalized(sign, 0), |product, pivot| {
+            let pivot = Determinant::normalized(*pivot, 0);
+            Determinant::normalized(product.mantissa * pivot.mantissa, product.exponent + pivot.exponent)
+        })
+    }
+
+    /// Exact value when it fits into Decimal without losing significant digits
+    pub fn value(&self) -> Option<Decimal> {
+        let exponent = u32::try_from(self.exponent.unsigned_abs()).ok()?;
+        let power = dec!(10).checked_powu(u64::from(exponent))?;
+        match self.exponent >= 0 {
+            true => self.mantissa.checked_mul(power),
+            // digits of tiny values are cut by scale of Decimal
+            false if exponent + MANTISSA_DIGITS <= 28 => self.mantissa.checked_div(power),
+            false => None,
+        }
+    }
+}
+
+/// Permutation takes odd number of swaps
+fn is_odd(permutation: &[usize]) -> bool {
+    let mut swaps = 0;
+    let mut visited = vec![false; permutation.len()];
+    for start in 0..permutation.len() {
+        // cycle of length k takes k - 1 swaps
+        let mut row = start;
+        while !visited[row] {
+            visited[row] = true;
+            row = permutation[row];
+            swaps += usize::from(row != start);
+        }
+    }
+    swaps % 2 == 1
+}
+
+impl fmt::Display for Determinant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value() {
+            Some(value) => write!(f, "{}", value.normalize()),
+            None => {
+                let mantissa = self.mantissa.round_sf(MANTISSA_DIGITS).unwrap_or(self.mantissa);
+                write!(f, "{}e{}", mantissa.normalize(), self.exponent)
+            }
+        }
+    }
+}
+
+impl Equation {
+    /// Determinant of `input_matrix` through LU factorization, zero when it
+    /// meets zero pivot. Equations swapped by [`Equation::row_order`] are
+    /// accounted for, so it is determinant of matrix as it was given
+    pub fn determinant(&self) -> Determinant {
+        let determinant = match self.factor_lu() {
+            Ok(factors) => Determinant::from_lu(&factors),
+            Err(_) => return Determinant::ZERO,
+        };
+        match is_odd(&self.row_order) {
+            true => Determinant {
+                mantissa: -determinant.mantissa,
+                ..determinant
+            },
+            false => determinant,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{change, dot, eliminated, pivot_tolerance, substituted, ESolveError, Equation, Overflow};
+
+impl Equation {
+    /// Gaussian elimination with partial pivoting, replaces approximation
+    /// with exact (up to rounding) solution and returns largest change
+    pub(super) fn direct_step(
+        &self,
+        result_vector: &mut DVector<Decimal>,
+    ) -> Result<Decimal, ESolveError> {
+        let matrix_size = result_vector.len();
+        let mut matrix = self.input_matrix.clone();
+        let mut rhs = self.expression_rhs.clone();
+        let tolerance = pivot_tolerance(&matrix);
+
+        for column in 0..matrix_size {
+            let pivot = (column..matrix_size)
+                .max_by_key(|row| matrix[(*row, column)].abs())
+                .expect("column has rows below diagonal");
+            // singular system has no unique solution to iterate to either
+            if matrix[(pivot, column)].abs() <= tolerance {
+                return Err(ESolveError::Singular { column: Some(column) });
+            }
+            matrix.swap_rows(column, pivot);
+            rhs.swap_rows(column, pivot);
+
+            for row in column + 1..matrix_size {
+                let factor = (matrix[(row, column)].checked_div(matrix[(column, column)])).ok_or(Overflow { row })?;
+                if factor.is_zero() {
+                    continue;
+                }
+                for index in column..matrix_size {
+                    matrix[(row, index)] = eliminated(row, matrix[(row, index)], factor, matrix[(column, index)])?;
+                }
+                rhs[row] = eliminated(row, rhs[row], factor, rhs[column])?;
+            }
+        }
+
+        let mut delta = dec!(0);
+        for row in (0..matrix_size).rev() {
+            let s = dot(row, (row + 1..matrix_size).map(|column| (matrix[(row, column)], result_vector[column])))?;
+            let x = substituted(row, rhs[row], s, matrix[(row, row)])?;
+            delta = delta.max(change(row, x, result_vector[row])?);
+            result_vector[row] = x;
+        }
+
+        Ok(delta)
+    }
+}
@@ -0,0 +1,36 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{Equation, Overflow};
+use crate::norm::Norm;
+
+/// Krylov methods compare residual their recurrence carries with true
+/// `b - A x` every this many iterations
+pub const RESIDUAL_CHECK_PERIOD: usize = 10;
+
+/// Recurrence restarts when its residual is off from true one by more than
+/// this share of true one: rounding broke orthogonality of its vectors, and
+/// it would go on reducing residual which is not that of approximation
+const RESIDUAL_DRIFT: Decimal = dec!(0.01);
+
+/// Mismatch below this share of largest right hand side entry is rounding
+/// noise near solution, never a reason to restart
+const DRIFT_FLOOR: Decimal = dec!(0.000000000000000001);
+
+impl Equation {
+    /// `b - A x`
+    pub(super) fn residual_of(&self, approximation: &DVector<Decimal>) -> Result<DVector<Decimal>, Overflow> {
+        let product = self.multiply(approximation)?;
+        let rows = (0..product.len())
+            .map(|row| self.expression_rhs[row].checked_sub(product[row]).ok_or(Overflow { row }));
+        Ok(DVector::from_vec(rows.collect::<Result<_, _>>()?))
+    }
+
+    /// Residual carried by recurrence is `mismatch` away from true one of
+    /// size `size`, both in the same norm
+    pub(super) fn drifted(&self, mismatch: Decimal, size: Decimal) -> bool {
+        let largest = Norm::LInf.of(self.expression_rhs.iter().copied());
+        mismatch > largest * DRIFT_FLOOR && mismatch > size * RESIDUAL_DRIFT
+    }
+}
@@ -0,0 +1,87 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::ops::ControlFlow;
+
+use super::{ESolveError, Equation, Progress};
+
+impl Equation {
+    /// Known unknowns are moved to right hand side: rows and columns of fixed
+    /// unknowns are dropped, and the rest of the system is solved by iterations.
+    /// Progress and solution are reported as full vectors
+    pub(super) fn solve_fixed(
+        &self,
+        mut on_iteration: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<DVector<Decimal>, ESolveError> {
+        let size = self.input_matrix.nrows();
+        let mut known = DVector::from_element(size, dec!(0));
+        let mut is_fixed = vec![false; size];
+        for &(index, value) in &self.fixed {
+            known[index] = value;
+            is_fixed[index] = true;
+        }
+        let free: Vec<usize> = (0..size).filter(|index| !is_fixed[*index]).collect();
+
+        let reduced = Equation {
+            input_matrix: DMatrix::from_fn(free.len(), free.len(), |row, column| {
+                self.input_matrix[(free[row], free[column])]
+            }),
+            expression_rhs: DVector::from_fn(free.len(), |row, _| {
+                let row = free[row];
+                let moved = self
+                    .fixed
+                    .iter()
+                    .fold(dec!(0), |acc, &(index, value)| acc + self.input_matrix[(row, index)] * value);
+                self.expression_rhs[row] - moved
+            }),
+            max_iterations: self.max_iterations,
+            epsilon: self.epsilon,
+            tolerance_mode: self.tolerance_mode,
+            norm: self.norm,
+            parallelism: self.parallelism.clone(),
+            schur_block: None,
+            epsilon_schedule: self.epsilon_schedule.clone(),
+            method: self.method,
+            relaxation: self.relaxation,
+            tau: self.tau,
+            restart: self.restart,
+            fast_path: self.fast_path,
+            acceleration: self.acceleration,
+            preconditioning: self.preconditioning,
+            row_order: Vec::new(),
+            refinement_steps: self.refinement_steps,
+            rounding: self.rounding,
+            rounding_log: self.rounding_log.clone(),
+            fixed: Vec::new(),
+            // input rejects blocks together with fixed unknowns
+            blocks: Vec::new(),
+            bounds: (self.bounds.iter())
+                .filter_map(|&(index, bounds)| Some((free.binary_search(&index).ok()?, bounds)))
+                .collect(),
+            initial_guess: self.initial_guess.select(&free),
+        };
+
+        let expand = |solution: &DVector<Decimal>| {
+            let mut full = known.clone();
+            for (position, &index) in free.iter().enumerate() {
+                full[index] = solution[position];
+            }
+            full
+        };
+
+        // schur_block and fixed variables are not combined, reduced system is plain one
+        reduced
+            .iterate(|progress| {
+                let iterate = expand(progress.iterate);
+                on_iteration(&Progress {
+                    iteration: progress.iteration,
+                    phase: progress.phase,
+                    delta: progress.delta,
+                    stage: progress.stage,
+                    iterate: &iterate,
+                    restarted: progress.restarted,
+                })
+            })
+            .map(|solution| expand(&solution))
+    }
+}
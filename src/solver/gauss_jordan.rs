@@ -0,0 +1,72 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{pivot_tolerance, ESolveError, Equation};
+
+/// Reduces `[A | B]` to `[I | A^-1 B]` with partial pivoting, every column
+/// is cleared both below and above the diagonal. Returns `A^-1 B`
+fn reduce(matrix: &DMatrix<Decimal>, right: DMatrix<Decimal>) -> Result<DMatrix<Decimal>, ESolveError> {
+    let size = matrix.nrows();
+    let mut augmented = DMatrix::from_fn(size, size + right.ncols(), |row, column| {
+        if column < size {
+            matrix[(row, column)]
+        } else {
+            right[(row, column - size)]
+        }
+    });
+    let width = augmented.ncols();
+    let tolerance = pivot_tolerance(matrix);
+
+    for column in 0..size {
+        let pivot = (column..size)
+            .max_by_key(|row| augmented[(*row, column)].abs())
+            .expect("column has rows below diagonal");
+        if augmented[(pivot, column)].abs() <= tolerance {
+            return Err(ESolveError::Singular { column: Some(column) });
+        }
+        augmented.swap_rows(column, pivot);
+
+        let divisor = augmented[(column, column)];
+        for index in column..width {
+            augmented[(column, index)] /= divisor;
+        }
+        for row in (0..size).filter(|row| *row != column) {
+            let factor = augmented[(row, column)];
+            if factor.is_zero() {
+                continue;
+            }
+            for index in column..width {
+                augmented[(row, index)] = augmented[(row, index)] - factor * augmented[(column, index)];
+            }
+        }
+    }
+
+    Ok(augmented.columns(size, width - size).into_owned())
+}
+
+impl Equation {
+    /// Inverse of `input_matrix` by Gauss-Jordan elimination of `[A | I]`.
+    /// Fails with [`ESolveError::Singular`] when matrix is singular or nearly so
+    pub fn inverse(&self) -> Result<DMatrix<Decimal>, ESolveError> {
+        let size = self.input_matrix.nrows();
+        reduce(&self.input_matrix, DMatrix::identity(size, size))
+    }
+
+    /// Gauss-Jordan elimination of `[A | b]`, replaces approximation
+    /// with solution and returns largest change
+    pub(super) fn gauss_jordan_step(
+        &self,
+        result_vector: &mut DVector<Decimal>,
+    ) -> Result<Decimal, ESolveError> {
+        let rhs = DMatrix::from_column_slice(self.expression_rhs.len(), 1, self.expression_rhs.as_slice());
+        let solution = reduce(&self.input_matrix, rhs)?;
+
+        let mut delta = dec!(0);
+        for (row, x) in solution.column(0).iter().enumerate() {
+            delta = delta.max((*x - result_vector[row]).abs());
+            result_vector[row] = *x;
+        }
+        Ok(delta)
+    }
+}
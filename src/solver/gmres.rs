@@ -0,0 +1,156 @@
+use nalgebra::DVector;
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{ESolveError, Equation, Preconditioner};
+
+/// Restarted GMRES(m): approximation minimizes residual over Krylov subspace
+/// `span{r, A r, ..., A^(k-1) r}`, subspace is dropped and built anew from
+/// current approximation after `restart` steps. Needs neither symmetry nor
+/// diagonal dominance, only non-singular matrix. Preconditioner is applied
+/// from the right, `A M^-1 u = b`, so residual minimized is that of `A x = b`
+pub(super) struct Gmres {
+    /// Approximation the current subspace is built from
+    start: DVector<Decimal>,
+    /// Orthonormal basis of subspace
+    basis: Vec<DVector<Decimal>>,
+    /// Columns of Hessenberg matrix, already rotated to upper triangular
+    columns: Vec<Vec<Decimal>>,
+    /// Givens rotations `(cos, sin)` applied so far
+    rotations: Vec<(Decimal, Decimal)>,
+    /// Rotated `|r| e1`, its last entry is residual norm of current approximation
+    rhs: Vec<Decimal>,
+    preconditioner: Box<dyn Preconditioner>,
+}
+
+impl Gmres {
+    pub(super) fn new(equation: &Equation, start: &DVector<Decimal>) -> Result<Self, ESolveError> {
+        let mut gmres = Gmres {
+            start: start.clone(),
+            basis: Vec::new(),
+            columns: Vec::new(),
+            rotations: Vec::new(),
+            rhs: Vec::new(),
+            preconditioner: equation.preconditioner()?,
+        };
+        gmres.begin_cycle(equation)?;
+        Ok(gmres)
+    }
+
+    fn begin_cycle(&mut self, equation: &Equation) -> Result<(), ESolveError> {
+        let residual = equation.residual_of(&self.start)?;
+        let norm = norm(&residual)?;
+        self.basis.clear();
+        self.columns.clear();
+        self.rotations.clear();
+        self.rhs = vec![norm];
+        if !norm.is_zero() {
+            self.basis.push(residual / norm);
+        }
+        Ok(())
+    }
+
+    /// Extends subspace by one vector, returns largest change of component.
+    /// Fails with [`ESolveError::Singular`] when matrix turns out singular
+    pub(super) fn step(
+        &mut self,
+        equation: &Equation,
+        result_vector: &mut DVector<Decimal>,
+        restart: usize,
+    ) -> Result<Decimal, ESolveError> {
+        if self.basis.is_empty() {
+            // residual is zero, approximation is exact
+            return Ok(dec!(0));
+        }
+
+        let j = self.basis.len() - 1;
+        let mut w = equation.multiply(&self.preconditioner.apply(&self.basis[j]))?;
+        let mut column = Vec::with_capacity(j + 2);
+        // modified Gram-Schmidt
+        for vector in &self.basis {
+            let h = w.dot(vector);
+            w -= vector * h;
+            column.push(h);
+        }
+        let next_norm = norm(&w)?;
+        column.push(next_norm);
+
+        for (i, &(cos, sin)) in self.rotations.iter().enumerate() {
+            let (a, b) = (column[i], column[i + 1]);
+            column[i] = cos * a + sin * b;
+            column[i + 1] = cos * b - sin * a;
+        }
+        let length = norm_of(column[j], column[j + 1])?;
+        if length.is_zero() {
+            return Err(ESolveError::Singular { column: None });
+        }
+        let (cos, sin) = (column[j] / length, column[j + 1] / length);
+        column[j] = length;
+        column[j + 1] = dec!(0);
+        self.rotations.push((cos, sin));
+        let g = self.rhs[j];
+        self.rhs[j] = cos * g;
+        self.rhs.push(-sin * g);
+        self.columns.push(column);
+
+        let approximation = self.approximation();
+        let delta = (0..approximation.len())
+            .map(|i| (approximation[i] - result_vector[i]).abs())
+            .fold(dec!(0), Decimal::max);
+        *result_vector = approximation;
+
+        if next_norm.is_zero() || self.basis.len() >= restart {
+            self.start = result_vector.clone();
+            self.begin_cycle(equation)?;
+        } else {
+            self.basis.push(w / next_norm);
+        }
+        Ok(delta)
+    }
+
+    /// Starts new cycle from `approximation` when residual norm estimated
+    /// by rotations drifted from its true residual norm, see
+    /// [`Equation::drifted`]. Returns whether it did
+    pub(super) fn resync(
+        &mut self,
+        equation: &Equation,
+        approximation: &DVector<Decimal>,
+    ) -> Result<bool, ESolveError> {
+        let carried = self.rhs.last().map_or(dec!(0), |value| value.abs());
+        let residual = norm(&equation.residual_of(approximation)?)?;
+        if !equation.drifted((residual - carried).abs(), residual) {
+            return Ok(false);
+        }
+        self.start = approximation.clone();
+        self.begin_cycle(equation)?;
+        Ok(true)
+    }
+
+    /// `start + M^-1 V y` where `R y = g` is solved by back substitution
+    fn approximation(&self) -> DVector<Decimal> {
+        let size = self.columns.len();
+        let mut y = vec![dec!(0); size];
+        for row in (0..size).rev() {
+            let s = (row + 1..size).fold(dec!(0), |acc, column| acc + self.columns[column][row] * y[column]);
+            y[row] = (self.rhs[row] - s) / self.columns[row][row];
+        }
+
+        let size = self.start.len();
+        let correction = y
+            .iter()
+            .zip(&self.basis)
+            .fold(DVector::from_element(size, dec!(0)), |acc, (coefficient, vector)| {
+                acc + vector * *coefficient
+            });
+        &self.start + self.preconditioner.apply(&correction)
+    }
+}
+
+fn norm(vector: &DVector<Decimal>) -> Result<Decimal, ESolveError> {
+    vector.dot(vector).sqrt().ok_or(ESolveError::Diverged { growth_factor: None })
+}
+
+fn norm_of(a: Decimal, b: Decimal) -> Result<Decimal, ESolveError> {
+    (a * a + b * b).sqrt().ok_or(ESolveError::Diverged { growth_factor: None })
+}
@@ -0,0 +1,94 @@
+use core::fmt;
+use core::str::FromStr;
+
+use nalgebra::DVector;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+
+use super::Equation;
+
+/// Random components are drawn with this many decimal places
+const RANDOM_PLACES: u32 = 9;
+
+/// Approximation iterations start from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InitialGuess {
+    Zeros,
+    #[default]
+    Ones,
+    /// `b_i / a_ii`, solution of diagonal part of system. Components of zero
+    /// diagonal start from 1
+    Diagonal,
+    /// Uniform in `[-1, 1]`, the same for the same seed
+    Random { seed: u64 },
+    /// Values of unknowns, like solution of similar system solved before
+    Given(DVector<Decimal>),
+}
+
+impl InitialGuess {
+    /// Starting approximation of `equation`. Size of given one is checked by
+    /// [`Equation::solve_with_progress`]
+    pub fn start(&self, equation: &Equation) -> DVector<Decimal> {
+        let size = equation.input_matrix.nrows();
+        match self {
+            InitialGuess::Zeros => DVector::zeros(size),
+            InitialGuess::Ones => DVector::from_element(size, Decimal::ONE),
+            InitialGuess::Diagonal => DVector::from_fn(size, |row, _| {
+                (equation.expression_rhs[row].checked_div(equation.input_matrix[(row, row)])).unwrap_or(Decimal::ONE)
+            }),
+            InitialGuess::Random { seed } => {
+                let mut rng = StdRng::seed_from_u64(*seed);
+                let scale = 10_i64.pow(RANDOM_PLACES);
+                DVector::from_fn(size, |_, _| Decimal::new(rng.gen_range(-scale..=scale), RANDOM_PLACES))
+            }
+            InitialGuess::Given(guess) => guess.clone(),
+        }
+    }
+
+    /// Guess of system of `unknowns`, by original index. Strategies apply
+    /// to any system as they are
+    pub(crate) fn select(&self, unknowns: &[usize]) -> InitialGuess {
+        match self {
+            InitialGuess::Given(guess) => {
+                InitialGuess::Given(DVector::from_iterator(unknowns.len(), unknowns.iter().map(|index| guess[*index])))
+            }
+            strategy => strategy.clone(),
+        }
+    }
+}
+
+/// Named strategies, given values are not parsed from one word
+impl FromStr for InitialGuess {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const EXPECTED: &str = "Expected one of: zeros, ones, diagonal, random:<seed>";
+        match value.split_once(':') {
+            Some(("random", seed)) => match seed.parse() {
+                Ok(seed) => Ok(InitialGuess::Random { seed }),
+                Err(_) => Err("Expected seed of random initial guess, like random:42".to_owned()),
+            },
+            Some(_) => Err(EXPECTED.to_owned()),
+            None => match value {
+                "zeros" => Ok(InitialGuess::Zeros),
+                "ones" => Ok(InitialGuess::Ones),
+                "diagonal" => Ok(InitialGuess::Diagonal),
+                "random" => Err("Expected seed of random initial guess, like random:42".to_owned()),
+                _ => Err(EXPECTED.to_owned()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for InitialGuess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitialGuess::Zeros => write!(f, "zeros"),
+            InitialGuess::Ones => write!(f, "ones"),
+            InitialGuess::Diagonal => write!(f, "diagonal"),
+            InitialGuess::Random { seed } => write!(f, "random:{seed}"),
+            InitialGuess::Given(_) => write!(f, "given"),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use std::ops::ControlFlow;
+use std::panic;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+
+use super::{ESolveError, Equation, Progress, Stage};
+
+/// Owned [`Progress`], yielded by [`Iterations`]
+#[derive(Debug, Clone)]
+pub struct IterationState {
+    /// Counted through all phases
+    pub iteration: usize,
+    pub phase: usize,
+    pub delta: Decimal,
+    pub stage: Stage,
+    /// Approximation after iteration, in unknown order of solved system
+    pub iterate: DVector<Decimal>,
+}
+
+/// Iterations of solve, one state per iteration. Solve runs on its own thread
+/// and waits for every state to be taken before doing next iteration, so it
+/// is never more than one iteration ahead. Dropping iterator cancels solve
+pub struct Iterations {
+    receiver: Option<Receiver<IterationState>>,
+    solve: Option<JoinHandle<Result<DVector<Decimal>, ESolveError>>>,
+}
+
+impl Iterator for Iterations {
+    type Item = IterationState;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+}
+
+impl Iterations {
+    /// Solution, once iterator is exhausted. States not taken yet are skipped
+    /// and cancel solve: then result is [`ESolveError::Cancelled`]
+    pub fn result(mut self) -> Result<DVector<Decimal>, ESolveError> {
+        self.receiver = None;
+        let solve = self.solve.take().expect("solve is joined only once");
+        solve.join().unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+}
+
+impl Drop for Iterations {
+    fn drop(&mut self) {
+        self.receiver = None;
+        if let Some(solve) = self.solve.take() {
+            // solve stops at the next iteration, after receiver is gone
+            let _ = solve.join();
+        }
+    }
+}
+
+impl Equation {
+    /// Iterations of solve as lazy iterator, for callers who drive the loop
+    /// themselves instead of passing callback to [`Equation::solve_with_progress`]
+    pub fn iterations(&self) -> Iterations {
+        let equation = self.clone();
+        let (sender, receiver) = mpsc::sync_channel(0);
+        let solve = thread::spawn(move || {
+            equation.solve_with_progress(|progress: &Progress| {
+                let state = IterationState {
+                    iteration: progress.iteration,
+                    phase: progress.phase,
+                    delta: progress.delta,
+                    stage: progress.stage,
+                    iterate: progress.iterate.clone(),
+                };
+                match sender.send(state) {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(_) => ControlFlow::Break(()),
+                }
+            })
+        });
+        Iterations {
+            receiver: Some(receiver),
+            solve: Some(solve),
+        }
+    }
+}
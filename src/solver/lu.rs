@@ -0,0 +1,102 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{change, dot, eliminated, pivot_tolerance, substituted, ESolveError, Equation, Overflow};
+
+/// `P A = L U` with partial pivoting. Factorization is done once,
+/// after that every right hand side takes only two triangular solves
+#[derive(Debug, Clone)]
+pub struct LuFactorization {
+    /// Unit lower triangular, ones on diagonal
+    pub lower: DMatrix<Decimal>,
+    pub upper: DMatrix<Decimal>,
+    /// Row `i` of `P A` is row `permutation[i]` of `A`
+    pub permutation: Vec<usize>,
+}
+
+impl LuFactorization {
+    /// Fails with [`ESolveError::Singular`] when matrix is singular or nearly so,
+    /// with [`ESolveError::NumericOverflow`] when elimination outgrows Decimal
+    pub fn new(matrix: &DMatrix<Decimal>) -> Result<Self, ESolveError> {
+        let size = matrix.nrows();
+        let mut upper = matrix.clone();
+        let mut lower = DMatrix::identity(size, size);
+        let mut permutation: Vec<usize> = (0..size).collect();
+        let tolerance = pivot_tolerance(matrix);
+
+        for column in 0..size {
+            let pivot = (column..size)
+                .max_by_key(|row| upper[(*row, column)].abs())
+                .expect("column has rows below diagonal");
+            if upper[(pivot, column)].abs() <= tolerance {
+                return Err(ESolveError::Singular { column: Some(column) });
+            }
+            upper.swap_rows(column, pivot);
+            permutation.swap(column, pivot);
+            // multipliers found so far belong to swapped rows too
+            for index in 0..column {
+                let above = lower[(column, index)];
+                lower[(column, index)] = lower[(pivot, index)];
+                lower[(pivot, index)] = above;
+            }
+
+            for row in column + 1..size {
+                let factor = (upper[(row, column)].checked_div(upper[(column, column)])).ok_or(Overflow { row })?;
+                lower[(row, column)] = factor;
+                if factor.is_zero() {
+                    continue;
+                }
+                for index in column..size {
+                    upper[(row, index)] = eliminated(row, upper[(row, index)], factor, upper[(column, index)])?;
+                }
+            }
+        }
+
+        Ok(LuFactorization {
+            lower,
+            upper,
+            permutation,
+        })
+    }
+
+    /// Solves `A x = rhs` by forward and back substitution, fails with
+    /// [`ESolveError::NumericOverflow`] when it outgrows Decimal
+    pub fn solve(&self, rhs: &DVector<Decimal>) -> Result<DVector<Decimal>, ESolveError> {
+        Ok(self.substitute(rhs)?)
+    }
+
+    /// [`LuFactorization::solve`] reporting row of factored matrix which overflows
+    pub(super) fn substitute(&self, rhs: &DVector<Decimal>) -> Result<DVector<Decimal>, Overflow> {
+        let size = rhs.len();
+        let mut y = DVector::from_element(size, dec!(0));
+        for row in 0..size {
+            let s = dot(row, (0..row).map(|column| (self.lower[(row, column)], y[column])))?;
+            y[row] = substituted(row, rhs[self.permutation[row]], s, Decimal::ONE)?;
+        }
+
+        let mut x = DVector::from_element(size, dec!(0));
+        for row in (0..size).rev() {
+            let s = dot(row, (row + 1..size).map(|column| (self.upper[(row, column)], x[column])))?;
+            x[row] = substituted(row, y[row], s, self.upper[(row, row)])?;
+        }
+        Ok(x)
+    }
+}
+
+impl Equation {
+    pub fn factor_lu(&self) -> Result<LuFactorization, ESolveError> {
+        LuFactorization::new(&self.input_matrix)
+    }
+
+    /// Replaces approximation with solution found through LU factorization,
+    /// returns largest change
+    pub(super) fn lu_step(&self, result_vector: &mut DVector<Decimal>) -> Result<Decimal, ESolveError> {
+        let solution = self.factor_lu()?.solve(&self.expression_rhs)?;
+        let delta = (0..solution.len())
+            .map(|row| change(row, solution[row], result_vector[row]))
+            .try_fold(dec!(0), |delta, change| Ok::<_, Overflow>(delta.max(change?)))?;
+        *result_vector = solution;
+        Ok(delta)
+    }
+}
@@ -0,0 +1,269 @@
+use core::fmt;
+use core::ops::{Add, AddAssign};
+
+use rust_decimal::Decimal;
+
+use super::{Equation, Method, Preconditioning, Stage};
+
+/// Multiplications and divisions of solve, the cost methods are compared by.
+/// Additions, comparisons and square roots are not counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Operations {
+    pub multiplications: u64,
+    pub divisions: u64,
+}
+
+impl Operations {
+    fn new(multiplications: usize, divisions: usize) -> Self {
+        Operations {
+            multiplications: multiplications as u64,
+            divisions: divisions as u64,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.multiplications + self.divisions
+    }
+
+    fn times(self, count: usize) -> Self {
+        Operations {
+            multiplications: self.multiplications * count as u64,
+            divisions: self.divisions * count as u64,
+        }
+    }
+}
+
+impl Add for Operations {
+    type Output = Operations;
+
+    fn add(self, other: Operations) -> Operations {
+        Operations {
+            multiplications: self.multiplications + other.multiplications,
+            divisions: self.divisions + other.divisions,
+        }
+    }
+}
+
+impl AddAssign for Operations {
+    fn add_assign(&mut self, other: Operations) {
+        *self = *self + other;
+    }
+}
+
+impl fmt::Display for Operations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} multiplications, {} divisions", self.multiplications, self.divisions)
+    }
+}
+
+/// Matrix times vector
+fn product(size: usize) -> Operations {
+    Operations::new(size * size, 0)
+}
+
+/// Scalar times vector, or dot product
+fn vector(size: usize) -> Operations {
+    Operations::new(size, 0)
+}
+
+/// `P A = L U` of dense matrix, zero multipliers included
+fn lu_factorization(size: usize) -> Operations {
+    (0..size).fold(Operations::default(), |total, column| {
+        let below = size - column - 1;
+        total + Operations::new(below * (size - column), below)
+    })
+}
+
+/// Forward and back substitution with `L U`
+fn lu_solve(size: usize) -> Operations {
+    Operations::new(size * (size - 1), size)
+}
+
+impl Equation {
+    /// Cost of applying preconditioner once
+    fn preconditioner_operations(&self, size: usize) -> Operations {
+        match self.preconditioning {
+            Preconditioning::None => Operations::default(),
+            Preconditioning::Jacobi => vector(size),
+            Preconditioning::Ilu0 => lu_solve(size),
+        }
+    }
+
+    /// Cost of building preconditioner, ILU(0) touches only non-zero entries
+    fn preconditioner_setup(&self, size: usize) -> Operations {
+        match self.preconditioning {
+            Preconditioning::None => Operations::default(),
+            Preconditioning::Jacobi => Operations::new(0, size),
+            Preconditioning::Ilu0 if self.input_matrix.nrows() != size => lu_factorization(size),
+            Preconditioning::Ilu0 => {
+                let matrix = &self.input_matrix;
+                let mut total = Operations::default();
+                for i in 0..size {
+                    for k in (0..i).filter(|k| !matrix[(i, *k)].is_zero()) {
+                        let updated = (k + 1..size).filter(|j| !matrix[(i, *j)].is_zero()).count();
+                        total += Operations::new(updated, 1);
+                    }
+                }
+                total
+            }
+        }
+    }
+
+    /// Relaxed update `x + w (x_new - x)` costs a multiplication per component
+    fn relaxation_operations(&self, stage: Stage, size: usize) -> Operations {
+        let relaxed = match stage {
+            // Jacobi stage of hybrid method is never damped
+            Stage::Jacobi => self.method == Method::Jacobi,
+            _ => true,
+        };
+        match relaxed && self.relaxation != Decimal::ONE {
+            true => vector(size),
+            false => Operations::default(),
+        }
+    }
+}
+
+impl Stage {
+    /// Multiplications and divisions of iteration number `index` (from 0) of
+    /// this stage over system of `size` unknowns. Model follows dense
+    /// implementation of every stage, zero entries are multiplied too.
+    /// Setup, like factorization of blocks or building of preconditioner,
+    /// is counted to the first iteration. Krylov methods count divisions of
+    /// their step lengths but not scalar multiplications of Givens rotations
+    /// and the like; reduction of fixed unknowns and Aitken extrapolation are
+    /// not counted
+    pub fn operations(&self, equation: &Equation, size: usize, index: usize) -> Operations {
+        let first = index == 0;
+        let sweep = Operations::new(size * (size - 1), size) + equation.relaxation_operations(*self, size);
+        match self {
+            Stage::Jacobi | Stage::GaussSeidel => sweep,
+            Stage::Ssor => sweep.times(2),
+            Stage::Richardson => product(size) + vector(size),
+            Stage::Chebyshev => {
+                let base = match equation.method {
+                    Method::Jacobi => sweep,
+                    _ => sweep.times(2),
+                };
+                // extrapolation and three-term recurrence
+                base + vector(size).times(2)
+            }
+            Stage::BlockGaussSeidel => {
+                let blocks = block_sizes(equation, size);
+                let mut total = Operations::default();
+                for block in &blocks {
+                    if first {
+                        total += lu_factorization(*block);
+                    }
+                    total += Operations::new(block * (size - block), 0) + lu_solve(*block);
+                }
+                total + equation.relaxation_operations(*self, size)
+            }
+            Stage::Thomas => Operations::new(3 * size - 2, 2 * size - 1),
+            Stage::BlockThomas => {
+                let blocks = block_sizes(equation, size);
+                let mut total = Operations::default();
+                for (index, block) in blocks.iter().enumerate() {
+                    if index > 0 {
+                        let previous = blocks[index - 1];
+                        // pivot block and right hand side of elimination
+                        total += Operations::new(block * previous * block + block * previous, 0);
+                    }
+                    let next = blocks.get(index + 1).copied().unwrap_or(0);
+                    total += lu_factorization(*block) + lu_solve(*block).times(next + 1);
+                    // back substitution
+                    total += Operations::new(block * next, 0);
+                }
+                total
+            }
+            Stage::Direct => {
+                let elimination = (0..size).fold(Operations::default(), |total, column| {
+                    let below = size - column - 1;
+                    total + Operations::new(below * (size - column + 1), below)
+                });
+                elimination + Operations::new(size * (size - 1) / 2, size)
+            }
+            Stage::GaussJordan => (0..size).fold(Operations::default(), |total, column| {
+                let width = size + 1 - column;
+                total + Operations::new((size - 1) * width, width)
+            }),
+            Stage::Lu => lu_factorization(size) + lu_solve(size),
+            Stage::Cholesky => {
+                let factorization = (0..size).fold(Operations::default(), |total, column| {
+                    let below = size - column - 1;
+                    total + Operations::new(column + below * column, below)
+                });
+                factorization + lu_solve(size) + Operations::new(0, size)
+            }
+            Stage::Qr => {
+                let factorization = (0..size).fold(Operations::default(), |total, column| {
+                    let rows = size - column;
+                    // norm and length of reflector, then reflection of upper
+                    // and orthogonal factor
+                    let reflection = Operations::new(2 * rows + 1, 1).times(rows + size);
+                    total + Operations::new(2 * rows, 0) + reflection
+                });
+                factorization + product(size) + Operations::new(size * (size - 1) / 2, size)
+            }
+            Stage::ConjugateGradient => {
+                let setup = match first {
+                    true => product(size) + vector(size) + equation.preconditioner_setup(size),
+                    false => Operations::default(),
+                };
+                // product, two dot products, three vector updates, alpha and beta
+                setup
+                    + product(size)
+                    + vector(size).times(5)
+                    + Operations::new(0, 2)
+                    + equation.preconditioner_operations(size)
+            }
+            Stage::Gmres => {
+                let restart = equation.restart.max(1);
+                let basis = index % restart + 1;
+                let cycle = product(size) + vector(size) + Operations::new(0, size);
+                let setup = match first {
+                    true => cycle + equation.preconditioner_setup(size),
+                    false => Operations::default(),
+                };
+                // new cycle starts after the last step, otherwise new basis
+                // vector is normalized
+                let next = match basis == restart {
+                    true => cycle,
+                    false => Operations::new(0, size),
+                };
+                // Arnoldi step with Gram-Schmidt against every basis vector,
+                // then triangular solve and combination of basis
+                let arnoldi = product(size) + vector(size).times(2 * basis + 1);
+                let triangular = Operations::new(basis * (basis - 1) / 2, basis);
+                let rotation = Operations::new(0, 2);
+                setup
+                    + arnoldi
+                    + rotation
+                    + triangular
+                    + vector(size).times(basis)
+                    + equation.preconditioner_operations(size).times(2)
+                    + next
+            }
+            Stage::BiCgStab => {
+                let setup = match first {
+                    true => product(size) + equation.preconditioner_setup(size),
+                    false => Operations::default(),
+                };
+                // two products, four dot products, six vector updates and
+                // alpha, beta and omega
+                setup
+                    + product(size).times(2)
+                    + vector(size).times(10)
+                    + Operations::new(0, 4)
+                    + equation.preconditioner_operations(size).times(2)
+            }
+        }
+    }
+}
+
+/// Sizes of blocks covering system, whole system when blocks do not fit it
+fn block_sizes(equation: &Equation, size: usize) -> Vec<usize> {
+    match equation.blocks.iter().sum::<usize>() == size {
+        true => equation.blocks.clone(),
+        false => vec![size],
+    }
+}
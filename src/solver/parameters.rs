@@ -0,0 +1,230 @@
+use core::fmt;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{Equation, Method, DEFAULT_RESTART};
+
+/// Setting of [`Equation`] which tunes method
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    /// [`Equation::relaxation`], `--omega`
+    Relaxation,
+    /// [`Equation::tau`], `--tau`
+    Tau,
+    /// [`Equation::restart`], `--restart`
+    Restart,
+}
+
+const PARAMETERS: [Parameter; 3] = [Parameter::Relaxation, Parameter::Tau, Parameter::Restart];
+
+impl Parameter {
+    /// Command line option setting parameter
+    pub fn option(&self) -> &'static str {
+        match self {
+            Parameter::Relaxation => "--omega",
+            Parameter::Tau => "--tau",
+            Parameter::Restart => "--restart",
+        }
+    }
+
+    /// Value of `equation` when it differs from what method does by default
+    fn value(&self, equation: &Equation) -> Option<Decimal> {
+        match self {
+            Parameter::Relaxation => (equation.relaxation != Decimal::ONE).then_some(equation.relaxation),
+            Parameter::Tau => equation.tau,
+            Parameter::Restart => (equation.restart != DEFAULT_RESTART).then(|| Decimal::from(equation.restart)),
+        }
+    }
+}
+
+/// End of [`Range`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Open(Decimal),
+    Closed(Decimal),
+    Unbounded,
+}
+
+/// Values parameter may take
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub lower: Bound,
+    pub upper: Bound,
+}
+
+impl Range {
+    pub fn contains(&self, value: Decimal) -> bool {
+        let above = match self.lower {
+            Bound::Open(lower) => value > lower,
+            Bound::Closed(lower) => value >= lower,
+            Bound::Unbounded => true,
+        };
+        let below = match self.upper {
+            Bound::Open(upper) => value < upper,
+            Bound::Closed(upper) => value <= upper,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.lower {
+            Bound::Open(lower) => write!(f, "({lower}, ")?,
+            Bound::Closed(lower) => write!(f, "[{lower}, ")?,
+            Bound::Unbounded => write!(f, "(-inf, ")?,
+        }
+        match self.upper {
+            Bound::Open(upper) => write!(f, "{upper})"),
+            Bound::Closed(upper) => write!(f, "{upper}]"),
+            Bound::Unbounded => write!(f, "inf)"),
+        }
+    }
+}
+
+/// How method takes parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterSpec {
+    pub parameter: Parameter,
+    /// What parameter means for the method
+    pub name: &'static str,
+    /// `None` when estimated from matrix
+    pub default: Option<Decimal>,
+    pub range: Range,
+}
+
+impl fmt::Display for ParameterSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}) in {}, ", self.name, self.parameter.option(), self.range)?;
+        match self.default {
+            Some(default) => write!(f, "{default} by default"),
+            None => write!(f, "estimated from matrix by default"),
+        }
+    }
+}
+
+const RELAXATION: ParameterSpec = ParameterSpec {
+    parameter: Parameter::Relaxation,
+    name: "relaxation factor",
+    default: Some(Decimal::ONE),
+    range: Range {
+        lower: Bound::Open(dec!(0)),
+        upper: Bound::Open(dec!(2)),
+    },
+};
+
+/// Weighted Jacobi takes `x + damping (x_jacobi - x)`, which converges for
+/// more matrices than plain Jacobi only when damping is below 1
+const DAMPING: ParameterSpec = ParameterSpec {
+    parameter: Parameter::Relaxation,
+    name: "damping",
+    default: Some(Decimal::ONE),
+    range: Range {
+        lower: Bound::Open(dec!(0)),
+        upper: Bound::Closed(dec!(1)),
+    },
+};
+
+const TAU: ParameterSpec = ParameterSpec {
+    parameter: Parameter::Tau,
+    name: "parameter of simple iteration",
+    default: None,
+    range: Range {
+        lower: Bound::Open(dec!(0)),
+        upper: Bound::Unbounded,
+    },
+};
+
+const RESTART: ParameterSpec = ParameterSpec {
+    parameter: Parameter::Restart,
+    name: "iterations between restarts",
+    default: Some(Decimal::from_parts(DEFAULT_RESTART as u32, 0, 0, false, 0)),
+    range: Range {
+        lower: Bound::Closed(dec!(1)),
+        upper: Bound::Unbounded,
+    },
+};
+
+impl Method {
+    /// Registry of parameters method takes, with their defaults and ranges.
+    /// New method with parameters adds them here and gets validation for free
+    pub fn parameters(&self) -> &'static [ParameterSpec] {
+        match self {
+            Method::GaussSeidel | Method::Ssor => &[RELAXATION],
+            // relaxation of Gauss-Seidel stage
+            Method::Hybrid => &[RELAXATION],
+            Method::Jacobi => &[DAMPING],
+            Method::Richardson => &[TAU],
+            Method::Gmres => &[RESTART],
+            Method::Gauss
+            | Method::GaussJordan
+            | Method::Lu
+            | Method::Cholesky
+            | Method::Qr
+            | Method::ConjugateGradient
+            | Method::BiCgStab
+            | Method::Auto => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    /// Method does not take parameter
+    NotApplicable { parameter: Parameter, method: Method },
+    OutOfRange {
+        spec: ParameterSpec,
+        method: Method,
+        value: Decimal,
+    },
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::NotApplicable { parameter, method } => {
+                write!(f, "{method} method does not take {}", parameter.option())?;
+                let specs = method.parameters();
+                if specs.is_empty() {
+                    return write!(f, ", it has no parameters");
+                }
+                let specs: Vec<String> = specs.iter().map(ToString::to_string).collect();
+                write!(f, ". It takes {}", specs.join("; "))
+            }
+            ParameterError::OutOfRange { spec, method, value } => write!(
+                f,
+                "{} ({}) of {method} must be in {}, got {value}",
+                spec.name,
+                spec.parameter.option(),
+                spec.range
+            ),
+        }
+    }
+}
+
+impl Equation {
+    /// Every parameter set away from default is taken by [`Equation::method`]
+    /// and is in its range
+    pub fn check_parameters(&self) -> Result<(), ParameterError> {
+        for parameter in PARAMETERS {
+            let Some(value) = parameter.value(self) else {
+                continue;
+            };
+            let spec = (self.method.parameters().iter())
+                .find(|spec| spec.parameter == parameter)
+                .ok_or(ParameterError::NotApplicable {
+                    parameter,
+                    method: self.method,
+                })?;
+            if !spec.range.contains(value) {
+                return Err(ParameterError::OutOfRange {
+                    spec: *spec,
+                    method: self.method,
+                    value,
+                });
+            }
+        }
+        Ok(())
+    }
+}
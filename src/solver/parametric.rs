@@ -0,0 +1,110 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{Equation, PIVOT_TOLERANCE};
+
+/// General solution `x = particular + t1 * null_space[0] + t2 * null_space[1] + ...`
+#[derive(Debug)]
+pub struct ParametricSolution {
+    pub rank: usize,
+    pub particular: DVector<Decimal>,
+    pub null_space: Vec<DVector<Decimal>>,
+}
+
+#[derive(Debug)]
+pub enum RankAnalysis {
+    /// Solution is unique, nothing to parametrize
+    FullRank,
+    Parametric(ParametricSolution),
+    /// Rank of augmented matrix exceeds rank of the system matrix
+    Inconsistent { rank: usize },
+}
+
+impl Equation {
+    /// Reduces augmented matrix `[A | b]` to reduced row echelon form
+    /// (with partial pivoting) to find rank and describe all solutions
+    pub fn analyze_rank(&self) -> RankAnalysis {
+        let matrix_size = self.input_matrix.nrows();
+        let mut augmented = DMatrix::from_fn(matrix_size, matrix_size + 1, |row, column| {
+            if column == matrix_size {
+                self.expression_rhs[row]
+            } else {
+                self.input_matrix[(row, column)]
+            }
+        });
+
+        let largest = augmented
+            .iter()
+            .map(|value| value.abs())
+            .fold(dec!(0), Decimal::max);
+        let tolerance = largest * PIVOT_TOLERANCE;
+
+        let mut pivot_columns = Vec::new();
+        for column in 0..matrix_size {
+            let row = pivot_columns.len();
+            let Some(pivot) = (row..matrix_size)
+                .max_by_key(|candidate| augmented[(*candidate, column)].abs())
+                .filter(|pivot| augmented[(*pivot, column)].abs() > tolerance)
+            else {
+                continue;
+            };
+
+            augmented.swap_rows(row, pivot);
+            let pivot_value = augmented[(row, column)];
+            for index in column..=matrix_size {
+                augmented[(row, index)] /= pivot_value;
+            }
+
+            for other in (0..matrix_size).filter(|other| *other != row) {
+                let factor = augmented[(other, column)];
+                if factor.is_zero() {
+                    continue;
+                }
+                for index in column..=matrix_size {
+                    let value = augmented[(other, index)] - factor * augmented[(row, index)];
+                    augmented[(other, index)] = if value.abs() > tolerance {
+                        value.normalize()
+                    } else {
+                        dec!(0)
+                    };
+                }
+            }
+
+            pivot_columns.push(column);
+        }
+
+        let rank = pivot_columns.len();
+        if rank == matrix_size {
+            return RankAnalysis::FullRank;
+        }
+
+        // zero rows of matrix part must have zero right hand side
+        if (rank..matrix_size).any(|row| !augmented[(row, matrix_size)].is_zero()) {
+            return RankAnalysis::Inconsistent { rank };
+        }
+
+        let mut particular = DVector::from_element(matrix_size, dec!(0));
+        for (row, column) in pivot_columns.iter().enumerate() {
+            particular[*column] = augmented[(row, matrix_size)].normalize();
+        }
+
+        let null_space = (0..matrix_size)
+            .filter(|column| !pivot_columns.contains(column))
+            .map(|free| {
+                let mut basis = DVector::from_element(matrix_size, dec!(0));
+                basis[free] = dec!(1);
+                for (row, column) in pivot_columns.iter().enumerate() {
+                    basis[*column] = (-augmented[(row, free)]).normalize();
+                }
+                basis
+            })
+            .collect();
+
+        RankAnalysis::Parametric(ParametricSolution {
+            rank,
+            particular,
+            null_space,
+        })
+    }
+}
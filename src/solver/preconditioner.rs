@@ -0,0 +1,136 @@
+use core::fmt;
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::str::FromStr;
+
+use super::{ESolveError, Equation, Preconditioner};
+
+/// Preconditioner Krylov methods apply, chosen by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preconditioning {
+    #[default]
+    None,
+    /// Division by diagonal, see [`DiagonalPreconditioner`]
+    Jacobi,
+    /// Incomplete LU factorization, see [`Ilu0`]
+    Ilu0,
+}
+
+impl FromStr for Preconditioning {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "none" => Ok(Preconditioning::None),
+            "jacobi" | "diagonal" => Ok(Preconditioning::Jacobi),
+            "ilu0" | "ilu" => Ok(Preconditioning::Ilu0),
+            _ => Err("Expected one of: none, jacobi, ilu0".to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Preconditioning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Preconditioning::None => "none",
+            Preconditioning::Jacobi => "jacobi",
+            Preconditioning::Ilu0 => "ilu0",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// `M = I`, leaves residual as is
+pub struct IdentityPreconditioner;
+
+impl Preconditioner for IdentityPreconditioner {
+    fn apply(&self, residual: &DVector<Decimal>) -> DVector<Decimal> {
+        residual.clone()
+    }
+}
+
+/// `M = D`, diagonal of matrix. Evens out rows of very different scale
+pub struct DiagonalPreconditioner {
+    inverse_diagonal: DVector<Decimal>,
+}
+
+impl DiagonalPreconditioner {
+    /// Fails with [`ESolveError::ZeroPivot`] when diagonal has zero
+    pub fn new(matrix: &DMatrix<Decimal>) -> Result<Self, ESolveError> {
+        let diagonal = matrix.diagonal();
+        if let Some(row) = diagonal.iter().position(Decimal::is_zero) {
+            return Err(ESolveError::ZeroPivot { row });
+        }
+        Ok(DiagonalPreconditioner {
+            inverse_diagonal: diagonal.map(|value| Decimal::ONE / value),
+        })
+    }
+}
+
+impl Preconditioner for DiagonalPreconditioner {
+    fn apply(&self, residual: &DVector<Decimal>) -> DVector<Decimal> {
+        residual.component_mul(&self.inverse_diagonal)
+    }
+}
+
+/// `M = L U`, where LU factorization keeps only entries which are non-zero
+/// in matrix itself, so factors are as sparse as matrix and fill-in is dropped
+pub struct Ilu0 {
+    /// Unit lower factor below diagonal, upper factor on and above it
+    factors: DMatrix<Decimal>,
+}
+
+impl Ilu0 {
+    /// Fails with [`ESolveError::ZeroPivot`] when elimination meets zero on diagonal
+    pub fn new(matrix: &DMatrix<Decimal>) -> Result<Self, ESolveError> {
+        let size = matrix.nrows();
+        let mut factors = matrix.clone();
+        for i in 0..size {
+            for k in (0..i).filter(|k| !matrix[(i, *k)].is_zero()) {
+                let pivot = factors[(k, k)];
+                if pivot.is_zero() {
+                    return Err(ESolveError::ZeroPivot { row: k });
+                }
+                let factor = factors[(i, k)] / pivot;
+                factors[(i, k)] = factor;
+                for j in (k + 1..size).filter(|j| !matrix[(i, *j)].is_zero()) {
+                    let update = factor * factors[(k, j)];
+                    factors[(i, j)] -= update;
+                }
+            }
+            if factors[(i, i)].is_zero() {
+                return Err(ESolveError::ZeroPivot { row: i });
+            }
+        }
+        Ok(Ilu0 { factors })
+    }
+}
+
+impl Preconditioner for Ilu0 {
+    fn apply(&self, residual: &DVector<Decimal>) -> DVector<Decimal> {
+        let size = residual.len();
+        let factors = &self.factors;
+        let mut solution = residual.clone();
+        for row in 0..size {
+            let s = (0..row).fold(dec!(0), |acc, column| acc + factors[(row, column)] * solution[column]);
+            solution[row] -= s;
+        }
+        for row in (0..size).rev() {
+            let s = (row + 1..size).fold(dec!(0), |acc, column| acc + factors[(row, column)] * solution[column]);
+            solution[row] = (solution[row] - s) / factors[(row, row)];
+        }
+        solution
+    }
+}
+
+impl Equation {
+    /// Preconditioner of [`Equation::preconditioning`], built for the matrix
+    pub fn preconditioner(&self) -> Result<Box<dyn Preconditioner>, ESolveError> {
+        Ok(match self.preconditioning {
+            Preconditioning::None => Box::new(IdentityPreconditioner),
+            Preconditioning::Jacobi => Box::new(DiagonalPreconditioner::new(&self.input_matrix)?),
+            Preconditioning::Ilu0 => Box::new(Ilu0::new(&self.input_matrix)?),
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use rust_decimal::Decimal;
+
+use super::{Equation, Method};
+
+/// Box constraint of one unknown. Iterate is projected onto the box after
+/// every update of the unknown, which turns Gauss-Seidel into projected
+/// Gauss-Seidel: solution of linear complementarity problem when bounds are active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Bounds {
+    pub lower: Option<Decimal>,
+    pub upper: Option<Decimal>,
+}
+
+impl Bounds {
+    pub fn clamp(&self, value: Decimal) -> Decimal {
+        let value = self.lower.map_or(value, |lower| value.max(lower));
+        self.upper.map_or(value, |upper| value.min(upper))
+    }
+}
+
+impl Method {
+    /// Updates unknowns one by one, so each update can be projected
+    pub fn supports_bounds(&self) -> bool {
+        matches!(self, Method::GaussSeidel | Method::Jacobi | Method::Richardson | Method::Ssor)
+    }
+}
+
+impl Equation {
+    /// Projects new value of unknown `index` onto its bounds
+    pub(super) fn project(&self, index: usize, value: Decimal) -> Decimal {
+        match self.bounds.binary_search_by_key(&index, |(bounded, _)| *bounded) {
+            Ok(position) => self.bounds[position].1.clamp(value),
+            Err(_) => value,
+        }
+    }
+}
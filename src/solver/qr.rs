@@ -0,0 +1,102 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::prelude::*;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{pivot_tolerance, ESolveError, Equation};
+
+/// `A = Q R` by Householder reflections. Orthogonal `Q` does not amplify
+/// rounding errors, so nearly singular matrices are solved more reliably
+/// than by elimination
+#[derive(Debug, Clone)]
+pub struct QrFactorization {
+    /// Orthogonal, `Q^T Q = I`
+    pub orthogonal: DMatrix<Decimal>,
+    /// Upper triangular
+    pub upper: DMatrix<Decimal>,
+}
+
+impl QrFactorization {
+    /// Fails with [`ESolveError::Singular`] when matrix is singular or nearly so
+    pub fn new(matrix: &DMatrix<Decimal>) -> Result<Self, ESolveError> {
+        let size = matrix.nrows();
+        let mut upper = matrix.clone();
+        let mut orthogonal = DMatrix::identity(size, size);
+        let tolerance = pivot_tolerance(matrix);
+
+        for column in 0..size {
+            let mut reflector: Vec<Decimal> = (column..size).map(|row| upper[(row, column)]).collect();
+            let norm = reflector
+                .iter()
+                .fold(dec!(0), |acc, value| acc + value * value)
+                .sqrt()
+                .ok_or(ESolveError::Diverged { growth_factor: None })?;
+            if norm <= tolerance {
+                return Err(ESolveError::Singular { column: Some(column) });
+            }
+            // reflecting onto the side away from x keeps v from cancelling out
+            let alpha = if reflector[0].is_sign_negative() { norm } else { -norm };
+            reflector[0] -= alpha;
+            let length = reflector.iter().fold(dec!(0), |acc, value| acc + value * value);
+            if length.is_zero() {
+                continue;
+            }
+
+            // H = I - 2 v v^T / (v^T v) applied to R from the left and to Q from the right
+            for index in column..size {
+                let dot = (column..size)
+                    .fold(dec!(0), |acc, row| acc + reflector[row - column] * upper[(row, index)]);
+                let scale = dec!(2) * dot / length;
+                for row in column..size {
+                    upper[(row, index)] -= scale * reflector[row - column];
+                }
+            }
+            for row in 0..size {
+                let dot = (column..size).fold(dec!(0), |acc, index| {
+                    acc + orthogonal[(row, index)] * reflector[index - column]
+                });
+                let scale = dec!(2) * dot / length;
+                for index in column..size {
+                    orthogonal[(row, index)] -= scale * reflector[index - column];
+                }
+            }
+        }
+
+        if let Some(row) = (0..size).find(|row| upper[(*row, *row)].abs() <= tolerance) {
+            return Err(ESolveError::Singular { column: Some(row) });
+        }
+        Ok(QrFactorization { orthogonal, upper })
+    }
+
+    /// Solves `R x = Q^T rhs` by back substitution
+    pub fn solve(&self, rhs: &DVector<Decimal>) -> DVector<Decimal> {
+        let size = rhs.len();
+        let y = DVector::from_fn(size, |row, _| {
+            (0..size).fold(dec!(0), |acc, index| acc + self.orthogonal[(index, row)] * rhs[index])
+        });
+
+        let mut x = DVector::from_element(size, dec!(0));
+        for row in (0..size).rev() {
+            let s = (row + 1..size).fold(dec!(0), |acc, column| acc + self.upper[(row, column)] * x[column]);
+            x[row] = (y[row] - s) / self.upper[(row, row)];
+        }
+        x
+    }
+}
+
+impl Equation {
+    pub fn factor_qr(&self) -> Result<QrFactorization, ESolveError> {
+        QrFactorization::new(&self.input_matrix)
+    }
+
+    /// Replaces approximation with solution found through QR factorization,
+    /// returns largest change
+    pub(super) fn qr_step(&self, result_vector: &mut DVector<Decimal>) -> Result<Decimal, ESolveError> {
+        let solution = self.factor_qr()?.solve(&self.expression_rhs);
+        let delta = (0..solution.len())
+            .map(|row| (solution[row] - result_vector[row]).abs())
+            .fold(dec!(0), Decimal::max);
+        *result_vector = solution;
+        Ok(delta)
+    }
+}
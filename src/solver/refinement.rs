@@ -0,0 +1,92 @@
+use core::fmt;
+use nalgebra::DVector;
+use rust_decimal::{Decimal, MathematicalOps};
+
+use super::{Equation, LuFactorization, DIRECT_MAX_SIZE};
+use crate::norm::Norm;
+
+/// Result of [`Equation::refine`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Refinement {
+    /// Steps which made residual smaller, the rest were cut short
+    pub steps: usize,
+    /// Euclidean norm of `A x - b` before and after refinement
+    pub residual_before: Decimal,
+    pub residual_after: Decimal,
+}
+
+impl Refinement {
+    /// Digits gained: how many times residual shrank, in powers of 10
+    pub fn digits(&self) -> Option<Decimal> {
+        if self.residual_after.is_zero() || self.residual_before.is_zero() {
+            return None;
+        }
+        Some((self.residual_before / self.residual_after).checked_log10()?.max(Decimal::ZERO))
+    }
+}
+
+impl fmt::Display for Refinement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let norm = |value: Decimal| value.round_sf(3).unwrap_or(value).normalize();
+        let steps = match self.steps {
+            1 => "1 step".to_owned(),
+            steps => format!("{steps} steps"),
+        };
+        write!(
+            f,
+            "{steps}, residual norm {} down to {}",
+            norm(self.residual_before),
+            norm(self.residual_after)
+        )?;
+        match (self.digits(), self.residual_after.is_zero()) {
+            (_, true) => write!(f, ", solution is exact"),
+            (Some(digits), false) => write!(f, ", {} digits gained", digits.round_dp(1).normalize()),
+            (None, false) => Ok(()),
+        }
+    }
+}
+
+impl Equation {
+    /// Iterative refinement of solution found by any method: residual
+    /// `r = b - A x` is solved for correction `A d = r` through LU
+    /// factorization and `x + d` is taken. Every step recovers digits lost by
+    /// rounding or stopped iterations, as long as residual is computed
+    /// exactly enough, which Decimal does. Stops early when correction no
+    /// longer makes residual smaller, so solution never gets worse. `None`
+    /// when matrix is singular, above [`DIRECT_MAX_SIZE`], or has fixed or
+    /// bounded unknowns, which refinement of the whole system would break
+    pub fn refine(&self, solution: &mut DVector<Decimal>, steps: usize) -> Option<Refinement> {
+        let size = self.input_matrix.nrows();
+        if steps == 0 || size > DIRECT_MAX_SIZE || !self.fixed.is_empty() || !self.bounds.is_empty() {
+            return None;
+        }
+        let factors = LuFactorization::new(&self.input_matrix).ok()?;
+
+        let residual = |solution: &DVector<Decimal>| &self.expression_rhs - &self.input_matrix * solution;
+        let mut current = residual(solution);
+        let residual_before = Norm::L2.of(current.iter().copied());
+        let mut residual_after = residual_before;
+        let mut done = 0;
+        while done < steps && !residual_after.is_zero() {
+            let Ok(correction) = factors.solve(&current) else {
+                break;
+            };
+            let refined = &*solution + correction;
+            let next = residual(&refined);
+            let norm = Norm::L2.of(next.iter().copied());
+            if norm >= residual_after {
+                break;
+            }
+            *solution = refined;
+            current = next;
+            residual_after = norm;
+            done += 1;
+        }
+
+        Some(Refinement {
+            steps: done,
+            residual_before,
+            residual_after,
+        })
+    }
+}
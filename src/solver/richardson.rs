@@ -0,0 +1,55 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{change, Equation, Overflow};
+
+impl Equation {
+    /// Parameter of Richardson iteration: the given one, or estimate from
+    /// Gershgorin discs. When all discs lie right of zero, eigenvalues of
+    /// symmetric matrix are in `[low, high]` and `2 / (low + high)` is optimal
+    /// for that interval, otherwise `1 / high` keeps step below instability.
+    /// `None` for zero matrix
+    pub fn richardson_tau(&self) -> Option<Decimal> {
+        if let Some(tau) = self.tau {
+            return Some(tau);
+        }
+
+        let matrix = &self.input_matrix;
+        let (low, high) = (0..matrix.nrows())
+            .map(|row| {
+                let radius = (0..matrix.ncols())
+                    .filter(|column| *column != row)
+                    .fold(dec!(0), |acc, column| acc + matrix[(row, column)].abs());
+                let center = matrix[(row, row)];
+                (center - radius, center.abs() + radius)
+            })
+            .fold((Decimal::MAX, dec!(0)), |(low, high), (disc_low, disc_high)| {
+                (low.min(disc_low), high.max(disc_high))
+            });
+
+        match (low, high) {
+            (_, high) if high.is_zero() => None,
+            (low, high) if low > dec!(0) => Some(Decimal::TWO / (low + high)),
+            (_, high) => Some(Decimal::ONE / high),
+        }
+    }
+
+    /// `x + tau (b - A x)`, every component from previous approximation.
+    /// Returns largest change of component
+    pub(super) fn richardson_step(&self, tau: Decimal, result_vector: &mut DVector<Decimal>) -> Result<Decimal, Overflow> {
+        let residual = self.residual_of(result_vector)?;
+        let mut delta = dec!(0);
+
+        for i in 0..result_vector.len() {
+            let step = (tau.checked_mul(residual[i]))
+                .and_then(|step| result_vector[i].checked_add(step))
+                .ok_or(Overflow { row: i })?;
+            let x = self.project(i, step);
+            delta = delta.max(change(i, x, result_vector[i])?);
+            result_vector[i] = x;
+        }
+
+        Ok(delta)
+    }
+}
@@ -0,0 +1,212 @@
+use core::fmt;
+use core::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use super::{Equation, Overflow};
+
+/// Significant digits of high part of factor in [`Rounding::Wide`]: product
+/// of two high parts has at most 28 digits, which Decimal keeps exactly
+const SPLIT_DIGITS: u32 = 14;
+
+/// How products `a_ij x_j` of row sums are rounded. Decimal keeps at most 28
+/// decimal places and 96 bits of digits, product of two numbers with many
+/// digits does not fit and is rounded silently. Applies to row sums of
+/// sweeps of stationary methods and of products by matrix of Krylov methods
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Decimal rounds product to whatever fits, so it is rounded at
+    /// different places depending on magnitudes and digits of factors
+    #[default]
+    Native,
+    /// Every product is rounded to this many decimal places before it is
+    /// added: sum keeps the same scale and is rounded at one place only
+    Rescale(u32),
+    /// Factors are split so that parts of product are exact, parts are
+    /// summed with compensation, like double-double arithmetic, and sum is
+    /// rounded once at the end. Row sums are done by one thread
+    Wide,
+}
+
+impl FromStr for Rounding {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        const EXPECTED: &str = "Expected one of: native, rescale:<places>, wide";
+        match value.split_once(':') {
+            Some(("rescale", places)) => match places.parse() {
+                Ok(places) if places <= 28 => Ok(Rounding::Rescale(places)),
+                _ => Err("Expected decimal places from 0 to 28, like rescale:20".to_owned()),
+            },
+            Some(_) => Err(EXPECTED.to_owned()),
+            None => match value {
+                "native" => Ok(Rounding::Native),
+                "wide" => Ok(Rounding::Wide),
+                _ => Err(EXPECTED.to_owned()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rounding::Native => write!(f, "native"),
+            Rounding::Rescale(places) => write!(f, "rescale:{places}"),
+            Rounding::Wide => write!(f, "wide"),
+        }
+    }
+}
+
+/// Coarsest rounding of products seen by row sums, shared by clones of
+/// equation, so that its parts solved separately record into the same log
+#[derive(Debug, Clone)]
+pub struct RoundingLog {
+    /// Fewest decimal places rounded product kept, `u32::MAX` when none was rounded
+    coarsest: Arc<AtomicU32>,
+}
+
+impl Default for RoundingLog {
+    fn default() -> Self {
+        RoundingLog {
+            coarsest: Arc::new(AtomicU32::new(u32::MAX)),
+        }
+    }
+}
+
+impl RoundingLog {
+    /// Product was rounded to `places` decimal places
+    fn record(&self, places: u32) {
+        // read first, so that threads write only when rounding gets coarser
+        if places < self.coarsest.load(Ordering::Relaxed) {
+            self.coarsest.fetch_min(places, Ordering::Relaxed);
+        }
+    }
+
+    /// Rounding of product which `product` may be, computed as `a b`
+    fn observe(&self, a: Decimal, b: Decimal, product: Decimal) {
+        if !product.is_zero() && product.scale() < a.scale() + b.scale() {
+            self.record(product.scale());
+        }
+    }
+
+    pub fn coarsest_places(&self) -> Option<u32> {
+        match self.coarsest.load(Ordering::Relaxed) {
+            u32::MAX => None,
+            places => Some(places),
+        }
+    }
+}
+
+/// Rounding strategy of solve and worst rounding it applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundingReport {
+    pub rounding: Rounding,
+    /// Fewest decimal places product was rounded to, `None` when every
+    /// product was exact
+    pub coarsest_places: Option<u32>,
+}
+
+impl RoundingReport {
+    /// Bound of error of single rounding, half a unit of the coarsest place
+    /// kept. Written as text, it may be below smallest Decimal
+    pub fn worst_error(&self) -> Option<String> {
+        self.coarsest_places.map(|places| format!("5e-{}", places + 1))
+    }
+}
+
+impl fmt::Display for RoundingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.coarsest_places, self.worst_error()) {
+            (Some(places), Some(error)) => write!(
+                f,
+                "{}, products were rounded to as few as {places} decimal places, by up to {error} each",
+                self.rounding
+            ),
+            _ => write!(f, "{}, every product was exact", self.rounding),
+        }
+    }
+}
+
+/// `a + b` and its rounding error, exact for rounding to nearest in any
+/// radix. `None` when sum does not fit, its parts then fit too
+fn two_sum(a: Decimal, b: Decimal) -> Option<(Decimal, Decimal)> {
+    let sum = a.checked_add(b)?;
+    let b_virtual = sum - a;
+    let a_virtual = sum - b_virtual;
+    Some((sum, (a - a_virtual) + (b - b_virtual)))
+}
+
+/// High part of `SPLIT_DIGITS` significant digits and exact rest
+fn split(value: Decimal) -> (Decimal, Decimal) {
+    let high = value.round_sf(SPLIT_DIGITS).unwrap_or(value);
+    (high, value - high)
+}
+
+impl Equation {
+    /// `sum of a_ij v_j` over row `row` of matrix, columns for which `skip`
+    /// holds are left out. Products are rounded by [`Equation::rounding`]
+    /// and recorded to [`Equation::rounding_log`]
+    pub(super) fn row_sum(
+        &self,
+        row: usize,
+        vector: &DVector<Decimal>,
+        skip: impl Fn(usize) -> bool + Sync,
+    ) -> Result<Decimal, Overflow> {
+        let size = vector.len();
+        let log = &self.rounding_log;
+        let product = |column: usize| {
+            let (a, b) = (self.input_matrix[(row, column)], vector[column]);
+            let product = a.checked_mul(b)?;
+            log.observe(a, b, product);
+            Some(product)
+        };
+        let sum = match self.rounding {
+            Rounding::Native => self.parallelism.sum(size, |column| match skip(column) {
+                true => Some(dec!(0)),
+                false => product(column),
+            }),
+            Rounding::Rescale(places) => self.parallelism.sum(size, |column| {
+                if skip(column) {
+                    return Some(dec!(0));
+                }
+                let product = product(column)?;
+                if product.scale() > places {
+                    log.record(places);
+                }
+                Some(product.round_dp(places))
+            }),
+            Rounding::Wide => self.wide_row_sum(row, vector, skip),
+        };
+        sum.ok_or(Overflow { row })
+    }
+
+    /// Row sum of [`Rounding::Wide`], `None` when it does not fit into Decimal
+    fn wide_row_sum(&self, row: usize, vector: &DVector<Decimal>, skip: impl Fn(usize) -> bool) -> Option<Decimal> {
+        let (mut sum, mut compensation) = (dec!(0), dec!(0));
+        for column in (0..vector.len()).filter(|column| !skip(*column)) {
+            let (a, b) = (self.input_matrix[(row, column)], vector[column]);
+            let ((a_high, a_low), (b_high, b_low)) = (split(a), split(b));
+            for (x, y) in [(a_high, b_high), (a_high, b_low), (a_low, b_high), (a_low, b_low)] {
+                let part = x.checked_mul(y)?;
+                self.rounding_log.observe(x, y, part);
+                let (total, error) = two_sum(sum, part)?;
+                sum = total;
+                compensation += error;
+            }
+        }
+        sum.checked_add(compensation)
+    }
+
+    /// Strategy of this equation and worst rounding recorded so far
+    pub fn rounding_report(&self) -> RoundingReport {
+        RoundingReport {
+            rounding: self.rounding,
+            coarsest_places: self.rounding_log.coarsest_places(),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use std::ops::ControlFlow;
+
+use super::{ESolveError, Equation, InitialGuess, Progress};
+
+impl Equation {
+    /// Solves `[[A, B], [C, D]] [x, y] = [f, g]` by eliminating `x`:
+    /// `(D - C A^-1 B) y = g - C A^-1 f`, then `x = A^-1 f - A^-1 B y`.
+    ///
+    /// `A^-1 f` and every column of `A^-1 B` are found by Gauss-Seidel on `A`,
+    /// so only `A` needs non-zero diagonal. `D` may be zero, as in saddle point
+    /// systems of constrained optimization. Iterations of all inner solves
+    /// are reported as one continuous count, with iterate of inner system.
+    /// Given initial guess is of `[x, y]`, so only complement solve starts
+    /// from it, from its `y` part
+    pub(super) fn solve_schur(
+        &self,
+        block: usize,
+        mut on_iteration: impl FnMut(&Progress) -> ControlFlow<()>,
+    ) -> Result<DVector<Decimal>, ESolveError> {
+        let size = self.input_matrix.nrows();
+        let rest = size - block;
+        let a = self.input_matrix.view((0, 0), (block, block)).into_owned();
+        let b = self.input_matrix.view((0, block), (block, rest));
+        let c = self.input_matrix.view((block, 0), (rest, block));
+        let d = self.input_matrix.view((block, block), (rest, rest));
+        let f = self.expression_rhs.rows(0, block);
+        let g = self.expression_rhs.rows(block, rest);
+
+        let mut total = 0;
+        let mut report = |progress: &Progress| {
+            total += 1;
+            on_iteration(&Progress {
+                iteration: total,
+                ..*progress
+            })
+        };
+        let mut solve_block = |matrix: DMatrix<Decimal>, rhs: DVector<Decimal>, initial_guess: InitialGuess| {
+            Equation {
+                initial_guess,
+                ..self.part(matrix, rhs)
+            }
+            .iterate(&mut report)
+        };
+        let inner = match &self.initial_guess {
+            InitialGuess::Given(_) => InitialGuess::default(),
+            strategy => strategy.clone(),
+        };
+
+        let a_inverse_f = solve_block(a.clone(), f.into_owned(), inner.clone())?;
+        let mut a_inverse_b = DMatrix::zeros(block, rest);
+        for column in 0..rest {
+            let solved = solve_block(a.clone(), b.column(column).into_owned(), inner.clone())?;
+            a_inverse_b.set_column(column, &solved);
+        }
+
+        let complement = d - c * &a_inverse_b;
+        if let Some(row) = (0..rest).find(|row| complement[(*row, *row)].is_zero()) {
+            return Err(ESolveError::SchurZeroDiagonal { row: block + row + 1 });
+        }
+        let y_guess = self.initial_guess.select(&(block..size).collect::<Vec<_>>());
+        let y = solve_block(complement, g - c * &a_inverse_f, y_guess)?;
+        let x = a_inverse_f - a_inverse_b * &y;
+
+        Ok(DVector::from_iterator(size, x.iter().chain(y.iter()).copied()))
+    }
+
+    /// Subsystem sharing settings of this one
+    fn part(&self, input_matrix: DMatrix<Decimal>, expression_rhs: DVector<Decimal>) -> Equation {
+        Equation {
+            input_matrix,
+            expression_rhs,
+            max_iterations: self.max_iterations,
+            epsilon: self.epsilon,
+            tolerance_mode: self.tolerance_mode,
+            norm: self.norm,
+            parallelism: self.parallelism.clone(),
+            schur_block: None,
+            epsilon_schedule: self.epsilon_schedule.clone(),
+            method: self.method,
+            relaxation: self.relaxation,
+            tau: self.tau,
+            restart: self.restart,
+            fast_path: self.fast_path,
+            acceleration: self.acceleration,
+            preconditioning: self.preconditioning,
+            row_order: Vec::new(),
+            refinement_steps: self.refinement_steps,
+            rounding: self.rounding,
+            rounding_log: self.rounding_log.clone(),
+            fixed: Vec::new(),
+            bounds: Vec::new(),
+            blocks: Vec::new(),
+            initial_guess: InitialGuess::default(),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use core::fmt;
+use rust_decimal::Decimal;
+
+use super::{Equation, Method, Preconditioning};
+use crate::preprocess::dominance_ratio;
+
+/// Systems up to this size are factored directly: factorization is cheap
+/// and, unlike iterations, can't fail to converge
+pub const DIRECT_MAX_SIZE: usize = 100;
+
+/// Method [`Method::Auto`] resolves to, with explanation for the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub method: Method,
+    pub reason: String,
+}
+
+/// Rows listed by [`DominanceWarning`] before the rest are only counted
+const LISTED_ROWS: usize = 5;
+
+/// Jacobi or Gauss-Seidel is about to iterate over matrix which is not strictly
+/// diagonally dominant, so nothing guarantees it converges
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominanceWarning {
+    pub method: Method,
+    /// Rows by index from 0 with diagonal entry not above sum of others,
+    /// along with their ratio, see [`dominance_ratio`]
+    pub rows: Vec<(usize, Decimal)>,
+}
+
+impl fmt::Display for DominanceWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = (self.rows.iter().take(LISTED_ROWS))
+            .map(|(row, ratio)| format!("{} (ratio {})", row + 1, ratio.round_dp(4).normalize()))
+            .collect();
+        let mut rows = rows.join(", ");
+        if self.rows.len() > LISTED_ROWS {
+            rows += &format!(" and {} more", self.rows.len() - LISTED_ROWS);
+        }
+        write!(
+            f,
+            "Matrix is not strictly diagonally dominant, diagonal entry is not above sum of the rest \
+             of row in rows {rows}. Convergence of {} is not guaranteed, it may diverge. \
+             Try --reorder dominance, --method auto or a direct method",
+            self.method
+        )
+    }
+}
+
+impl Selection {
+    fn new(method: Method, reason: impl Into<String>) -> Self {
+        Selection {
+            method,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Equation {
+    pub fn is_symmetric(&self) -> bool {
+        let matrix = &self.input_matrix;
+        (0..matrix.nrows()).all(|row| (0..row).all(|column| matrix[(row, column)] == matrix[(column, row)]))
+    }
+
+    /// Every row has diagonal entry larger than sum of others, so Jacobi and
+    /// Gauss-Seidel converge from any start
+    pub fn is_diagonally_dominant(&self) -> bool {
+        (0..self.input_matrix.nrows())
+            .all(|row| dominance_ratio(&self.input_matrix, row).is_none_or(|ratio| ratio > Decimal::ONE))
+    }
+
+    /// Warning for stationary methods which are not guaranteed to converge.
+    /// Symmetric positive definite matrices are fine for Gauss-Seidel, and
+    /// `None` is returned too when no iterations over single unknowns are done
+    pub fn dominance_warning(&self) -> Option<DominanceWarning> {
+        if !matches!(self.method, Method::Jacobi | Method::GaussSeidel | Method::Hybrid)
+            || !self.blocks.is_empty()
+            || self.uses_thomas()
+        {
+            return None;
+        }
+        let matrix = &self.input_matrix;
+        let rows: Vec<(usize, Decimal)> = (0..matrix.nrows())
+            .filter_map(|row| dominance_ratio(matrix, row).map(|ratio| (row, ratio)))
+            .filter(|(_, ratio)| *ratio <= Decimal::ONE)
+            .collect();
+        if rows.is_empty() {
+            return None;
+        }
+        let definite = self.is_symmetric()
+            && matrix.nrows() <= DIRECT_MAX_SIZE
+            && self.is_symmetric_positive_definite();
+        if self.method == Method::GaussSeidel && definite {
+            return None;
+        }
+        Some(DominanceWarning {
+            method: self.method,
+            rows,
+        })
+    }
+
+    /// Picks method by structure of matrix: bandwidth, symmetry, positive
+    /// definiteness, diagonal dominance and size, checked in that order
+    pub fn select_method(&self) -> Selection {
+        let size = self.input_matrix.nrows();
+        if !self.bounds.is_empty() {
+            return Selection::new(
+                Method::GaussSeidel,
+                "bounds are applied only by methods updating unknowns one by one",
+            );
+        }
+        if !self.blocks.is_empty() {
+            return Selection::new(
+                Method::GaussSeidel,
+                "blocks of unknowns are given, block Gauss-Seidel solves them",
+            );
+        }
+        if self.preconditioning != Preconditioning::None {
+            let positive = (0..size).all(|row| self.input_matrix[(row, row)] > Decimal::ZERO);
+            return match self.is_symmetric() && positive {
+                true => Selection::new(
+                    Method::ConjugateGradient,
+                    "preconditioner is given and matrix is symmetric with positive diagonal",
+                ),
+                false => Selection::new(Method::Gmres, "preconditioner is given and matrix is not symmetric"),
+            };
+        }
+        if self.would_use_thomas(Method::GaussSeidel) {
+            return Selection::new(
+                Method::GaussSeidel,
+                "matrix is tridiagonal, Thomas algorithm solves it in linear time",
+            );
+        }
+
+        let dominant = self.is_diagonally_dominant();
+        if self.is_symmetric() {
+            // factoring large matrix only to learn it is definite costs as much
+            // as solving, dominance with positive diagonal is sufficient and cheap
+            if size <= DIRECT_MAX_SIZE && self.is_symmetric_positive_definite() {
+                return Selection::new(
+                    Method::Cholesky,
+                    format!("matrix is symmetric positive definite and has only {size} rows"),
+                );
+            }
+            let positive = (0..size).all(|row| self.input_matrix[(row, row)] > Decimal::ZERO);
+            if size > DIRECT_MAX_SIZE && dominant && positive {
+                return Selection::new(
+                    Method::ConjugateGradient,
+                    format!("matrix is symmetric positive definite and has {size} rows"),
+                );
+            }
+        }
+        if dominant {
+            return Selection::new(
+                Method::GaussSeidel,
+                "matrix is diagonally dominant, so Gauss-Seidel converges",
+            );
+        }
+        if size <= DIRECT_MAX_SIZE {
+            return Selection::new(
+                Method::Lu,
+                format!("matrix is not diagonally dominant and has only {size} rows"),
+            );
+        }
+        Selection::new(
+            Method::Gmres,
+            format!("matrix is not diagonally dominant and has {size} rows, too many to factor"),
+        )
+    }
+}
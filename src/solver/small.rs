@@ -0,0 +1,58 @@
+use nalgebra::{SMatrix, SVector};
+use rust_decimal::Decimal;
+use seidel_kernel::{KernelError, System};
+
+use super::{exhausted, ESolveError};
+
+/// Largest system [`solve_small`] is meant for: beyond it stack copies of
+/// matrix cost more than allocation saves
+pub const SMALL_MAX_SIZE: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmallSolution<const N: usize> {
+    pub solution: SVector<Decimal, N>,
+    pub iterations: usize,
+    /// Largest change of component on the last iteration
+    pub delta: Decimal,
+}
+
+/// Plain Gauss-Seidel from approximation of all ones for system of size known at
+/// compile time. Nothing is allocated on heap, which matters when thousands
+/// of tiny systems are solved per second. Iterations stop on the same
+/// criterion as [`super::Equation::solve_with_progress`] uses, and at least
+/// one sweep is done like it does. Calls for more than [`SMALL_MAX_SIZE`]
+/// unknowns do not compile
+pub fn solve_small<const N: usize>(
+    matrix: &SMatrix<Decimal, N, N>,
+    rhs: &SVector<Decimal, N>,
+    epsilon: Decimal,
+    max_iterations: usize,
+) -> Result<SmallSolution<N>, ESolveError> {
+    const { assert!(N <= SMALL_MAX_SIZE, "solve_small is meant for systems of at most SMALL_MAX_SIZE unknowns") };
+
+    let system = System::new(
+        std::array::from_fn(|row| std::array::from_fn(|column| matrix[(row, column)])),
+        std::array::from_fn(|row| rhs[row]),
+    );
+
+    match system.solve(epsilon, max_iterations) {
+        Ok(solution) => Ok(SmallSolution {
+            solution: SVector::from(solution.values),
+            iterations: solution.iterations,
+            delta: solution.delta,
+        }),
+        Err(KernelError::ZeroDiagonal { row }) => Err(ESolveError::ZeroDiagonal { row }),
+        // kernel keeps no deltas, iterations are repeated to tell why they failed
+        Err(KernelError::Diverge) => {
+            let mut values = [Decimal::ONE; N];
+            let (mut best, mut last) = (Decimal::MAX, Decimal::MAX);
+            for iteration in 1..=max_iterations.max(1) {
+                last = (system.sweep(&mut values))
+                    .map_err(|row| ESolveError::NumericOverflow { row, iteration })?;
+                best = best.min(last);
+            }
+            Err(exhausted(best, last))
+        }
+        Err(KernelError::Overflow { row, iteration }) => Err(ESolveError::NumericOverflow { row, iteration }),
+    }
+}
@@ -0,0 +1,227 @@
+use core::fmt;
+use nalgebra::DVector;
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+
+use super::block::BlockSweep;
+use super::{Acceleration, Equation, Method, RoundingLog, DIRECT_MAX_SIZE};
+use crate::condition::CONDITION_MAX_SIZE;
+
+/// Steps of power iteration. Growth of the second half is averaged, the
+/// first half lets components of smaller eigenvalues die out
+const POWER_STEPS: usize = 60;
+
+/// Estimates closer to 1 than this are not trusted to tell slow convergence
+/// from divergence
+const UNSURE_MARGIN: Decimal = dec!(0.05);
+
+/// Why iterations converge regardless of estimate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Guarantee {
+    /// Every row is strictly diagonally dominant
+    DiagonalDominance,
+    /// Matrix is symmetric positive definite and relaxation is in `(0, 2)`
+    PositiveDefinite,
+}
+
+impl fmt::Display for Guarantee {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Guarantee::DiagonalDominance => write!(f, "strict diagonal dominance"),
+            Guarantee::PositiveDefinite => write!(f, "symmetric positive definite matrix"),
+        }
+    }
+}
+
+/// What estimate tells about iterations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Converges,
+    /// Estimate is within [`UNSURE_MARGIN`] of 1 and nothing guarantees convergence
+    Uncertain,
+    Diverges,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Converges => write!(f, "converges"),
+            Verdict::Uncertain => write!(f, "uncertain"),
+            Verdict::Diverges => write!(f, "diverges"),
+        }
+    }
+}
+
+/// Spectral radius of iteration matrix `M` of stationary method: iterations
+/// converge for every start exactly when it is below 1, and error shrinks
+/// about this many times per iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpectralRadius {
+    /// Found by power iteration, `|M^k x|` grows like `radius^k`. Estimate
+    /// may be off for matrices whose largest eigenvalues are close in modulus
+    pub estimate: Decimal,
+    pub guarantee: Option<Guarantee>,
+}
+
+impl SpectralRadius {
+    pub fn verdict(&self) -> Verdict {
+        if self.guarantee.is_some() || self.estimate <= Decimal::ONE - UNSURE_MARGIN {
+            Verdict::Converges
+        } else if self.diverges() {
+            Verdict::Diverges
+        } else {
+            Verdict::Uncertain
+        }
+    }
+
+    /// Estimate is clearly above 1 and nothing guarantees convergence
+    pub fn diverges(&self) -> bool {
+        self.guarantee.is_none() && self.estimate > Decimal::ONE + UNSURE_MARGIN
+    }
+
+    /// Iterations to shrink error 10 times, `None` when it does not shrink
+    pub fn iterations_per_digit(&self) -> Option<Decimal> {
+        if self.estimate.is_zero() {
+            return Some(dec!(0));
+        }
+        let log = self.estimate.checked_log10()?;
+        (log < dec!(0)).then(|| -Decimal::ONE / log)
+    }
+
+    /// Iterations for error of first iteration to fall below epsilon
+    pub fn predicted_iterations(&self, epsilon: Decimal) -> Option<usize> {
+        let per_digit = self.iterations_per_digit()?;
+        let digits = -epsilon.checked_log10()?;
+        let iterations = (per_digit * digits.max(dec!(0))).ceil();
+        iterations.try_into().ok()
+    }
+}
+
+impl fmt::Display for SpectralRadius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let estimate = self.estimate.round_sf(3).unwrap_or(self.estimate).normalize();
+        write!(f, "about {estimate}, ")?;
+        match (self.guarantee, self.iterations_per_digit()) {
+            (Some(guarantee), Some(per_digit)) => write!(
+                f,
+                "convergence is guaranteed by {guarantee}, {} iterations per digit",
+                per_digit.round_dp(2).normalize()
+            ),
+            (Some(guarantee), None) => write!(f, "convergence is guaranteed by {guarantee}, but slow"),
+            (None, _) if self.verdict() == Verdict::Uncertain => {
+                write!(f, "iterations may converge slowly or not at all")
+            }
+            (None, Some(per_digit)) => write!(
+                f,
+                "iterations converge, {} iterations per digit",
+                per_digit.round_dp(2).normalize()
+            ),
+            (None, None) => write!(f, "iterations diverge"),
+        }
+    }
+}
+
+impl Equation {
+    /// Spectral radius of iteration matrix of Jacobi, Gauss-Seidel (also
+    /// by blocks), SSOR and Richardson, or of Jacobi stage of hybrid method.
+    /// `M` is never built: sweep over the same system with zero right hand
+    /// side multiplies by it. `None` for other methods, for systems solved
+    /// by Thomas algorithm, with acceleration, fixed unknowns, bounds or
+    /// Schur complement, whose iterations are not plain sweeps over the whole
+    /// system, and above [`CONDITION_MAX_SIZE`]
+    pub fn spectral_radius(&self) -> Option<SpectralRadius> {
+        let size = self.input_matrix.nrows();
+        let stationary = matches!(
+            self.method,
+            Method::Jacobi | Method::GaussSeidel | Method::Ssor | Method::Richardson | Method::Hybrid
+        );
+        if !stationary
+            || self.uses_thomas()
+            || self.uses_block_thomas()
+            || self.acceleration != Acceleration::None
+            || !self.fixed.is_empty()
+            || !self.bounds.is_empty()
+            || self.schur_block.is_some()
+            || size > CONDITION_MAX_SIZE
+            || size == 0
+        {
+            return None;
+        }
+        if self.method != Method::Richardson && (0..size).any(|row| self.input_matrix[(row, row)].is_zero()) {
+            return None;
+        }
+
+        let mut homogeneous = self.clone();
+        homogeneous.expression_rhs = DVector::from_element(size, dec!(0));
+        // products of estimate are not ones of solve
+        homogeneous.rounding_log = RoundingLog::default();
+        let blocks = match self.method {
+            Method::GaussSeidel if !self.blocks.is_empty() => Some(BlockSweep::new(self).ok()?),
+            _ => None,
+        };
+        let tau = match self.method {
+            Method::Richardson => Some(self.richardson_tau()?),
+            _ => None,
+        };
+        let apply = |vector: &mut DVector<Decimal>| match self.method {
+            Method::Jacobi | Method::Hybrid => homogeneous.jacobi_sweep(vector),
+            Method::GaussSeidel => match &blocks {
+                Some(blocks) => blocks.step(&homogeneous, vector),
+                None => homogeneous.sweep(vector, 0..size),
+            },
+            Method::Ssor => homogeneous.symmetric_sweep(vector),
+            Method::Richardson => homogeneous.richardson_step(tau.expect("tau is found for Richardson"), vector),
+            _ => unreachable!("only stationary methods are estimated"),
+        };
+
+        // uneven start is unlikely to miss dominant eigenvector
+        let mut vector = DVector::from_fn(size, |row, _| Decimal::from(1 + (row * 7919) % 13) / dec!(13));
+        let mut logs = Vec::with_capacity(POWER_STEPS);
+        for _ in 0..POWER_STEPS {
+            // vector is normalized every step, only huge coefficients overflow
+            apply(&mut vector).ok()?;
+            let norm = vector.iter().map(|value| value.abs()).fold(dec!(0), Decimal::max);
+            if norm.is_zero() {
+                // nilpotent, like Gauss-Seidel over triangular matrix
+                logs.clear();
+                break;
+            }
+            logs.push(norm.checked_ln()?);
+            vector /= norm;
+        }
+        let estimate = match logs.len() {
+            0 => dec!(0),
+            steps => {
+                let tail = &logs[steps / 2..];
+                let mean = tail.iter().sum::<Decimal>() / Decimal::from(tail.len());
+                mean.checked_exp().unwrap_or(Decimal::MAX)
+            }
+        };
+
+        Some(SpectralRadius {
+            estimate,
+            guarantee: self.convergence_guarantee(),
+        })
+    }
+
+    /// Sufficient condition of convergence met by matrix, if any
+    fn convergence_guarantee(&self) -> Option<Guarantee> {
+        let relaxation = self.relaxation;
+        let dominated = match self.method {
+            Method::Jacobi | Method::Hybrid => true,
+            // block version needs dominance of blocks, not rows
+            Method::GaussSeidel => self.blocks.is_empty() && relaxation <= Decimal::ONE,
+            Method::Ssor => relaxation <= Decimal::ONE,
+            _ => false,
+        };
+        if dominated && self.is_diagonally_dominant() {
+            return Some(Guarantee::DiagonalDominance);
+        }
+        let definite = matches!(self.method, Method::GaussSeidel | Method::Ssor)
+            && relaxation > dec!(0)
+            && relaxation < Decimal::TWO
+            && self.input_matrix.nrows() <= DIRECT_MAX_SIZE
+            && self.is_symmetric_positive_definite();
+        definite.then_some(Guarantee::PositiveDefinite)
+    }
+}
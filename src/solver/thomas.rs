@@ -0,0 +1,177 @@
+use nalgebra::{DMatrix, DVector};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::ops::Range;
+
+use super::{change, dot, eliminated, substituted, Acceleration, ESolveError, Equation, LuFactorization, Method, Overflow};
+
+/// Every smaller matrix is tridiagonal (by blocks, block tridiagonal), which
+/// tells nothing about it: asking for Gauss-Seidel there means Gauss-Seidel
+const THOMAS_MIN_SIZE: usize = 3;
+
+impl Equation {
+    /// Every non-zero entry is on main diagonal or right next to it
+    pub fn is_tridiagonal(&self) -> bool {
+        let matrix = &self.input_matrix;
+        (0..matrix.nrows()).all(|row| {
+            (0..matrix.ncols())
+                .filter(|column| row.abs_diff(*column) > 1)
+                .all(|column| matrix[(row, column)].is_zero())
+        })
+    }
+
+    /// Plain Gauss-Seidel over tridiagonal matrix of at least [`THOMAS_MIN_SIZE`]
+    /// rows is replaced with O(n) Thomas algorithm, unless fast path is turned off
+    pub fn uses_thomas(&self) -> bool {
+        self.would_use_thomas(self.method)
+    }
+
+    /// Whether [`Equation::uses_thomas`] would hold with `method` chosen
+    pub fn would_use_thomas(&self, method: Method) -> bool {
+        self.fast_path
+            && self.input_matrix.nrows() >= THOMAS_MIN_SIZE
+            && method == Method::GaussSeidel
+            && self.relaxation == Decimal::ONE
+            && self.bounds.is_empty()
+            && self.blocks.is_empty()
+            && self.acceleration == Acceleration::None
+            && self.is_tridiagonal()
+    }
+
+    /// Every non-zero entry is in diagonal block of [`Equation::blocks`] or in
+    /// block right next to it, like matrices of grid problems where unknowns
+    /// of a grid line are coupled only to neighbouring lines
+    pub fn is_block_tridiagonal(&self) -> bool {
+        let block_of: Vec<usize> = (self.block_ranges().into_iter().enumerate())
+            .flat_map(|(block, range)| range.map(move |_| block))
+            .collect();
+        let matrix = &self.input_matrix;
+        (0..matrix.nrows()).all(|row| {
+            (0..matrix.ncols())
+                .filter(|column| block_of[row].abs_diff(block_of[*column]) > 1)
+                .all(|column| matrix[(row, column)].is_zero())
+        })
+    }
+
+    /// Block Gauss-Seidel over block tridiagonal matrix of at least
+    /// [`THOMAS_MIN_SIZE`] blocks is replaced with block Thomas algorithm,
+    /// unless fast path is turned off
+    pub fn uses_block_thomas(&self) -> bool {
+        self.fast_path
+            && self.blocks.len() >= THOMAS_MIN_SIZE
+            && self.method == Method::GaussSeidel
+            && self.relaxation == Decimal::ONE
+            && self.acceleration == Acceleration::None
+            && self.is_block_tridiagonal()
+    }
+
+    /// Tridiagonal elimination without pivoting. Returns largest change of
+    /// component, or `None` if pivot turns zero: then system is left to iterations
+    pub(super) fn thomas_step(&self, result_vector: &mut DVector<Decimal>) -> Result<Option<Decimal>, Overflow> {
+        let matrix = &self.input_matrix;
+        let size = matrix.nrows();
+        let mut upper = vec![dec!(0); size];
+        let mut rhs = vec![dec!(0); size];
+
+        for row in 0..size {
+            let (below, previous_upper, previous_rhs) = match row {
+                0 => (dec!(0), dec!(0), dec!(0)),
+                _ => (matrix[(row, row - 1)], upper[row - 1], rhs[row - 1]),
+            };
+            let pivot = eliminated(row, matrix[(row, row)], below, previous_upper)?;
+            if pivot.is_zero() {
+                return Ok(None);
+            }
+            if row + 1 < size {
+                upper[row] = substituted(row, matrix[(row, row + 1)], dec!(0), pivot)?;
+            }
+            rhs[row] = substituted(row, self.expression_rhs[row], dot(row, [(below, previous_rhs)])?, pivot)?;
+        }
+
+        let mut delta = dec!(0);
+        let mut next = dec!(0);
+        for row in (0..size).rev() {
+            let x = eliminated(row, rhs[row], upper[row], next)?;
+            delta = delta.max(change(row, x, result_vector[row])?);
+            result_vector[row] = x;
+            next = x;
+        }
+        Ok(Some(delta))
+    }
+
+    /// Thomas algorithm over blocks: divisions turn into solves with pivot
+    /// blocks `B_i - A_i C'_(i-1)`. Returns largest change of component,
+    /// or `None` if pivot block is singular: then system is left to iterations
+    pub(super) fn block_thomas_step(&self, result_vector: &mut DVector<Decimal>) -> Result<Option<Decimal>, Overflow> {
+        let ranges = self.block_ranges();
+        let block = |rows: &Range<usize>, columns: &Range<usize>| {
+            DMatrix::from_fn(rows.len(), columns.len(), |row, column| {
+                self.input_matrix[(rows.start + row, columns.start + column)]
+            })
+        };
+        // right hand sides are kept as one column blocks, so they are reduced like blocks
+        let rhs_of = |rows: &Range<usize>| DMatrix::from_fn(rows.len(), 1, |row, _| self.expression_rhs[rows.start + row]);
+
+        // C'_i and d'_i of every block
+        let mut upper: Vec<DMatrix<Decimal>> = Vec::with_capacity(ranges.len());
+        let mut rhs: Vec<DMatrix<Decimal>> = Vec::with_capacity(ranges.len());
+        for (index, range) in ranges.iter().enumerate() {
+            let mut pivot = block(range, range);
+            let mut right = rhs_of(range);
+            if index > 0 {
+                let below = block(range, &ranges[index - 1]);
+                pivot = reduced(range.start, &pivot, &below, &upper[index - 1])?;
+                right = reduced(range.start, &right, &below, &rhs[index - 1])?;
+            }
+            let factors = match LuFactorization::new(&pivot) {
+                Ok(factors) => factors,
+                Err(ESolveError::NumericOverflow { row, .. }) => return Err(Overflow { row: range.start + row }),
+                Err(_) => return Ok(None),
+            };
+            let solve = |column: DVector<Decimal>| {
+                (factors.substitute(&column)).map_err(|Overflow { row }| Overflow { row: range.start + row })
+            };
+            if let Some(next) = ranges.get(index + 1) {
+                let coupling = block(range, next);
+                let columns = (0..coupling.ncols())
+                    .map(|column| solve(coupling.column(column).into_owned()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                upper.push(DMatrix::from_columns(&columns));
+            }
+            rhs.push(DMatrix::from_columns(&[solve(right.column(0).into_owned())?]));
+        }
+
+        let mut delta = dec!(0);
+        let mut next: Option<DMatrix<Decimal>> = None;
+        for (index, range) in ranges.iter().enumerate().rev() {
+            let x = match &next {
+                Some(next) => reduced(range.start, &rhs[index], &upper[index], next)?,
+                None => rhs[index].clone(),
+            };
+            for (row, value) in x.iter().enumerate() {
+                let i = range.start + row;
+                delta = delta.max(change(i, *value, result_vector[i])?);
+                result_vector[i] = *value;
+            }
+            next = Some(x);
+        }
+        Ok(Some(delta))
+    }
+}
+
+/// `left - a * b` of blocks whose rows start at row `offset` of system
+fn reduced(
+    offset: usize,
+    left: &DMatrix<Decimal>,
+    a: &DMatrix<Decimal>,
+    b: &DMatrix<Decimal>,
+) -> Result<DMatrix<Decimal>, Overflow> {
+    let mut result = left.clone();
+    for row in 0..left.nrows() {
+        for column in 0..left.ncols() {
+            let s = dot(offset + row, (0..a.ncols()).map(|k| (a[(row, k)], b[(k, column)])))?;
+            result[(row, column)] = substituted(offset + row, left[(row, column)], s, Decimal::ONE)?;
+        }
+    }
+    Ok(result)
+}
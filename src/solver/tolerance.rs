@@ -0,0 +1,59 @@
+use core::fmt;
+use core::str::FromStr;
+
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+
+use crate::norm::Norm;
+
+/// What largest change of component during iteration is compared with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToleranceMode {
+    /// `delta < epsilon`, the same for every solution
+    #[default]
+    Absolute,
+    /// `delta < epsilon * |x|`, norm of current iterate: asks
+    /// for the same significant digits of huge and tiny solutions. Solution
+    /// of all zeros never converges this way
+    Relative,
+    /// `delta < epsilon * (1 + |x|)`: absolute for small solutions, relative for large ones
+    Mixed,
+}
+
+impl ToleranceMode {
+    /// Iteration which changed `iterate` by `delta`, measured in `norm`, is the last one
+    pub fn converged(&self, delta: Decimal, epsilon: Decimal, iterate: &DVector<Decimal>, norm: Norm) -> bool {
+        let largest = || norm.of(iterate.iter().copied());
+        // scale too large for Decimal accepts any delta
+        let scaled = |scale: Option<Decimal>| scale.and_then(|scale| epsilon.checked_mul(scale)).unwrap_or(Decimal::MAX);
+        match self {
+            ToleranceMode::Absolute => delta < epsilon,
+            ToleranceMode::Relative => delta < scaled(Some(largest())),
+            ToleranceMode::Mixed => delta < scaled(Decimal::ONE.checked_add(largest())),
+        }
+    }
+}
+
+impl FromStr for ToleranceMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "absolute" => Ok(ToleranceMode::Absolute),
+            "relative" => Ok(ToleranceMode::Relative),
+            "mixed" => Ok(ToleranceMode::Mixed),
+            _ => Err("Expected one of: absolute, relative, mixed".to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for ToleranceMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ToleranceMode::Absolute => "absolute",
+            ToleranceMode::Relative => "relative",
+            ToleranceMode::Mixed => "mixed",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,61 @@
+use rust_decimal::Decimal;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Latest progress of running solve, printed to stderr on `SIGUSR1`:
+///
+/// ```sh
+/// kill -USR1 <pid>
+/// ```
+///
+/// Signal is handled on its own thread, so status shows up even while
+/// a long iteration is in progress, and solve is not interrupted
+pub struct StatusBoard {
+    started: Instant,
+    current: Mutex<Option<(usize, Decimal)>>,
+}
+
+impl StatusBoard {
+    pub fn update(&self, iteration: usize, delta: Decimal) {
+        *self.current.lock().expect("status is never poisoned") = Some((iteration, delta));
+    }
+
+    fn print(&self) {
+        let elapsed = self.started.elapsed();
+        match *self.current.lock().expect("status is never poisoned") {
+            Some((iteration, delta)) => {
+                eprintln!("Status: iteration {iteration}, delta {delta}, elapsed {elapsed:?}")
+            }
+            None => eprintln!("Status: first iteration in progress, elapsed {elapsed:?}"),
+        }
+    }
+}
+
+/// Starts listening for status requests. Signal handling is not available
+/// on every platform, then board is just never printed
+pub fn watch() -> Arc<StatusBoard> {
+    let board = Arc::new(StatusBoard {
+        started: Instant::now(),
+        current: Mutex::new(None),
+    });
+
+    #[cfg(unix)]
+    {
+        use signal_hook::consts::SIGUSR1;
+        use signal_hook::iterator::Signals;
+
+        match Signals::new([SIGUSR1]) {
+            Ok(mut signals) => {
+                let board = Arc::clone(&board);
+                std::thread::spawn(move || {
+                    for _ in signals.forever() {
+                        board.print();
+                    }
+                });
+            }
+            Err(err) => eprintln!("Status on SIGUSR1 is not available: {err}"),
+        }
+    }
+
+    board
+}
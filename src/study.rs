@@ -0,0 +1,153 @@
+use nalgebra::DVector;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::str::FromStr;
+
+use crate::input::{build_equation, check_memory, fixed_name, RawInput};
+use crate::result::LastIteration;
+use crate::solver::Equation;
+
+/// Study with more points than this is most likely a typo in step
+pub const MAX_STUDY_POINTS: usize = 10_000;
+
+/// `name=start:end:step` from command line. Both ends are included
+#[derive(Debug, Clone)]
+pub struct ParamRange {
+    pub name: String,
+    pub start: Decimal,
+    pub end: Decimal,
+    pub step: Decimal,
+}
+
+impl FromStr for ParamRange {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, range) = crate::template::parse_param(value)?;
+        let bounds: Vec<&str> = range.split(':').collect();
+        let [start, end, step] = bounds[..] else {
+            return Err("Expected <name>=<start>:<end>:<step>".to_owned());
+        };
+        let parse = |bound: &str| {
+            Decimal::from_str(bound)
+                .or_else(|_| Decimal::from_scientific(bound))
+                .map_err(|_| format!("\"{bound}\" is not a number"))
+        };
+        let range = ParamRange {
+            name,
+            start: parse(start)?,
+            end: parse(end)?,
+            step: parse(step)?,
+        };
+
+        if range.step <= Decimal::ZERO {
+            return Err("Step must be positive".to_owned());
+        }
+        if range.start > range.end {
+            return Err("Start must not be greater than end".to_owned());
+        }
+        if (range.end - range.start) / range.step >= Decimal::from(MAX_STUDY_POINTS) {
+            return Err(format!("Range has more than {MAX_STUDY_POINTS} values"));
+        }
+        Ok(range)
+    }
+}
+
+impl ParamRange {
+    pub fn values(&self) -> Vec<Decimal> {
+        std::iter::successors(Some(self.start), |value| Some(value + self.step))
+            .take_while(|value| *value <= self.end)
+            .collect()
+    }
+}
+
+/// Solve of templated system for one value of parameter
+pub struct StudyPoint {
+    pub value: Decimal,
+    pub outcome: Result<(DVector<Decimal>, LastIteration), String>,
+}
+
+/// Solves template for every value of range, everything but the parameter is
+/// taken from `params`. `configure` applies command line settings to each system
+pub fn run(
+    raw: &RawInput,
+    params: &BTreeMap<String, String>,
+    range: &ParamRange,
+    max_memory: Option<u64>,
+    configure: impl Fn(&mut Equation),
+) -> Vec<StudyPoint> {
+    range
+        .values()
+        .into_iter()
+        .map(|value| {
+            let mut params = params.clone();
+            params.insert(range.name.clone(), value.to_string());
+            StudyPoint {
+                value,
+                outcome: solve_point(raw, &params, max_memory, &configure),
+            }
+        })
+        .collect()
+}
+
+fn solve_point(
+    raw: &RawInput,
+    params: &BTreeMap<String, String>,
+    max_memory: Option<u64>,
+    configure: &impl Fn(&mut Equation),
+) -> Result<(DVector<Decimal>, LastIteration), String> {
+    let parsed = raw.parse(params).map_err(|err| err.to_string())?;
+    let mut equation = build_equation(parsed, max_memory).map_err(|err| err.to_string())?;
+    configure(&mut equation);
+    check_memory(&equation, max_memory).map_err(|err| err.to_string())?;
+
+    let mut last = LastIteration::default();
+    let solution = equation
+        .solve_with_progress(|progress| {
+            last.record(progress);
+            ControlFlow::Continue(())
+        })
+        .map_err(|err| err.to_string())?;
+    Ok((solution, last))
+}
+
+/// Header and rows of table: parameter, status, iterations and unknowns.
+/// Failed values leave unknowns empty
+pub fn table(
+    range: &ParamRange,
+    points: &[StudyPoint],
+    number: impl Fn(Decimal) -> String,
+) -> (Vec<String>, Vec<Vec<String>>) {
+    let unknowns = points
+        .iter()
+        .filter_map(|point| point.outcome.as_ref().ok())
+        .map(|(solution, _)| solution.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut header = vec![range.name.clone(), "status".to_owned(), "iterations".to_owned()];
+    header.extend((0..unknowns).map(fixed_name));
+
+    let rows = points
+        .iter()
+        .map(|point| {
+            let mut row = vec![number(point.value)];
+            match &point.outcome {
+                Ok((solution, last)) => {
+                    row.push("solved".to_owned());
+                    row.push(last.iteration.to_string());
+                    row.extend(solution.iter().map(|value| number(*value)));
+                }
+                Err(err) => {
+                    row.push(err.clone());
+                    row.push(String::new());
+                }
+            }
+            row.resize(header.len(), String::new());
+            row
+        })
+        .collect();
+
+    (header, rows)
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+/// Replaces `${name}` placeholders of input document with parameter values,
+/// so one template file describes whole family of systems:
+///
+/// ```json
+/// { "input_matrix": [["${k}", "1"], ["1", "2"]], ... }
+/// ```
+///
+/// Values are inserted as is, before document is parsed.
+/// Placeholder without parameter given for it is an error
+pub fn substitute(content: &str, params: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| "Placeholder \"${\" is not closed with \"}\"".to_owned())?;
+        let name = &after[..end];
+        let value = params
+            .get(name)
+            .ok_or_else(|| format!("Parameter \"{name}\" is not given! Use --param {name}=<value>"))?;
+        out.push_str(value);
+        rest = &after[end + 1..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// `name=value` from command line
+pub fn parse_param(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value
+        .split_once('=')
+        .ok_or_else(|| "Expected <name>=<value>".to_owned())?;
+
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err("Parameter name may consist of letters, digits and underscores".to_owned());
+    }
+
+    Ok((name.to_owned(), value.to_owned()))
+}
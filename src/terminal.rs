@@ -0,0 +1,37 @@
+//! Width text output has to fit into
+
+use std::io::{self, IsTerminal};
+
+/// Columns of terminal stdout is shown in, or `COLUMNS` of shell when
+/// stdout is not terminal itself. `None` when neither is known
+pub fn width() -> Option<usize> {
+    let columns = || std::env::var("COLUMNS").ok()?.parse().ok().filter(|columns| *columns > 0);
+    match io::stdout().is_terminal() {
+        true => terminal_columns().or_else(columns),
+        false => columns(),
+    }
+}
+
+/// Width of output: `--width`, where 0 turns fitting off, and width of
+/// terminal when result is not written into file
+pub fn output_width(requested: Option<usize>, output: Option<&str>) -> Option<usize> {
+    match requested {
+        Some(0) => None,
+        Some(columns) => Some(columns),
+        None if output.is_some() => None,
+        None => width(),
+    }
+}
+
+#[cfg(unix)]
+fn terminal_columns() -> Option<usize> {
+    // SAFETY: winsize is plain data, which TIOCGWINSZ fills in or leaves zeroed
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(not(unix))]
+fn terminal_columns() -> Option<usize> {
+    None
+}
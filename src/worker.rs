@@ -0,0 +1,53 @@
+//! Worker of distributed batch, see [`crate::batch::WorkerSpec`]. Speaks
+//! line protocol of [`crate::daemon`]: one JSON input per line in, one JSON
+//! result per line out
+
+use std::io::{self, BufReader};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+use crate::daemon::{refuse, serve_lines, IDLE_TIMEOUT};
+use crate::service::{Connections, ServiceSettings};
+
+/// Serves coordinator on stdin and stdout until stdin is closed. Started
+/// by coordinator itself, often over SSH
+pub fn serve_stdio(settings: &ServiceSettings) -> io::Result<()> {
+    serve_lines(io::stdin().lock(), io::stdout().lock(), settings)
+}
+
+/// Serves every connection on its own thread, with the same idle timeout
+/// and limit of connections as daemon. There is no authentication: anyone
+/// who can connect may solve, so address should be reachable from the
+/// cluster only
+pub fn listen(address: &str, settings: ServiceSettings) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    eprintln!("Listening on {}", listener.local_addr()?);
+
+    let connections = Arc::new(Connections::default());
+    let settings = Arc::new(settings);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let _ = stream.set_write_timeout(Some(IDLE_TIMEOUT));
+                let Some(slot) = connections.acquire() else {
+                    refuse(&stream);
+                    continue;
+                };
+                let settings = Arc::clone(&settings);
+                thread::spawn(move || {
+                    let _slot = slot;
+                    let served = (stream.set_read_timeout(Some(IDLE_TIMEOUT)))
+                        .and_then(|()| stream.try_clone())
+                        .and_then(|writer| serve_lines(BufReader::new(stream), writer, &settings));
+                    if let Err(err) = served {
+                        eprintln!("Connection failed: {err}");
+                    }
+                });
+            }
+            Err(err) => eprintln!("Can't accept connection: {err}"),
+        }
+    }
+
+    Ok(())
+}